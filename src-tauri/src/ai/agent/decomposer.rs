@@ -1,3 +1,4 @@
+use crate::ai::openrouter::types::{JsonSchema, ResponseFormat};
 use crate::ai::openrouter::OpenRouterClient;
 use crate::ai::agent::{Message, MessageRole, QuestionType};
 use crate::db::schema::Schema;
@@ -45,11 +46,44 @@ pub struct DecomposerResult {
 pub struct DecomposerAgent<'a> {
     client: &'a OpenRouterClient,
     model: &'a str,
+    max_rows: u64,
+    allow_writes: bool,
+    temperature: f32,
+    max_tokens: u32,
+    /// Column values the selector sampled from the data, one `- table.column: ...` line each
+    value_samples: String,
 }
 
 impl<'a> DecomposerAgent<'a> {
-    pub fn new(client: &'a OpenRouterClient, model: &'a str) -> Self {
-        Self { client, model }
+    pub fn new(client: &'a OpenRouterClient, model: &'a str, max_rows: u64) -> Self {
+        Self {
+            client,
+            model,
+            max_rows,
+            allow_writes: false,
+            temperature: 0.2, // Slightly higher temperature for creative SQL
+            max_tokens: DEFAULT_MAX_TOKENS,
+            value_samples: String::new(),
+        }
+    }
+
+    /// Real column values to show with the schema, from `ValueSampler::prompt_section`
+    pub fn with_value_samples(mut self, value_samples: String) -> Self {
+        self.value_samples = value_samples;
+        self
+    }
+
+    /// Sampling temperature and output token cap for SQL generation
+    pub fn with_sampling(mut self, temperature: f32, max_tokens: u32) -> Self {
+        self.temperature = temperature;
+        self.max_tokens = max_tokens;
+        self
+    }
+
+    /// Let data-change questions produce a single INSERT/UPDATE/DELETE instead of a SELECT
+    pub fn with_writes(mut self, allow_writes: bool) -> Self {
+        self.allow_writes = allow_writes;
+        self
     }
 
     /// Decompose the question and generate SQL queries
@@ -64,6 +98,19 @@ impl<'a> DecomposerAgent<'a> {
         let schema_str = self.format_schema(schema, db_type);
         let history_str = self.format_conversation_history(conversation_history);
 
+        // Writes are only offered for questions classified as data changes, never mixed with reads
+        let statement_rules = if self.allow_writes && *question_type == QuestionType::DataChange {
+            "- The user wants to change data: respond with exactly ONE INSERT, UPDATE or DELETE statement and no other queries\n\
+             - UPDATE and DELETE must have a WHERE clause that targets only the rows the user described\n\
+             - Never change the schema (no CREATE, ALTER, DROP, TRUNCATE)"
+                .to_string()
+        } else {
+            format!(
+                "- Only SELECT queries (no INSERT, UPDATE, DELETE, etc.)\n- Always include LIMIT clause (max {} rows)",
+                self.max_rows
+            )
+        };
+
         let system_prompt = format!(
             r#"You are an expert SQL analyst. Your task is to analyze a user's question and generate the SQL needed to answer it.
 
@@ -87,10 +134,10 @@ PROCESS:
    - Generate SQL for each step
 
 RULES:
-- Only SELECT queries (no INSERT, UPDATE, DELETE, etc.)
-- Always include LIMIT clause (max 100 rows)
+{statement_rules}
 - Use proper {} SQL syntax
 - Prefer CTEs (WITH clause) for complex logic in a single query
+- Prefer filtering and joining on indexed columns (listed under "Indexes"; a composite index helps most when its leading column is used)
 - Only mark as COMPLEX if truly requiring multiple separate queries
 - If the user refers to "that", "those", "it", etc., use the CONVERSATION HISTORY to understand what they mean
 
@@ -101,13 +148,15 @@ Respond in this exact JSON format:
     "queries": [
         {{
             "question": "The sub-question this query answers",
-            "sql": "SELECT ... FROM ... LIMIT 100",
+            "sql": "SELECT ... FROM ... LIMIT {max_rows}",
             "order": 0,
             "depends_on_previous": false
         }}
     ]
 }}"#,
-            schema_str, db_type, db_type, history_str, db_type
+            schema_str, db_type, db_type, history_str, db_type,
+            max_rows = self.max_rows,
+            statement_rules = statement_rules
         );
 
         // Add context about question type
@@ -115,8 +164,11 @@ Respond in this exact JSON format:
             QuestionType::Statistic => "\n\nNote: This question asks for a specific metric or count. Use aggregate functions.",
             QuestionType::TemporalChart => "\n\nNote: This question involves time-series data. Include date grouping and ordering.",
             QuestionType::CategoryChart => "\n\nNote: This question involves categories. Use GROUP BY for grouping.",
+            QuestionType::Heatmap => "\n\nNote: This question will be shown as a heatmap. Return exactly two grouping columns followed by one numeric measure, grouped by both.",
+            QuestionType::Distribution => "\n\nNote: This question will be shown as a box plot. Return the individual numeric values rather than aggregates, plus one grouping column if groups are compared.",
             QuestionType::TableView => "\n\nNote: User wants to view table data. Simple SELECT with appropriate columns.",
             QuestionType::Complex => "\n\nNote: This has been classified as a complex analytical question.",
+            QuestionType::DataChange => "\n\nNote: This question asks to change data.",
             QuestionType::General => "",
         };
 
@@ -129,8 +181,9 @@ Respond in this exact JSON format:
             .chat_with_format(
                 self.model,
                 &messages,
-                Some(0.2), // Slightly higher temperature for creative SQL
-                None,
+                Some(self.temperature),
+                Some(self.max_tokens),
+                Some(decomposition_response_format()),
                 None,
             )
             .await?;
@@ -146,6 +199,11 @@ Respond in this exact JSON format:
 
         let mut output = String::from("\nCONVERSATION HISTORY:\n");
 
+        // Summary of turns beyond the history limit, kept whole
+        for msg in history.iter().filter(|m| matches!(m.role, MessageRole::System)) {
+            output.push_str(&format!("{}\n", msg.content));
+        }
+
         // Only include the last 5 exchanges to keep context manageable
         let recent_history: Vec<_> = history.iter()
             .rev()
@@ -179,7 +237,7 @@ Respond in this exact JSON format:
         let mut output = format!("Database: {} (Type: {})\n\nTables:\n", schema.database_name, db_type);
 
         for table in &schema.tables {
-            output.push_str(&format!("\n{}:\n", table.name));
+            output.push_str(&format!("\n{}:{}\n", table.prompt_label(), table.prompt_comment()));
 
             for col in &table.columns {
                 let nullable = if col.is_nullable { "NULL" } else { "NOT NULL" };
@@ -195,21 +253,34 @@ Respond in this exact JSON format:
                 };
 
                 output.push_str(&format!(
-                    "  - {} ({}) {}{}{}\n",
-                    col.name, col.data_type, nullable, pk, fk
+                    "  - {} ({}) {}{}{}{}\n",
+                    col.name, col.prompt_type(), nullable, pk, fk, col.prompt_comment()
                 ));
             }
+
+            let indexes = table.prompt_indexes();
+            if !indexes.is_empty() {
+                output.push_str(&format!("  Indexes: {}\n", indexes));
+            }
+        }
+
+        if !self.value_samples.is_empty() {
+            output.push_str("\nSample values from the data (use this exact spelling and casing):\n");
+            output.push_str(&self.value_samples);
         }
 
+        output.push_str(&schema.prompt_routines());
         output
     }
 
     /// Parse the LLM response into DecomposerResult
     fn parse_decomposer_response(&self, response: &str) -> AppResult<DecomposerResult> {
-        let json_str = self.extract_json(response);
-
-        let parsed: serde_json::Value = serde_json::from_str(&json_str)
-            .map_err(|e| AppError::AgentError(format!("Failed to parse decomposer response: {}. Response: {}", e, response)))?;
+        // Structured output is plain JSON; models that ignore response_format may wrap it
+        let parsed: serde_json::Value = match serde_json::from_str(response.trim()) {
+            Ok(parsed) => parsed,
+            Err(_) => serde_json::from_str(&self.extract_json(response))
+                .map_err(|e| AppError::AgentError(format!("Failed to parse decomposer response: {}. Response: {}", e, response)))?,
+        };
 
         let complexity_str = parsed["complexity"]
             .as_str()
@@ -308,3 +379,67 @@ Respond in this exact JSON format:
         response.trim().to_string()
     }
 }
+
+/// Strict JSON schema for `{complexity, reasoning, queries: [...]}`
+fn decomposition_response_format() -> ResponseFormat {
+    let schema = serde_json::json!({
+        "type": "object",
+        "properties": {
+            "complexity": {
+                "type": "string",
+                "enum": ["simple", "complex"]
+            },
+            "reasoning": {
+                "type": "string",
+                "description": "Chain of thought explaining how to answer the question"
+            },
+            "queries": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "question": { "type": "string" },
+                        "sql": { "type": "string" },
+                        "order": { "type": "integer" },
+                        "depends_on_previous": { "type": "boolean" }
+                    },
+                    "required": ["question", "sql", "order", "depends_on_previous"],
+                    "additionalProperties": false
+                }
+            }
+        },
+        "required": ["complexity", "reasoning", "queries"],
+        "additionalProperties": false
+    });
+
+    ResponseFormat {
+        format_type: "json_schema".to_string(),
+        json_schema: Some(JsonSchema {
+            name: "sql_decomposition".to_string(),
+            strict: true,
+            schema,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_structured_and_prose_responses() {
+        let client = OpenRouterClient::new("test".to_string());
+        let agent = DecomposerAgent::new(&client, "test-model", 100);
+
+        // SQL containing braces and a fence marker breaks heuristic extraction but not structured output
+        let structured = r#"{"complexity":"simple","reasoning":"r","queries":[{"question":"q","sql":"SELECT '{```}' AS x LIMIT 1","order":0,"depends_on_previous":false}]}"#;
+        let result = agent.parse_decomposer_response(structured).unwrap();
+        assert_eq!(result.complexity, QueryComplexity::Simple);
+        assert_eq!(result.queries[0].sql, "SELECT '{```}' AS x LIMIT 1");
+
+        let prose = "Here you go:\n```json\n{\"complexity\":\"complex\",\"reasoning\":\"r\",\"queries\":[{\"sql\":\"SELECT 1\"}]}\n```";
+        let result = agent.parse_decomposer_response(prose).unwrap();
+        assert_eq!(result.complexity, QueryComplexity::Complex);
+        assert_eq!(result.queries[0].sql, "SELECT 1");
+    }
+}
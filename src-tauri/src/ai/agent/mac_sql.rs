@@ -1,16 +1,112 @@
 use super::selector::SelectorAgent;
-use super::decomposer::{DecomposerAgent, QueryComplexity};
+use super::decomposer::{DecomposerAgent, DecomposerResult, QueryComplexity};
 use super::refiner::{RefinerAgent, RefinerResult};
 use super::state::*;
+use super::tools::ValueSampler;
 use crate::ai::classification;
+use crate::ai::memory;
 use crate::ai::openrouter::OpenRouterClient;
-use crate::ai::visualization::generate_plotly_code;
+use crate::ai::sanitizer;
+use crate::ai::visualization::{generate_plotly_code_with_options, ChartOptions};
 use crate::db::connection::{ConnectionManager, DatabaseType};
-use crate::db::query::QueryResult;
+use crate::db::query::{self, QueryResult};
 use crate::db::schema::{self, Schema};
-use crate::error::AppResult;
+use crate::error::{AppError, AppResult};
 use crate::storage::AppSettings;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
 use tauri::{AppHandle, Emitter};
+use tokio::sync::RwLock;
+use tokio_util::sync::CancellationToken;
+
+// Global AI chat cancellation tokens, keyed by session id
+lazy_static::lazy_static! {
+    static ref CHAT_TOKENS: Arc<RwLock<HashMap<String, CancellationToken>>> = Arc::new(RwLock::new(HashMap::new()));
+}
+
+/// A data change proposed by the agent, held until the user confirms or rejects it
+#[derive(Debug, Clone)]
+struct PendingWrite {
+    /// Sent with the `ai_confirm_write` event; confirming must echo it back
+    proposal_id: String,
+    connection_id: String,
+    sql: String,
+}
+
+// AI-proposed writes awaiting confirmation, keyed by session id
+lazy_static::lazy_static! {
+    static ref PENDING_WRITES: Arc<RwLock<HashMap<String, PendingWrite>>> = Arc::new(RwLock::new(HashMap::new()));
+}
+
+/// Run or discard the write the agent proposed for a session. `proposal_id` must
+/// match the pending proposal, so a confirmation meant for an earlier proposal
+/// can't run a newer one. Returns the result (with the affected row count) when
+/// approved, None when rejected.
+pub async fn confirm_write(
+    connections: &ConnectionManager,
+    session_id: &str,
+    proposal_id: &str,
+    approved: bool,
+    timeout_ms: u64,
+) -> AppResult<Option<QueryResult>> {
+    let pending = {
+        let mut pending_writes = PENDING_WRITES.write().await;
+        match pending_writes.remove(session_id) {
+            Some(pending) if pending.proposal_id == proposal_id => pending,
+            Some(newer) => {
+                // The newer proposal keeps waiting for its own confirmation
+                pending_writes.insert(session_id.to_string(), newer);
+                return Err(AppError::ValidationError(
+                    "This AI data change was replaced by a newer proposal".into(),
+                ));
+            }
+            None => {
+                return Err(AppError::ValidationError(
+                    "No AI data change is awaiting confirmation for this session".into(),
+                ))
+            }
+        }
+    };
+    if !approved {
+        return Ok(None);
+    }
+
+    let conn = connections.get_connection(&pending.connection_id)?;
+    conn.ensure_writable("AI data changes")?;
+    let sql = sanitizer::validate_sql_write(&pending.sql, &conn.database_type)?;
+    let mut results =
+        query::execute_query_batch(connections, &pending.connection_id, &sql, timeout_ms).await?;
+    connections.invalidate_schema(&pending.connection_id);
+    query::invalidate_query_cache(&pending.connection_id);
+
+    Ok(results.pop())
+}
+
+/// Create and register a cancellation token for a chat session
+pub async fn register_chat_session(session_id: &str) -> CancellationToken {
+    let token = CancellationToken::new();
+    let mut tokens = CHAT_TOKENS.write().await;
+    tokens.insert(session_id.to_string(), token.clone());
+    token
+}
+
+/// Remove a chat session's cancellation token once it has finished
+pub async fn unregister_chat_session(session_id: &str) {
+    let mut tokens = CHAT_TOKENS.write().await;
+    tokens.remove(session_id);
+}
+
+/// Cancel an in-flight chat session
+pub async fn cancel_chat_session(session_id: &str) -> AppResult<()> {
+    let tokens = CHAT_TOKENS.read().await;
+    if let Some(token) = tokens.get(session_id) {
+        token.cancel();
+        Ok(())
+    } else {
+        Err(AppError::Other("No active AI chat found for this session".to_string()))
+    }
+}
 
 /// Run the MAC-SQL multi-agent pipeline
 ///
@@ -18,6 +114,14 @@ use tauri::{AppHandle, Emitter};
 /// 1. Selector: Prune schema to relevant tables/columns
 /// 2. Decomposer: Judge complexity and generate SQL
 /// 3. Refiner: Validate, execute, and self-correct SQL
+///
+/// Token usage from every stage is added to the session total and reported
+/// through an `ai_usage` event once the run ends, whether or not it succeeded.
+///
+/// With `allow_writes`, a data-change request produces a single INSERT/UPDATE/DELETE
+/// that is announced with an `ai_confirm_write` event and only runs once
+/// `confirm_write` approves it.
+#[allow(clippy::too_many_arguments)]
 pub async fn run_mac_sql_agent(
     session_id: String,
     connection_id: String,
@@ -26,48 +130,154 @@ pub async fn run_mac_sql_agent(
     app: &AppHandle,
     connections: &ConnectionManager,
     settings: &AppSettings,
+    cancel_token: &CancellationToken,
+    allow_writes: bool,
+) -> AppResult<AgentResponse> {
+    let fallback_app = app.clone();
+    let fallback_session = session_id.clone();
+    let client = OpenRouterClient::new(settings.openrouter_api_key.clone())
+        .with_max_retries(settings.max_retries)
+        .with_max_concurrent_requests(settings.max_concurrent_ai_requests)
+        .with_cancellation(cancel_token.clone())
+        .with_fallback_models(settings.fallback_models.clone())
+        .with_model_params(settings.model_params())
+        .on_fallback(move |from, to, reason| {
+            if let Err(e) = fallback_app.emit(
+                "ai_model_fallback",
+                serde_json::json!({
+                    "session_id": fallback_session,
+                    "from_model": from,
+                    "to_model": to,
+                    "reason": reason,
+                }),
+            ) {
+                eprintln!("Failed to emit model fallback: {:?}", e);
+            }
+        });
+
+    let result = run_pipeline(
+        &session_id,
+        connection_id,
+        question,
+        previous_messages,
+        app,
+        connections,
+        settings,
+        cancel_token,
+        &client,
+        allow_writes,
+    ).await;
+
+    let usage = client.usage();
+    let session_total = memory::record_session_usage(&session_id, &usage);
+    if let Err(e) = app.emit(
+        "ai_usage",
+        serde_json::json!({
+            "session_id": session_id,
+            "usage": usage,
+            "session_total": session_total,
+        }),
+    ) {
+        eprintln!("Failed to emit usage: {:?}", e);
+    }
+
+    result
+}
+
+/// Run the pipeline stages with a shared client so their usage is tallied together
+async fn run_pipeline(
+    session_id: &str,
+    connection_id: String,
+    question: String,
+    previous_messages: Vec<Message>,
+    app: &AppHandle,
+    connections: &ConnectionManager,
+    settings: &AppSettings,
+    cancel_token: &CancellationToken,
+    client: &OpenRouterClient,
+    allow_writes: bool,
 ) -> AppResult<AgentResponse> {
-    let client = OpenRouterClient::new(settings.openrouter_api_key.clone());
     let model = &settings.text_to_sql_model;
 
     // Emit starting message
-    emit_thinking(app, &session_id, "Analyzing your question...\n").await?;
+    emit_thinking(app, session_id, "Analyzing your question...\n").await?;
+
+    if cancel_token.is_cancelled() {
+        return cancelled_response(app, session_id, vec![]).await;
+    }
 
     // Step 1: Classify the question
+    let cache_ttl = Duration::from_secs(settings.llm_cache_ttl_secs);
     let question_type = classification::classify_question(
         &question,
-        &client,
+        client,
         model,
+        cache_ttl,
+        settings.classification_temperature,
+        settings.max_tokens,
     ).await?;
 
     // For general questions, skip the pipeline and respond directly
     if matches!(question_type, QuestionType::General) {
         return handle_general_question(
-            session_id,
+            session_id.to_string(),
             question,
             previous_messages,
-            &client,
+            client,
             model,
             connections,
             &connection_id,
             app,
+            cancel_token,
+            settings,
         ).await;
     }
 
+    if question_type == QuestionType::DataChange && !allow_writes {
+        let answer = "That request would change data, but write mode is off. \
+            Turn on write mode to have me propose the change for your confirmation."
+            .to_string();
+        emit_token(app, session_id, &answer)?;
+        emit_complete(app, session_id, &answer).await?;
+        return Ok(AgentResponse {
+            answer,
+            sql_queries: vec![],
+            results: Vec::new(),
+            iterations: 0,
+            cancelled: false,
+        });
+    }
+
     // Get full schema
-    let full_schema = schema::get_schema(connections, &connection_id, app).await?;
+    let full_schema = load_schema(connections, &connection_id, app, settings.include_routines).await?;
     let conn = connections.get_connection(&connection_id)?;
     let db_type = get_db_type_str(&conn.database_type);
 
     // Step 2: Selector Agent - Prune schema
-    emit_thinking(app, &session_id, "Identifying relevant tables...\n").await?;
-
-    let selector = SelectorAgent::new(&client, model);
+    if cancel_token.is_cancelled() {
+        return cancelled_response(app, session_id, vec![]).await;
+    }
+    emit_thinking(app, session_id, "Identifying relevant tables...\n").await?;
+
+    let sampler = ValueSampler::new(
+        connections,
+        &connection_id,
+        conn.database_type.clone(),
+        settings.ai_max_rows,
+        settings.query_timeout_ms,
+    );
+    let mut selector = SelectorAgent::new(client, model)
+        .with_cache_ttl(cache_ttl)
+        .with_sampling(settings.selector_temperature, settings.max_tokens);
+    // Sampling is a tool call; models without tools select from the schema alone
+    if client.supports_tools(model).await {
+        selector = selector.with_value_sampler(&sampler);
+    }
     let selector_result = selector.select_relevant_schema(&question, &full_schema).await?;
 
     emit_thinking(
         app,
-        &session_id,
+        session_id,
         &format!(
             "Selected tables: {}\n",
             selector_result.selected_tables.join(", ")
@@ -75,9 +285,15 @@ pub async fn run_mac_sql_agent(
     ).await?;
 
     // Step 3: Decomposer Agent - Generate SQL
-    emit_thinking(app, &session_id, "Generating SQL query...\n").await?;
+    if cancel_token.is_cancelled() {
+        return cancelled_response(app, session_id, vec![]).await;
+    }
+    emit_thinking(app, session_id, "Generating SQL query...\n").await?;
 
-    let decomposer = DecomposerAgent::new(&client, model);
+    let decomposer = DecomposerAgent::new(client, model, settings.ai_max_rows)
+        .with_writes(allow_writes)
+        .with_sampling(settings.decomposer_temperature, settings.max_tokens)
+        .with_value_samples(sampler.prompt_section());
     let decomposer_result = decomposer.decompose(
         &question,
         &selector_result.pruned_schema,
@@ -86,6 +302,10 @@ pub async fn run_mac_sql_agent(
         &previous_messages,
     ).await?;
 
+    if question_type == QuestionType::DataChange {
+        return propose_write(app, session_id, &connection_id, connections, &decomposer_result).await;
+    }
+
     // Log complexity
     let complexity_msg = match decomposer_result.complexity {
         QueryComplexity::Simple => "Single query generated",
@@ -94,18 +314,24 @@ pub async fn run_mac_sql_agent(
             decomposer_result.queries.len()
         ),
     };
-    emit_thinking(app, &session_id, &format!("{}\n", complexity_msg)).await?;
+    emit_thinking(app, session_id, &format!("{}\n", complexity_msg)).await?;
 
     // Step 4: Refiner Agent - Execute and validate each query
-    let refiner = RefinerAgent::new(&client, model);
+    let refiner = RefinerAgent::new(client, model, settings.query_timeout_ms, settings.ai_max_rows)
+        .with_sampling(settings.refiner_temperature, settings.max_tokens)
+        .with_result_cache_ttl(Duration::from_secs(settings.query_result_cache_ttl_secs));
     let mut all_results: Vec<QueryResult> = Vec::new();
     let mut all_sql: Vec<String> = Vec::new();
     let mut refiner_results: Vec<RefinerResult> = Vec::new();
 
     for (idx, sub_query) in decomposer_result.queries.iter().enumerate() {
+        if cancel_token.is_cancelled() {
+            return cancelled_response(app, session_id, all_sql).await;
+        }
+
         emit_thinking(
             app,
-            &session_id,
+            session_id,
             &format!("Executing SQL: {}\n", sub_query.sql),
         ).await?;
 
@@ -117,13 +343,14 @@ pub async fn run_mac_sql_agent(
             db_type,
             &connection_id,
             connections,
+            cancel_token,
         ).await {
             Ok(result) => {
                 // Emit results
                 if result.attempts > 1 {
                     emit_thinking(
                         app,
-                        &session_id,
+                        session_id,
                         &format!("Query succeeded after {} refinement(s)\n", result.attempts),
                     ).await?;
                 }
@@ -133,7 +360,7 @@ pub async fn run_mac_sql_agent(
                 // Emit data to frontend
                 emit_query_results(
                     app,
-                    &session_id,
+                    session_id,
                     &question_type,
                     &result.result,
                     &question,
@@ -142,11 +369,14 @@ pub async fn run_mac_sql_agent(
                 all_results.push(result.result.clone());
                 refiner_results.push(result);
             }
+            Err(AppError::OperationCancelled(_)) => {
+                return cancelled_response(app, session_id, all_sql).await;
+            }
             Err(e) => {
                 // Query failed after all refinement attempts
                 emit_thinking(
                     app,
-                    &session_id,
+                    session_id,
                     &format!("Query failed: {}\n", e),
                 ).await?;
 
@@ -161,12 +391,14 @@ pub async fn run_mac_sql_agent(
                         e, sub_query.sql
                     );
 
-                    emit_complete(app, &session_id, &answer).await?;
+                    emit_complete(app, session_id, &answer).await?;
 
                     return Ok(AgentResponse {
                         answer,
                         sql_queries: vec![sub_query.sql.clone()],
+                        results: all_results,
                         iterations: 1,
+                        cancelled: false,
                     });
                 }
             }
@@ -174,25 +406,146 @@ pub async fn run_mac_sql_agent(
     }
 
     // Step 5: Generate final answer
-    let answer = generate_final_answer(
+    if cancel_token.is_cancelled() {
+        return cancelled_response(app, session_id, all_sql).await;
+    }
+    let answer = match generate_final_answer(
         &question,
         &all_results,
         &decomposer_result.reasoning,
-        &client,
+        client,
         model,
-    ).await?;
+        app,
+        session_id,
+        cancel_token,
+        settings.final_answer_temperature,
+        settings.final_answer_max_tokens,
+    ).await {
+        Ok(answer) => answer,
+        Err(AppError::OperationCancelled(_)) => {
+            return cancelled_response(app, session_id, all_sql).await;
+        }
+        Err(e) => return Err(e),
+    };
 
-    emit_token(app, &session_id, &answer).await?;
-    emit_complete(app, &session_id, &answer).await?;
+    emit_complete(app, session_id, &answer).await?;
 
     Ok(AgentResponse {
         answer,
         sql_queries: all_sql,
+        results: all_results,
         iterations: refiner_results.iter().map(|r| r.attempts as u8).sum(),
+        cancelled: false,
+    })
+}
+
+/// Hold the decomposer's data change for confirmation instead of running it
+async fn propose_write(
+    app: &AppHandle,
+    session_id: &str,
+    connection_id: &str,
+    connections: &ConnectionManager,
+    decomposition: &DecomposerResult,
+) -> AppResult<AgentResponse> {
+    let [statement] = decomposition.queries.as_slice() else {
+        return Err(AppError::AgentError(
+            "A data change must be a single statement".into(),
+        ));
+    };
+    let conn = connections.get_connection(connection_id)?;
+    let sql = sanitizer::validate_sql_write(&statement.sql, &conn.database_type)?;
+    conn.ensure_writable("AI data changes")?;
+
+    let proposal_id = uuid::Uuid::new_v4().to_string();
+    PENDING_WRITES.write().await.insert(
+        session_id.to_string(),
+        PendingWrite {
+            proposal_id: proposal_id.clone(),
+            connection_id: connection_id.to_string(),
+            sql: sql.clone(),
+        },
+    );
+    app.emit(
+        "ai_confirm_write",
+        serde_json::json!({
+            "session_id": session_id,
+            "proposal_id": proposal_id,
+            "sql": sql,
+            "reasoning": decomposition.reasoning,
+        }),
+    )?;
+
+    let answer = format!(
+        "This change will only run after you confirm it:\n```sql\n{}\n```",
+        sql
+    );
+    emit_token(app, session_id, &answer)?;
+    emit_complete(app, session_id, &answer).await?;
+
+    Ok(AgentResponse {
+        answer,
+        sql_queries: vec![],
+        results: Vec::new(),
+        iterations: 1,
+        cancelled: false,
     })
 }
 
-/// Handle general (non-data) questions
+/// Load the schema for AI prompts, adding stored routines when enabled.
+/// Routine lookup failures are logged rather than failing the question.
+async fn load_schema(
+    connections: &ConnectionManager,
+    connection_id: &str,
+    app: &AppHandle,
+    include_routines: bool,
+) -> AppResult<Schema> {
+    let mut full_schema = schema::get_schema(connections, connection_id, app).await?;
+
+    if include_routines {
+        match schema::get_routines(connections, connection_id).await {
+            Ok(routines) => full_schema.routines = routines,
+            Err(e) => eprintln!("Failed to load routines: {:?}", e),
+        }
+    }
+
+    Ok(full_schema)
+}
+
+/// Build the response for a cancelled session, keeping any SQL that already ran
+/// so the saved conversation still has the context
+async fn cancelled_response(
+    app: &AppHandle,
+    session_id: &str,
+    sql_queries: Vec<String>,
+) -> AppResult<AgentResponse> {
+    app.emit(
+        "ai_cancelled",
+        serde_json::json!({
+            "session_id": session_id,
+        }),
+    )?;
+
+    let answer = if sql_queries.is_empty() {
+        "Request cancelled.".to_string()
+    } else {
+        format!(
+            "Request cancelled. Queries run before cancelling:\n```sql\n{}\n```",
+            sql_queries.join(";\n")
+        )
+    };
+
+    Ok(AgentResponse {
+        answer,
+        sql_queries,
+        results: Vec::new(),
+        iterations: 0,
+        cancelled: true,
+    })
+}
+
+/// Handle general (non-data) questions, sampled like the pipeline's final answer; the
+/// client already carries the shared `top_p`, penalty and provider settings
+#[allow(clippy::too_many_arguments)]
 async fn handle_general_question(
     session_id: String,
     question: String,
@@ -202,9 +555,11 @@ async fn handle_general_question(
     connections: &ConnectionManager,
     connection_id: &str,
     app: &AppHandle,
+    cancel_token: &CancellationToken,
+    settings: &AppSettings,
 ) -> AppResult<AgentResponse> {
     // Get schema for context (for schema-related questions)
-    let schema = schema::get_schema(connections, connection_id, app).await?;
+    let schema = load_schema(connections, connection_id, app, settings.include_routines).await?;
     let conn = connections.get_connection(connection_id)?;
     let schema_str = format_schema_for_general(&schema, &conn.database_type);
 
@@ -229,57 +584,51 @@ Keep responses concise and helpful."#,
     messages.extend(previous_messages);
     messages.push(Message::user(&question));
 
+    if cancel_token.is_cancelled() {
+        return cancelled_response(app, &session_id, vec![]).await;
+    }
+
     let response = client
-        .chat_with_format(model, &messages, Some(0.7), None, None)
+        .chat_with_format(
+            model,
+            &messages,
+            Some(settings.final_answer_temperature),
+            Some(settings.final_answer_max_tokens),
+            None,
+            None,
+        )
         .await?;
 
-    emit_token(app, &session_id, &response).await?;
+    emit_token(app, &session_id, &response)?;
     emit_complete(app, &session_id, &response).await?;
 
     Ok(AgentResponse {
         answer: response,
         sql_queries: vec![],
+        results: Vec::new(),
         iterations: 1,
+        cancelled: false,
     })
 }
 
-/// Generate a final answer summarizing the query results
+/// Generate a final answer summarizing the query results, emitting it as `ai_token` events.
+/// LLM summaries are streamed as they arrive; the full text is returned for history.
+#[allow(clippy::too_many_arguments)]
 async fn generate_final_answer(
     question: &str,
     results: &[QueryResult],
     reasoning: &str,
     client: &OpenRouterClient,
     model: &str,
+    app: &AppHandle,
+    session_id: &str,
+    cancel_token: &CancellationToken,
+    temperature: f32,
+    max_tokens: u32,
 ) -> AppResult<String> {
-    if results.is_empty() {
-        return Ok("No data was retrieved to answer your question.".to_string());
-    }
-
-    // For simple single-result queries, we can provide a brief summary
-    if results.len() == 1 {
-        let result = &results[0];
-
-        if result.row_count == 0 {
-            return Ok("The query returned no results matching your criteria.".to_string());
-        }
-
-        if result.row_count == 1 && result.columns.len() == 1 {
-            // Single value result
-            if let Some(row) = result.rows.first() {
-                if let Some(value) = row.values().next() {
-                    return Ok(format!(
-                        "Based on your query, the answer is: **{}**",
-                        format_value(value)
-                    ));
-                }
-            }
-        }
-
-        // For table results, provide a summary
-        return Ok(format!(
-            "Found {} row(s) of data. The results are displayed in the table above.",
-            result.row_count
-        ));
+    if let Some(answer) = canned_final_answer(results) {
+        emit_token(app, session_id, &answer)?;
+        return Ok(answer);
     }
 
     // For complex multi-query results, use LLM to summarize
@@ -314,7 +663,50 @@ The actual data tables are already displayed, so focus on insights and summary."
         Message::user("Summarize the results."),
     ];
 
-    client.chat_with_format(model, &messages, Some(0.3), None, None).await
+    client
+        .chat_stream(model, &messages, Some(temperature), Some(max_tokens), |token| {
+            if cancel_token.is_cancelled() {
+                return Err(AppError::OperationCancelled("AI chat cancelled by user".into()));
+            }
+            emit_token(app, session_id, token)
+        })
+        .await
+}
+
+/// Answers that don't need the LLM: no data, no rows, a single value or a single table
+fn canned_final_answer(results: &[QueryResult]) -> Option<String> {
+    if results.is_empty() {
+        return Some("No data was retrieved to answer your question.".to_string());
+    }
+
+    // For simple single-result queries, we can provide a brief summary
+    if results.len() == 1 {
+        let result = &results[0];
+
+        if result.row_count == 0 {
+            return Some("The query returned no results matching your criteria.".to_string());
+        }
+
+        if result.row_count == 1 && result.columns.len() == 1 {
+            // Single value result
+            if let Some(row) = result.rows.first() {
+                if let Some(value) = row.values().next() {
+                    return Some(format!(
+                        "Based on your query, the answer is: **{}**",
+                        format_value(value)
+                    ));
+                }
+            }
+        }
+
+        // For table results, provide a summary
+        return Some(format!(
+            "Found {} row(s) of data. The results are displayed in the table above.",
+            result.row_count
+        ));
+    }
+
+    None
 }
 
 /// Format a JSON value for display
@@ -351,7 +743,8 @@ async fn emit_query_results(
 
     if should_emit_chart {
         // Generate Plotly visualization data as JSON
-        match generate_plotly_code(data, question_type, question) {
+        let options = ChartOptions::for_question(question);
+        match generate_plotly_code_with_options(data, question_type, question, &options) {
             Ok(plotly_viz) => {
                 app.emit(
                     "ai_plotly_chart",
@@ -381,24 +774,28 @@ fn should_show_table(question_type: &QuestionType, data: &QueryResult) -> bool {
         QuestionType::TemporalChart | QuestionType::CategoryChart => {
             data.row_count > 1 || data.columns.len() > 2
         }
+        QuestionType::Heatmap | QuestionType::Distribution => true,
         QuestionType::Complex => true,
-        QuestionType::General => false,
+        QuestionType::General | QuestionType::DataChange => false,
     }
 }
 
 /// Determine if chart should be shown
 fn should_show_chart(question_type: &QuestionType, data: &QueryResult) -> bool {
     match question_type {
-        QuestionType::TemporalChart | QuestionType::CategoryChart => data.row_count > 1,
+        QuestionType::TemporalChart
+        | QuestionType::CategoryChart
+        | QuestionType::Heatmap
+        | QuestionType::Distribution => data.row_count > 1,
         QuestionType::Statistic => false, // Single values don't need charts
         QuestionType::TableView => false,
         QuestionType::Complex => data.row_count > 1 && data.columns.len() >= 2,
-        QuestionType::General => false,
+        QuestionType::General | QuestionType::DataChange => false,
     }
 }
 
 /// Emit a token to the frontend (final answer content)
-async fn emit_token(app: &AppHandle, session_id: &str, content: &str) -> AppResult<()> {
+fn emit_token(app: &AppHandle, session_id: &str, content: &str) -> AppResult<()> {
     app.emit(
         "ai_token",
         serde_json::json!({
@@ -451,7 +848,7 @@ fn format_schema_for_general(schema: &Schema, db_type: &DatabaseType) -> String
     );
 
     for table in &schema.tables {
-        output.push_str(&format!("\n{}:\n", table.name));
+        output.push_str(&format!("\n{}:{}\n", table.prompt_label(), table.prompt_comment()));
 
         for col in &table.columns {
             let nullable = if col.is_nullable { "NULL" } else { "NOT NULL" };
@@ -467,11 +864,12 @@ fn format_schema_for_general(schema: &Schema, db_type: &DatabaseType) -> String
             };
 
             output.push_str(&format!(
-                "  - {} ({}) {}{}{}\n",
-                col.name, col.data_type, nullable, pk, fk
+                "  - {} ({}) {}{}{}{}\n",
+                col.name, col.prompt_type(), nullable, pk, fk, col.prompt_comment()
             ));
         }
     }
 
+    output.push_str(&schema.prompt_routines());
     output
 }
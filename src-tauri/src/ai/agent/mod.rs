@@ -3,6 +3,10 @@ pub mod selector;
 pub mod decomposer;
 pub mod refiner;
 pub mod mac_sql;
+pub mod tools;
 
 pub use state::*;
-pub use mac_sql::run_mac_sql_agent;
+pub use mac_sql::{
+    cancel_chat_session, confirm_write, register_chat_session, run_mac_sql_agent,
+    unregister_chat_session,
+};
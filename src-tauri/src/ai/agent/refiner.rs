@@ -1,3 +1,4 @@
+use crate::ai::openrouter::client::DEFAULT_MAX_TOKENS;
 use crate::ai::openrouter::OpenRouterClient;
 use crate::ai::agent::Message;
 use crate::ai::sanitizer;
@@ -5,6 +6,8 @@ use crate::db::connection::ConnectionManager;
 use crate::db::query::{self, QueryResult};
 use crate::db::schema::Schema;
 use crate::error::{AppError, AppResult};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
 
 /// Result from a single query refinement attempt
 #[derive(Debug, Clone)]
@@ -39,17 +42,40 @@ pub struct RefinerAgent<'a> {
     client: &'a OpenRouterClient,
     model: &'a str,
     max_attempts: u32,
+    query_timeout_ms: u64,
+    max_rows: u64,
+    result_cache_ttl: Duration,
+    temperature: f32,
+    max_tokens: u32,
 }
 
 impl<'a> RefinerAgent<'a> {
-    pub fn new(client: &'a OpenRouterClient, model: &'a str) -> Self {
+    pub fn new(client: &'a OpenRouterClient, model: &'a str, query_timeout_ms: u64, max_rows: u64) -> Self {
         Self {
             client,
             model,
             max_attempts: 3,
+            query_timeout_ms,
+            max_rows,
+            result_cache_ttl: Duration::ZERO,
+            temperature: 0.1, // Low temperature for consistent correction
+            max_tokens: DEFAULT_MAX_TOKENS,
         }
     }
 
+    /// Sampling temperature and output token cap for correction calls
+    pub fn with_sampling(mut self, temperature: f32, max_tokens: u32) -> Self {
+        self.temperature = temperature;
+        self.max_tokens = max_tokens;
+        self
+    }
+
+    /// Reuse results of identical read queries for `ttl` (zero always runs them)
+    pub fn with_result_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.result_cache_ttl = ttl;
+        self
+    }
+
     /// Refine and execute a SQL query with self-correction
     pub async fn refine_and_execute(
         &self,
@@ -59,6 +85,7 @@ impl<'a> RefinerAgent<'a> {
         db_type: &str,
         connection_id: &str,
         connections: &ConnectionManager,
+        cancel_token: &CancellationToken,
     ) -> AppResult<RefinerResult> {
         let mut current_sql = original_sql.to_string();
         let mut history: Vec<RefinementAttempt> = Vec::new();
@@ -93,6 +120,11 @@ impl<'a> RefinerAgent<'a> {
                         )));
                     }
 
+                    // Don't spend another LLM call if the user gave up
+                    if cancel_token.is_cancelled() {
+                        return Err(AppError::OperationCancelled("AI chat cancelled by user".into()));
+                    }
+
                     // Try to refine the query
                     current_sql = self.generate_corrected_sql(
                         original_question,
@@ -121,18 +153,22 @@ impl<'a> RefinerAgent<'a> {
         connections: &ConnectionManager,
     ) -> AppResult<QueryResult> {
         // First, sanitize the SQL
-        let sanitized = sanitizer::validate_sql(sql)?;
+        let database_type = connections.get_connection(connection_id)?.database_type;
+        let sanitized = sanitizer::validate_sql_with_limit(sql, self.max_rows, &database_type)?;
 
         // Validate for the specific database type
         sanitizer::validate_for_db_type(&sanitized, db_type)?;
 
         // Execute the query
-        query::execute_query(
+        query::execute_query_cached(
             connections,
             connection_id,
             &sanitized,
-            100, // Max rows
-            0,   // Offset
+            &[],
+            Some(i32::try_from(self.max_rows).unwrap_or(i32::MAX)),
+            0, // Offset
+            self.query_timeout_ms,
+            self.result_cache_ttl,
         ).await
     }
 
@@ -193,11 +229,11 @@ COMMON FIXES:
 - Column not found: Verify column exists in the table
 - Syntax error: Check for missing quotes, commas, or parentheses
 - Type mismatch: Ensure comparisons use matching types
-- Missing LIMIT: Always include LIMIT clause (max 100)
+- Missing LIMIT: Always include LIMIT clause (max {})
 
 Respond with ONLY the corrected SQL query, no explanation. The query must:
 - Be a valid SELECT statement
-- Include LIMIT clause (max 100)
+- Include LIMIT clause (max {})
 - Use correct {} syntax"#,
             db_type, db_type,
             schema_str,
@@ -205,7 +241,10 @@ Respond with ONLY the corrected SQL query, no explanation. The query must:
             failed_sql,
             error_message,
             attempt_history,
-            db_type, db_type
+            db_type,
+            self.max_rows,
+            self.max_rows,
+            db_type
         );
 
         let messages = vec![
@@ -217,7 +256,8 @@ Respond with ONLY the corrected SQL query, no explanation. The query must:
             .chat_with_format(
                 self.model,
                 &messages,
-                Some(0.1), // Low temperature for consistent correction
+                Some(self.temperature),
+                Some(self.max_tokens),
                 None,
                 None,
             )
@@ -232,7 +272,7 @@ Respond with ONLY the corrected SQL query, no explanation. The query must:
         let mut output = String::new();
 
         for table in &schema.tables {
-            output.push_str(&format!("\n{}:\n", table.name));
+            output.push_str(&format!("\n{}:{}\n", table.prompt_label(), table.prompt_comment()));
 
             for col in &table.columns {
                 let nullable = if col.is_nullable { "NULL" } else { "NOT NULL" };
@@ -255,12 +295,13 @@ Respond with ONLY the corrected SQL query, no explanation. The query must:
                 };
 
                 output.push_str(&format!(
-                    "  - {} ({}) {}{}{}{}\n",
-                    col.name, col.data_type, nullable, pk, fk, highlight
+                    "  - {} ({}) {}{}{}{}{}\n",
+                    col.name, col.prompt_type(), nullable, pk, fk, col.prompt_comment(), highlight
                 ));
             }
         }
 
+        output.push_str(&schema.prompt_routines());
         output
     }
 
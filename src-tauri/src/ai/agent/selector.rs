@@ -1,7 +1,25 @@
+use crate::ai::cache::{cache_key, ResponseCache};
+use crate::ai::openrouter::types::{JsonSchema, ResponseFormat};
+use crate::ai::openrouter::client::DEFAULT_MAX_TOKENS;
 use crate::ai::openrouter::OpenRouterClient;
-use crate::ai::agent::Message;
+use crate::ai::agent::{tools, Message};
 use crate::db::schema::{Schema, Table, ColumnInfo};
 use crate::error::{AppError, AppResult};
+use std::time::Duration;
+
+/// Maximum cached schema selection responses
+const SELECTION_CACHE_SIZE: usize = 128;
+
+/// Above this many tables the prompt lists table names only and the model
+/// fetches columns with the `describe_table` tool
+const TOOL_LOOKUP_TABLE_THRESHOLD: usize = 40;
+
+/// Tool-call rounds allowed before the selection is requested without tools
+const MAX_LOOKUP_ROUNDS: usize = 4;
+
+lazy_static::lazy_static! {
+    static ref SELECTION_CACHE: ResponseCache = ResponseCache::new(SELECTION_CACHE_SIZE);
+}
 
 /// Result from the Selector Agent
 #[derive(Debug, Clone)]
@@ -19,11 +37,43 @@ pub struct SelectorResult {
 pub struct SelectorAgent<'a> {
     client: &'a OpenRouterClient,
     model: &'a str,
+    cache_ttl: Duration,
+    temperature: f32,
+    max_tokens: u32,
+    /// Answers `sample_column_values`; without one the tool isn't offered
+    sampler: Option<&'a tools::ValueSampler<'a>>,
 }
 
 impl<'a> SelectorAgent<'a> {
     pub fn new(client: &'a OpenRouterClient, model: &'a str) -> Self {
-        Self { client, model }
+        Self {
+            client,
+            model,
+            cache_ttl: Duration::ZERO,
+            temperature: 0.1, // Low temperature for consistent selection
+            max_tokens: DEFAULT_MAX_TOKENS,
+            sampler: None,
+        }
+    }
+
+    /// Let the model look at real column values while selecting; selections reused from
+    /// the cache sample nothing
+    pub fn with_value_sampler(mut self, sampler: &'a tools::ValueSampler<'a>) -> Self {
+        self.sampler = Some(sampler);
+        self
+    }
+
+    /// Sampling temperature and output token cap for selection calls
+    pub fn with_sampling(mut self, temperature: f32, max_tokens: u32) -> Self {
+        self.temperature = temperature;
+        self.max_tokens = max_tokens;
+        self
+    }
+
+    /// Reuse selections for the same question, model and schema for `ttl` (zero disables)
+    pub fn with_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache_ttl = ttl;
+        self
     }
 
     /// Run the selector agent to prune the schema
@@ -32,13 +82,33 @@ impl<'a> SelectorAgent<'a> {
         question: &str,
         full_schema: &Schema,
     ) -> AppResult<SelectorResult> {
-        // Build the prompt for schema selection
-        let schema_summary = self.build_schema_summary(full_schema);
+        // Identical question against an unchanged schema: reuse the earlier selection
+        let fingerprint = full_schema.fingerprint();
+        let key = cache_key(&["selector", self.model, &fingerprint, question]);
+        if let Some(cached) = SELECTION_CACHE.get(key, self.cache_ttl) {
+            return self.parse_selection_response(&cached, full_schema);
+        }
+
+        // Build the prompt for schema selection; large schemas are listed by name only
+        let lookup = full_schema.tables.len() > TOOL_LOOKUP_TABLE_THRESHOLD;
+        let mut schema_section = if lookup {
+            format!(
+                "TABLES (call describe_table to see a table's columns before selecting it):\n{}",
+                tools::table_name_list(full_schema)
+            )
+        } else {
+            format!("DATABASE SCHEMA:\n{}", self.build_schema_summary(full_schema))
+        };
+        if self.sampler.is_some() {
+            schema_section.push_str(
+                "\nWhen the question filters on a text or categorical value, call sample_column_values \
+                 on that column to see how its values are actually written.\n",
+            );
+        }
 
         let system_prompt = format!(
             r#"You are a database schema analyst. Your task is to identify which tables and columns are relevant to answer a user's question.
 
-DATABASE SCHEMA:
 {}
 
 INSTRUCTIONS:
@@ -63,7 +133,7 @@ Respond in this exact JSON format:
         }}
     ]
 }}"#,
-            schema_summary
+            schema_section
         );
 
         let messages = vec![
@@ -72,56 +142,87 @@ Respond in this exact JSON format:
         ];
 
         // Call LLM for schema selection
-        let response = self.client
+        let response = if lookup || self.sampler.is_some() {
+            self.select_with_lookup(messages, full_schema).await?
+        } else {
+            self.client
+                .chat_with_format(
+                    self.model,
+                    &messages,
+                    Some(self.temperature),
+                    Some(self.max_tokens),
+                    Some(selection_response_format()),
+                    None,
+                )
+                .await?
+        };
+
+        let result = self.parse_selection_response(&response, full_schema)?;
+        if !self.cache_ttl.is_zero() {
+            SELECTION_CACHE.insert(key, response);
+        }
+        Ok(result)
+    }
+
+    /// Answer the model's `describe_table` and `sample_column_values` calls until it stops
+    /// calling tools or the allowed rounds run out, then request the selection without
+    /// tools under the strict selection response format.
+    async fn select_with_lookup(
+        &self,
+        mut messages: Vec<Message>,
+        full_schema: &Schema,
+    ) -> AppResult<String> {
+        for _ in 0..MAX_LOOKUP_ROUNDS {
+            let reply = self
+                .client
+                .chat_with_tools(
+                    self.model,
+                    &messages,
+                    Some(self.temperature),
+                    Some(self.max_tokens),
+                    tools::build_tools(self.sampler.is_some()),
+                )
+                .await?;
+
+            let calls = reply.tool_calls.unwrap_or_default();
+            if calls.is_empty() {
+                // Free-form tool-turn content isn't held to the response format
+                break;
+            }
+
+            let mut results: Vec<Message> = Vec::with_capacity(calls.len());
+            for call in &calls {
+                let output = match self.sampler {
+                    Some(sampler) if call.function.name == tools::SAMPLE_COLUMN_VALUES_TOOL => {
+                        sampler.sample(call, full_schema).await
+                    }
+                    _ => tools::execute_tool_call(call, full_schema),
+                };
+                results.push(Message::tool(&call.id, output));
+            }
+            messages.push(Message::assistant_tool_calls(reply.content.unwrap_or_default(), calls));
+            messages.extend(results);
+        }
+
+        self.client
             .chat_with_format(
                 self.model,
                 &messages,
-                Some(0.1), // Low temperature for consistent selection
-                None,
+                Some(self.temperature),
+                Some(self.max_tokens),
+                Some(selection_response_format()),
                 None,
             )
-            .await?;
-
-        // Parse the response
-        self.parse_selection_response(&response, full_schema)
+            .await
     }
 
     /// Build a compact schema summary for the LLM
     fn build_schema_summary(&self, schema: &Schema) -> String {
-        let mut output = String::new();
-
-        for table in &schema.tables {
-            output.push_str(&format!("\n{}:\n", table.name));
-
-            for col in &table.columns {
-                let markers = self.column_markers(col);
-                output.push_str(&format!("  - {} ({}){}\n", col.name, col.data_type, markers));
-            }
-        }
-
-        output
-    }
-
-    /// Build column markers (PK, FK, etc.)
-    fn column_markers(&self, col: &ColumnInfo) -> String {
-        let mut markers = Vec::new();
-
-        if col.is_primary_key {
-            markers.push("PK".to_string());
-        }
-        if col.is_foreign_key {
-            if let (Some(ref_table), Some(ref_col)) = (&col.foreign_key_table, &col.foreign_key_column) {
-                markers.push(format!("FK->{}.{}", ref_table, ref_col));
-            } else {
-                markers.push("FK".to_string());
-            }
-        }
-
-        if markers.is_empty() {
-            String::new()
-        } else {
-            format!(" [{}]", markers.join(", "))
-        }
+        schema
+            .tables
+            .iter()
+            .map(|table| format!("\n{}", tools::describe_table(table)))
+            .collect()
     }
 
     /// Parse the LLM response and build the pruned schema
@@ -130,12 +231,13 @@ Respond in this exact JSON format:
         response: &str,
         full_schema: &Schema,
     ) -> AppResult<SelectorResult> {
-        // Extract JSON from response (handle markdown code blocks)
-        let json_str = self.extract_json(response);
-
-        // Parse the JSON response
-        let parsed: serde_json::Value = serde_json::from_str(&json_str)
-            .map_err(|e| AppError::AgentError(format!("Failed to parse selector response: {}. Response: {}", e, response)))?;
+        // Structured output is plain JSON; fall back to extraction for models that
+        // ignore response_format and wrap it in prose or code blocks
+        let parsed: serde_json::Value = match serde_json::from_str(response.trim()) {
+            Ok(parsed) => parsed,
+            Err(_) => serde_json::from_str(&self.extract_json(response))
+                .map_err(|e| AppError::AgentError(format!("Failed to parse selector response: {}. Response: {}", e, response)))?,
+        };
 
         let tables_array = parsed["tables"]
             .as_array()
@@ -151,7 +253,8 @@ Respond in this exact JSON format:
                 .ok_or_else(|| AppError::AgentError("Invalid table object: missing name".into()))?;
 
             // Find the table in the full schema
-            if let Some(full_table) = full_schema.tables.iter().find(|t| t.name.eq_ignore_ascii_case(table_name)) {
+            // Tables outside `public` are shown schema-qualified; accept either form
+            if let Some(full_table) = full_schema.find_table(table_name) {
                 let column_names: Vec<String> = table_obj["columns"]
                     .as_array()
                     .map(|arr| {
@@ -195,9 +298,11 @@ Respond in this exact JSON format:
                     indexes: full_table.indexes.clone(),
                     triggers: full_table.triggers.clone(),
                     constraints: full_table.constraints.clone(),
+                    is_view: full_table.is_view,
+                    comment: full_table.comment.clone(),
                 });
 
-                selected_table_names.push(full_table.name.clone());
+                selected_table_names.push(full_table.qualified_name());
             }
         }
 
@@ -205,7 +310,7 @@ Respond in this exact JSON format:
         if pruned_tables.is_empty() {
             return Ok(SelectorResult {
                 pruned_schema: full_schema.clone(),
-                selected_tables: full_schema.tables.iter().map(|t| t.name.clone()).collect(),
+                selected_tables: full_schema.tables.iter().map(|t| t.qualified_name()).collect(),
             });
         }
 
@@ -213,6 +318,7 @@ Respond in this exact JSON format:
             pruned_schema: Schema {
                 database_name: full_schema.database_name.clone(),
                 tables: pruned_tables,
+                routines: full_schema.routines.clone(),
             },
             selected_tables: selected_table_names,
         })
@@ -252,10 +358,73 @@ Respond in this exact JSON format:
     }
 }
 
+/// Strict JSON schema for `{reasoning, tables: [{name, columns}]}`
+fn selection_response_format() -> ResponseFormat {
+    let schema = serde_json::json!({
+        "type": "object",
+        "properties": {
+            "reasoning": {
+                "type": "string",
+                "description": "Brief explanation of why these tables/columns are needed"
+            },
+            "tables": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "name": { "type": "string" },
+                        "columns": { "type": "array", "items": { "type": "string" } }
+                    },
+                    "required": ["name", "columns"],
+                    "additionalProperties": false
+                }
+            }
+        },
+        "required": ["reasoning", "tables"],
+        "additionalProperties": false
+    });
+
+    ResponseFormat {
+        format_type: "json_schema".to_string(),
+        json_schema: Some(JsonSchema {
+            name: "schema_selection".to_string(),
+            strict: true,
+            schema,
+        }),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_structured_and_prose_responses() {
+        let client = OpenRouterClient::new("test".to_string());
+        let agent = SelectorAgent::new(&client, "test-model");
+        let schema = Schema {
+            database_name: "db".to_string(),
+            tables: vec![Table::fixture(
+                "users",
+                vec![ColumnInfo::fixture("email"), ColumnInfo::fixture("name")],
+            )],
+            routines: vec![],
+        };
+
+        // Structured output whose reasoning mentions a code fence would trip extract_json
+        let structured = r#"{"reasoning":"filter on ``` markers","tables":[{"name":"users","columns":["email"]}]}"#;
+        let result = agent.parse_selection_response(structured, &schema).unwrap();
+        assert_eq!(result.selected_tables, vec!["users"]);
+        assert_eq!(result.pruned_schema.tables[0].columns.len(), 1);
+
+        let prose = "Sure!\n```json\n{\"reasoning\":\"x\",\"tables\":[{\"name\":\"users\",\"columns\":[]}]}\n```";
+        let result = agent.parse_selection_response(prose, &schema).unwrap();
+        assert_eq!(result.pruned_schema.tables[0].columns.len(), 2);
+
+        let format = selection_response_format();
+        assert!(format.json_schema.unwrap().strict);
+    }
+
     #[test]
     fn test_extract_json_from_code_block() {
         let client = OpenRouterClient::new("test".to_string());
@@ -1,3 +1,4 @@
+use crate::db::query::QueryResult;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
@@ -9,8 +10,11 @@ pub enum QuestionType {
     TableView,       // "show me users"
     TemporalChart,   // "users joined over time"
     CategoryChart,   // "users by country"
+    Heatmap,         // "orders by weekday and hour"
+    Distribution,    // "spread of order values by region"
     Statistic,       // "how many users"
     Complex,         // Multi-step analysis
+    DataChange,      // "mark order 5 as shipped" (write mode only)
 }
 
 
@@ -25,6 +29,12 @@ pub struct Message {
     pub tool_call_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tool_calls: Option<Vec<crate::ai::openrouter::types::ToolCall>>,
+    /// SQL run to produce an assistant answer (kept for conversation export)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sql_queries: Vec<String>,
+    /// Query results behind an assistant answer (kept for conversation export)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub results: Vec<QueryResult>,
 }
 
 impl Message {
@@ -35,6 +45,8 @@ impl Message {
             timestamp: Utc::now(),
             tool_call_id: None,
             tool_calls: None,
+            sql_queries: Vec::new(),
+            results: Vec::new(),
         }
     }
 
@@ -45,6 +57,8 @@ impl Message {
             timestamp: Utc::now(),
             tool_call_id: None,
             tool_calls: None,
+            sql_queries: Vec::new(),
+            results: Vec::new(),
         }
     }
 
@@ -55,8 +69,41 @@ impl Message {
             timestamp: Utc::now(),
             tool_call_id: None,
             tool_calls: None,
+            sql_queries: Vec::new(),
+            results: Vec::new(),
+        }
+    }
+
+    /// Assistant turn that requested tool calls
+    pub fn assistant_tool_calls(
+        content: impl Into<String>,
+        tool_calls: Vec<crate::ai::openrouter::types::ToolCall>,
+    ) -> Self {
+        Self {
+            tool_calls: Some(tool_calls),
+            ..Self::assistant(content)
+        }
+    }
+
+    /// Result of a tool call, answering the call with `tool_call_id`
+    pub fn tool(tool_call_id: impl Into<String>, content: impl Into<String>) -> Self {
+        Self {
+            role: MessageRole::Tool,
+            content: content.into(),
+            timestamp: Utc::now(),
+            tool_call_id: Some(tool_call_id.into()),
+            tool_calls: None,
+            sql_queries: Vec::new(),
+            results: Vec::new(),
         }
     }
+
+    /// Attach the SQL and results an assistant answer was based on
+    pub fn with_query_output(mut self, sql_queries: Vec<String>, results: Vec<QueryResult>) -> Self {
+        self.sql_queries = sql_queries;
+        self.results = results;
+        self
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -73,5 +120,9 @@ pub enum MessageRole {
 pub struct AgentResponse {
     pub answer: String,
     pub sql_queries: Vec<String>,
+    /// Results of the queries that succeeded, in execution order
+    pub results: Vec<QueryResult>,
     pub iterations: u8,
+    /// True when the user cancelled the session before it finished
+    pub cancelled: bool,
 }
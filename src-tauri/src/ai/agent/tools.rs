@@ -0,0 +1,374 @@
+use crate::ai::openrouter::types::{FunctionDefinition, Tool, ToolCall};
+use crate::ai::sanitizer;
+use crate::db::connection::{ConnectionManager, DatabaseType};
+use crate::db::query::{self, grid_cell_text, quote_identifier, quote_table};
+use crate::db::schema::{ColumnInfo, Schema, Table};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Name of the tool that returns a table's columns
+pub const DESCRIBE_TABLE_TOOL: &str = "describe_table";
+
+/// Name of the tool that returns distinct values of a column from the live data
+pub const SAMPLE_COLUMN_VALUES_TOOL: &str = "sample_column_values";
+
+/// Distinct values returned per sampled column
+const SAMPLE_VALUE_LIMIT: u64 = 20;
+
+/// Rows scanned per sample, so sampling a huge table stays cheap
+const SAMPLE_SCAN_ROWS: u64 = 10_000;
+
+/// Sampling queries a single question may run; repeated samples of a column are free
+const MAX_SAMPLE_QUERIES: usize = 6;
+
+/// Time limit for one sampling query, also when query timeouts are disabled
+const SAMPLE_QUERY_TIMEOUT_MS: u64 = 5_000;
+
+/// Sampled values longer than this are cut short
+const SAMPLE_VALUE_MAX_CHARS: usize = 100;
+
+/// Tools offered to the model in the schema lookup loop; `sample_column_values` only
+/// when a `ValueSampler` can answer it
+pub fn build_tools(sampling: bool) -> Vec<Tool> {
+    let mut tools = vec![Tool {
+        tool_type: "function".to_string(),
+        function: FunctionDefinition {
+            name: DESCRIBE_TABLE_TOOL.to_string(),
+            description: "Get the columns of a table, with types and primary/foreign key markers. \
+                Call it for every table you might use before answering."
+                .to_string(),
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "table_name": {
+                        "type": "string",
+                        "description": "Table name exactly as listed"
+                    }
+                },
+                "required": ["table_name"],
+                "additionalProperties": false
+            }),
+        },
+    }];
+
+    if sampling {
+        tools.push(Tool {
+            tool_type: "function".to_string(),
+            function: FunctionDefinition {
+                name: SAMPLE_COLUMN_VALUES_TOOL.to_string(),
+                description: format!(
+                    "Get up to {} distinct non-null values of a column from the actual data. \
+                     Use it for text or categorical columns you will filter on, to learn the exact \
+                     spelling and casing of values (e.g. 'active' vs 'ACTIVE'). Sampling is limited, \
+                     so only sample columns that matter.",
+                    SAMPLE_VALUE_LIMIT
+                ),
+                parameters: serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "table_name": {
+                            "type": "string",
+                            "description": "Table name exactly as listed"
+                        },
+                        "column_name": {
+                            "type": "string",
+                            "description": "Column of that table"
+                        }
+                    },
+                    "required": ["table_name", "column_name"],
+                    "additionalProperties": false
+                }),
+            },
+        });
+    }
+
+    tools
+}
+
+/// Run a tool call against the loaded schema and return the text sent back to the model.
+/// Unknown tools, bad arguments and missing tables are reported to the model, not raised.
+pub fn execute_tool_call(call: &ToolCall, schema: &Schema) -> String {
+    if call.function.name != DESCRIBE_TABLE_TOOL {
+        return format!("Unknown tool '{}'", call.function.name);
+    }
+
+    let arguments: serde_json::Value =
+        serde_json::from_str(&call.function.arguments).unwrap_or_default();
+    let Some(table_name) = arguments["table_name"].as_str() else {
+        return "Missing required argument 'table_name'".to_string();
+    };
+
+    match schema.find_table(table_name) {
+        Some(table) => describe_table(table),
+        None => format!("Table '{}' does not exist", table_name),
+    }
+}
+
+/// Answers `sample_column_values` calls for one question by querying the connection.
+/// Queries are read-only SELECTs checked by the sanitizer, scan at most
+/// `SAMPLE_SCAN_ROWS` rows, and are capped at `MAX_SAMPLE_QUERIES` per question;
+/// a column sampled before is answered from memory.
+pub struct ValueSampler<'a> {
+    connections: &'a ConnectionManager,
+    connection_id: &'a str,
+    db_type: DatabaseType,
+    max_rows: u64,
+    timeout_ms: u64,
+    state: Mutex<SamplerState>,
+}
+
+#[derive(Default)]
+struct SamplerState {
+    queries_run: usize,
+    /// `table.column` and the values found, in the order they were sampled
+    samples: Vec<(String, Vec<String>)>,
+}
+
+impl<'a> ValueSampler<'a> {
+    /// `max_rows` is the AI row limit; `timeout_ms` the query timeout (0 means none)
+    pub fn new(
+        connections: &'a ConnectionManager,
+        connection_id: &'a str,
+        db_type: DatabaseType,
+        max_rows: u64,
+        timeout_ms: u64,
+    ) -> Self {
+        Self {
+            connections,
+            connection_id,
+            db_type,
+            max_rows,
+            timeout_ms,
+            state: Mutex::new(SamplerState::default()),
+        }
+    }
+
+    /// Run a `sample_column_values` call and return the text sent back to the model.
+    /// Bad arguments, unknown columns, an exhausted budget and query errors are reported
+    /// to the model, not raised.
+    pub async fn sample(&self, call: &ToolCall, schema: &Schema) -> String {
+        let arguments: serde_json::Value =
+            serde_json::from_str(&call.function.arguments).unwrap_or_default();
+        let (Some(table_name), Some(column_name)) =
+            (arguments["table_name"].as_str(), arguments["column_name"].as_str())
+        else {
+            return "Missing required arguments 'table_name' and 'column_name'".to_string();
+        };
+        let Some(table) = schema.find_table(table_name) else {
+            return format!("Table '{}' does not exist", table_name);
+        };
+        let Some(column) = table.columns.iter().find(|c| c.name.eq_ignore_ascii_case(column_name)) else {
+            return format!("Column '{}' does not exist in table '{}'", column_name, table_name);
+        };
+
+        let key = format!("{}.{}", table.qualified_name(), column.name);
+        {
+            let Ok(mut state) = self.state.lock() else {
+                return "Sampling is unavailable".to_string();
+            };
+            if let Some((_, values)) = state.samples.iter().find(|(k, _)| *k == key) {
+                return format_sample(&key, values);
+            }
+            if state.queries_run >= MAX_SAMPLE_QUERIES {
+                return format!(
+                    "Sampling limit reached ({} columns per question); continue without sampling",
+                    MAX_SAMPLE_QUERIES
+                );
+            }
+            state.queries_run += 1;
+        }
+
+        match self.query_values(&table.qualified_name(), &column.name).await {
+            Ok(values) => {
+                let text = format_sample(&key, &values);
+                if let Ok(mut state) = self.state.lock() {
+                    state.samples.push((key, values));
+                }
+                text
+            }
+            Err(e) => format!("Could not sample {}: {}", key, e),
+        }
+    }
+
+    async fn query_values(&self, table_name: &str, column_name: &str) -> crate::error::AppResult<Vec<String>> {
+        let column = quote_identifier(column_name, &self.db_type);
+        let sql = format!(
+            "SELECT DISTINCT {col} FROM (SELECT {col} FROM {table} LIMIT {scan}) AS sampled \
+             WHERE {col} IS NOT NULL ORDER BY 1 LIMIT {limit}",
+            col = column,
+            table = quote_table(table_name, &self.db_type),
+            scan = SAMPLE_SCAN_ROWS,
+            limit = SAMPLE_VALUE_LIMIT,
+        );
+
+        // Same checks as any AI query: read-only, and within the AI row limit
+        let max_rows = SAMPLE_VALUE_LIMIT.min(self.max_rows.max(1));
+        let sanitized = sanitizer::validate_sql_with_limit(&sql, max_rows, &self.db_type)?;
+        let timeout_ms = match self.timeout_ms {
+            0 => SAMPLE_QUERY_TIMEOUT_MS,
+            timeout_ms => timeout_ms.min(SAMPLE_QUERY_TIMEOUT_MS),
+        };
+        let result = query::execute_query_cached(
+            self.connections,
+            self.connection_id,
+            &sanitized,
+            &[],
+            Some(max_rows as i32),
+            0,
+            timeout_ms,
+            Duration::ZERO,
+        )
+        .await?;
+
+        Ok(result
+            .rows
+            .iter()
+            .filter_map(|row| row.values().next())
+            .map(|value| grid_cell_text(Some(value)).chars().take(SAMPLE_VALUE_MAX_CHARS).collect())
+            .collect())
+    }
+
+    /// Values sampled so far as prompt lines, e.g. `- orders.status: 'active', 'cancelled'`;
+    /// empty when nothing was sampled
+    pub fn prompt_section(&self) -> String {
+        let Ok(state) = self.state.lock() else {
+            return String::new();
+        };
+        state
+            .samples
+            .iter()
+            .map(|(key, values)| format!("- {}\n", format_sample(key, values)))
+            .collect()
+    }
+}
+
+/// `orders.status: 'active', 'cancelled'`, quoted as SQL literals
+fn format_sample(key: &str, values: &[String]) -> String {
+    if values.is_empty() {
+        return format!("{}: no non-null values", key);
+    }
+    let listed: Vec<String> = values.iter().map(|v| format!("'{}'", v.replace('\'', "''"))).collect();
+    let more = if values.len() as u64 >= SAMPLE_VALUE_LIMIT { ", ..." } else { "" };
+    format!("{}: {}{}", key, listed.join(", "), more)
+}
+
+/// Table heading followed by one line per column, e.g. `  - user_id (integer) [FK->users.id]`
+pub fn describe_table(table: &Table) -> String {
+    let mut output = format!("{}:{}\n", table.prompt_label(), table.prompt_comment());
+    for col in &table.columns {
+        output.push_str(&format!(
+            "  - {} ({}){}{}\n",
+            col.name,
+            col.prompt_type(),
+            column_markers(col),
+            col.prompt_comment()
+        ));
+    }
+    output
+}
+
+/// Table names only, one per line, for prompts where columns are fetched with `describe_table`
+pub fn table_name_list(schema: &Schema) -> String {
+    schema
+        .tables
+        .iter()
+        .map(|table| format!("- {}\n", table.prompt_label()))
+        .collect()
+}
+
+/// Build column markers (PK, FK, etc.)
+fn column_markers(col: &ColumnInfo) -> String {
+    let mut markers = Vec::new();
+
+    if col.is_primary_key {
+        markers.push("PK".to_string());
+    }
+    if col.is_foreign_key {
+        if let (Some(ref_table), Some(ref_col)) = (&col.foreign_key_table, &col.foreign_key_column) {
+            markers.push(format!("FK->{}.{}", ref_table, ref_col));
+        } else {
+            markers.push("FK".to_string());
+        }
+    }
+
+    if markers.is_empty() {
+        String::new()
+    } else {
+        format!(" [{}]", markers.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::openrouter::types::FunctionCall;
+
+    fn schema_with_tables(count: usize) -> Schema {
+        let column = |name: &str| ColumnInfo::fixture(name).with_type("integer").not_null();
+        let tables = (0..count)
+            .map(|i| {
+                let columns = std::iter::once(column("id").primary_key())
+                    .chain((0..12).map(|c| column(&format!("attribute_{}", c))))
+                    .chain(std::iter::once(column("parent_id").references("table_0", "id")))
+                    .collect();
+                Table::fixture(&format!("table_{}", i), columns)
+            })
+            .collect();
+        Schema {
+            database_name: "db".to_string(),
+            tables,
+            routines: vec![],
+        }
+    }
+
+    fn call(arguments: &str) -> ToolCall {
+        ToolCall {
+            id: "call_1".to_string(),
+            call_type: "function".to_string(),
+            function: FunctionCall {
+                name: DESCRIBE_TABLE_TOOL.to_string(),
+                arguments: arguments.to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn describe_table_tool_returns_columns_or_reports_problems() {
+        let schema = schema_with_tables(2);
+
+        let described = execute_tool_call(&call(r#"{"table_name":"TABLE_1"}"#), &schema);
+        assert!(described.starts_with("table_1:\n  - id (integer) [PK]\n"));
+        assert!(described.contains("  - parent_id (integer) [FK->table_0.id]\n"));
+
+        assert_eq!(
+            execute_tool_call(&call(r#"{"table_name":"missing"}"#), &schema),
+            "Table 'missing' does not exist"
+        );
+        assert!(execute_tool_call(&call("not json"), &schema).starts_with("Missing required argument"));
+    }
+
+    #[test]
+    fn sampling_tool_is_only_offered_with_a_sampler() {
+        let names = |tools: Vec<Tool>| tools.into_iter().map(|t| t.function.name).collect::<Vec<_>>();
+        assert_eq!(names(build_tools(false)), vec![DESCRIBE_TABLE_TOOL]);
+        assert_eq!(names(build_tools(true)), vec![DESCRIBE_TABLE_TOOL, SAMPLE_COLUMN_VALUES_TOOL]);
+
+        assert_eq!(
+            format_sample("orders.status", &["active".to_string(), "on 'hold'".to_string()]),
+            "orders.status: 'active', 'on ''hold'''"
+        );
+        let full: Vec<String> = (0..SAMPLE_VALUE_LIMIT).map(|i| i.to_string()).collect();
+        assert!(format_sample("t.c", &full).ends_with(", ..."));
+        assert_eq!(format_sample("t.c", &[]), "t.c: no non-null values");
+    }
+
+    #[test]
+    fn table_name_list_is_far_smaller_than_full_schema() {
+        let schema = schema_with_tables(200);
+        let full: usize = schema.tables.iter().map(|t| describe_table(t).len()).sum();
+        let names = table_name_list(&schema);
+
+        assert_eq!(names.lines().count(), 200);
+        assert!(names.len() * 10 < full, "{} vs {}", names.len(), full);
+    }
+}
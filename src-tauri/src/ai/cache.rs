@@ -0,0 +1,119 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Bounded in-memory cache of raw LLM responses with least-recently-used eviction.
+/// Entries older than the caller's TTL are treated as missing.
+pub struct ResponseCache {
+    capacity: usize,
+    entries: Mutex<CacheEntries>,
+}
+
+#[derive(Default)]
+struct CacheEntries {
+    values: HashMap<u64, (String, Instant)>,
+    /// Keys from least to most recently used
+    order: VecDeque<u64>,
+}
+
+impl CacheEntries {
+    fn touch(&mut self, key: u64) {
+        if let Some(pos) = self.order.iter().position(|k| *k == key) {
+            self.order.remove(pos);
+        }
+        self.order.push_back(key);
+    }
+
+    fn remove(&mut self, key: u64) {
+        self.values.remove(&key);
+        if let Some(pos) = self.order.iter().position(|k| *k == key) {
+            self.order.remove(pos);
+        }
+    }
+}
+
+impl ResponseCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            entries: Mutex::new(CacheEntries::default()),
+        }
+    }
+
+    /// Look up a response stored less than `ttl` ago. A zero TTL disables the cache.
+    pub fn get(&self, key: u64, ttl: Duration) -> Option<String> {
+        if ttl.is_zero() {
+            return None;
+        }
+
+        let mut entries = self.entries.lock().ok()?;
+        let (value, stored_at) = entries.values.get(&key)?.clone();
+
+        if stored_at.elapsed() > ttl {
+            entries.remove(key);
+            return None;
+        }
+
+        entries.touch(key);
+        Some(value)
+    }
+
+    /// Store a response, evicting the least recently used entry when full
+    pub fn insert(&self, key: u64, value: String) {
+        let Ok(mut entries) = self.entries.lock() else {
+            return;
+        };
+
+        entries.values.insert(key, (value, Instant::now()));
+        entries.touch(key);
+
+        while entries.values.len() > self.capacity {
+            let Some(oldest) = entries.order.pop_front() else {
+                break;
+            };
+            entries.values.remove(&oldest);
+        }
+    }
+}
+
+/// Hash the parts that identify a cached LLM call (question, model, schema fingerprint, ...)
+pub fn cache_key(parts: &[&str]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for part in parts {
+        part.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let cache = ResponseCache::new(2);
+        let ttl = Duration::from_secs(60);
+
+        cache.insert(1, "a".to_string());
+        cache.insert(2, "b".to_string());
+        // Reading 1 makes 2 the eviction candidate
+        assert_eq!(cache.get(1, ttl), Some("a".to_string()));
+        cache.insert(3, "c".to_string());
+
+        assert_eq!(cache.get(2, ttl), None);
+        assert_eq!(cache.get(1, ttl), Some("a".to_string()));
+        assert_eq!(cache.get(3, ttl), Some("c".to_string()));
+    }
+
+    #[test]
+    fn test_zero_ttl_disables_and_keys_differ() {
+        let cache = ResponseCache::new(4);
+        cache.insert(1, "a".to_string());
+        assert_eq!(cache.get(1, Duration::ZERO), None);
+
+        assert_ne!(cache_key(&["q", "model"]), cache_key(&["q", "other-model"]));
+        assert_eq!(cache_key(&["q", "model"]), cache_key(&["q", "model"]));
+    }
+}
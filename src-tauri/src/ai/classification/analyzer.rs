@@ -1,13 +1,26 @@
 use crate::ai::agent::{Message, QuestionType};
+use crate::ai::cache::{cache_key, ResponseCache};
 use crate::ai::openrouter::OpenRouterClient;
 use crate::ai::prompts;
 use crate::error::AppResult;
+use std::time::Duration;
 
-/// Classify question using LLM with structured outputs
+/// Maximum cached classification responses
+const CLASSIFICATION_CACHE_SIZE: usize = 256;
+
+lazy_static::lazy_static! {
+    static ref CLASSIFICATION_CACHE: ResponseCache = ResponseCache::new(CLASSIFICATION_CACHE_SIZE);
+}
+
+/// Classify question using LLM with structured outputs.
+/// Responses for the same question and model are reused for `cache_ttl` (zero disables).
 pub async fn classify_question(
     question: &str,
     openrouter_client: &OpenRouterClient,
     model: &str,
+    cache_ttl: Duration,
+    temperature: f32,
+    max_tokens: u32,
 ) -> AppResult<QuestionType> {
     use crate::ai::openrouter::types::{ResponseFormat, JsonSchema};
 
@@ -24,7 +37,7 @@ pub async fn classify_question(
         "properties": {
             "category": {
                 "type": "string",
-                "enum": ["general", "table_view", "temporal_chart", "category_chart", "statistic", "complex"],
+                "enum": ["general", "table_view", "temporal_chart", "category_chart", "heatmap", "distribution", "statistic", "complex", "data_change"],
                 "description": "The classification category for the question"
             },
             "confidence": {
@@ -46,9 +59,26 @@ pub async fn classify_question(
         }),
     };
 
-    let response = openrouter_client
-        .chat_with_format(model, &messages, Some(0.0), Some(response_format), None)
-        .await?;
+    let key = cache_key(&["classification", model, question]);
+    let response = match CLASSIFICATION_CACHE.get(key, cache_ttl) {
+        Some(cached) => cached,
+        None => {
+            let response = openrouter_client
+                .chat_with_format(
+                    model,
+                    &messages,
+                    Some(temperature),
+                    Some(max_tokens),
+                    Some(response_format),
+                    None,
+                )
+                .await?;
+            if !cache_ttl.is_zero() {
+                CLASSIFICATION_CACHE.insert(key, response.clone());
+            }
+            response
+        }
+    };
 
     // Parse JSON response
     let parsed: serde_json::Value = serde_json::from_str(&response)
@@ -63,8 +93,11 @@ pub async fn classify_question(
         "table_view" => Ok(QuestionType::TableView),
         "temporal_chart" => Ok(QuestionType::TemporalChart),
         "category_chart" => Ok(QuestionType::CategoryChart),
+        "heatmap" => Ok(QuestionType::Heatmap),
+        "distribution" => Ok(QuestionType::Distribution),
         "statistic" => Ok(QuestionType::Statistic),
         "complex" => Ok(QuestionType::Complex),
+        "data_change" => Ok(QuestionType::DataChange),
         _ => Ok(QuestionType::Complex),
     }
 }
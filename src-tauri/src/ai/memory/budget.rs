@@ -0,0 +1,122 @@
+use crate::ai::agent::{Message, MessageRole};
+
+/// Rough characters per token for English text and SQL; deliberately on the high side
+/// of real tokenizers so estimates err towards trimming too much rather than too little
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Role markers and separators the API adds around every message
+const MESSAGE_OVERHEAD_TOKENS: usize = 4;
+
+/// Approximate tokens a message takes up in a request, including its tool calls
+pub fn estimate_message_tokens(message: &Message) -> usize {
+    let tool_call_chars: usize = message
+        .tool_calls
+        .iter()
+        .flatten()
+        .map(|call| call.id.len() + call.function.name.len() + call.function.arguments.len())
+        .sum();
+    let chars = message.content.chars().count() + tool_call_chars;
+    MESSAGE_OVERHEAD_TOKENS + chars.div_ceil(CHARS_PER_TOKEN)
+}
+
+/// Drop the oldest history until its estimated size fits `budget` tokens (0 disables).
+/// Leading system messages (the conversation summary) are always kept. An assistant
+/// message with tool calls and the tool results answering it are dropped together, and
+/// tool results whose call is no longer in the history are dropped, so the request never
+/// starts mid-exchange. The system prompt and the new user message are not part of the
+/// history and never count against the budget.
+pub fn trim_to_token_budget(messages: Vec<Message>, budget: usize) -> Vec<Message> {
+    let pinned = messages
+        .iter()
+        .take_while(|m| matches!(m.role, MessageRole::System))
+        .count();
+    let mut messages = messages;
+    let mut rest = messages.split_off(pinned);
+
+    // Tool results left over from an exchange cut off by the message limit
+    let orphans = rest
+        .iter()
+        .take_while(|m| matches!(m.role, MessageRole::Tool))
+        .count();
+    rest.drain(..orphans);
+
+    if budget > 0 {
+        let mut total: usize = messages.iter().chain(&rest).map(estimate_message_tokens).sum();
+        let mut start = 0;
+        while total > budget && start < rest.len() {
+            // An exchange is one message plus the tool results that follow it
+            let end = start
+                + 1
+                + rest[start + 1..]
+                    .iter()
+                    .take_while(|m| matches!(m.role, MessageRole::Tool))
+                    .count();
+            total -= rest[start..end].iter().map(estimate_message_tokens).sum::<usize>();
+            start = end;
+        }
+        rest.drain(..start);
+    }
+
+    messages.extend(rest);
+    messages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::openrouter::types::{FunctionCall, ToolCall};
+
+    fn tool_call(id: &str) -> ToolCall {
+        ToolCall {
+            id: id.to_string(),
+            call_type: "function".to_string(),
+            function: FunctionCall {
+                name: "describe_table".to_string(),
+                arguments: r#"{"table_name":"orders"}"#.to_string(),
+            },
+        }
+    }
+
+    fn contents(messages: &[Message]) -> Vec<&str> {
+        messages.iter().map(|m| m.content.as_str()).collect()
+    }
+
+    #[test]
+    fn oldest_exchanges_go_first_and_tool_results_stay_with_their_call() {
+        let history = vec![
+            Message::system("Summary of the earlier conversation:\nTalked about orders"),
+            Message::user("first question"),
+            Message::assistant_tool_calls("", vec![tool_call("call_1")]),
+            Message::tool("call_1", "x".repeat(4_000)),
+            Message::assistant("first answer"),
+            Message::user("second question"),
+            Message::assistant("second answer"),
+        ];
+
+        let untouched = trim_to_token_budget(history.clone(), 0);
+        assert_eq!(untouched.len(), history.len());
+
+        let trimmed = trim_to_token_budget(history, 100);
+        assert_eq!(
+            contents(&trimmed),
+            vec![
+                "Summary of the earlier conversation:\nTalked about orders",
+                "first answer",
+                "second question",
+                "second answer"
+            ]
+        );
+        assert!(trimmed.iter().map(estimate_message_tokens).sum::<usize>() <= 100);
+    }
+
+    #[test]
+    fn orphaned_tool_results_are_dropped_and_the_summary_is_kept() {
+        let history = vec![
+            Message::system("summary"),
+            Message::tool("call_9", "late result"),
+            Message::user("question"),
+        ];
+        let trimmed = trim_to_token_budget(history, 1);
+        assert_eq!(contents(&trimmed), vec!["summary"]);
+    }
+}
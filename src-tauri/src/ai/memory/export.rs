@@ -0,0 +1,156 @@
+use super::storage::{load_conversation_history, ConversationHistory};
+use crate::ai::agent::{Message, MessageRole};
+use crate::db::query::{grid_cell_text, markdown_cell, QueryResult};
+use crate::error::{AppError, AppResult};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::path::Path;
+use tauri::AppHandle;
+
+/// Maximum result rows rendered per table in Markdown exports
+const MARKDOWN_MAX_ROWS: usize = 50;
+
+/// File format for exported conversations
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConversationExportFormat {
+    Markdown,
+    Json,
+}
+
+/// Write a saved conversation, including generated SQL and result data, to `output_path`.
+/// System and tool messages are only written when `include_internal` is set.
+pub fn export_conversation(
+    app: &AppHandle,
+    session_id: &str,
+    format: ConversationExportFormat,
+    output_path: &Path,
+    include_internal: bool,
+) -> AppResult<String> {
+    let mut history = load_conversation_history(app, session_id)?.ok_or_else(|| {
+        AppError::StorageError(format!("Conversation '{}' not found", session_id))
+    })?;
+    history
+        .messages
+        .retain(|message| include_internal || !is_internal(message));
+
+    let contents = match format {
+        ConversationExportFormat::Markdown => render_markdown(&history),
+        ConversationExportFormat::Json => serde_json::to_string_pretty(&history)?,
+    };
+    std::fs::write(output_path, contents)?;
+
+    Ok(output_path.to_string_lossy().to_string())
+}
+
+fn is_internal(message: &Message) -> bool {
+    matches!(message.role, MessageRole::System | MessageRole::Tool)
+}
+
+/// Render a conversation as Markdown with fenced SQL blocks and result tables
+fn render_markdown(history: &ConversationHistory) -> String {
+    let mut out = String::new();
+
+    let _ = writeln!(out, "# Conversation {}\n", history.session_id);
+    let _ = writeln!(
+        out,
+        "_Started {} · exported {}_\n",
+        history.created_at.format("%Y-%m-%d %H:%M UTC"),
+        Utc::now().format("%Y-%m-%d %H:%M UTC")
+    );
+
+    for message in &history.messages {
+        let role = match message.role {
+            MessageRole::System => "System",
+            MessageRole::User => "User",
+            MessageRole::Assistant => "Assistant",
+            MessageRole::Tool => "Tool",
+        };
+        let _ = writeln!(
+            out,
+            "## {} · {}\n",
+            role,
+            message.timestamp.format("%Y-%m-%d %H:%M:%S")
+        );
+        let _ = writeln!(out, "{}\n", message.content.trim());
+
+        for sql in &message.sql_queries {
+            let _ = writeln!(out, "```sql\n{}\n```\n", sql.trim());
+        }
+        for result in &message.results {
+            render_result_table(&mut out, result);
+        }
+    }
+
+    out
+}
+
+fn render_result_table(out: &mut String, result: &QueryResult) {
+    if result.columns.is_empty() {
+        return;
+    }
+
+    let header: Vec<String> = result.columns.iter().map(|c| markdown_cell(c)).collect();
+    let _ = writeln!(out, "| {} |", header.join(" | "));
+    let _ = writeln!(out, "|{}", " --- |".repeat(result.columns.len()));
+
+    for row in result.rows.iter().take(MARKDOWN_MAX_ROWS) {
+        let cells: Vec<String> = result
+            .columns
+            .iter()
+            .map(|column| markdown_cell(&grid_cell_text(row.get(column))))
+            .collect();
+        let _ = writeln!(out, "| {} |", cells.join(" | "));
+    }
+
+    if result.rows.len() > MARKDOWN_MAX_ROWS {
+        let _ = writeln!(
+            out,
+            "\n_Showing {} of {} rows_",
+            MARKDOWN_MAX_ROWS,
+            result.rows.len()
+        );
+    }
+    out.push('\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn markdown_includes_sql_and_escaped_results() {
+        let mut row = serde_json::Map::new();
+        row.insert("name".to_string(), serde_json::json!("a|b"));
+        row.insert("total".to_string(), serde_json::json!(3));
+
+        let history = ConversationHistory {
+            session_id: "s1".to_string(),
+            connection_id: "c1".to_string(),
+            summary: None,
+            messages: vec![
+                Message::user("How many orders?"),
+                Message::assistant("There are 3.").with_query_output(
+                    vec!["SELECT name, COUNT(*) AS total FROM orders GROUP BY name".to_string()],
+                    vec![QueryResult {
+                        columns: vec!["name".to_string(), "total".to_string()],
+                        column_metadata: Vec::new(),
+                        rows: vec![row],
+                        row_count: 1,
+                        execution_time_ms: 1,
+                        summary: None,
+                    }],
+                ),
+            ],
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+
+        let markdown = render_markdown(&history);
+        assert!(markdown.contains("## User"));
+        assert!(markdown.contains("```sql\nSELECT name, COUNT(*) AS total FROM orders GROUP BY name\n```"));
+        assert!(markdown.contains("| name | total |"));
+        assert!(markdown.contains("| a\\|b | 3 |"));
+    }
+}
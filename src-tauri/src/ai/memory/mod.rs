@@ -1,3 +1,11 @@
+pub mod budget;
+pub mod export;
 pub mod storage;
+pub mod summary;
+pub mod usage;
 
+pub use budget::*;
+pub use export::*;
 pub use storage::*;
+pub use summary::*;
+pub use usage::*;
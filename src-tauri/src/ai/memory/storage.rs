@@ -1,3 +1,5 @@
+use super::budget::trim_to_token_budget;
+use super::summary::ConversationSummary;
 use crate::ai::agent::{Message, MessageRole};
 use crate::error::AppResult;
 use chrono::{DateTime, Utc};
@@ -23,25 +25,32 @@ pub struct ConversationHistory {
     pub session_id: String,
     pub connection_id: String,
     pub messages: Vec<Message>,
+    /// Summary of the oldest messages, used when history exceeds the context limit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<ConversationSummary>,
     #[serde(with = "chrono::serde::ts_seconds")]
     pub created_at: DateTime<Utc>,
     #[serde(with = "chrono::serde::ts_seconds")]
     pub updated_at: DateTime<Utc>,
 }
 
-/// Save conversation to disk
+/// Save conversation to disk, keeping a stored summary that still matches the messages
 pub fn save_conversation(
     app: &AppHandle,
     session_id: &str,
     connection_id: &str,
     messages: &[Message],
 ) -> AppResult<()> {
+    let summary = load_conversation_history(app, session_id)?
+        .and_then(|history| history.summary)
+        .filter(|summary| summary.covered_messages <= messages.len());
     let path = get_conversation_path(app, session_id)?;
 
     let history = ConversationHistory {
         session_id: session_id.to_string(),
         connection_id: connection_id.to_string(),
         messages: messages.to_vec(),
+        summary,
         created_at: Utc::now(),
         updated_at: Utc::now(),
     };
@@ -54,33 +63,72 @@ pub fn save_conversation(
 
 /// Load conversation from disk
 pub fn load_conversation(app: &AppHandle, session_id: &str) -> AppResult<Vec<Message>> {
+    Ok(load_conversation_history(app, session_id)?
+        .map(|history| history.messages)
+        .unwrap_or_default())
+}
+
+/// Load a conversation with its metadata, if it has been saved
+pub fn load_conversation_history(
+    app: &AppHandle,
+    session_id: &str,
+) -> AppResult<Option<ConversationHistory>> {
     let path = get_conversation_path(app, session_id)?;
 
     if !path.exists() {
-        return Ok(Vec::new());
+        return Ok(None);
     }
 
     let json = std::fs::read_to_string(path)?;
     let history: ConversationHistory = serde_json::from_str(&json)?;
 
-    Ok(history.messages)
+    Ok(Some(history))
 }
 
-/// Load last N messages from conversation (for context window management)
+/// Load last N messages from conversation (for context window management).
+/// When a stored summary covers exactly the dropped messages, it is prepended
+/// as a system message so earlier context is not lost. The result is then cut
+/// down to `token_budget` estimated tokens (0 disables), oldest messages first.
 pub fn load_conversation_with_limit(
     app: &AppHandle,
     session_id: &str,
     limit: usize,
+    token_budget: usize,
 ) -> AppResult<Vec<Message>> {
-    let all_messages = load_conversation(app, session_id)?;
+    let Some(history) = load_conversation_history(app, session_id)? else {
+        return Ok(Vec::new());
+    };
+    let all_messages = history.messages;
 
     if all_messages.len() <= limit {
-        return Ok(all_messages);
+        return Ok(trim_to_token_budget(all_messages, token_budget));
     }
 
     // Take the last N messages
     let start_index = all_messages.len() - limit;
-    Ok(all_messages[start_index..].to_vec())
+    let mut messages = Vec::with_capacity(limit + 1);
+    if let Some(summary) = history.summary.filter(|s| s.covered_messages == start_index) {
+        messages.push(summary.to_message());
+    }
+    messages.extend_from_slice(&all_messages[start_index..]);
+    Ok(trim_to_token_budget(messages, token_budget))
+}
+
+/// Store the summary of a conversation's oldest messages alongside it
+pub fn save_conversation_summary(
+    app: &AppHandle,
+    session_id: &str,
+    summary: ConversationSummary,
+) -> AppResult<()> {
+    let Some(mut history) = load_conversation_history(app, session_id)? else {
+        return Ok(());
+    };
+    history.summary = Some(summary);
+
+    let json = serde_json::to_string_pretty(&history)?;
+    std::fs::write(get_conversation_path(app, session_id)?, json)?;
+
+    Ok(())
 }
 
 /// Clear conversation from disk
@@ -90,6 +138,7 @@ pub fn clear_conversation(app: &AppHandle, session_id: &str) -> AppResult<()> {
     if path.exists() {
         std::fs::remove_file(path)?;
     }
+    super::usage::clear_session_usage(session_id);
 
     Ok(())
 }
@@ -0,0 +1,142 @@
+use super::storage::{load_conversation_history, save_conversation_summary};
+use crate::ai::agent::{Message, MessageRole};
+use crate::ai::openrouter::OpenRouterClient;
+use crate::error::AppResult;
+use crate::storage::AppSettings;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+/// Opening line of the system message that stands in for summarized turns
+pub const SUMMARY_MESSAGE_PREFIX: &str = "Summary of the earlier conversation:";
+
+const SUMMARY_SYSTEM_PROMPT: &str = r#"You condense the older part of a conversation between a user and a database assistant so later questions keep their context.
+
+Write a short plain-text summary that:
+- Names every table, column and entity (IDs, names, categories) that was discussed
+- Keeps the filters, time ranges and groupings that were applied
+- States what "it", "those", "that table" etc. referred to most recently
+- Notes conclusions or numbers the user may refer back to
+
+Do not invent details and do not write SQL unless a query was essential to the discussion."#;
+
+/// LLM summary of a conversation's oldest messages, saved with the conversation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationSummary {
+    /// Number of leading messages the summary covers
+    pub covered_messages: usize,
+    pub content: String,
+}
+
+impl ConversationSummary {
+    /// Synthetic system message placed before the messages kept verbatim
+    pub fn to_message(&self) -> Message {
+        Message::system(format!("{}\n{}", SUMMARY_MESSAGE_PREFIX, self.content))
+    }
+}
+
+/// Summarize the messages that no longer fit in the last `limit`, so
+/// `load_conversation_with_limit` can replace them with one system message.
+/// An existing summary is extended with only the messages that have since
+/// fallen out of the window; nothing is sent when it is already current.
+pub async fn update_conversation_summary(
+    app: &AppHandle,
+    session_id: &str,
+    limit: usize,
+    settings: &AppSettings,
+) -> AppResult<()> {
+    let Some(history) = load_conversation_history(app, session_id)? else {
+        return Ok(());
+    };
+
+    let split = history.messages.len().saturating_sub(limit);
+    let previous = history.summary.filter(|s| s.covered_messages <= split);
+    let covered = previous.as_ref().map_or(0, |s| s.covered_messages);
+    if split == covered {
+        return Ok(());
+    }
+
+    let client = OpenRouterClient::new(settings.openrouter_api_key.clone())
+        .with_max_retries(settings.max_retries)
+        .with_fallback_models(settings.fallback_models.clone())
+        .with_model_params(settings.model_params());
+    let prompt = build_summary_prompt(
+        previous.as_ref().map(|s| s.content.as_str()),
+        &history.messages[covered..split],
+    );
+    let response = client
+        .chat_with_format(
+            &settings.text_to_sql_model,
+            &[Message::system(SUMMARY_SYSTEM_PROMPT), Message::user(prompt)],
+            Some(0.0),
+            None,
+            None,
+            None,
+        )
+        .await;
+    super::usage::record_session_usage(session_id, &client.usage());
+
+    save_conversation_summary(
+        app,
+        session_id,
+        ConversationSummary {
+            covered_messages: split,
+            content: response?.trim().to_string(),
+        },
+    )
+}
+
+/// Request text with the current summary (if any) and the messages to fold into it.
+/// SQL run by the assistant is included since it names the tables and filters used.
+fn build_summary_prompt(previous: Option<&str>, messages: &[Message]) -> String {
+    let mut prompt = String::new();
+    if let Some(previous) = previous {
+        prompt.push_str(&format!("Existing summary:\n{}\n\nLater messages to add to it:\n", previous));
+    } else {
+        prompt.push_str("Messages to summarize:\n");
+    }
+
+    for msg in messages {
+        let role = match msg.role {
+            MessageRole::User => "User",
+            MessageRole::Assistant => "Assistant",
+            _ => continue,
+        };
+        prompt.push_str(&format!("{}: {}\n", role, msg.content.trim()));
+        for sql in &msg.sql_queries {
+            prompt.push_str(&format!("  SQL: {}\n", sql.trim()));
+        }
+    }
+
+    prompt.push_str("\nReturn only the updated summary.");
+    prompt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prompt_folds_new_messages_into_previous_summary() {
+        let messages = vec![
+            Message::user("Show orders for customer 42"),
+            Message::assistant("Customer 42 has 3 orders.")
+                .with_query_output(vec!["SELECT * FROM orders WHERE customer_id = 42".to_string()], vec![]),
+            Message::system("internal note"),
+        ];
+
+        let prompt = build_summary_prompt(Some("Discussed the customers table."), &messages);
+        assert!(prompt.starts_with("Existing summary:\nDiscussed the customers table.\n"));
+        assert!(prompt.contains("User: Show orders for customer 42\n"));
+        assert!(prompt.contains("  SQL: SELECT * FROM orders WHERE customer_id = 42\n"));
+        assert!(!prompt.contains("internal note"));
+
+        let summary = ConversationSummary {
+            covered_messages: 3,
+            content: "Orders of customer 42".to_string(),
+        };
+        assert_eq!(
+            summary.to_message().content,
+            "Summary of the earlier conversation:\nOrders of customer 42"
+        );
+    }
+}
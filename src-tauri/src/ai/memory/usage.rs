@@ -0,0 +1,35 @@
+use crate::ai::openrouter::types::TokenUsage;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+// Running token usage totals, keyed by chat session id
+lazy_static::lazy_static! {
+    static ref SESSION_USAGE: Mutex<HashMap<String, TokenUsage>> = Mutex::new(HashMap::new());
+}
+
+/// Add a run's usage to the session total and return the new total
+pub fn record_session_usage(session_id: &str, usage: &TokenUsage) -> TokenUsage {
+    let mut sessions = match SESSION_USAGE.lock() {
+        Ok(sessions) => sessions,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    let total = sessions.entry(session_id.to_string()).or_default();
+    total.merge(usage);
+    total.clone()
+}
+
+/// Current usage total for a session (zero if nothing has been recorded)
+pub fn get_session_usage(session_id: &str) -> TokenUsage {
+    SESSION_USAGE
+        .lock()
+        .ok()
+        .and_then(|sessions| sessions.get(session_id).cloned())
+        .unwrap_or_default()
+}
+
+/// Drop a session's usage total
+pub fn clear_session_usage(session_id: &str) {
+    if let Ok(mut sessions) = SESSION_USAGE.lock() {
+        sessions.remove(session_id);
+    }
+}
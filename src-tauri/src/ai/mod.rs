@@ -1,4 +1,5 @@
 pub mod agent;
+pub mod cache;
 pub mod classification;
 pub mod memory;
 pub mod openrouter;
@@ -7,8 +8,12 @@ pub mod sanitizer;
 pub mod visualization;
 
 // Re-export commonly used types
-pub use agent::run_mac_sql_agent;
+pub use agent::{
+    cancel_chat_session, confirm_write, register_chat_session, run_mac_sql_agent,
+    unregister_chat_session,
+};
 pub use memory::{
     clear_conversation, list_conversations, load_conversation, load_conversation_with_limit,
-    save_conversation, ConversationMetadata,
+    save_conversation, get_session_usage, export_conversation, ConversationExportFormat,
+    ConversationMetadata,
 };
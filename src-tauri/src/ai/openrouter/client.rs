@@ -1,13 +1,94 @@
+use crate::ai::cache::{cache_key, ResponseCache};
 use crate::error::{AppError, AppResult};
-use super::types::{OpenRouterRequest, OpenRouterResponse, OpenRouterMessage, ResponseFormat, Tool};
-use reqwest::Client;
+use super::types::{
+    ModelInfo, ModelParams, ModelsResponse, OpenRouterModel, OpenRouterRequest, OpenRouterResponse, OpenRouterMessage, ResponseFormat,
+    SettingsValidation, SettingsValidationStatus, StreamChunk, TokenUsage, Tool, UsageOptions,
+};
+use rand::Rng;
+use futures::StreamExt;
+use reqwest::{Client, Response, StatusCode};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio_util::sync::CancellationToken;
 
 const OPENROUTER_API_URL: &str = "https://openrouter.ai/api/v1/chat/completions";
 
+const OPENROUTER_MODELS_URL: &str = "https://openrouter.ai/api/v1/models";
+
+/// How long a fetched model list is reused
+const MODEL_LIST_CACHE_TTL: Duration = Duration::from_secs(300);
+
+lazy_static::lazy_static! {
+    static ref MODEL_LIST_CACHE: ResponseCache = ResponseCache::new(4);
+    /// Shared by every client so the cap spans all chat sessions, with the limit it was built for
+    static ref REQUEST_LIMITER: Mutex<(usize, Arc<Semaphore>)> = Mutex::new((
+        DEFAULT_MAX_CONCURRENT_REQUESTS,
+        Arc::new(Semaphore::new(DEFAULT_MAX_CONCURRENT_REQUESTS)),
+    ));
+}
+
+/// The shared request semaphore, rebuilt when `max_concurrent` differs from its current size.
+/// Requests holding permits on a replaced semaphore finish normally.
+fn request_limiter(max_concurrent: Option<usize>) -> Arc<Semaphore> {
+    let mut limiter = REQUEST_LIMITER.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(max_concurrent) = max_concurrent.map(|n| n.max(1)) {
+        if limiter.0 != max_concurrent {
+            *limiter = (max_concurrent, Arc::new(Semaphore::new(max_concurrent)));
+        }
+    }
+    Arc::clone(&limiter.1)
+}
+
+/// Retries after the first attempt for transient failures (429, 5xx, network errors)
+pub const DEFAULT_MAX_RETRIES: u32 = 3;
+
+/// Output token cap for a completion when the caller doesn't set one
+pub const DEFAULT_MAX_TOKENS: u32 = 2000;
+
+/// Chat requests allowed in flight at once, across every client
+pub const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 4;
+
+/// Backoff before the first retry; doubled on each subsequent attempt
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// Upper bound for any single wait, including server-provided Retry-After values
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(60);
+
+/// Called with `(failed_model, replacement_model, reason)` when a fallback model takes over
+pub type FallbackHandler = Box<dyn Fn(&str, &str, &str) + Send + Sync>;
+
 /// OpenRouter API client
 pub struct OpenRouterClient {
     client: Client,
     api_key: String,
+    max_retries: u32,
+    /// Token usage summed over every completion made through this client
+    usage: Mutex<TokenUsage>,
+    /// Models tried in order when the requested model is unavailable
+    fallback_models: Vec<String>,
+    /// Replacement chosen for each unavailable model, reused for the rest of this client's calls
+    active_fallbacks: Mutex<HashMap<String, String>>,
+    on_fallback: Option<FallbackHandler>,
+    /// Size of the shared request limiter; `None` keeps whatever limit is in effect
+    max_concurrent_requests: Option<usize>,
+    /// Stops waiting for a request slot once cancelled
+    cancel_token: Option<CancellationToken>,
+    /// Sampling parameters and provider routing added to every request
+    model_params: ModelParams,
+}
+
+/// A failed request, flagged when the failure is specific to the requested model
+struct SendFailure {
+    error: AppError,
+    model_unavailable: bool,
+}
+
+impl From<AppError> for SendFailure {
+    fn from(error: AppError) -> Self {
+        Self { error, model_unavailable: false }
+    }
 }
 
 impl OpenRouterClient {
@@ -15,64 +96,670 @@ impl OpenRouterClient {
         Self {
             client: Client::new(),
             api_key,
+            max_retries: DEFAULT_MAX_RETRIES,
+            usage: Mutex::new(TokenUsage::default()),
+            fallback_models: Vec::new(),
+            active_fallbacks: Mutex::new(HashMap::new()),
+            on_fallback: None,
+            max_concurrent_requests: None,
+            cancel_token: None,
+            model_params: ModelParams::default(),
+        }
+    }
+
+    /// Token usage accumulated so far by this client
+    pub fn usage(&self) -> TokenUsage {
+        self.usage.lock().map(|u| u.clone()).unwrap_or_default()
+    }
+
+    /// Set how many times transient failures are retried (0 disables retries)
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Cap chat requests in flight across all clients; extra requests wait for a free slot
+    pub fn with_max_concurrent_requests(mut self, max_concurrent_requests: usize) -> Self {
+        self.max_concurrent_requests = Some(max_concurrent_requests);
+        self
+    }
+
+    /// Send `top_p`, penalties and provider preferences with every request
+    pub fn with_model_params(mut self, model_params: ModelParams) -> Self {
+        self.model_params = model_params;
+        self
+    }
+
+    /// Give up waiting for a request slot when `cancel_token` is cancelled
+    pub fn with_cancellation(mut self, cancel_token: CancellationToken) -> Self {
+        self.cancel_token = Some(cancel_token);
+        self
+    }
+
+    /// Models to try, in order, when a request fails because its model is unavailable.
+    /// Auth, rate-limit and other errors never trigger a fallback.
+    pub fn with_fallback_models(mut self, fallback_models: Vec<String>) -> Self {
+        self.fallback_models = fallback_models
+            .into_iter()
+            .map(|m| m.trim().to_string())
+            .filter(|m| !m.is_empty())
+            .collect();
+        self
+    }
+
+    /// Register a callback invoked whenever a fallback model replaces an unavailable one
+    pub fn on_fallback(mut self, handler: impl Fn(&str, &str, &str) + Send + Sync + 'static) -> Self {
+        self.on_fallback = Some(Box::new(handler));
+        self
+    }
+
+    /// Wait for a slot under the shared concurrency cap. The permit is held until the
+    /// response has been read, including retries and the whole of a stream.
+    async fn acquire_permit(&self) -> AppResult<OwnedSemaphorePermit> {
+        let acquire = request_limiter(self.max_concurrent_requests).acquire_owned();
+        let permit = match &self.cancel_token {
+            Some(token) => tokio::select! {
+                permit = acquire => permit,
+                _ = token.cancelled() => {
+                    return Err(AppError::OperationCancelled(
+                        "Cancelled while waiting to send an AI request".into(),
+                    ));
+                }
+            },
+            None => acquire.await,
+        };
+        permit.map_err(|_| AppError::OpenRouterError("Request limiter closed".into()))
+    }
+
+    /// Send a request, moving down the fallback chain while the model is unavailable.
+    /// Once a replacement works it is used directly for later requests for the same model.
+    async fn send(&self, request: &mut OpenRouterRequest) -> AppResult<Response> {
+        let requested = request.model.clone();
+        if let Some(active) = self.active_fallbacks.lock().ok().and_then(|m| m.get(&requested).cloned()) {
+            request.model = active;
+        }
+
+        let first = request.model.clone();
+        let mut candidates = self
+            .fallback_models
+            .iter()
+            .filter(|m| **m != requested && **m != first);
+
+        loop {
+            let failure = match self.send_with_retry(request).await {
+                Ok(response) => {
+                    if request.model != requested {
+                        if let Ok(mut active) = self.active_fallbacks.lock() {
+                            active.insert(requested.clone(), request.model.clone());
+                        }
+                    }
+                    return Ok(response);
+                }
+                Err(failure) => failure,
+            };
+
+            if !failure.model_unavailable {
+                return Err(failure.error);
+            }
+            let Some(next) = candidates.next() else {
+                return Err(failure.error);
+            };
+
+            eprintln!("OpenRouter model {} unavailable, falling back to {}", request.model, next);
+            if let Some(handler) = &self.on_fallback {
+                handler(&request.model, next, &failure.error.to_string());
+            }
+            request.model = next.clone();
+        }
+    }
+
+    /// POST a request, retrying rate limits, server errors and network failures with
+    /// exponential backoff plus jitter. Other 4xx responses (e.g. 401) fail immediately.
+    async fn send_with_retry(&self, request: &OpenRouterRequest) -> Result<Response, SendFailure> {
+        let mut attempt = 0;
+
+        loop {
+            let result = self.post_completion(request).await;
+
+            let (reason, retry_after) = match result {
+                Ok(response) if response.status().is_success() => return Ok(response),
+                Ok(response) => {
+                    let status = response.status();
+                    let retry_after = parse_retry_after(&response);
+                    let error_text = response
+                        .text()
+                        .await
+                        .unwrap_or_else(|_| "Unknown error".to_string());
+
+                    if !is_retryable_status(status) || attempt >= self.max_retries {
+                        return Err(SendFailure {
+                            model_unavailable: is_model_unavailable(status, &error_text),
+                            error: AppError::OpenRouterError(format!(
+                                "API error {}: {}",
+                                status, error_text
+                            )),
+                        });
+                    }
+                    (format!("API error {}", status), retry_after)
+                }
+                Err(e) => {
+                    // Builder and encoding errors also report is_request(); only network failures retry
+                    let transient = e.is_timeout() || e.is_connect();
+                    if !transient || attempt >= self.max_retries {
+                        return Err(AppError::OpenRouterError(format!("Request failed: {}", e)).into());
+                    }
+                    (format!("Request failed: {}", e), None)
+                }
+            };
+
+            let delay = retry_delay(attempt, retry_after);
+            eprintln!(
+                "OpenRouter {} (model {}), retrying in {} ms (retry {}/{})",
+                reason,
+                request.model,
+                delay.as_millis(),
+                attempt + 1,
+                self.max_retries
+            );
+            match &self.cancel_token {
+                Some(token) => tokio::select! {
+                    _ = tokio::time::sleep(delay) => {}
+                    _ = token.cancelled() => {
+                        return Err(AppError::OperationCancelled(
+                            "Cancelled while waiting to retry an AI request".into(),
+                        )
+                        .into());
+                    }
+                },
+                None => tokio::time::sleep(delay).await,
+            }
+            attempt += 1;
         }
     }
 
+    async fn post_completion(&self, request: &OpenRouterRequest) -> reqwest::Result<Response> {
+        self.client
+            .post(OPENROUTER_API_URL)
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .header("HTTP-Referer", "https://dataspeak.app")
+            .header("X-Title", "DataSpeak")
+            .json(request)
+            .send()
+            .await
+    }
+
+    /// Check the key and `model` with a one-token completion, sent once without retries or
+    /// fallbacks so the failure is reported as-is, and look up the model's context length
+    pub async fn validate(&self, model: &str) -> SettingsValidation {
+        let request = OpenRouterRequest {
+            model: model.to_string(),
+            messages: vec![(&crate::ai::agent::Message::user("ping")).into()],
+            temperature: None,
+            max_tokens: Some(1),
+            stream: Some(false),
+            response_format: None,
+            tools: None,
+            parallel_tool_calls: None,
+            usage: None,
+            params: self.model_params.clone(),
+        };
+
+        let (probe, listed) = tokio::join!(self.post_completion(&request), self.find_model(model));
+
+        let (status, message) = match probe {
+            Ok(response) if response.status().is_success() => (SettingsValidationStatus::Valid, None),
+            Ok(response) => {
+                let code = response.status();
+                let body = response.text().await.unwrap_or_default();
+                (validation_status(code, &body), Some(format!("API error {}: {}", code, body)))
+            }
+            Err(e) => (SettingsValidationStatus::Error, Some(format!("Request failed: {}", e))),
+        };
+
+        let key_valid = match status {
+            SettingsValidationStatus::InvalidKey => Some(false),
+            SettingsValidationStatus::Error => None,
+            _ => Some(true),
+        };
+        // The model list can still answer when the completion probe fails
+        let model_available = match (&listed, status) {
+            (Ok(info), _) => Some(info.is_some()),
+            (Err(_), SettingsValidationStatus::Valid) => Some(true),
+            (Err(_), SettingsValidationStatus::ModelNotFound) => Some(false),
+            (Err(_), _) => None,
+        };
+
+        SettingsValidation {
+            status,
+            key_valid,
+            model_available,
+            context_length: listed.ok().flatten().and_then(|info| info.context_length),
+            message,
+        }
+    }
+
+    /// Models available through OpenRouter, cached for a few minutes per API key
+    pub async fn list_models(&self) -> AppResult<Vec<OpenRouterModel>> {
+        let key = cache_key(&[&self.api_key]);
+        if let Some(cached) = MODEL_LIST_CACHE.get(key, MODEL_LIST_CACHE_TTL) {
+            if let Ok(models) = serde_json::from_str(&cached) {
+                return Ok(models);
+            }
+        }
+
+        let models: Vec<OpenRouterModel> =
+            self.fetch_models().await?.into_iter().map(OpenRouterModel::from).collect();
+        if let Ok(json) = serde_json::to_string(&models) {
+            MODEL_LIST_CACHE.insert(key, json);
+        }
+        Ok(models)
+    }
+
+    /// Whether `model` accepts tool calls, per the cached model list. A model that
+    /// isn't listed, or a list that can't be fetched, counts as not supporting them.
+    pub async fn supports_tools(&self, model: &str) -> bool {
+        self.list_models()
+            .await
+            .map(|models| models.iter().any(|m| m.id == model && m.supports_tools))
+            .unwrap_or(false)
+    }
+
+    /// Look `model` up in `/models`; `Ok(None)` when it isn't listed
+    async fn find_model(&self, model: &str) -> AppResult<Option<ModelInfo>> {
+        Ok(self.fetch_models().await?.into_iter().find(|m| m.id == model))
+    }
+
+    async fn fetch_models(&self) -> AppResult<Vec<ModelInfo>> {
+        let models: ModelsResponse = self
+            .client
+            .get(OPENROUTER_MODELS_URL)
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| AppError::OpenRouterError(format!("Failed to list models: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| AppError::OpenRouterError(format!("Parse error: {}", e)))?;
+
+        Ok(models.data)
+    }
+
     /// Call OpenRouter API with response format (for structured outputs)
     pub async fn chat_with_format(
         &self,
         model: &str,
         messages: &[crate::ai::agent::Message],
         temperature: Option<f32>,
+        max_tokens: Option<u32>,
         response_format: Option<ResponseFormat>,
         tools: Option<Vec<Tool>>,
     ) -> AppResult<String> {
+        self.complete(model, messages, temperature, max_tokens, response_format, tools)
+            .await?
+            .content
+            .ok_or_else(|| AppError::OpenRouterError("No response from API".into()))
+    }
+
+    /// Call OpenRouter API with tools, returning the assistant message so its
+    /// `tool_calls` can be answered before asking again
+    pub async fn chat_with_tools(
+        &self,
+        model: &str,
+        messages: &[crate::ai::agent::Message],
+        temperature: Option<f32>,
+        max_tokens: Option<u32>,
+        tools: Vec<Tool>,
+    ) -> AppResult<OpenRouterMessage> {
+        self.complete(model, messages, temperature, max_tokens, None, Some(tools)).await
+    }
+
+    /// Send a non-streaming completion and return the first choice's message
+    async fn complete(
+        &self,
+        model: &str,
+        messages: &[crate::ai::agent::Message],
+        temperature: Option<f32>,
+        max_tokens: Option<u32>,
+        response_format: Option<ResponseFormat>,
+        tools: Option<Vec<Tool>>,
+    ) -> AppResult<OpenRouterMessage> {
         let openrouter_messages: Vec<OpenRouterMessage> =
             messages.iter().map(|m| m.into()).collect();
 
-        let request = OpenRouterRequest {
+        let mut request = OpenRouterRequest {
             model: model.to_string(),
             messages: openrouter_messages,
             temperature,
-            max_tokens: Some(2000),
+            max_tokens: Some(max_tokens.unwrap_or(DEFAULT_MAX_TOKENS)),
             stream: Some(false),
             response_format,
             tools,
             parallel_tool_calls: None,
+            usage: Some(UsageOptions { include: true }),
+            params: self.model_params.clone(),
         };
 
-        let response = self
-            .client
-            .post(OPENROUTER_API_URL)
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .header("HTTP-Referer", "https://dataspeak.app")
-            .header("X-Title", "DataSpeak")
-            .json(&request)
-            .send()
-            .await
-            .map_err(|e| AppError::OpenRouterError(format!("Request failed: {}", e)))?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(AppError::OpenRouterError(format!(
-                "API error {}: {}",
-                status, error_text
-            )));
-        }
+        let _permit = self.acquire_permit().await?;
+        let response = self.send(&mut request).await?;
 
         let api_response: OpenRouterResponse = response
             .json()
             .await
             .map_err(|e| AppError::OpenRouterError(format!("Parse error: {}", e)))?;
 
+        if let Some(usage) = &api_response.usage {
+            if let Ok(mut total) = self.usage.lock() {
+                total.add(usage);
+            }
+        }
+
         api_response
             .choices
-            .first()
-            .and_then(|choice| choice.message.content.clone())
+            .into_iter()
+            .next()
+            .map(|choice| choice.message)
             .ok_or_else(|| AppError::OpenRouterError("No response from API".into()))
     }
+
+    /// Stream a completion, passing each piece of content to `on_token` as it arrives,
+    /// and return the full text. An error from `on_token` (e.g. cancellation) stops the stream.
+    pub async fn chat_stream<F>(
+        &self,
+        model: &str,
+        messages: &[crate::ai::agent::Message],
+        temperature: Option<f32>,
+        max_tokens: Option<u32>,
+        mut on_token: F,
+    ) -> AppResult<String>
+    where
+        F: FnMut(&str) -> AppResult<()>,
+    {
+        let mut request = OpenRouterRequest {
+            model: model.to_string(),
+            messages: messages.iter().map(|m| m.into()).collect(),
+            temperature,
+            max_tokens: Some(max_tokens.unwrap_or(DEFAULT_MAX_TOKENS)),
+            stream: Some(true),
+            response_format: None,
+            tools: None,
+            parallel_tool_calls: None,
+            usage: Some(UsageOptions { include: true }),
+            params: self.model_params.clone(),
+        };
+
+        let _permit = self.acquire_permit().await?;
+        let response = self.send(&mut request).await?;
+        let mut body = response.bytes_stream();
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut text = String::new();
+
+        'read: while let Some(bytes) = body.next().await {
+            let bytes = bytes.map_err(|e| AppError::OpenRouterError(format!("Stream error: {}", e)))?;
+            buffer.extend_from_slice(&bytes);
+
+            // Only complete lines are parsed; a chunk may end mid-line (or mid-character)
+            while let Some(newline) = buffer.iter().position(|&b| b == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=newline).collect();
+                match parse_stream_line(&String::from_utf8_lossy(&line))? {
+                    Some(StreamEvent::Done) => break 'read,
+                    Some(StreamEvent::Chunk(chunk)) => {
+                        if let Some(usage) = &chunk.usage {
+                            if let Ok(mut total) = self.usage.lock() {
+                                total.add(usage);
+                            }
+                        }
+                        for content in chunk.choices.iter().filter_map(|c| c.delta.content.as_deref()) {
+                            if !content.is_empty() {
+                                text.push_str(content);
+                                on_token(content)?;
+                            }
+                        }
+                    }
+                    None => {}
+                }
+            }
+        }
+
+        if text.is_empty() {
+            return Err(AppError::OpenRouterError("No response from API".into()));
+        }
+        Ok(text)
+    }
+}
+
+/// A parsed line of an OpenRouter server-sent event stream
+enum StreamEvent {
+    Chunk(StreamChunk),
+    Done,
+}
+
+/// Parse one SSE line. Blank lines, comments (`: OPENROUTER PROCESSING` keepalives)
+/// and other fields yield None.
+fn parse_stream_line(line: &str) -> AppResult<Option<StreamEvent>> {
+    let Some(data) = line.trim_end_matches(['\r', '\n']).strip_prefix("data:") else {
+        return Ok(None);
+    };
+    let data = data.trim();
+    if data == "[DONE]" {
+        return Ok(Some(StreamEvent::Done));
+    }
+
+    let chunk: StreamChunk = serde_json::from_str(data)
+        .map_err(|e| AppError::OpenRouterError(format!("Stream parse error: {}", e)))?;
+    if let Some(error) = &chunk.error {
+        return Err(AppError::OpenRouterError(format!("Stream error: {}", error)));
+    }
+    Ok(Some(StreamEvent::Chunk(chunk)))
+}
+
+/// Rate limits and server-side failures are worth retrying; other client errors are not
+fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Whether a failure means the requested model can't serve the request (unknown ID, no
+/// available endpoints), as opposed to auth, credit, rate-limit or request errors
+fn is_model_unavailable(status: StatusCode, body: &str) -> bool {
+    if status == StatusCode::NOT_FOUND {
+        return true;
+    }
+    if status != StatusCode::BAD_REQUEST && status != StatusCode::SERVICE_UNAVAILABLE {
+        return false;
+    }
+    let body = body.to_lowercase();
+    body.contains("not a valid model")
+        || body.contains("no endpoints found")
+        || body.contains("model not found")
+}
+
+/// Classify a failed validation probe, telling a bad key from a bad model or an empty balance
+fn validation_status(status: StatusCode, body: &str) -> SettingsValidationStatus {
+    match status {
+        StatusCode::UNAUTHORIZED => SettingsValidationStatus::InvalidKey,
+        StatusCode::PAYMENT_REQUIRED => SettingsValidationStatus::InsufficientCredits,
+        _ if is_model_unavailable(status, body) => SettingsValidationStatus::ModelNotFound,
+        _ => SettingsValidationStatus::Error,
+    }
+}
+
+/// Read a Retry-After header given in seconds
+fn parse_retry_after(response: &Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+/// Exponential backoff with up to 50% random jitter, or the server's Retry-After when given
+fn retry_delay(attempt: u32, retry_after: Option<Duration>) -> Duration {
+    if let Some(retry_after) = retry_after {
+        return retry_after.min(RETRY_MAX_DELAY);
+    }
+
+    let backoff = RETRY_BASE_DELAY
+        .saturating_mul(2u32.saturating_pow(attempt))
+        .min(RETRY_MAX_DELAY);
+    let jitter_ms = rand::thread_rng().gen_range(0..=backoff.as_millis() as u64 / 2);
+    (backoff + Duration::from_millis(jitter_ms)).min(RETRY_MAX_DELAY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::openrouter::types::ProviderPreferences;
+
+    #[test]
+    fn test_is_retryable_status() {
+        assert!(is_retryable_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retryable_status(StatusCode::BAD_GATEWAY));
+        assert!(!is_retryable_status(StatusCode::UNAUTHORIZED));
+        assert!(!is_retryable_status(StatusCode::BAD_REQUEST));
+    }
+
+    #[test]
+    fn unset_model_params_are_left_out_of_the_request() {
+        let request = |params: ModelParams| {
+            serde_json::to_value(OpenRouterRequest {
+                model: "x/y".to_string(),
+                messages: vec![],
+                temperature: None,
+                max_tokens: Some(1),
+                stream: None,
+                response_format: None,
+                tools: None,
+                parallel_tool_calls: None,
+                usage: None,
+                params,
+            })
+            .unwrap()
+        };
+
+        let plain = request(ModelParams::default());
+        assert_eq!(plain, serde_json::json!({"model": "x/y", "messages": [], "max_tokens": 1}));
+
+        let pinned = request(ModelParams {
+            top_p: Some(0.5),
+            presence_penalty: Some(0.25),
+            provider: Some(ProviderPreferences {
+                order: vec!["Anthropic".to_string()],
+                allow_fallbacks: Some(false),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        assert_eq!(pinned["top_p"], 0.5);
+        assert_eq!(pinned["presence_penalty"], 0.25);
+        assert!(pinned.get("frequency_penalty").is_none());
+        assert_eq!(
+            pinned["provider"],
+            serde_json::json!({"order": ["Anthropic"], "allow_fallbacks": false})
+        );
+    }
+
+    #[test]
+    fn test_is_model_unavailable() {
+        assert!(is_model_unavailable(StatusCode::NOT_FOUND, "No endpoints found for x/y"));
+        assert!(is_model_unavailable(
+            StatusCode::BAD_REQUEST,
+            r#"{"error":{"message":"x/y is not a valid model ID"}}"#
+        ));
+        assert!(!is_model_unavailable(StatusCode::BAD_REQUEST, "max_tokens too large"));
+        assert!(!is_model_unavailable(StatusCode::UNAUTHORIZED, "No auth credentials found"));
+        assert!(!is_model_unavailable(StatusCode::TOO_MANY_REQUESTS, "model not found"));
+    }
+
+    #[test]
+    fn test_model_list_flags_tool_support() {
+        let response: ModelsResponse = serde_json::from_str(
+            r#"{"data":[
+                {"id":"openai/gpt-4o","name":"OpenAI: GPT-4o","context_length":128000,
+                 "pricing":{"prompt":"0.0000025","completion":"0.00001"},
+                 "supported_parameters":["tools","tool_choice","max_tokens"]},
+                {"id":"some/base-model","context_length":null}
+            ]}"#,
+        )
+        .unwrap();
+        let models: Vec<OpenRouterModel> = response.data.into_iter().map(Into::into).collect();
+
+        assert!(models[0].supports_tools);
+        assert_eq!(models[0].context_length, Some(128000));
+        assert_eq!(models[0].pricing.completion, "0.00001");
+        assert!(!models[1].supports_tools);
+        assert_eq!(models[1].name, "some/base-model");
+        assert_eq!(models[1].pricing.prompt, "");
+    }
+
+    #[tokio::test]
+    async fn test_request_limiter_is_shared_and_respects_cancellation() {
+        let token = CancellationToken::new();
+        let client = OpenRouterClient::new("test".to_string())
+            .with_max_concurrent_requests(1)
+            .with_cancellation(token.clone());
+        let other = OpenRouterClient::new("test".to_string());
+
+        let held = client.acquire_permit().await.unwrap();
+        let waiting = other.acquire_permit();
+        tokio::pin!(waiting);
+        assert!(tokio::time::timeout(Duration::from_millis(20), &mut waiting).await.is_err());
+
+        token.cancel();
+        assert!(matches!(client.acquire_permit().await, Err(AppError::OperationCancelled(_))));
+
+        drop(held);
+        assert!(waiting.await.is_ok());
+    }
+
+    #[test]
+    fn test_validation_status() {
+        assert_eq!(
+            validation_status(StatusCode::UNAUTHORIZED, "No auth credentials found"),
+            SettingsValidationStatus::InvalidKey
+        );
+        assert_eq!(
+            validation_status(StatusCode::PAYMENT_REQUIRED, "Insufficient credits"),
+            SettingsValidationStatus::InsufficientCredits
+        );
+        assert_eq!(
+            validation_status(StatusCode::NOT_FOUND, "No endpoints found for x/y"),
+            SettingsValidationStatus::ModelNotFound
+        );
+        assert_eq!(
+            validation_status(StatusCode::INTERNAL_SERVER_ERROR, "oops"),
+            SettingsValidationStatus::Error
+        );
+    }
+
+    #[test]
+    fn test_parse_stream_line() {
+        let line = r#"data: {"choices":[{"delta":{"content":"Hel"}}]}"#;
+        match parse_stream_line(line).unwrap() {
+            Some(StreamEvent::Chunk(chunk)) => {
+                assert_eq!(chunk.choices[0].delta.content.as_deref(), Some("Hel"));
+            }
+            _ => panic!("expected a chunk"),
+        }
+
+        assert!(matches!(parse_stream_line("data: [DONE]\r\n").unwrap(), Some(StreamEvent::Done)));
+        assert!(parse_stream_line(": OPENROUTER PROCESSING").unwrap().is_none());
+        assert!(parse_stream_line("").unwrap().is_none());
+        assert!(parse_stream_line(r#"data: {"error":{"message":"overloaded"}}"#).is_err());
+    }
+
+    #[test]
+    fn test_retry_delay_grows_and_honors_retry_after() {
+        let first = retry_delay(0, None);
+        assert!(first >= RETRY_BASE_DELAY && first <= RETRY_BASE_DELAY * 3 / 2);
+
+        let third = retry_delay(2, None);
+        assert!(third >= RETRY_BASE_DELAY * 4);
+
+        assert_eq!(retry_delay(0, Some(Duration::from_secs(7))), Duration::from_secs(7));
+        assert!(retry_delay(10, None) <= RETRY_MAX_DELAY);
+    }
 }
@@ -17,6 +17,51 @@ pub struct OpenRouterRequest {
     pub tools: Option<Vec<Tool>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub parallel_tool_calls: Option<bool>,
+    /// Ask OpenRouter to include cost in the returned usage object
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage: Option<UsageOptions>,
+    #[serde(flatten)]
+    pub params: ModelParams,
+}
+
+/// Optional sampling parameters and provider routing sent with every completion.
+/// Unset fields are left out of the request, so OpenRouter's defaults apply.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelParams {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frequency_penalty: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub presence_penalty: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<ProviderPreferences>,
+}
+
+/// Which upstream providers OpenRouter may route a request to
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProviderPreferences {
+    /// Provider names tried in order, e.g. `["Anthropic", "Together"]`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub order: Vec<String>,
+    /// Whether providers outside `order` may serve the request when those fail
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allow_fallbacks: Option<bool>,
+    /// Only use providers that support every parameter in the request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub require_parameters: Option<bool>,
+    /// Providers never used
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignore: Vec<String>,
+    /// `"allow"` or `"deny"` providers that may store prompts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_collection: Option<String>,
+}
+
+/// Usage accounting options for a request
+#[derive(Debug, Serialize)]
+pub struct UsageOptions {
+    pub include: bool,
 }
 
 /// Response format for structured outputs
@@ -102,6 +147,134 @@ impl From<&crate::ai::agent::Message> for OpenRouterMessage {
 #[derive(Debug, Deserialize)]
 pub struct OpenRouterResponse {
     pub choices: Vec<Choice>,
+    #[serde(default)]
+    pub usage: Option<Usage>,
+}
+
+/// Token usage reported for a single completion
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Usage {
+    #[serde(default)]
+    pub prompt_tokens: u64,
+    #[serde(default)]
+    pub completion_tokens: u64,
+    #[serde(default)]
+    pub total_tokens: u64,
+    /// Cost in OpenRouter credits (USD), present when usage accounting is requested
+    #[serde(default)]
+    pub cost: Option<f64>,
+}
+
+/// Usage accumulated over several completions
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TokenUsage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub total_tokens: u64,
+    /// Estimated cost in USD; completions that did not report a cost count as zero
+    pub cost: f64,
+    pub requests: u32,
+}
+
+impl TokenUsage {
+    pub fn add(&mut self, usage: &Usage) {
+        self.prompt_tokens += usage.prompt_tokens;
+        self.completion_tokens += usage.completion_tokens;
+        self.total_tokens += if usage.total_tokens > 0 {
+            usage.total_tokens
+        } else {
+            usage.prompt_tokens + usage.completion_tokens
+        };
+        self.cost += usage.cost.unwrap_or(0.0);
+        self.requests += 1;
+    }
+
+    pub fn merge(&mut self, other: &TokenUsage) {
+        self.prompt_tokens += other.prompt_tokens;
+        self.completion_tokens += other.completion_tokens;
+        self.total_tokens += other.total_tokens;
+        self.cost += other.cost;
+        self.requests += other.requests;
+    }
+}
+
+/// Model listing from OpenRouter's `/models` endpoint
+#[derive(Debug, Deserialize)]
+pub struct ModelsResponse {
+    pub data: Vec<ModelInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ModelInfo {
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub context_length: Option<u64>,
+    #[serde(default)]
+    pub pricing: ModelPricing,
+    /// Request parameters the model accepts, e.g. "tools", "response_format"
+    #[serde(default)]
+    pub supported_parameters: Vec<String>,
+}
+
+/// USD per token, as the decimal strings OpenRouter returns
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ModelPricing {
+    #[serde(default)]
+    pub prompt: String,
+    #[serde(default)]
+    pub completion: String,
+}
+
+/// Model entry returned by `list_openrouter_models`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenRouterModel {
+    pub id: String,
+    pub name: String,
+    pub context_length: Option<u64>,
+    pub pricing: ModelPricing,
+    /// Accepts `tools`, which the SQL agent's table lookup needs
+    pub supports_tools: bool,
+}
+
+impl From<ModelInfo> for OpenRouterModel {
+    fn from(info: ModelInfo) -> Self {
+        let supports_tools = info.supported_parameters.iter().any(|p| p == "tools");
+        Self {
+            name: if info.name.is_empty() { info.id.clone() } else { info.name },
+            id: info.id,
+            context_length: info.context_length,
+            pricing: info.pricing,
+            supports_tools,
+        }
+    }
+}
+
+/// Outcome of probing OpenRouter with an API key and model
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SettingsValidationStatus {
+    Valid,
+    /// 401: the key is missing, malformed or revoked
+    InvalidKey,
+    /// 402: the key works but the account has no credit left
+    InsufficientCredits,
+    /// 404: the key works but the model does not exist
+    ModelNotFound,
+    /// Network failure or any other unexpected response
+    Error,
+}
+
+/// Result of `validate_settings`. `None` means the check could not tell.
+#[derive(Debug, Clone, Serialize)]
+pub struct SettingsValidation {
+    pub status: SettingsValidationStatus,
+    pub key_valid: Option<bool>,
+    pub model_available: Option<bool>,
+    /// Context window of the model as listed by OpenRouter
+    pub context_length: Option<u64>,
+    pub message: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -109,3 +282,28 @@ pub struct Choice {
     pub message: OpenRouterMessage,
 }
 
+
+/// One `data:` chunk of a streamed completion
+#[derive(Debug, Deserialize)]
+pub struct StreamChunk {
+    #[serde(default)]
+    pub choices: Vec<StreamChoice>,
+    /// Sent with the final chunk when usage accounting is requested
+    #[serde(default)]
+    pub usage: Option<Usage>,
+    /// Mid-stream failure reported by OpenRouter after the HTTP status was already sent
+    #[serde(default)]
+    pub error: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StreamChoice {
+    #[serde(default)]
+    pub delta: StreamDelta,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct StreamDelta {
+    #[serde(default)]
+    pub content: Option<String>,
+}
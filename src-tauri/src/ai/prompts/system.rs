@@ -22,5 +22,15 @@ pub fn build_classification_prompt() -> &'static str {
 6. complex: Multi-step analysis requiring joins or complex aggregation
    Examples: "top 10 customers by lifetime value", "cohort analysis", "users who ordered more than 3 times"
 
+7. heatmap: User wants one metric broken down by TWO dimensions at once, or a correlation/pivot grid
+   Examples: "orders by weekday and hour", "sales by region and product category", "correlation matrix"
+
+8. data_change: User asks to add, change or remove data rather than read it
+   Examples: "mark order 5 as shipped", "add a customer named Ada", "delete the test users"
+
+9. distribution: User wants the spread of individual numeric values, optionally compared across groups (box plot)
+   Examples: "distribution of order amounts", "spread of salaries by department", "range of response times", "outliers in delivery days"
+   NOT for counts per category like "distribution of statuses" (category_chart)
+
 Return the category that best matches."#
 }
@@ -1,3 +1,4 @@
+use crate::db::connection::DatabaseType;
 use crate::error::{AppError, AppResult};
 use regex::Regex;
 use std::sync::LazyLock;
@@ -15,7 +16,7 @@ static DENY_PATTERNS: LazyLock<Vec<Regex>> = LazyLock::new(|| {
         // Union-based injection
         Regex::new(r"(?i)\bUNION\b.*\bSELECT\b").unwrap(),
         // Stacked queries
-        Regex::new(r";\s*(SELECT|INSERT|UPDATE|DELETE|DROP|ALTER|CREATE)").unwrap(),
+        Regex::new(r"(?i);\s*(SELECT|WITH|EXPLAIN|INSERT|UPDATE|DELETE|DROP|ALTER|CREATE)").unwrap(),
     ]
 });
 
@@ -24,8 +25,116 @@ static HAS_LIMIT_RE: LazyLock<Regex> = LazyLock::new(|| {
     Regex::new(r"(?i)\bLIMIT\s+\d+").unwrap()
 });
 
-/// Validate and sanitize SQL query for agent execution
-pub fn validate_sql(query: &str) -> AppResult<String> {
+/// EXPLAIN prefix with PostgreSQL `(...)` options or MySQL/PostgreSQL keyword options
+static EXPLAIN_PREFIX_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)^EXPLAIN(\s*\([^)]*\)|\s+(ANALYZE|VERBOSE|EXTENDED|FORMAT\s*=\s*\w+))*\s+").unwrap()
+});
+
+/// Blank out string literals and quoted identifiers (and, optionally, everything
+/// inside parentheses) so structural checks only see top-level SQL.
+/// Byte offsets are preserved so matches map back onto the original query.
+fn mask_sql(query: &str, mask_nested: bool) -> AppResult<String> {
+    let mut masked = String::with_capacity(query.len());
+    let mut quote: Option<char> = None;
+    let mut depth = 0usize;
+    let mut prev = '\0';
+
+    for c in query.chars() {
+        let hidden = match quote {
+            Some(q) => {
+                if c == q {
+                    // MySQL treats \' as an escaped quote and PostgreSQL as the end of the
+                    // string; refuse rather than guess where the literal ends
+                    if prev == '\\' {
+                        return Err(AppError::SecurityError(
+                            "Backslash-escaped quotes are not allowed".into(),
+                        ));
+                    }
+                    // A doubled quote ('') closes and immediately reopens, so toggling is enough
+                    quote = None;
+                }
+                true
+            }
+            None => match c {
+                '\'' | '"' | '`' => {
+                    quote = Some(c);
+                    true
+                }
+                '(' => {
+                    depth += 1;
+                    mask_nested && depth > 1
+                }
+                ')' => {
+                    depth = depth.checked_sub(1).ok_or_else(|| {
+                        AppError::SecurityError("Unbalanced parentheses in query".into())
+                    })?;
+                    mask_nested && depth > 0
+                }
+                _ => mask_nested && depth > 0,
+            },
+        };
+
+        prev = c;
+        if hidden {
+            masked.extend(std::iter::repeat(' ').take(c.len_utf8()));
+        } else {
+            masked.push(c);
+        }
+    }
+
+    if quote.is_some() {
+        return Err(AppError::SecurityError("Unterminated quoted string in query".into()));
+    }
+    if depth != 0 {
+        return Err(AppError::SecurityError("Unbalanced parentheses in query".into()));
+    }
+
+    Ok(masked)
+}
+
+/// For a `WITH` query, the keyword of the statement that follows the CTE list.
+/// `top_level` must have nested content masked by `mask_sql`.
+fn cte_main_statement(top_level: &str) -> Option<String> {
+    let mut rest = top_level.trim_start();
+    rest = rest.get(4..)?.trim_start();
+    if rest.len() >= 9 && rest[..9].eq_ignore_ascii_case("RECURSIVE") {
+        rest = rest[9..].trim_start();
+    }
+
+    // Each CTE is `name [(columns)] AS [[NOT] MATERIALIZED] (body)`, separated by commas.
+    // Nested text is blanked, so every CTE body reads as "()"
+    loop {
+        let body_end = rest.find(')')?;
+        rest = rest[body_end + 1..].trim_start();
+        // A column list is followed by AS, not by a comma or the main statement
+        let next_word: String = rest.chars().take_while(|c| c.is_ascii_alphabetic()).collect();
+        if next_word.eq_ignore_ascii_case("AS") {
+            continue;
+        }
+        match rest.strip_prefix(',') {
+            Some(after) => rest = after,
+            None => return Some(next_word.to_uppercase()),
+        }
+    }
+}
+
+/// Default cap on rows returned by AI-executed queries
+pub const DEFAULT_MAX_ROWS: u64 = 100;
+
+/// `#` starts a comment only in MySQL and MariaDB; PostgreSQL uses it for operators
+/// such as `#>`, `#>>` and bitwise XOR
+fn has_hash_comment(unquoted: &str, db_type: &DatabaseType) -> bool {
+    matches!(db_type, DatabaseType::MySQL | DatabaseType::MariaDB) && unquoted.contains('#')
+}
+
+/// Validate and sanitize SQL query for agent execution, capping it at `DEFAULT_MAX_ROWS`
+pub fn validate_sql(query: &str, db_type: &DatabaseType) -> AppResult<String> {
+    validate_sql_with_limit(query, DEFAULT_MAX_ROWS, db_type)
+}
+
+/// Validate and sanitize SQL query for agent execution.
+/// A missing top-level LIMIT is added and one above `max_rows` is lowered to it.
+pub fn validate_sql_with_limit(query: &str, max_rows: u64, db_type: &DatabaseType) -> AppResult<String> {
     let trimmed = query.trim();
 
     // Must not be empty
@@ -33,10 +142,36 @@ pub fn validate_sql(query: &str) -> AppResult<String> {
         return Err(AppError::SecurityError("Empty query".into()));
     }
 
-    let normalized = trimmed.to_uppercase();
+    // String contents are ignored so quoted text can't trip (or hide from) the checks below
+    let unquoted = mask_sql(trimmed, false)?;
 
-    // Must start with SELECT
-    if !normalized.starts_with("SELECT") {
+    // Comments could hide a second statement from the checks below
+    if has_hash_comment(&unquoted, db_type) {
+        return Err(AppError::SecurityError("SQL comments are not allowed".into()));
+    }
+
+    // Only a trailing semicolon is allowed; anything else is a second statement
+    if unquoted.trim_end_matches(|c: char| c == ';' || c.is_whitespace()).contains(';') {
+        return Err(AppError::SecurityError("Multiple statements are not allowed".into()));
+    }
+
+    // EXPLAIN [ANALYZE] is allowed in front of an otherwise valid query
+    let statement_start = EXPLAIN_PREFIX_RE
+        .find(trimmed)
+        .map(|m| m.end())
+        .unwrap_or(0);
+    let statement = &trimmed[statement_start..];
+    let normalized = statement.to_uppercase();
+
+    // Must be a SELECT, or a WITH whose main statement is a SELECT
+    if normalized.starts_with("WITH") {
+        let top_level = mask_sql(statement, true)?;
+        if cte_main_statement(&top_level).as_deref() != Some("SELECT") {
+            return Err(AppError::SecurityError(
+                "Only WITH ... SELECT queries are allowed for AI agent".into(),
+            ));
+        }
+    } else if !normalized.starts_with("SELECT") {
         return Err(AppError::SecurityError(
             "Only SELECT queries are allowed for AI agent".into(),
         ));
@@ -61,21 +196,18 @@ pub fn validate_sql(query: &str) -> AppResult<String> {
         sanitized.pop();
     }
 
-    // Ensure LIMIT exists (max 100 rows for AI)
-    if !HAS_LIMIT_RE.is_match(&sanitized) {
-        sanitized.push_str(" LIMIT 100");
+    // Ensure a top-level LIMIT exists (at most max_rows); a LIMIT inside a CTE
+    // or subquery doesn't bound the result
+    let top_level = mask_sql(&sanitized, true)?;
+    if !HAS_LIMIT_RE.is_match(&top_level) {
+        sanitized.push_str(&format!(" LIMIT {}", max_rows));
     } else {
-        // Check that LIMIT doesn't exceed 100
-        if let Some(captures) = Regex::new(r"(?i)LIMIT\s+(\d+)").unwrap().captures(&sanitized) {
+        // Check that LIMIT doesn't exceed max_rows
+        if let Some(captures) = Regex::new(r"(?i)LIMIT\s+(\d+)").unwrap().captures(&top_level) {
             if let Some(limit_str) = captures.get(1) {
-                if let Ok(limit) = limit_str.as_str().parse::<i32>() {
-                    if limit > 100 {
-                        // Replace with max limit
-                        sanitized = Regex::new(r"(?i)LIMIT\s+\d+")
-                            .unwrap()
-                            .replace(&sanitized, "LIMIT 100")
-                            .to_string();
-                    }
+                if !matches!(limit_str.as_str().parse::<u64>(), Ok(limit) if limit <= max_rows) {
+                    // Replace with max limit (same byte range in the original query)
+                    sanitized.replace_range(limit_str.range(), &max_rows.to_string());
                 }
             }
         }
@@ -84,6 +216,77 @@ pub fn validate_sql(query: &str) -> AppResult<String> {
     Ok(sanitized)
 }
 
+/// Schema and permission changes, never allowed even in write mode
+static DDL_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(DROP|ALTER|CREATE|TRUNCATE|GRANT|REVOKE|RENAME)\b").unwrap()
+});
+
+/// Top-level WHERE keyword
+static HAS_WHERE_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)\bWHERE\b").unwrap());
+
+/// Validate a data-changing statement proposed by the agent in write mode. Only a single
+/// INSERT, UPDATE or DELETE is accepted, so a write can never ride along with a read (or
+/// the reverse, since `validate_sql` rejects all of these). UPDATE and DELETE must have a
+/// top-level WHERE clause so they can't silently touch every row.
+pub fn validate_sql_write(query: &str, db_type: &DatabaseType) -> AppResult<String> {
+    let trimmed = query.trim();
+    if trimmed.is_empty() {
+        return Err(AppError::SecurityError("Empty query".into()));
+    }
+
+    let unquoted = mask_sql(trimmed, false)?;
+    if has_hash_comment(&unquoted, db_type) || unquoted.contains("--") || unquoted.contains("/*") {
+        return Err(AppError::SecurityError("SQL comments are not allowed".into()));
+    }
+    if unquoted.trim_end_matches(|c: char| c == ';' || c.is_whitespace()).contains(';') {
+        return Err(AppError::SecurityError("Multiple statements are not allowed".into()));
+    }
+    if DDL_RE.is_match(&unquoted) {
+        return Err(AppError::SecurityError(
+            "Schema changes are not allowed for AI agent".into(),
+        ));
+    }
+
+    let keyword: String = trimmed
+        .chars()
+        .take_while(|c| c.is_ascii_alphabetic())
+        .collect::<String>()
+        .to_uppercase();
+    match keyword.as_str() {
+        "INSERT" => {}
+        "UPDATE" | "DELETE" => {
+            let top_level = mask_sql(trimmed, true)?;
+            if !HAS_WHERE_RE.is_match(&top_level) {
+                return Err(AppError::SecurityError(format!(
+                    "{} without a WHERE clause is not allowed for AI agent",
+                    keyword
+                )));
+            }
+        }
+        _ => {
+            return Err(AppError::SecurityError(
+                "Only INSERT, UPDATE or DELETE statements are allowed in write mode".into(),
+            ));
+        }
+    }
+
+    Ok(trimmed.trim_end_matches(|c: char| c == ';' || c.is_whitespace()).to_string())
+}
+
+/// Validate a user-supplied WHERE condition (without the WHERE keyword) before it is
+/// spliced into a generated SELECT. Its parentheses must balance on their own so it
+/// can't close the wrapping `WHERE (...)` early, and it may not contain a statement separator.
+pub fn validate_where_clause(clause: &str, db_type: &DatabaseType) -> AppResult<()> {
+    let trimmed = clause.trim();
+    if trimmed.is_empty() {
+        return Err(AppError::SecurityError("Empty WHERE clause".into()));
+    }
+    if mask_sql(trimmed, false)?.contains(';') {
+        return Err(AppError::SecurityError("Multiple statements are not allowed".into()));
+    }
+    validate_sql(&format!("SELECT 1 FROM t WHERE ({})", trimmed), db_type).map(|_| ())
+}
+
 /// Additional validation for specific database types
 pub fn validate_for_db_type(query: &str, db_type: &str) -> AppResult<()> {
     match db_type {
@@ -114,63 +317,171 @@ pub fn validate_for_db_type(query: &str, db_type: &str) -> AppResult<()> {
 mod tests {
     use super::*;
 
+    const PG: DatabaseType = DatabaseType::PostgreSQL;
+
     #[test]
     fn test_valid_select() {
-        let result = validate_sql("SELECT * FROM users");
+        let result = validate_sql("SELECT * FROM users", &PG);
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), "SELECT * FROM users LIMIT 100");
     }
 
     #[test]
     fn test_select_with_limit() {
-        let result = validate_sql("SELECT * FROM users LIMIT 50");
+        let result = validate_sql("SELECT * FROM users LIMIT 50", &PG);
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), "SELECT * FROM users LIMIT 50");
     }
 
     #[test]
     fn test_limit_too_high() {
-        let result = validate_sql("SELECT * FROM users LIMIT 500");
+        let result = validate_sql("SELECT * FROM users LIMIT 500", &PG);
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), "SELECT * FROM users LIMIT 100");
     }
 
+    #[test]
+    fn test_configured_row_cap() {
+        assert_eq!(
+            validate_sql_with_limit("SELECT * FROM users", 1000, &PG).unwrap(),
+            "SELECT * FROM users LIMIT 1000"
+        );
+        assert_eq!(
+            validate_sql_with_limit("SELECT * FROM users LIMIT 500", 1000, &PG).unwrap(),
+            "SELECT * FROM users LIMIT 500"
+        );
+        assert_eq!(
+            validate_sql_with_limit("SELECT * FROM users LIMIT 5000", 1000, &PG).unwrap(),
+            "SELECT * FROM users LIMIT 1000"
+        );
+    }
+
     #[test]
     fn test_reject_insert() {
-        let result = validate_sql("INSERT INTO users (name) VALUES ('test')");
+        let result = validate_sql("INSERT INTO users (name) VALUES ('test')", &PG);
         assert!(result.is_err());
     }
 
     #[test]
     fn test_reject_update() {
-        let result = validate_sql("UPDATE users SET name = 'test'");
+        let result = validate_sql("UPDATE users SET name = 'test'", &PG);
         assert!(result.is_err());
     }
 
     #[test]
     fn test_reject_delete() {
-        let result = validate_sql("DELETE FROM users");
+        let result = validate_sql("DELETE FROM users", &PG);
         assert!(result.is_err());
     }
 
     #[test]
     fn test_reject_drop() {
-        let result = validate_sql("DROP TABLE users");
+        let result = validate_sql("DROP TABLE users", &PG);
         assert!(result.is_err());
     }
 
     #[test]
     fn test_reject_comment() {
-        let result = validate_sql("SELECT * FROM users -- comment");
+        let result = validate_sql("SELECT * FROM users -- comment", &PG);
         assert!(result.is_err());
     }
 
     #[test]
     fn test_reject_union_injection() {
-        let result = validate_sql("SELECT * FROM users UNION SELECT * FROM passwords");
+        let result = validate_sql("SELECT * FROM users UNION SELECT * FROM passwords", &PG);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_cte_select_allowed() {
+        let result = validate_sql(
+            "WITH recent AS (SELECT id FROM orders WHERE created_at > '2024-01-01' LIMIT 500) \
+             SELECT COUNT(*) FROM recent",
+            &PG,
+        );
+        assert_eq!(
+            result.unwrap(),
+            "WITH recent AS (SELECT id FROM orders WHERE created_at > '2024-01-01' LIMIT 500) \
+             SELECT COUNT(*) FROM recent LIMIT 100"
+        );
+
+        let result = validate_sql("WITH RECURSIVE t(n) AS (SELECT 1), u AS (SELECT 2) SELECT * FROM t, u LIMIT 10", &PG);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_reject_data_modifying_cte() {
+        let result = validate_sql("WITH gone AS (DELETE FROM users RETURNING id) SELECT * FROM gone", &PG);
+        assert!(result.is_err());
+
+        let result = validate_sql("WITH x AS (SELECT 1) INSERT INTO t SELECT * FROM x", &PG);
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_explain_select_allowed() {
+        assert!(validate_sql("EXPLAIN SELECT * FROM users", &PG).is_ok());
+        assert!(validate_sql("EXPLAIN ANALYZE SELECT * FROM users", &PG).is_ok());
+        assert!(validate_sql("EXPLAIN (ANALYZE, FORMAT JSON) WITH x AS (SELECT 1) SELECT * FROM x", &PG).is_ok());
+        assert!(validate_sql("EXPLAIN ANALYZE DELETE FROM users", &PG).is_err());
+    }
+
+    #[test]
+    fn test_reject_smuggled_statements() {
+        assert!(validate_sql("SELECT 1; SELECT 2", &PG).is_err());
+        assert!(validate_sql("SELECT 1 # comment\n; WITH x AS (SELECT 1) SELECT * FROM x", &DatabaseType::MySQL).is_err());
+        assert!(validate_sql("SELECT 1 /* ; */", &PG).is_err());
+        assert!(validate_sql("SELECT 'unterminated", &PG).is_err());
+        // Semicolons and hashes inside literals are data, not syntax
+        assert!(validate_sql("SELECT * FROM tags WHERE name = '#a;b'", &PG).is_ok());
+        // Dialects disagree on where this literal ends
+        assert!(validate_sql("SELECT 'a\\' , ' ; SELECT 2; '", &PG).is_err());
+    }
+
+    #[test]
+    fn test_hash_is_a_comment_only_on_mysql() {
+        // PostgreSQL JSON path and XOR operators
+        assert!(validate_sql("SELECT data #>> '{a,b}' FROM t", &PG).is_ok());
+        assert!(validate_sql("SELECT data #> '{a}', 5 # 3 FROM t", &PG).is_ok());
+
+        assert!(validate_sql("SELECT 1 # c", &DatabaseType::MySQL).is_err());
+        assert!(validate_sql("SELECT 1 # c", &DatabaseType::MariaDB).is_err());
+        assert!(validate_sql_write("DELETE FROM t WHERE id = 1 # c", &DatabaseType::MySQL).is_err());
+        assert!(validate_where_clause("id = 1 # c", &DatabaseType::MariaDB).is_err());
+    }
+
+    #[test]
+    fn test_where_clause_validation() {
+        assert!(validate_where_clause("status = 'active' AND total > 10", &PG).is_ok());
+        assert!(validate_where_clause("name = 'a;b'", &PG).is_ok());
+        assert!(validate_where_clause("1=1; DROP TABLE users", &PG).is_err());
+        assert!(validate_where_clause("1=1) UNION SELECT password FROM users WHERE (1=1", &PG).is_err());
+        assert!(validate_where_clause("1=1) OR (1=1", &PG).is_err());
+        assert!(validate_where_clause("id = 1 -- ", &PG).is_err());
+        assert!(validate_where_clause("  ", &PG).is_err());
+    }
+
+    #[test]
+    fn test_write_mode_validation() {
+        assert_eq!(
+            validate_sql_write("UPDATE orders SET status = 'shipped' WHERE id = 5;", &PG).unwrap(),
+            "UPDATE orders SET status = 'shipped' WHERE id = 5"
+        );
+        assert!(validate_sql_write("INSERT INTO tags (name) VALUES ('a;b')", &PG).is_ok());
+        assert!(validate_sql_write("DELETE FROM orders WHERE id IN (SELECT id FROM stale)", &PG).is_ok());
+
+        // Whole-table changes, reads, DDL and stacked statements are refused
+        assert!(validate_sql_write("DELETE FROM orders", &PG).is_err());
+        assert!(validate_sql_write("UPDATE t SET a = (SELECT 1 WHERE true)", &PG).is_err());
+        assert!(validate_sql_write("SELECT * FROM orders", &PG).is_err());
+        assert!(validate_sql_write("DROP TABLE orders", &PG).is_err());
+        assert!(validate_sql_write("UPDATE t SET a = 1 WHERE id = 1; SELECT 1", &PG).is_err());
+        assert!(validate_sql_write("DELETE FROM t WHERE id = 1 -- ", &PG).is_err());
+
+        // The read path still rejects every write
+        assert!(validate_sql("UPDATE orders SET status = 'shipped' WHERE id = 5", &PG).is_err());
+    }
+
     #[test]
     fn test_complex_valid_query() {
         let query = "SELECT u.id, u.name, COUNT(o.id) as order_count
@@ -179,7 +490,7 @@ mod tests {
                      WHERE u.created_at > '2024-01-01'
                      GROUP BY u.id, u.name
                      ORDER BY order_count DESC";
-        let result = validate_sql(query);
+        let result = validate_sql(query, &PG);
         assert!(result.is_ok());
     }
 }
@@ -1,3 +1,3 @@
 pub mod plotly_generator;
 
-pub use plotly_generator::generate_plotly_code;
+pub use plotly_generator::{generate_plotly_code, generate_plotly_code_with_options, ChartOptions};
@@ -3,6 +3,14 @@ use crate::db::query::QueryResult;
 use crate::error::{AppError, AppResult};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::HashMap;
+
+/// Fewer rows than this are too few to show as a distribution
+const MIN_BOX_PLOT_ROWS: usize = 8;
+/// Rows needed before a lone numeric column is shown as a distribution unasked
+const AUTO_BOX_PLOT_ROWS: usize = 50;
+/// More groups than this would squeeze the boxes into slivers
+const MAX_BOX_PLOT_GROUPS: usize = 20;
 
 /// Generated Plotly visualization data (JSON format)
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,11 +25,58 @@ pub struct PlotlyVisualization {
     pub chart_type: String,
 }
 
+/// How multi-series bar charts arrange their bars
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BarMode {
+    #[default]
+    Group,
+    Stack,
+}
+
+impl BarMode {
+    fn as_plotly(self) -> &'static str {
+        match self {
+            BarMode::Group => "group",
+            BarMode::Stack => "stack",
+        }
+    }
+}
+
+/// Options that adjust chart generation
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChartOptions {
+    #[serde(default)]
+    pub bar_mode: BarMode,
+}
+
+impl ChartOptions {
+    /// Derive options from the wording of the question (e.g. "stacked bar chart")
+    pub fn for_question(question: &str) -> Self {
+        let bar_mode = if question.to_lowercase().contains("stack") {
+            BarMode::Stack
+        } else {
+            BarMode::Group
+        };
+        Self { bar_mode }
+    }
+}
+
 /// Generate Plotly.js code from query result
 pub fn generate_plotly_code(
     data: &QueryResult,
     question_type: &QuestionType,
     question: &str,
+) -> AppResult<PlotlyVisualization> {
+    generate_plotly_code_with_options(data, question_type, question, &ChartOptions::default())
+}
+
+/// Generate Plotly.js code from query result with explicit chart options
+pub fn generate_plotly_code_with_options(
+    data: &QueryResult,
+    question_type: &QuestionType,
+    question: &str,
+    options: &ChartOptions,
 ) -> AppResult<PlotlyVisualization> {
     if data.row_count == 0 {
         return Err(AppError::VisualizationError(
@@ -45,14 +100,27 @@ pub fn generate_plotly_code(
 
     match question_type {
         QuestionType::TemporalChart => generate_temporal_chart(data, &temporal_cols, &numeric_cols, question),
-        QuestionType::CategoryChart => generate_category_chart(data, &categorical_cols, &numeric_cols, question),
+        QuestionType::CategoryChart => generate_category_chart(data, &categorical_cols, &numeric_cols, question, options),
         QuestionType::Statistic => generate_statistic_chart(data, question),
+        QuestionType::Heatmap => generate_heatmap_chart(data, &numeric_cols, question),
+        QuestionType::Distribution if data.row_count >= MIN_BOX_PLOT_ROWS && !numeric_cols.is_empty() => {
+            generate_box_plot(data, &categorical_cols, &numeric_cols, question)
+        }
         _ => {
             // Auto-detect based on data
             if !temporal_cols.is_empty() && !numeric_cols.is_empty() {
                 generate_temporal_chart(data, &temporal_cols, &numeric_cols, question)
+            } else if temporal_cols.is_empty()
+                && numeric_cols.len() == 1
+                && data.row_count >= AUTO_BOX_PLOT_ROWS
+                && suits_box_plot(data, &categorical_cols)
+            {
+                generate_box_plot(data, &categorical_cols, &numeric_cols, question)
+            } else if temporal_cols.is_empty() && numeric_cols.len() == 2 {
+                let color_col = categorical_cols.iter().find(|c| !is_id_column(c));
+                generate_scatter_chart(data, &numeric_cols, color_col, question)
             } else if !categorical_cols.is_empty() && !numeric_cols.is_empty() {
-                generate_category_chart(data, &categorical_cols, &numeric_cols, question)
+                generate_category_chart(data, &categorical_cols, &numeric_cols, question, options)
             } else {
                 generate_default_chart(data, question)
             }
@@ -120,12 +188,14 @@ fn generate_temporal_chart(
     })
 }
 
-/// Generate a bar chart for categorical data
+/// Generate a bar chart for categorical data. A second categorical column
+/// splits the values into one bar series per group.
 fn generate_category_chart(
     data: &QueryResult,
     categorical_cols: &[String],
     numeric_cols: &[String],
     question: &str,
+    options: &ChartOptions,
 ) -> AppResult<PlotlyVisualization> {
     let x_col = categorical_cols
         .first()
@@ -135,6 +205,10 @@ fn generate_category_chart(
         .first()
         .ok_or_else(|| AppError::VisualizationError("No numeric column found".into()))?;
 
+    if let Some(group_col) = categorical_cols.get(1) {
+        return generate_multi_series_bar_chart(data, x_col, group_col, y_col, question, options.bar_mode);
+    }
+
     let x_values = extract_column_values_json(data, x_col);
     let y_values = extract_column_values_json(data, y_col);
 
@@ -203,6 +277,351 @@ fn generate_category_chart(
     })
 }
 
+/// Generate a grouped or stacked bar chart with one trace per value of `group_col`
+fn generate_multi_series_bar_chart(
+    data: &QueryResult,
+    x_col: &str,
+    group_col: &str,
+    y_col: &str,
+    question: &str,
+    bar_mode: BarMode,
+) -> AppResult<PlotlyVisualization> {
+    let pivot = Pivot::build(data, group_col, x_col, y_col);
+
+    let traces: Vec<Value> = pivot
+        .row_labels
+        .iter()
+        .enumerate()
+        .map(|(gi, group)| {
+            serde_json::json!({
+                "x": pivot.col_labels,
+                "y": pivot.row_values(gi),
+                "type": "bar",
+                "name": group
+            })
+        })
+        .collect();
+
+    let title = generate_title_from_question(question, "Distribution by Category");
+    let layout = serde_json::json!({
+        "title": { "text": title, "font": { "size": 16 } },
+        "xaxis": {
+            "title": x_col,
+            "type": "category",
+            "tickangle": -45,
+            "automargin": true
+        },
+        "yaxis": { "title": y_col },
+        "barmode": bar_mode.as_plotly(),
+        "showlegend": true,
+        "legend": { "title": { "text": group_col } },
+        "margin": { "l": 60, "r": 30, "t": 50, "b": 100 },
+        "paper_bgcolor": "transparent",
+        "plot_bgcolor": "transparent",
+        "font": { "color": "currentColor" },
+        "bargap": 0.3
+    });
+
+    Ok(PlotlyVisualization {
+        data: traces,
+        layout,
+        title,
+        chart_type: "bar".to_string(),
+    })
+}
+
+/// Values of one numeric column laid out on a grid keyed by two label columns.
+/// Labels keep their first-seen order, repeated cells are summed and
+/// cells with no matching row stay empty.
+struct Pivot {
+    row_labels: Vec<String>,
+    col_labels: Vec<String>,
+    cells: HashMap<(usize, usize), f64>,
+}
+
+impl Pivot {
+    fn build(data: &QueryResult, row_col: &str, col_col: &str, value_col: &str) -> Self {
+        let mut row_labels: Vec<String> = Vec::new();
+        let mut col_labels: Vec<String> = Vec::new();
+        let mut row_index: HashMap<String, usize> = HashMap::new();
+        let mut col_index: HashMap<String, usize> = HashMap::new();
+        let mut cells: HashMap<(usize, usize), f64> = HashMap::new();
+
+        for row in &data.rows {
+            let r = axis_label(row.get(row_col));
+            let c = axis_label(row.get(col_col));
+
+            let ri = *row_index.entry(r.clone()).or_insert_with(|| {
+                row_labels.push(r);
+                row_labels.len() - 1
+            });
+            let ci = *col_index.entry(c.clone()).or_insert_with(|| {
+                col_labels.push(c);
+                col_labels.len() - 1
+            });
+
+            if let Some(value) = row.get(value_col).and_then(value_as_f64) {
+                *cells.entry((ri, ci)).or_insert(0.0) += value;
+            }
+        }
+
+        Self { row_labels, col_labels, cells }
+    }
+
+    /// One row of the grid, with null for missing cells
+    fn row_values(&self, ri: usize) -> Vec<Value> {
+        (0..self.col_labels.len())
+            .map(|ci| self.cells.get(&(ri, ci)).map(|v| serde_json::json!(v)).unwrap_or(Value::Null))
+            .collect()
+    }
+}
+
+/// Generate a heatmap by pivoting two categorical columns against one numeric column.
+/// Axis labels keep their first-seen order; cells with no matching row are null.
+fn generate_heatmap_chart(
+    data: &QueryResult,
+    numeric_cols: &[String],
+    question: &str,
+) -> AppResult<PlotlyVisualization> {
+    let axis_cols: Vec<&String> = data
+        .columns
+        .iter()
+        .filter(|c| !numeric_cols.contains(c))
+        .collect();
+
+    let (Some(y_col), Some(x_col), Some(z_col)) = (axis_cols.first(), axis_cols.get(1), numeric_cols.first()) else {
+        return generate_default_chart(data, question);
+    };
+
+    let pivot = Pivot::build(data, y_col, x_col, z_col);
+    let z: Vec<Vec<Value>> = (0..pivot.row_labels.len())
+        .map(|yi| pivot.row_values(yi))
+        .collect();
+
+    let trace = serde_json::json!({
+        "x": pivot.col_labels,
+        "y": pivot.row_labels,
+        "z": z,
+        "type": "heatmap",
+        "colorscale": "Viridis",
+        "hoverongaps": false,
+        "colorbar": { "title": z_col }
+    });
+
+    let title = generate_title_from_question(question, "Heatmap");
+    let layout = serde_json::json!({
+        "title": { "text": title, "font": { "size": 16 } },
+        "xaxis": {
+            "title": x_col,
+            "type": "category",
+            "tickangle": -45,
+            "automargin": true
+        },
+        "yaxis": {
+            "title": y_col,
+            "type": "category",
+            "automargin": true
+        },
+        "margin": { "l": 80, "r": 30, "t": 50, "b": 100 },
+        "paper_bgcolor": "transparent",
+        "plot_bgcolor": "transparent",
+        "font": { "color": "currentColor" }
+    });
+
+    Ok(PlotlyVisualization {
+        data: vec![trace],
+        layout,
+        title,
+        chart_type: "heatmap".to_string(),
+    })
+}
+
+/// Generate a box plot of value distributions: one box per group of the first usable
+/// categorical column, otherwise one box per numeric column
+fn generate_box_plot(
+    data: &QueryResult,
+    categorical_cols: &[String],
+    numeric_cols: &[String],
+    question: &str,
+) -> AppResult<PlotlyVisualization> {
+    let box_trace = |name: &str, values: Vec<f64>| {
+        serde_json::json!({
+            "y": values,
+            "type": "box",
+            "name": name,
+            "boxpoints": "outliers",
+            "boxmean": true
+        })
+    };
+
+    let (traces, y_title, x_title) = match box_plot_group_column(data, categorical_cols) {
+        Some(group_col) => {
+            let y_col = numeric_cols
+                .first()
+                .ok_or_else(|| AppError::VisualizationError("No numeric column found".into()))?;
+
+            // Groups in order of first appearance
+            let mut groups: Vec<String> = Vec::new();
+            for row in &data.rows {
+                let label = axis_label(row.get(group_col));
+                if !groups.contains(&label) {
+                    groups.push(label);
+                }
+            }
+            let traces = groups
+                .iter()
+                .map(|group| {
+                    let values = data
+                        .rows
+                        .iter()
+                        .filter(|row| axis_label(row.get(group_col)) == *group)
+                        .filter_map(|row| row.get(y_col).and_then(value_as_f64))
+                        .collect();
+                    box_trace(group, values)
+                })
+                .collect();
+            (traces, y_col.as_str(), Some(group_col.as_str()))
+        }
+        None => {
+            let traces = numeric_cols
+                .iter()
+                .map(|col| {
+                    let values = data.rows.iter().filter_map(|row| row.get(col).and_then(value_as_f64)).collect();
+                    box_trace(col, values)
+                })
+                .collect();
+            let y_title = if numeric_cols.len() == 1 { numeric_cols[0].as_str() } else { "Value" };
+            (traces, y_title, None)
+        }
+    };
+
+    let title = generate_title_from_question(question, "Value Distribution");
+    let layout = serde_json::json!({
+        "title": { "text": title, "font": { "size": 16 } },
+        "xaxis": {
+            "title": x_title.unwrap_or(""),
+            "tickangle": -45,
+            "automargin": true
+        },
+        "yaxis": { "title": y_title, "zeroline": false },
+        "margin": { "l": 60, "r": 30, "t": 50, "b": 100 },
+        "showlegend": false,
+        "paper_bgcolor": "transparent",
+        "plot_bgcolor": "transparent",
+        "font": { "color": "currentColor" }
+    });
+
+    Ok(PlotlyVisualization {
+        data: traces,
+        layout,
+        title,
+        chart_type: "box".to_string(),
+    })
+}
+
+/// First non-ID categorical column that splits the rows into a readable number of
+/// groups, each holding more than one value on average
+fn box_plot_group_column<'a>(data: &QueryResult, categorical_cols: &'a [String]) -> Option<&'a String> {
+    categorical_cols.iter().filter(|c| !is_id_column(c)).find(|col| {
+        let mut groups: Vec<String> = Vec::new();
+        for row in &data.rows {
+            let label = axis_label(row.get(col.as_str()));
+            if !groups.contains(&label) {
+                groups.push(label);
+                if groups.len() > MAX_BOX_PLOT_GROUPS {
+                    return false;
+                }
+            }
+        }
+        groups.len() >= 2 && groups.len() * 2 <= data.row_count
+    })
+}
+
+/// A lone numeric column reads as a distribution when nothing labels individual rows
+/// (only IDs, or no other column) or when its rows fall into a few repeated groups
+fn suits_box_plot(data: &QueryResult, categorical_cols: &[String]) -> bool {
+    categorical_cols.iter().all(|c| is_id_column(c)) || box_plot_group_column(data, categorical_cols).is_some()
+}
+
+/// Generate a scatter plot of two numeric columns, one trace per value of
+/// `color_col` when a categorical column is available
+fn generate_scatter_chart(
+    data: &QueryResult,
+    numeric_cols: &[String],
+    color_col: Option<&String>,
+    question: &str,
+) -> AppResult<PlotlyVisualization> {
+    let (Some(x_col), Some(y_col)) = (numeric_cols.first(), numeric_cols.get(1)) else {
+        return Err(AppError::VisualizationError(
+            "Need two numeric columns for scatter plot".into(),
+        ));
+    };
+
+    let marker = serde_json::json!({ "size": 8, "opacity": 0.8 });
+
+    let traces: Vec<Value> = match color_col {
+        Some(color_col) => {
+            let mut groups: Vec<String> = Vec::new();
+            let mut points: HashMap<String, (Vec<Value>, Vec<Value>)> = HashMap::new();
+
+            for row in &data.rows {
+                let group = axis_label(row.get(color_col));
+                let entry = points.entry(group.clone()).or_insert_with(|| {
+                    groups.push(group);
+                    (Vec::new(), Vec::new())
+                });
+                entry.0.push(row.get(x_col).cloned().unwrap_or(Value::Null));
+                entry.1.push(row.get(y_col).cloned().unwrap_or(Value::Null));
+            }
+
+            groups
+                .iter()
+                .map(|group| {
+                    let (xs, ys) = &points[group];
+                    serde_json::json!({
+                        "x": xs,
+                        "y": ys,
+                        "type": "scatter",
+                        "mode": "markers",
+                        "name": group,
+                        "marker": marker
+                    })
+                })
+                .collect()
+        }
+        None => vec![serde_json::json!({
+            "x": extract_column_values_json(data, x_col),
+            "y": extract_column_values_json(data, y_col),
+            "type": "scatter",
+            "mode": "markers",
+            "marker": {
+                "size": 8,
+                "opacity": 0.8,
+                "color": "#8884d8"
+            }
+        })],
+    };
+
+    let title = generate_title_from_question(question, &format!("{} vs {}", y_col, x_col));
+    let layout = serde_json::json!({
+        "title": { "text": title, "font": { "size": 16 } },
+        "xaxis": { "title": x_col, "automargin": true },
+        "yaxis": { "title": y_col, "automargin": true },
+        "showlegend": traces.len() > 1,
+        "margin": { "l": 60, "r": 30, "t": 50, "b": 60 },
+        "paper_bgcolor": "transparent",
+        "plot_bgcolor": "transparent",
+        "font": { "color": "currentColor" }
+    });
+
+    Ok(PlotlyVisualization {
+        data: traces,
+        layout,
+        title,
+        chart_type: "scatter".to_string(),
+    })
+}
+
 /// Generate a statistic indicator chart
 fn generate_statistic_chart(data: &QueryResult, question: &str) -> AppResult<PlotlyVisualization> {
     if data.row_count != 1 || data.columns.is_empty() {
@@ -251,14 +670,8 @@ fn generate_statistic_chart(data: &QueryResult, question: &str) -> AppResult<Plo
 
 /// Generate a default bar chart
 fn generate_default_chart(data: &QueryResult, question: &str) -> AppResult<PlotlyVisualization> {
-    if data.columns.len() < 2 {
-        return Err(AppError::VisualizationError(
-            "Need at least 2 columns for chart".into(),
-        ));
-    }
-
-    let x_col = &data.columns[0];
-    let y_col = &data.columns[1];
+    let (x_col, y_col) = default_chart_axes(data)?;
+    let (x_col, y_col) = (&x_col, &y_col);
 
     let x_values = extract_column_values_json(data, x_col);
     let y_values = extract_column_values_json(data, y_col);
@@ -298,6 +711,86 @@ fn generate_default_chart(data: &QueryResult, question: &str) -> AppResult<Plotl
     })
 }
 
+/// Pick the (x, y) columns for the default chart regardless of their position: a temporal
+/// or categorical column on x, and the most-varying numeric column on y
+fn default_chart_axes(data: &QueryResult) -> AppResult<(String, String)> {
+    if data.columns.len() < 2 {
+        return Err(AppError::VisualizationError(
+            "Need at least 2 columns for chart".into(),
+        ));
+    }
+
+    let temporal_cols = detect_temporal_columns(&data.columns, &data.rows);
+    let mut numeric_cols = detect_numeric_columns(&data.columns, &data.rows);
+    // Numbers sent as text (NUMERIC/DECIMAL) and keys are measures only when nothing else is
+    if numeric_cols.is_empty() {
+        numeric_cols = data
+            .columns
+            .iter()
+            .filter(|c| !temporal_cols.contains(c) && is_numeric_column(data, c))
+            .cloned()
+            .collect();
+    }
+
+    let x_col = temporal_cols
+        .first()
+        .or_else(|| data.columns.iter().find(|c| !numeric_cols.contains(c)))
+        .unwrap_or(&data.columns[0])
+        .clone();
+
+    let mut y_col: Option<(&String, f64)> = None;
+    for col in numeric_cols.iter().filter(|c| **c != x_col) {
+        let spread = column_spread(data, col);
+        // Ties keep the earlier column
+        let better = match y_col {
+            Some((_, best)) => spread > best,
+            None => true,
+        };
+        if better {
+            y_col = Some((col, spread));
+        }
+    }
+    let y_col = match y_col {
+        Some((col, _)) => col.clone(),
+        None => data.columns.iter().find(|c| **c != x_col).cloned().unwrap_or_default(),
+    };
+
+    Ok((x_col, y_col))
+}
+
+/// Whether every non-null value in a column reads as a number
+fn is_numeric_column(data: &QueryResult, column: &str) -> bool {
+    let mut values = data
+        .rows
+        .iter()
+        .filter_map(|row| row.get(column))
+        .filter(|v| !v.is_null())
+        .peekable();
+    values.peek().is_some() && values.all(|v| value_as_f64(v).is_some())
+}
+
+/// Relative spread of a numeric column (standard deviation over the mean's magnitude),
+/// so measures on different scales compare fairly
+fn column_spread(data: &QueryResult, column: &str) -> f64 {
+    let values: Vec<f64> = data
+        .rows
+        .iter()
+        .filter_map(|row| row.get(column).and_then(value_as_f64))
+        .collect();
+    if values.len() < 2 {
+        return 0.0;
+    }
+
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+    let std_dev = variance.sqrt();
+    if mean.abs() > f64::EPSILON {
+        std_dev / mean.abs()
+    } else {
+        std_dev
+    }
+}
+
 /// Extract column values as a JSON array
 fn extract_column_values_json(data: &QueryResult, column: &str) -> Vec<Value> {
     data.rows
@@ -310,6 +803,24 @@ fn extract_column_values_json(data: &QueryResult, column: &str) -> Vec<Value> {
         .collect()
 }
 
+/// Axis label for a categorical cell value
+fn axis_label(value: Option<&Value>) -> String {
+    match value {
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Null) | None => "(null)".to_string(),
+        Some(other) => other.to_string(),
+    }
+}
+
+/// Read a numeric cell, accepting numbers sent as strings (e.g. NUMERIC/DECIMAL)
+fn value_as_f64(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.parse::<f64>().ok(),
+        _ => None,
+    }
+}
+
 /// Convert a JSON value to a number for Plotly indicators
 fn value_to_number_json(value: &Value) -> Value {
     match value {
@@ -437,9 +948,8 @@ fn detect_numeric_columns(
     let mut numeric = Vec::new();
 
     for col in columns {
-        // Skip if it's clearly an ID column
-        let col_lower = col.to_lowercase();
-        if col_lower == "id" || col_lower.ends_with("_id") || col_lower.starts_with("id_") {
+        // Skip ID columns so keys aren't plotted as measures
+        if is_id_column(col) {
             continue;
         }
 
@@ -456,6 +966,23 @@ fn detect_numeric_columns(
     numeric
 }
 
+/// Identifier columns: id, user_id, id_user, userId, UserID
+fn is_id_column(col: &str) -> bool {
+    let col_lower = col.to_lowercase();
+    col_lower == "id"
+        || col_lower.ends_with("_id")
+        || col_lower.starts_with("id_")
+        || has_camel_case_id_suffix(col)
+}
+
+/// `Id`/`ID` ending a lowercase word (userId, UserID); all-caps words like PAID or VALID don't count
+fn has_camel_case_id_suffix(col: &str) -> bool {
+    col.strip_suffix("Id")
+        .or_else(|| col.strip_suffix("ID"))
+        .and_then(|stem| stem.chars().last())
+        .is_some_and(|c| c.is_lowercase() || c.is_ascii_digit())
+}
+
 /// Simple date string detection
 fn is_date_like(s: &str) -> bool {
     s.contains('-') && s.len() >= 8 && s.chars().filter(|c| c.is_numeric()).count() >= 4
@@ -482,6 +1009,7 @@ mod tests {
             rows: vec![row1, row2],
             row_count: 2,
             execution_time_ms: 0,
+            summary: None,
         };
 
         let result = generate_plotly_code(&data, &QuestionType::CategoryChart, "Show values by category");
@@ -493,6 +1021,213 @@ mod tests {
         assert_eq!(viz.chart_type, "pie"); // 2 rows = pie chart
     }
 
+    #[test]
+    fn test_generate_heatmap_pivots_with_null_gaps() {
+        let row = |day: &str, hour: i64, orders: i64| {
+            let mut r = serde_json::Map::new();
+            r.insert("day".to_string(), json!(day));
+            r.insert("hour_of_day".to_string(), json!(hour.to_string()));
+            r.insert("orders".to_string(), json!(orders));
+            r
+        };
+
+        let data = QueryResult {
+            columns: vec!["day".to_string(), "hour_of_day".to_string(), "orders".to_string()],
+            column_metadata: vec![],
+            rows: vec![row("Mon", 9, 3), row("Mon", 10, 5), row("Tue", 10, 7)],
+            row_count: 3,
+            execution_time_ms: 0,
+            summary: None,
+        };
+
+        let viz = generate_plotly_code(&data, &QuestionType::Heatmap, "Orders by day and hour").unwrap();
+        assert_eq!(viz.chart_type, "heatmap");
+
+        let trace = &viz.data[0];
+        assert_eq!(trace["y"], json!(["Mon", "Tue"]));
+        assert_eq!(trace["x"], json!(["9", "10"]));
+        assert_eq!(trace["z"], json!([[3.0, 5.0], [null, 7.0]]));
+    }
+
+    #[test]
+    fn test_generate_grouped_bar_chart_one_trace_per_group() {
+        let mut rows = Vec::new();
+        for region in ["North", "South", "West"] {
+            for quarter in ["Q1", "Q2", "Q3", "Q4"] {
+                // West has no Q4 sales yet
+                if region == "West" && quarter == "Q4" {
+                    continue;
+                }
+                let mut r = serde_json::Map::new();
+                r.insert("region".to_string(), json!(region));
+                r.insert("quarter".to_string(), json!(quarter));
+                r.insert("sales".to_string(), json!(10));
+                rows.push(r);
+            }
+        }
+
+        let data = QueryResult {
+            columns: vec!["region".to_string(), "quarter".to_string(), "sales".to_string()],
+            column_metadata: vec![],
+            row_count: rows.len(),
+            rows,
+            execution_time_ms: 0,
+            summary: None,
+        };
+
+        let viz = generate_plotly_code(&data, &QuestionType::CategoryChart, "Sales by region by quarter").unwrap();
+        assert_eq!(viz.chart_type, "bar");
+        assert_eq!(viz.layout["barmode"], json!("group"));
+        assert_eq!(viz.data.len(), 4);
+        assert_eq!(viz.data[0]["name"], json!("Q1"));
+        assert_eq!(viz.data[0]["x"], json!(["North", "South", "West"]));
+        assert_eq!(viz.data[3]["y"], json!([10.0, 10.0, null]));
+
+        let stacked = generate_plotly_code_with_options(
+            &data,
+            &QuestionType::CategoryChart,
+            "Stacked sales by region",
+            &ChartOptions::for_question("Stacked sales by region"),
+        )
+        .unwrap();
+        assert_eq!(stacked.layout["barmode"], json!("stack"));
+    }
+
+    #[test]
+    fn test_scatter_for_two_numeric_columns_ignores_ids() {
+        let row = |id: i64, team: &str, height: f64, weight: f64| {
+            let mut r = serde_json::Map::new();
+            r.insert("id".to_string(), json!(id));
+            r.insert("team".to_string(), json!(team));
+            r.insert("height".to_string(), json!(height));
+            r.insert("weight".to_string(), json!(weight));
+            r
+        };
+
+        let data = QueryResult {
+            columns: vec!["id".to_string(), "team".to_string(), "height".to_string(), "weight".to_string()],
+            column_metadata: vec![],
+            rows: vec![row(1, "A", 180.0, 75.0), row(2, "B", 170.0, 68.0), row(3, "A", 190.0, 90.0)],
+            row_count: 3,
+            execution_time_ms: 0,
+            summary: None,
+        };
+
+        let viz = generate_plotly_code(&data, &QuestionType::Complex, "Height vs weight").unwrap();
+        assert_eq!(viz.chart_type, "scatter");
+        assert_eq!(viz.layout["xaxis"]["title"], json!("height"));
+        assert_eq!(viz.data.len(), 2);
+        assert_eq!(viz.data[0]["name"], json!("A"));
+        assert_eq!(viz.data[0]["y"], json!([75.0, 90.0]));
+
+        assert!(is_id_column("userId"));
+        assert!(is_id_column("UserID"));
+        assert!(is_id_column("order_id"));
+        assert!(is_id_column("ID"));
+        assert!(!is_id_column("paid"));
+        assert!(!is_id_column("PAID"));
+        assert!(!is_id_column("VALID"));
+        assert!(!is_id_column("VOID"));
+    }
+
+    #[test]
+    fn test_default_chart_puts_category_on_x_and_most_varying_measure_on_y() {
+        let row = |target: i64, revenue: f64, region: &str| {
+            let mut r = serde_json::Map::new();
+            r.insert("target".to_string(), json!(target));
+            r.insert("revenue".to_string(), json!(revenue));
+            r.insert("region".to_string(), json!(region));
+            r
+        };
+
+        let data = QueryResult {
+            columns: vec!["target".to_string(), "revenue".to_string(), "region".to_string()],
+            column_metadata: vec![],
+            rows: vec![row(100, 40.0, "North"), row(100, 250.0, "South"), row(100, 90.0, "East")],
+            row_count: 3,
+            execution_time_ms: 0,
+            summary: None,
+        };
+
+        // A multi-row statistic falls back to the default chart
+        let viz = generate_plotly_code(&data, &QuestionType::Statistic, "Revenue by region").unwrap();
+        assert_eq!(viz.chart_type, "bar");
+        assert_eq!(viz.layout["xaxis"]["title"], json!("region"));
+        assert_eq!(viz.layout["yaxis"]["title"], json!("revenue"));
+        assert_eq!(viz.data[0]["x"], json!(["North", "South", "East"]));
+
+        // Decimals sent as text still count as the measure
+        let mut row = serde_json::Map::new();
+        row.insert("total".to_string(), json!("12.50"));
+        row.insert("label".to_string(), json!("a"));
+        let text_numbers = QueryResult {
+            columns: vec!["total".to_string(), "label".to_string()],
+            rows: vec![row],
+            row_count: 1,
+            ..data
+        };
+        assert_eq!(
+            default_chart_axes(&text_numbers).unwrap(),
+            ("label".to_string(), "total".to_string())
+        );
+    }
+
+    #[test]
+    fn test_box_plot_groups_values_and_skips_tiny_results() {
+        let row = |department: &str, salary: i64| {
+            let mut r = serde_json::Map::new();
+            r.insert("department".to_string(), json!(department));
+            r.insert("salary".to_string(), json!(salary));
+            r
+        };
+        let departments = ["Sales", "Ops"];
+        let rows: Vec<_> = (0..10).map(|i| row(departments[i % 2], 1000 + i as i64 * 100)).collect();
+        let data = QueryResult {
+            columns: vec!["department".to_string(), "salary".to_string()],
+            column_metadata: vec![],
+            row_count: rows.len(),
+            rows,
+            execution_time_ms: 0,
+            summary: None,
+        };
+
+        let viz = generate_plotly_code(&data, &QuestionType::Distribution, "Salary spread by department").unwrap();
+        assert_eq!(viz.chart_type, "box");
+        assert_eq!(viz.data.len(), 2);
+        assert_eq!(viz.data[0]["name"], json!("Sales"));
+        assert_eq!(viz.data[0]["y"], json!([1000.0, 1200.0, 1400.0, 1600.0, 1800.0]));
+        assert_eq!(viz.layout["xaxis"]["title"], json!("department"));
+
+        // A lone numeric column with many rows becomes one box without being asked
+        let latency_rows: Vec<_> = (0..AUTO_BOX_PLOT_ROWS as i64)
+            .map(|i| {
+                let mut r = serde_json::Map::new();
+                r.insert("id".to_string(), json!(i));
+                r.insert("latency_ms".to_string(), json!(i * 3));
+                r
+            })
+            .collect();
+        let latencies = QueryResult {
+            columns: vec!["id".to_string(), "latency_ms".to_string()],
+            row_count: latency_rows.len(),
+            rows: latency_rows,
+            ..data.clone()
+        };
+        let viz = generate_plotly_code(&latencies, &QuestionType::Complex, "Request latencies").unwrap();
+        assert_eq!(viz.chart_type, "box");
+        assert_eq!(viz.data.len(), 1);
+        assert_eq!(viz.data[0]["name"], json!("latency_ms"));
+
+        // Too few rows for a distribution fall back to the usual chart
+        let tiny = QueryResult {
+            rows: data.rows[..3].to_vec(),
+            row_count: 3,
+            ..data
+        };
+        let viz = generate_plotly_code(&tiny, &QuestionType::Distribution, "Salary spread").unwrap();
+        assert_ne!(viz.chart_type, "box");
+    }
+
     #[test]
     fn test_extract_column_values_json() {
         let mut row1 = serde_json::Map::new();
@@ -509,6 +1244,7 @@ mod tests {
             rows: vec![row1, row2],
             row_count: 2,
             execution_time_ms: 0,
+            summary: None,
         };
 
         let names = extract_column_values_json(&data, "name");
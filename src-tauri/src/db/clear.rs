@@ -1,6 +1,15 @@
-use crate::db::connection::{ConnectionManager, DatabaseType};
-use crate::error::AppResult;
+use crate::db::connection::{Connection, ConnectionManager, DatabaseType};
+use crate::db::query::quote_table;
+use crate::error::{AppError, AppResult};
 use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+
+/// PostgreSQL SQLSTATE raised when TRUNCATE hits a referencing foreign key
+const PG_FEATURE_NOT_SUPPORTED: &str = "0A000";
+/// MySQL: TRUNCATE on a table referenced by a foreign key
+const MYSQL_TRUNCATE_ILLEGAL_FK: u16 = 1701;
+/// MySQL: DELETE blocked by rows in a referencing table
+const MYSQL_ROW_IS_REFERENCED: u16 = 1451;
 
 /// Safely quote a PostgreSQL identifier (table name)
 fn quote_identifier_postgres(identifier: &str) -> String {
@@ -12,49 +21,253 @@ fn quote_identifier_mysql(identifier: &str) -> String {
     format!("`{}`", identifier.replace('`', "``"))
 }
 
-/// Clear all data from tables (TRUNCATE - keeps table structures)
+/// Which whole-database clear a plan describes
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClearKind {
+    /// TRUNCATE every table, keeping the structures
+    DataOnly,
+    /// DROP every table
+    Database,
+}
+
+/// Tables a whole-database clear affects. Without a confirmation the plan is returned and
+/// nothing is touched; the clear only runs when `confirmation_token` is passed back, which
+/// scripts can do up front.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClearPlan {
+    pub kind: ClearKind,
+    pub database: String,
+    pub tables: Vec<String>,
+    /// The database name, echoed back as `confirmation` to run the clear
+    pub confirmation_token: String,
+    /// Whether the tables were actually cleared or dropped
+    pub executed: bool,
+}
+
+impl ClearPlan {
+    fn new(kind: ClearKind, conn: &Connection, tables: Vec<String>) -> Self {
+        // Same name the confirmation dialog shows
+        let database = if conn.default_database.is_empty() {
+            conn.name.clone()
+        } else {
+            conn.default_database.clone()
+        };
+        Self {
+            kind,
+            confirmation_token: database.clone(),
+            database,
+            tables,
+            executed: false,
+        }
+    }
+
+    /// Whether the clear should run: no confirmation only plans, a wrong one is an error
+    fn is_confirmed(&self, confirmation: Option<&str>) -> AppResult<bool> {
+        match confirmation {
+            None => Ok(false),
+            Some(token) if token == self.confirmation_token => Ok(true),
+            Some(_) => Err(AppError::ValidationError(format!(
+                "Confirmation does not match; pass the database name '{}' to clear it",
+                self.confirmation_token
+            ))),
+        }
+    }
+}
+
+/// Clear all data from tables (TRUNCATE - keeps table structures) once confirmed
 pub async fn clear_data_only(
     manager: &ConnectionManager,
     connection_id: &str,
-) -> AppResult<()> {
+    confirmation: Option<&str>,
+) -> AppResult<ClearPlan> {
     let conn = manager.get_connection(connection_id)?;
+    conn.ensure_writable("Clearing data")?;
+
+    let tables = list_clearable_tables(manager, &conn).await?;
+    let mut plan = ClearPlan::new(ClearKind::DataOnly, &conn, tables);
+    if !plan.is_confirmed(confirmation)? {
+        return Ok(plan);
+    }
 
     match conn.database_type {
-        DatabaseType::PostgreSQL => truncate_postgres_tables(manager, connection_id).await,
-        DatabaseType::MariaDB | DatabaseType::MySQL => truncate_mysql_tables(manager, connection_id).await,
+        DatabaseType::PostgreSQL => truncate_postgres_tables(manager, connection_id, &plan.tables).await?,
+        DatabaseType::MariaDB | DatabaseType::MySQL => {
+            truncate_mysql_tables(manager, connection_id, &plan.tables).await?
+        }
     }
+    plan.executed = true;
+    Ok(plan)
 }
 
-/// Clear entire database (DROP - removes all tables)
+/// Clear entire database (DROP - removes all tables) once confirmed
 pub async fn clear_database(
     manager: &ConnectionManager,
     connection_id: &str,
+    confirmation: Option<&str>,
+) -> AppResult<ClearPlan> {
+    let conn = manager.get_connection(connection_id)?;
+    conn.ensure_writable("Clearing the database")?;
+
+    let tables = list_clearable_tables(manager, &conn).await?;
+    let mut plan = ClearPlan::new(ClearKind::Database, &conn, tables);
+    if !plan.is_confirmed(confirmation)? {
+        return Ok(plan);
+    }
+
+    match conn.database_type {
+        DatabaseType::PostgreSQL => drop_postgres_tables(manager, connection_id, &plan.tables).await?,
+        DatabaseType::MariaDB | DatabaseType::MySQL => drop_mysql_tables(manager, connection_id, &plan.tables).await?,
+    }
+    plan.executed = true;
+    Ok(plan)
+}
+
+/// Tables touched by the whole-database clears: `public` on PostgreSQL, the default database on MySQL
+async fn list_clearable_tables(manager: &ConnectionManager, conn: &Connection) -> AppResult<Vec<String>> {
+    let tables = match conn.database_type {
+        DatabaseType::PostgreSQL => {
+            let pool = manager.get_pool_postgres(&conn.id).await?;
+            sqlx::query_scalar("SELECT tablename FROM pg_tables WHERE schemaname = 'public' ORDER BY tablename")
+                .fetch_all(&pool)
+                .await?
+        }
+        DatabaseType::MariaDB | DatabaseType::MySQL => {
+            let pool = manager.get_pool_mysql(&conn.id).await?;
+            sqlx::query_scalar(
+                "SELECT table_name FROM information_schema.tables WHERE table_schema = ? ORDER BY table_name"
+            )
+            .bind(&conn.default_database)
+            .fetch_all(&pool)
+            .await?
+        }
+    };
+    Ok(tables)
+}
+
+/// Remove every row from a single table, keeping its structure.
+/// PostgreSQL: `cascade` also empties tables referencing it, `restart_identity` resets its sequences.
+/// MySQL/MariaDB: falls back to DELETE when foreign keys forbid TRUNCATE.
+pub async fn clear_table(
+    manager: &ConnectionManager,
+    connection_id: &str,
+    table_name: &str,
+    cascade: bool,
+    restart_identity: bool,
 ) -> AppResult<()> {
     let conn = manager.get_connection(connection_id)?;
+    conn.ensure_writable("Clearing a table")?;
 
     match conn.database_type {
-        DatabaseType::PostgreSQL => drop_postgres_tables(manager, connection_id).await,
-        DatabaseType::MariaDB | DatabaseType::MySQL => drop_mysql_tables(manager, connection_id).await,
+        DatabaseType::PostgreSQL => {
+            truncate_postgres_table(manager, connection_id, table_name, cascade, restart_identity).await
+        }
+        DatabaseType::MariaDB | DatabaseType::MySQL => {
+            clear_mysql_table(manager, connection_id, table_name).await
+        }
     }
 }
 
-// PostgreSQL - TRUNCATE (clear data only)
-async fn truncate_postgres_tables(
+// PostgreSQL - TRUNCATE a single table
+async fn truncate_postgres_table(
     manager: &ConnectionManager,
     connection_id: &str,
+    table_name: &str,
+    cascade: bool,
+    restart_identity: bool,
 ) -> AppResult<()> {
     let pool = manager.get_pool_postgres(connection_id).await?;
+    let quoted_table = quote_table(table_name, &DatabaseType::PostgreSQL);
+
+    let exists: bool = sqlx::query_scalar("SELECT to_regclass($1) IS NOT NULL")
+        .bind(&quoted_table)
+        .fetch_one(&pool)
+        .await?;
+    if !exists {
+        return Err(AppError::ValidationError(format!("Table '{}' does not exist", table_name)));
+    }
+
+    let query = format!(
+        "TRUNCATE TABLE {}{}{}",
+        quoted_table,
+        if restart_identity { " RESTART IDENTITY" } else { "" },
+        if cascade { " CASCADE" } else { "" }
+    );
+
+    match sqlx::query(&query).execute(&pool).await {
+        Ok(_) => Ok(()),
+        Err(sqlx::Error::Database(e)) if e.code().as_deref() == Some(PG_FEATURE_NOT_SUPPORTED) => {
+            Err(AppError::ValidationError(format!(
+                "Cannot clear '{}' because other tables reference it through foreign keys; \
+                 enable cascade to also clear the referencing tables ({})",
+                table_name,
+                e.message()
+            )))
+        }
+        Err(e) => Err(e.into()),
+    }
+}
 
-    let tables: Vec<String> = sqlx::query_scalar(
-        "SELECT tablename FROM pg_tables WHERE schemaname = 'public'"
+// MySQL/MariaDB - TRUNCATE a single table, or DELETE when foreign keys forbid it
+async fn clear_mysql_table(
+    manager: &ConnectionManager,
+    connection_id: &str,
+    table_name: &str,
+) -> AppResult<()> {
+    let pool = manager.get_pool_mysql(connection_id).await?;
+    let conn_info = manager.get_connection(connection_id)?;
+
+    let exists: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM information_schema.tables WHERE table_schema = ? AND table_name = ?"
     )
-    .fetch_all(&pool)
+    .bind(&conn_info.default_database)
+    .bind(table_name)
+    .fetch_one(&pool)
     .await?;
+    if exists == 0 {
+        return Err(AppError::ValidationError(format!("Table '{}' does not exist", table_name)));
+    }
+
+    let quoted_table = quote_identifier_mysql(table_name);
+    let error_number = |e: &sqlx::Error| match e {
+        sqlx::Error::Database(db) => db
+            .try_downcast_ref::<sqlx::mysql::MySqlDatabaseError>()
+            .map(|e| e.number()),
+        _ => None,
+    };
+
+    let truncate = sqlx::query(&format!("TRUNCATE TABLE {}", quoted_table))
+        .execute(&pool)
+        .await;
+    match truncate {
+        Ok(_) => return Ok(()),
+        Err(e) if error_number(&e) == Some(MYSQL_TRUNCATE_ILLEGAL_FK) => {}
+        Err(e) => return Err(e.into()),
+    }
+
+    // Referenced tables can't be truncated; DELETE works as long as no referencing rows remain
+    match sqlx::query(&format!("DELETE FROM {}", quoted_table)).execute(&pool).await {
+        Ok(_) => Ok(()),
+        Err(e) if error_number(&e) == Some(MYSQL_ROW_IS_REFERENCED) => Err(AppError::ValidationError(format!(
+            "Cannot clear '{}' because rows in other tables still reference it through foreign keys",
+            table_name
+        ))),
+        Err(e) => Err(e.into()),
+    }
+}
 
+// PostgreSQL - TRUNCATE (clear data only)
+async fn truncate_postgres_tables(
+    manager: &ConnectionManager,
+    connection_id: &str,
+    tables: &[String],
+) -> AppResult<()> {
     if tables.is_empty() {
         return Ok(());
     }
 
+    let pool = manager.get_pool_postgres(connection_id).await?;
+
     let quoted_tables: Vec<String> = tables
         .iter()
         .map(|t| quote_identifier_postgres(t))
@@ -74,19 +287,14 @@ async fn truncate_postgres_tables(
 async fn drop_postgres_tables(
     manager: &ConnectionManager,
     connection_id: &str,
+    tables: &[String],
 ) -> AppResult<()> {
-    let pool = manager.get_pool_postgres(connection_id).await?;
-
-    let tables: Vec<String> = sqlx::query_scalar(
-        "SELECT tablename FROM pg_tables WHERE schemaname = 'public'"
-    )
-    .fetch_all(&pool)
-    .await?;
-
     if tables.is_empty() {
         return Ok(());
     }
 
+    let pool = manager.get_pool_postgres(connection_id).await?;
+
     let quoted_tables: Vec<String> = tables
         .iter()
         .map(|t| quote_identifier_postgres(t))
@@ -106,21 +314,14 @@ async fn drop_postgres_tables(
 async fn truncate_mysql_tables(
     manager: &ConnectionManager,
     connection_id: &str,
+    tables: &[String],
 ) -> AppResult<()> {
-    let pool = manager.get_pool_mysql(connection_id).await?;
-    let conn_info = manager.get_connection(connection_id)?;
-
-    let tables: Vec<String> = sqlx::query_scalar(
-        "SELECT table_name FROM information_schema.tables WHERE table_schema = ?"
-    )
-    .bind(&conn_info.default_database)
-    .fetch_all(&pool)
-    .await?;
-
     if tables.is_empty() {
         return Ok(());
     }
 
+    let pool = manager.get_pool_mysql(connection_id).await?;
+
     // Strategy: Each connection disables FK checks for its session, then parallel truncate
     // This ensures FK checks are disabled for each connection that does the work
 
@@ -171,21 +372,14 @@ async fn truncate_mysql_tables(
 async fn drop_mysql_tables(
     manager: &ConnectionManager,
     connection_id: &str,
+    tables: &[String],
 ) -> AppResult<()> {
-    let pool = manager.get_pool_mysql(connection_id).await?;
-    let conn_info = manager.get_connection(connection_id)?;
-
-    let tables: Vec<String> = sqlx::query_scalar(
-        "SELECT table_name FROM information_schema.tables WHERE table_schema = ?"
-    )
-    .bind(&conn_info.default_database)
-    .fetch_all(&pool)
-    .await?;
-
     if tables.is_empty() {
         return Ok(());
     }
 
+    let pool = manager.get_pool_mysql(connection_id).await?;
+
     // Strategy: Use single connection with batched DROP statements for speed
     // Batching avoids metadata lock contention while keeping it fast
     let mut conn = pool.acquire().await?;
@@ -213,3 +407,44 @@ async fn drop_mysql_tables(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn connection(default_database: &str) -> Connection {
+        serde_json::from_value(serde_json::json!({
+            "id": "c1",
+            "name": "Billing",
+            "database_type": "MySQL",
+            "host": "localhost",
+            "port": 3306,
+            "username": "app",
+            "password": "secret",
+            "default_database": default_database,
+            "created_at": "2024-01-01T00:00:00Z",
+            "updated_at": "2024-01-01T00:00:00Z",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn clear_runs_only_with_the_database_name_echoed_back() {
+        let plan = ClearPlan::new(
+            ClearKind::Database,
+            &connection("billing"),
+            vec!["invoices".to_string(), "orders".to_string()],
+        );
+        assert_eq!(plan.confirmation_token, "billing");
+        assert!(!plan.executed);
+
+        assert!(!plan.is_confirmed(None).unwrap());
+        assert!(plan.is_confirmed(Some("billing")).unwrap());
+        assert!(matches!(plan.is_confirmed(Some("Billing")), Err(AppError::ValidationError(_))));
+        assert!(plan.is_confirmed(Some("")).is_err());
+
+        // Connections without a default database confirm with their name, as the dialog shows
+        let plan = ClearPlan::new(ClearKind::DataOnly, &connection(""), vec![]);
+        assert_eq!(plan.confirmation_token, "Billing");
+    }
+}
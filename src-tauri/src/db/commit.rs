@@ -1,7 +1,11 @@
 use crate::db::connection::{ConnectionManager, DatabaseType};
-use crate::error::AppResult;
+use crate::db::query::{quote_table, resolve_postgres_table};
+use crate::error::{AppError, AppResult};
 use serde::{Deserialize, Serialize};
-use sqlx::QueryBuilder;
+use sqlx::{QueryBuilder, Row};
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::time::Duration;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CellEdit {
@@ -24,6 +28,23 @@ pub struct DataGridChanges {
     pub inserts: Vec<RowInsert>,
 }
 
+/// How UPDATE and DELETE statements find the loaded row
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RowIdentifier {
+    /// The table's primary key columns; refused when the table has none
+    #[default]
+    PrimaryKey,
+    /// Every comparable column of the loaded row, for tables without a primary key.
+    /// A statement that would touch several identical rows fails instead.
+    FullRow,
+    /// PostgreSQL's physical row address; the loaded rows must include a `ctid` column
+    Ctid,
+}
+
+/// System column holding a PostgreSQL row's physical address
+pub const CTID_COLUMN: &str = "ctid";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CommitRequest {
     pub connection_id: String,
@@ -31,6 +52,17 @@ pub struct CommitRequest {
     pub primary_key_columns: Vec<String>,
     pub changes: DataGridChanges,
     pub original_rows: Vec<serde_json::Map<String, serde_json::Value>>,
+    /// Build the statements without executing them
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Also match original non-key values so rows changed by someone else are not overwritten
+    #[serde(default)]
+    pub check_conflicts: bool,
+    #[serde(default)]
+    pub row_identifier: RowIdentifier,
+    /// Remember how to undo this commit so `revert_last_commit` can roll it back
+    #[serde(default)]
+    pub record_undo: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -40,6 +72,124 @@ pub struct CommitResult {
     pub edits_count: usize,
     pub deletes_count: usize,
     pub inserts_count: usize,
+    /// Generated statements with parameter values rendered inline, in execution order
+    #[serde(default)]
+    pub statements: Vec<String>,
+    /// Transactions run before the commit went through; more than 1 after a deadlock
+    #[serde(default = "default_commit_attempts")]
+    pub attempts: u32,
+    /// Whether the commit was recorded and can be reverted
+    #[serde(default)]
+    pub undoable: bool,
+    /// Inserted rows with generated keys filled in, for building the undo entry
+    #[serde(skip)]
+    inserted_rows: Vec<serde_json::Map<String, serde_json::Value>>,
+}
+
+fn default_commit_attempts() -> u32 {
+    1
+}
+
+/// Transactions tried before a deadlock is reported
+const MAX_COMMIT_ATTEMPTS: u32 = 3;
+
+/// Wait before the first retry; doubled for each further one
+const COMMIT_RETRY_BACKOFF: Duration = Duration::from_millis(50);
+
+/// PostgreSQL deadlock_detected and serialization_failure SQLSTATEs
+const POSTGRES_TRANSIENT_CODES: [&str; 2] = ["40P01", "40001"];
+
+/// MySQL/MariaDB ER_LOCK_DEADLOCK
+const MYSQL_DEADLOCK_ERROR: u16 = 1213;
+
+/// Recorded commits kept per connection; the oldest is forgotten first
+const MAX_UNDO_ENTRIES: usize = 20;
+
+lazy_static::lazy_static! {
+    /// Inverse of each recorded commit keyed by connection, newest last
+    static ref UNDO_LOG: std::sync::Mutex<HashMap<String, Vec<CommitRequest>>> =
+        std::sync::Mutex::new(HashMap::new());
+}
+
+/// Why a commit attempt failed. Database errors stay unconverted so a deadlock
+/// can be told apart from errors that would fail again.
+enum CommitAttemptError {
+    Database(sqlx::Error),
+    App(AppError),
+}
+
+impl From<sqlx::Error> for CommitAttemptError {
+    fn from(err: sqlx::Error) -> Self {
+        CommitAttemptError::Database(err)
+    }
+}
+
+impl From<AppError> for CommitAttemptError {
+    fn from(err: AppError) -> Self {
+        CommitAttemptError::App(err)
+    }
+}
+
+/// Deadlock victims and serialization failures succeed when the transaction is run again
+fn is_transient_commit_error(err: &sqlx::Error) -> bool {
+    let Some(db_err) = err.as_database_error() else {
+        return false;
+    };
+    if let Some(mysql_err) = db_err.try_downcast_ref::<sqlx::mysql::MySqlDatabaseError>() {
+        return mysql_err.number() == MYSQL_DEADLOCK_ERROR;
+    }
+    db_err
+        .code()
+        .is_some_and(|code| POSTGRES_TRANSIENT_CODES.contains(&code.as_ref()))
+}
+
+/// Run a commit, starting over in a fresh transaction when it fails with a deadlock.
+/// The failed transaction is rolled back as a whole, so nothing from it is applied twice.
+async fn retry_transient_commit<F, Fut>(mut attempt: F) -> AppResult<CommitResult>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<CommitResult, CommitAttemptError>>,
+{
+    let mut attempts = 1;
+    loop {
+        match attempt().await {
+            Ok(mut result) => {
+                if attempts > 1 {
+                    result.message = format!("{} after {} attempts", result.message, attempts);
+                }
+                result.attempts = attempts;
+                return Ok(result);
+            }
+            Err(CommitAttemptError::Database(err))
+                if attempts < MAX_COMMIT_ATTEMPTS && is_transient_commit_error(&err) =>
+            {
+                tokio::time::sleep(COMMIT_RETRY_BACKOFF * 2u32.pow(attempts - 1)).await;
+                attempts += 1;
+            }
+            Err(CommitAttemptError::Database(err)) => return Err(err.into()),
+            Err(CommitAttemptError::App(err)) => return Err(err),
+        }
+    }
+}
+
+/// Builds a statement as a bound query and, alongside it, as inline SQL for previews
+struct StatementBuilder<'args, DB: sqlx::Database> {
+    query: QueryBuilder<'args, DB>,
+    rendered: String,
+}
+
+impl<'args, DB: sqlx::Database> StatementBuilder<'args, DB> {
+    fn new(sql: String) -> Self {
+        Self {
+            rendered: sql.clone(),
+            query: QueryBuilder::new(sql),
+        }
+    }
+
+    fn push(&mut self, sql: &str) {
+        self.query.push(sql);
+        self.rendered.push_str(sql);
+    }
 }
 
 pub async fn commit_data_changes(
@@ -47,10 +197,298 @@ pub async fn commit_data_changes(
     request: CommitRequest,
 ) -> AppResult<CommitResult> {
     let conn = manager.get_connection(&request.connection_id)?;
+    // A dry run only renders statements, so it stays available for auditing
+    if !request.dry_run {
+        conn.ensure_writable("Committing data changes")?;
+    }
 
-    match conn.database_type {
+    let committed = (request.record_undo && !request.dry_run).then(|| request.clone());
+    let mut result = match conn.database_type {
         DatabaseType::PostgreSQL => commit_postgres_changes(manager, request).await,
         DatabaseType::MariaDB | DatabaseType::MySQL => commit_mysql_changes(manager, request).await,
+    }?;
+
+    // The changes are already committed, so a commit that can't be inverted still succeeds
+    if let Some(committed) = committed {
+        match inverse_request(&committed, &result.inserted_rows) {
+            Ok(inverse) => {
+                let changes = &inverse.changes;
+                if !(changes.edits.is_empty() && changes.deletes.is_empty() && changes.inserts.is_empty()) {
+                    push_undo_entry(inverse)?;
+                    result.undoable = true;
+                }
+            }
+            Err(e) => result.message = format!("{}; it can't be reverted: {}", result.message, e),
+        }
+    }
+    Ok(result)
+}
+
+/// Undo the newest recorded commit on a connection in one transaction. Rows changed since
+/// that commit are left alone and fail the revert. A dry run only renders the statements.
+pub async fn revert_last_commit(
+    manager: &ConnectionManager,
+    connection_id: &str,
+    dry_run: bool,
+) -> AppResult<CommitResult> {
+    let mut inverse = {
+        let mut log = UNDO_LOG
+            .lock()
+            .map_err(|e| AppError::Other(format!("Failed to lock undo log: {}", e)))?;
+        let entries = log.get_mut(connection_id);
+        let entry = if dry_run {
+            entries.and_then(|entries| entries.last().cloned())
+        } else {
+            // Taken out up front so two reverts can't apply the same entry
+            entries.and_then(|entries| entries.pop())
+        };
+        entry.ok_or_else(|| AppError::ValidationError("There is no recorded commit to revert".to_string()))?
+    };
+    inverse.dry_run = dry_run;
+
+    match commit_data_changes(manager, inverse.clone()).await {
+        Ok(mut result) if !dry_run => {
+            result.message = format!(
+                "Reverted the last commit: removed {} inserted rows, restored {} cells and {} deleted rows",
+                result.deletes_count, result.edits_count, result.inserts_count
+            );
+            Ok(result)
+        }
+        Ok(result) => Ok(result),
+        Err(e) => {
+            // Nothing was applied, so the commit can still be reverted later
+            if !dry_run {
+                inverse.dry_run = false;
+                push_undo_entry(inverse)?;
+            }
+            Err(e)
+        }
+    }
+}
+
+/// Forget the recorded commits of a connection, e.g. when it is deleted or repointed
+pub fn clear_undo_log(connection_id: &str) {
+    if let Ok(mut log) = UNDO_LOG.lock() {
+        log.remove(connection_id);
+    }
+}
+
+fn push_undo_entry(inverse: CommitRequest) -> AppResult<()> {
+    let mut log = UNDO_LOG
+        .lock()
+        .map_err(|e| AppError::Other(format!("Failed to lock undo log: {}", e)))?;
+    let entries = log.entry(inverse.connection_id.clone()).or_default();
+    entries.push(inverse);
+    if entries.len() > MAX_UNDO_ENTRIES {
+        entries.remove(0);
+    }
+    Ok(())
+}
+
+/// The changes that undo a commit: rows it inserted are deleted, edited cells get their
+/// `old_value` back and deleted rows are inserted again with every loaded value. The
+/// inverse checks for conflicts so it never overwrites a row changed after the commit.
+fn inverse_request(
+    request: &CommitRequest,
+    inserted_rows: &[serde_json::Map<String, serde_json::Value>],
+) -> AppResult<CommitRequest> {
+    // Updating a row moves it to a new ctid, so reverted rows are matched on their values
+    let row_identifier = match request.row_identifier {
+        RowIdentifier::Ctid => RowIdentifier::FullRow,
+        other => other,
+    };
+    let without_ctid = |row: &serde_json::Map<String, serde_json::Value>| {
+        let mut row = row.clone();
+        row.remove(CTID_COLUMN);
+        row
+    };
+
+    let mut original_rows = Vec::new();
+    let mut changes = DataGridChanges {
+        edits: Vec::new(),
+        deletes: Vec::new(),
+        inserts: Vec::new(),
+    };
+
+    for row in inserted_rows {
+        if row_identifier == RowIdentifier::PrimaryKey {
+            if let Some(missing) = request
+                .primary_key_columns
+                .iter()
+                .find(|pk| row.get(pk.as_str()).unwrap_or(&serde_json::Value::Null).is_null())
+            {
+                return Err(AppError::ValidationError(format!(
+                    "the generated '{}' of an inserted row is unknown",
+                    missing
+                )));
+            }
+        }
+        changes.deletes.push(original_rows.len());
+        original_rows.push(row.clone());
+    }
+
+    // Edited rows are found by the values they hold after the commit
+    for (row_index, row_edits) in group_edits_by_row(&request.changes.edits) {
+        let Some(row_data) = request.original_rows.get(row_index) else {
+            continue;
+        };
+        let mut committed = without_ctid(row_data);
+        for edit in &row_edits {
+            committed.insert(edit.column_name.clone(), edit.new_value.clone());
+        }
+        let inverse_index = original_rows.len();
+        original_rows.push(committed);
+        changes.edits.extend(row_edits.iter().map(|edit| CellEdit {
+            row_index: inverse_index,
+            column_name: edit.column_name.clone(),
+            old_value: edit.new_value.clone(),
+            new_value: edit.old_value.clone(),
+        }));
+    }
+
+    for row_index in &request.changes.deletes {
+        if let Some(row_data) = request.original_rows.get(*row_index) {
+            changes.inserts.push(RowInsert {
+                temp_id: format!("undo-{}", row_index),
+                row_data: without_ctid(row_data),
+            });
+        }
+    }
+
+    Ok(CommitRequest {
+        connection_id: request.connection_id.clone(),
+        table_name: request.table_name.clone(),
+        primary_key_columns: request.primary_key_columns.clone(),
+        changes,
+        original_rows,
+        dry_run: false,
+        check_conflicts: true,
+        row_identifier,
+        record_undo: false,
+    })
+}
+
+/// Group cell edits by row, in row order so previews are stable
+fn group_edits_by_row(edits: &[CellEdit]) -> BTreeMap<usize, Vec<&CellEdit>> {
+    let mut edits_by_row: BTreeMap<usize, Vec<&CellEdit>> = BTreeMap::new();
+    for edit in edits {
+        edits_by_row.entry(edit.row_index).or_default().push(edit);
+    }
+    edits_by_row
+}
+
+/// With conflict checking on, an UPDATE/DELETE that matched nothing means the row changed
+/// underneath us. Returning the error drops the transaction, rolling back earlier statements.
+fn ensure_row_matched(check_conflicts: bool, rows_affected: u64, row_index: usize) -> AppResult<()> {
+    if check_conflicts && rows_affected == 0 {
+        return Err(AppError::ConcurrentModification(format!(
+            "Row {} was modified or deleted by someone else since it was loaded; no changes were committed",
+            row_index
+        )));
+    }
+    Ok(())
+}
+
+/// Matching on values alone can hit several identical rows; refuse rather than change them all
+fn ensure_single_row(row_identifier: RowIdentifier, rows_affected: u64, row_index: usize) -> AppResult<()> {
+    if row_identifier == RowIdentifier::FullRow && rows_affected > 1 {
+        return Err(AppError::ValidationError(format!(
+            "Row {} matches {} identical rows, so it can't be changed on its own; no changes were committed",
+            row_index, rows_affected
+        )));
+    }
+    Ok(())
+}
+
+/// Columns whose loaded values identify `row_data` in a WHERE clause
+fn row_match_columns(
+    request: &CommitRequest,
+    row_data: &serde_json::Map<String, serde_json::Value>,
+    column_types: &HashMap<String, String>,
+    is_comparable: fn(&str) -> bool,
+    supports_ctid: bool,
+) -> AppResult<Vec<String>> {
+    match request.row_identifier {
+        RowIdentifier::PrimaryKey => {
+            if request.primary_key_columns.is_empty() {
+                return Err(AppError::NoPrimaryKey(format!(
+                    "Table '{}' has no primary key, so edited rows can't be identified. Match on the full row{} instead",
+                    request.table_name,
+                    if supports_ctid { " or on ctid" } else { "" }
+                )));
+            }
+            // A missing key column would otherwise be matched as NULL
+            if let Some(missing) = request
+                .primary_key_columns
+                .iter()
+                .find(|pk| !row_data.contains_key(pk.as_str()))
+            {
+                return Err(AppError::ValidationError(format!(
+                    "Loaded row is missing primary key column '{}'",
+                    missing
+                )));
+            }
+            Ok(request.primary_key_columns.clone())
+        }
+        RowIdentifier::FullRow => {
+            let columns: Vec<String> = row_data
+                .keys()
+                .filter(|column| column_types.get(column.as_str()).is_some_and(|ty| is_comparable(ty)))
+                .cloned()
+                .collect();
+            if columns.is_empty() {
+                return Err(AppError::ValidationError(format!(
+                    "None of the loaded columns of '{}' can be compared, so rows can't be matched",
+                    request.table_name
+                )));
+            }
+            Ok(columns)
+        }
+        RowIdentifier::Ctid => {
+            if !supports_ctid {
+                return Err(AppError::ValidationError(
+                    "ctid row matching is only available on PostgreSQL".to_string(),
+                ));
+            }
+            if !row_data.get(CTID_COLUMN).is_some_and(|value| !value.is_null()) {
+                return Err(AppError::ValidationError(
+                    "Loaded rows must include the ctid column to match on it".to_string(),
+                ));
+            }
+            Ok(vec![CTID_COLUMN.to_string()])
+        }
+    }
+}
+
+fn build_commit_result(
+    dry_run: bool,
+    edits_count: usize,
+    deletes_count: usize,
+    inserts_count: usize,
+    statements: Vec<String>,
+) -> CommitResult {
+    let message = if dry_run {
+        format!(
+            "Dry run: would commit {} edits, {} deletes, {} inserts",
+            edits_count, deletes_count, inserts_count
+        )
+    } else {
+        format!(
+            "Successfully committed {} edits, {} deletes, {} inserts",
+            edits_count, deletes_count, inserts_count
+        )
+    };
+
+    CommitResult {
+        success: true,
+        message,
+        edits_count,
+        deletes_count,
+        inserts_count,
+        statements,
+        attempts: 1,
+        undoable: false,
+        inserted_rows: Vec::new(),
     }
 }
 
@@ -59,64 +497,99 @@ async fn commit_postgres_changes(
     request: CommitRequest,
 ) -> AppResult<CommitResult> {
     let pool = manager.get_pool_postgres(&request.connection_id).await?;
-    let mut tx = pool.begin().await?;
+    let search_path = manager.get_connection(&request.connection_id)?.search_path();
+    let (schema, table) = resolve_postgres_table(&pool, &request.table_name, &search_path).await;
+    let mut column_types = get_postgres_column_types(&pool, &schema, table).await?;
+    if request.row_identifier == RowIdentifier::Ctid {
+        column_types.insert(CTID_COLUMN.to_string(), "tid".to_string());
+    }
+    let quoted_table = quote_table(&format!("{}.{}", schema, table), &DatabaseType::PostgreSQL);
+    let (pool, request, column_types, quoted_table) = (&pool, &request, &column_types, quoted_table.as_str());
+    retry_transient_commit(move || commit_postgres_attempt(pool, request, column_types, quoted_table)).await
+}
+
+/// Run every statement of a commit in one transaction
+async fn commit_postgres_attempt(
+    pool: &sqlx::PgPool,
+    request: &CommitRequest,
+    column_types: &HashMap<String, String>,
+    quoted_table: &str,
+) -> Result<CommitResult, CommitAttemptError> {
+    // A dry run never opens a transaction, so nothing can be written
+    let mut tx = if request.dry_run { None } else { Some(pool.begin().await?) };
 
     let mut edits_count = 0;
     let mut deletes_count = 0;
     let mut inserts_count = 0;
-    let quoted_table = quote_identifier_postgres(&request.table_name);
+    let mut statements = Vec::new();
+    let mut inserted_rows = Vec::new();
 
     // Process deletes first
     for row_index in &request.changes.deletes {
         if let Some(row_data) = request.original_rows.get(*row_index) {
-            let mut query_builder: QueryBuilder<sqlx::Postgres> =
-                QueryBuilder::new(format!("DELETE FROM {} WHERE ", quoted_table));
+            let mut builder: StatementBuilder<sqlx::Postgres> =
+                StatementBuilder::new(format!("DELETE FROM {} WHERE ", quoted_table));
 
+            let match_columns =
+                row_match_columns(request, row_data, column_types, is_comparable_postgres_type, true)?;
             build_where_clause_with_binds_postgres(
-                &mut query_builder,
-                &request.primary_key_columns,
+                &mut builder,
+                &match_columns,
                 row_data,
-            );
+                column_types,
+                request.check_conflicts,
+            )?;
 
-            query_builder.build().execute(&mut *tx).await?;
+            if let Some(tx) = tx.as_mut() {
+                let result = builder.query.build().execute(&mut **tx).await?;
+                ensure_row_matched(request.check_conflicts, result.rows_affected(), *row_index)?;
+                ensure_single_row(request.row_identifier, result.rows_affected(), *row_index)?;
+            }
+            statements.push(builder.rendered);
             deletes_count += 1;
         }
     }
 
     // Process edits (group by row)
-    let mut edits_by_row: std::collections::HashMap<usize, Vec<&CellEdit>> =
-        std::collections::HashMap::new();
-
-    for edit in &request.changes.edits {
-        edits_by_row.entry(edit.row_index).or_default().push(edit);
-    }
-
-    for (row_index, row_edits) in edits_by_row {
+    for (row_index, row_edits) in group_edits_by_row(&request.changes.edits) {
         if let Some(row_data) = request.original_rows.get(row_index) {
-            let mut query_builder: QueryBuilder<sqlx::Postgres> =
-                QueryBuilder::new(format!("UPDATE {} SET ", quoted_table));
+            let mut builder: StatementBuilder<sqlx::Postgres> =
+                StatementBuilder::new(format!("UPDATE {} SET ", quoted_table));
 
             // Build SET clause with bind parameters
             let mut first = true;
             for edit in &row_edits {
                 if !first {
-                    query_builder.push(", ");
+                    builder.push(", ");
                 }
                 first = false;
 
-                query_builder.push(quote_identifier_postgres(&edit.column_name));
-                query_builder.push(" = ");
-                push_json_value_postgres(&mut query_builder, &edit.new_value);
+                builder.push(&quote_identifier_postgres(&edit.column_name));
+                builder.push(" = ");
+                push_json_value_postgres(
+                    &mut builder,
+                    column_types.get(&edit.column_name).map(String::as_str),
+                    &edit.new_value,
+                )?;
             }
 
-            query_builder.push(" WHERE ");
+            builder.push(" WHERE ");
+            let match_columns =
+                row_match_columns(request, row_data, column_types, is_comparable_postgres_type, true)?;
             build_where_clause_with_binds_postgres(
-                &mut query_builder,
-                &request.primary_key_columns,
+                &mut builder,
+                &match_columns,
                 row_data,
-            );
+                column_types,
+                request.check_conflicts,
+            )?;
 
-            query_builder.build().execute(&mut *tx).await?;
+            if let Some(tx) = tx.as_mut() {
+                let result = builder.query.build().execute(&mut **tx).await?;
+                ensure_row_matched(request.check_conflicts, result.rows_affected(), row_index)?;
+                ensure_single_row(request.row_identifier, result.rows_affected(), row_index)?;
+            }
+            statements.push(builder.rendered);
             edits_count += row_edits.len();
         }
     }
@@ -127,43 +600,48 @@ async fn commit_postgres_changes(
             continue;
         }
 
-        let mut query_builder: QueryBuilder<sqlx::Postgres> =
-            QueryBuilder::new(format!("INSERT INTO {} (", quoted_table));
+        let mut builder: StatementBuilder<sqlx::Postgres> =
+            StatementBuilder::new(format!("INSERT INTO {} (", quoted_table));
 
         // Build column list
         let columns: Vec<String> = insert.row_data.keys()
             .map(|k| quote_identifier_postgres(k))
             .collect();
-        query_builder.push(columns.join(", "));
-        query_builder.push(") VALUES (");
+        builder.push(&columns.join(", "));
+        builder.push(") VALUES (");
 
         // Build values with bind parameters
         let mut first = true;
-        for value in insert.row_data.values() {
+        for (column, value) in &insert.row_data {
             if !first {
-                query_builder.push(", ");
+                builder.push(", ");
             }
             first = false;
-            push_json_value_postgres(&mut query_builder, value);
+            push_json_value_postgres(&mut builder, column_types.get(column).map(String::as_str), value)?;
         }
-        query_builder.push(")");
+        builder.push(")");
 
-        query_builder.build().execute(&mut *tx).await?;
+        if let Some(tx) = tx.as_mut() {
+            if request.record_undo {
+                // The stored row, generated keys and defaults included, is what an undo deletes
+                builder.push(" RETURNING *");
+                let row = builder.query.build().fetch_one(&mut **tx).await?;
+                inserted_rows.push(crate::db::query::postgres_row_to_json(&row)?);
+            } else {
+                builder.query.build().execute(&mut **tx).await?;
+            }
+        }
+        statements.push(builder.rendered);
         inserts_count += 1;
     }
 
-    tx.commit().await?;
+    if let Some(tx) = tx {
+        tx.commit().await?;
+    }
 
-    Ok(CommitResult {
-        success: true,
-        message: format!(
-            "Successfully committed {} edits, {} deletes, {} inserts",
-            edits_count, deletes_count, inserts_count
-        ),
-        edits_count,
-        deletes_count,
-        inserts_count,
-    })
+    let mut result = build_commit_result(request.dry_run, edits_count, deletes_count, inserts_count, statements);
+    result.inserted_rows = inserted_rows;
+    Ok(result)
 }
 
 async fn commit_mysql_changes(
@@ -171,64 +649,93 @@ async fn commit_mysql_changes(
     request: CommitRequest,
 ) -> AppResult<CommitResult> {
     let pool = manager.get_pool_mysql(&request.connection_id).await?;
-    let mut tx = pool.begin().await?;
+    let column_types = get_mysql_column_types(&pool, &request.table_name).await?;
+    let (pool, request, column_types) = (&pool, &request, &column_types);
+    retry_transient_commit(move || commit_mysql_attempt(pool, request, column_types)).await
+}
+
+/// Run every statement of a commit in one transaction
+async fn commit_mysql_attempt(
+    pool: &sqlx::MySqlPool,
+    request: &CommitRequest,
+    column_types: &HashMap<String, String>,
+) -> Result<CommitResult, CommitAttemptError> {
+    // A dry run never opens a transaction, so nothing can be written
+    let mut tx = if request.dry_run { None } else { Some(pool.begin().await?) };
 
     let mut edits_count = 0;
     let mut deletes_count = 0;
     let mut inserts_count = 0;
+    let mut statements = Vec::new();
+    let mut inserted_rows = Vec::new();
     let quoted_table = quote_identifier_mysql(&request.table_name);
 
     // Process deletes first
     for row_index in &request.changes.deletes {
         if let Some(row_data) = request.original_rows.get(*row_index) {
-            let mut query_builder: QueryBuilder<sqlx::MySql> =
-                QueryBuilder::new(format!("DELETE FROM {} WHERE ", quoted_table));
+            let mut builder: StatementBuilder<sqlx::MySql> =
+                StatementBuilder::new(format!("DELETE FROM {} WHERE ", quoted_table));
 
+            let match_columns =
+                row_match_columns(request, row_data, column_types, is_comparable_mysql_type, false)?;
             build_where_clause_with_binds_mysql(
-                &mut query_builder,
-                &request.primary_key_columns,
+                &mut builder,
+                &match_columns,
                 row_data,
-            );
+                column_types,
+                request.check_conflicts,
+            )?;
 
-            query_builder.build().execute(&mut *tx).await?;
+            if let Some(tx) = tx.as_mut() {
+                let result = builder.query.build().execute(&mut **tx).await?;
+                ensure_row_matched(request.check_conflicts, result.rows_affected(), *row_index)?;
+                ensure_single_row(request.row_identifier, result.rows_affected(), *row_index)?;
+            }
+            statements.push(builder.rendered);
             deletes_count += 1;
         }
     }
 
     // Process edits (group by row)
-    let mut edits_by_row: std::collections::HashMap<usize, Vec<&CellEdit>> =
-        std::collections::HashMap::new();
-
-    for edit in &request.changes.edits {
-        edits_by_row.entry(edit.row_index).or_default().push(edit);
-    }
-
-    for (row_index, row_edits) in edits_by_row {
+    for (row_index, row_edits) in group_edits_by_row(&request.changes.edits) {
         if let Some(row_data) = request.original_rows.get(row_index) {
-            let mut query_builder: QueryBuilder<sqlx::MySql> =
-                QueryBuilder::new(format!("UPDATE {} SET ", quoted_table));
+            let mut builder: StatementBuilder<sqlx::MySql> =
+                StatementBuilder::new(format!("UPDATE {} SET ", quoted_table));
 
             // Build SET clause with bind parameters
             let mut first = true;
             for edit in &row_edits {
                 if !first {
-                    query_builder.push(", ");
+                    builder.push(", ");
                 }
                 first = false;
 
-                query_builder.push(quote_identifier_mysql(&edit.column_name));
-                query_builder.push(" = ");
-                push_json_value_mysql(&mut query_builder, &edit.new_value);
+                builder.push(&quote_identifier_mysql(&edit.column_name));
+                builder.push(" = ");
+                push_json_value_mysql(
+                    &mut builder,
+                    column_types.get(&edit.column_name).map(String::as_str),
+                    &edit.new_value,
+                )?;
             }
 
-            query_builder.push(" WHERE ");
+            builder.push(" WHERE ");
+            let match_columns =
+                row_match_columns(request, row_data, column_types, is_comparable_mysql_type, false)?;
             build_where_clause_with_binds_mysql(
-                &mut query_builder,
-                &request.primary_key_columns,
+                &mut builder,
+                &match_columns,
                 row_data,
-            );
+                column_types,
+                request.check_conflicts,
+            )?;
 
-            query_builder.build().execute(&mut *tx).await?;
+            if let Some(tx) = tx.as_mut() {
+                let result = builder.query.build().execute(&mut **tx).await?;
+                ensure_row_matched(request.check_conflicts, result.rows_affected(), row_index)?;
+                ensure_single_row(request.row_identifier, result.rows_affected(), row_index)?;
+            }
+            statements.push(builder.rendered);
             edits_count += row_edits.len();
         }
     }
@@ -239,42 +746,95 @@ async fn commit_mysql_changes(
             continue;
         }
 
-        let mut query_builder: QueryBuilder<sqlx::MySql> =
-            QueryBuilder::new(format!("INSERT INTO {} (", quoted_table));
+        let mut builder: StatementBuilder<sqlx::MySql> =
+            StatementBuilder::new(format!("INSERT INTO {} (", quoted_table));
 
         // Build column list
         let columns: Vec<String> = insert.row_data.keys()
             .map(|k| quote_identifier_mysql(k))
             .collect();
-        query_builder.push(columns.join(", "));
-        query_builder.push(") VALUES (");
+        builder.push(&columns.join(", "));
+        builder.push(") VALUES (");
 
         // Build values with bind parameters
         let mut first = true;
-        for value in insert.row_data.values() {
+        for (column, value) in &insert.row_data {
             if !first {
-                query_builder.push(", ");
+                builder.push(", ");
             }
             first = false;
-            push_json_value_mysql(&mut query_builder, value);
+            push_json_value_mysql(&mut builder, column_types.get(column).map(String::as_str), value)?;
         }
-        query_builder.push(")");
+        builder.push(")");
 
-        query_builder.build().execute(&mut *tx).await?;
+        if let Some(tx) = tx.as_mut() {
+            let result = builder.query.build().execute(&mut **tx).await?;
+            if request.record_undo {
+                inserted_rows.push(mysql_inserted_row(request, &insert.row_data, result.last_insert_id()));
+            }
+        }
+        statements.push(builder.rendered);
         inserts_count += 1;
     }
 
-    tx.commit().await?;
+    if let Some(tx) = tx {
+        tx.commit().await?;
+    }
 
-    Ok(CommitResult {
-        success: true,
-        message: format!(
-            "Successfully committed {} edits, {} deletes, {} inserts",
-            edits_count, deletes_count, inserts_count
-        ),
-        edits_count,
-        deletes_count,
-        inserts_count,
+    let mut result = build_commit_result(request.dry_run, edits_count, deletes_count, inserts_count, statements);
+    result.inserted_rows = inserted_rows;
+    Ok(result)
+}
+
+/// An inserted MySQL row as the undo log identifies it: the inserted values plus the
+/// AUTO_INCREMENT value when that is the one key column left out
+fn mysql_inserted_row(
+    request: &CommitRequest,
+    row_data: &serde_json::Map<String, serde_json::Value>,
+    last_insert_id: u64,
+) -> serde_json::Map<String, serde_json::Value> {
+    let mut row = row_data.clone();
+    let missing: Vec<&String> = request
+        .primary_key_columns
+        .iter()
+        .filter(|pk| row.get(pk.as_str()).unwrap_or(&serde_json::Value::Null).is_null())
+        .collect();
+    if let ([column], true) = (missing.as_slice(), last_insert_id != 0) {
+        row.insert(column.to_string(), serde_json::json!(last_insert_id));
+    }
+    row
+}
+
+/// Render a string as a single-quoted SQL literal
+fn quote_literal(value: &str, escape_backslashes: bool) -> String {
+    let mut escaped = value.replace('\'', "''");
+    if escape_backslashes {
+        escaped = escaped.replace('\\', "\\\\");
+    }
+    format!("'{}'", escaped)
+}
+
+/// Text form of a scalar JSON value as the database would parse it
+fn json_value_to_text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Decode a binary cell as shown in the grid ("0x..." or Postgres "\x...") back into bytes
+fn decode_hex_bytes(column_type: &str, value: &serde_json::Value) -> AppResult<Vec<u8>> {
+    let text = json_value_to_text(value);
+    let hex_digits = text
+        .strip_prefix("0x")
+        .or_else(|| text.strip_prefix("\\x"))
+        .unwrap_or(&text);
+
+    hex::decode(hex_digits).map_err(|_| {
+        AppError::ValidationError(format!(
+            "Value for {} column is not a valid hex string: {}",
+            column_type, text
+        ))
     })
 }
 
@@ -283,56 +843,199 @@ fn quote_identifier_postgres(identifier: &str) -> String {
     format!("\"{}\"", identifier.replace('"', "\"\""))
 }
 
-/// Build WHERE clause with proper NULL handling using bind parameters
+/// Declared type of each column without its modifier (`character varying`, not
+/// `character varying(10)`), used to bind values with the right type. An explicit cast
+/// to a length or precision would silently truncate or round an over-long value;
+/// without one the column's own assignment check rejects it.
+async fn get_postgres_column_types(
+    pool: &sqlx::PgPool,
+    schema_name: &str,
+    table_name: &str,
+) -> AppResult<HashMap<String, String>> {
+    let rows = sqlx::query(
+        "SELECT a.attname::TEXT AS column_name, pg_catalog.format_type(a.atttypid, NULL) AS column_type
+         FROM pg_attribute a
+         JOIN pg_class c ON c.oid = a.attrelid
+         JOIN pg_namespace n ON n.oid = c.relnamespace
+         WHERE n.nspname = $2 AND c.relname = $1
+           AND a.attnum > 0 AND NOT a.attisdropped"
+    )
+    .bind(table_name)
+    .bind(schema_name)
+    .fetch_all(pool)
+    .await?;
+
+    let mut column_types = HashMap::with_capacity(rows.len());
+    for row in rows {
+        column_types.insert(row.try_get("column_name")?, row.try_get("column_type")?);
+    }
+    Ok(column_types)
+}
+
+/// Render a JSON array as a Postgres array literal, e.g. {"a","b",NULL}
+fn json_array_to_postgres_literal(values: &[serde_json::Value]) -> String {
+    let elements: Vec<String> = values
+        .iter()
+        .map(|value| match value {
+            serde_json::Value::Null => "NULL".to_string(),
+            serde_json::Value::Array(inner) => json_array_to_postgres_literal(inner),
+            other => format!(
+                "\"{}\"",
+                json_value_to_text(other).replace('\\', "\\\\").replace('"', "\\\"")
+            ),
+        })
+        .collect();
+    format!("{{{}}}", elements.join(","))
+}
+
+/// Build WHERE clause with proper NULL handling using bind parameters.
+/// With `match_original_values`, every comparable non-key column must also still hold its loaded value.
 fn build_where_clause_with_binds_postgres(
-    query_builder: &mut QueryBuilder<sqlx::Postgres>,
-    primary_keys: &[String],
+    builder: &mut StatementBuilder<sqlx::Postgres>,
+    key_columns: &[String],
     row_data: &serde_json::Map<String, serde_json::Value>,
-) {
+    column_types: &HashMap<String, String>,
+    match_original_values: bool,
+) -> AppResult<()> {
     let mut first = true;
-    for pk in primary_keys {
+    for pk in key_columns {
         if !first {
-            query_builder.push(" AND ");
+            builder.push(" AND ");
         }
         first = false;
 
         let value = row_data.get(pk).unwrap_or(&serde_json::Value::Null);
-        query_builder.push(quote_identifier_postgres(pk));
+        builder.push(&quote_identifier_postgres(pk));
 
         // Use IS NULL for null values (NULL = NULL is never true in SQL!)
         if value.is_null() {
-            query_builder.push(" IS NULL");
+            builder.push(" IS NULL");
         } else {
-            query_builder.push(" = ");
-            push_json_value_postgres(query_builder, value);
+            builder.push(" = ");
+            push_json_value_postgres(builder, column_types.get(pk).map(String::as_str), value)?;
+        }
+    }
+
+    if match_original_values {
+        for (column, value) in row_data {
+            if key_columns.contains(column) {
+                continue;
+            }
+            let Some(column_type) = column_types.get(column) else { continue };
+            if !is_comparable_postgres_type(column_type) {
+                continue;
+            }
+
+            builder.push(" AND ");
+            builder.push(&quote_identifier_postgres(column));
+            builder.push(" IS NOT DISTINCT FROM ");
+            push_json_value_postgres(builder, Some(column_type), value)?;
+        }
+    }
+    Ok(())
+}
+
+/// Types whose grid representation round-trips exactly and that support equality
+fn is_comparable_postgres_type(column_type: &str) -> bool {
+    !matches!(
+        column_type,
+        "json" | "bytea" | "real" | "double precision" | "xml" | "point" | "line" | "lseg"
+            | "box" | "path" | "polygon" | "circle" | "geometry" | "geography"
+    )
+}
+
+/// Push a JSON value as a bind parameter for PostgreSQL.
+/// When the column type is known the value is bound as text and cast to that type,
+/// so Postgres parses it with its own input rules (exact numerics, dates, enums, ...).
+fn push_json_value_postgres(
+    builder: &mut StatementBuilder<sqlx::Postgres>,
+    column_type: Option<&str>,
+    value: &serde_json::Value,
+) -> AppResult<()> {
+    if value.is_null() {
+        builder.push("NULL");
+        return Ok(());
+    }
+
+    let Some(column_type) = column_type else {
+        push_untyped_json_value_postgres(builder, value);
+        return Ok(());
+    };
+
+    match (column_type, value) {
+        ("bytea", _) => {
+            let bytes = decode_hex_bytes(column_type, value)?;
+            builder
+                .rendered
+                .push_str(&format!("'\\x{}'::bytea", hex::encode(&bytes)));
+            builder.query.push_bind(bytes);
+        }
+        ("json" | "jsonb", _) => {
+            // Strings typed into a JSON cell are parsed so they are stored as JSON, not a JSON string
+            let json = match value {
+                serde_json::Value::String(s) => serde_json::from_str(s).unwrap_or_else(|_| value.clone()),
+                other => other.clone(),
+            };
+            builder.rendered.push_str(&format!(
+                "{}::{}",
+                quote_literal(&json.to_string(), false),
+                column_type
+            ));
+            builder.query.push_bind(sqlx::types::Json(json));
+        }
+        ("boolean", serde_json::Value::Bool(b)) => {
+            builder.query.push_bind(*b);
+            builder.rendered.push_str(if *b { "TRUE" } else { "FALSE" });
+        }
+        (_, serde_json::Value::Array(items)) if column_type.ends_with("[]") => {
+            let literal = json_array_to_postgres_literal(items);
+            builder.rendered.push_str(&quote_literal(&literal, false));
+            builder.query.push_bind(literal);
+            builder.push(&format!("::{}", column_type));
+        }
+        _ => {
+            let text = match value {
+                serde_json::Value::Array(_) | serde_json::Value::Object(_) => value.to_string(),
+                other => json_value_to_text(other),
+            };
+            builder.rendered.push_str(&quote_literal(&text, false));
+            builder.query.push_bind(text);
+            builder.push(&format!("::{}", column_type));
         }
     }
+
+    Ok(())
 }
 
-/// Push a JSON value as a bind parameter for PostgreSQL
-fn push_json_value_postgres(query_builder: &mut QueryBuilder<sqlx::Postgres>, value: &serde_json::Value) {
+/// Fallback binding for columns whose type could not be looked up
+fn push_untyped_json_value_postgres(builder: &mut StatementBuilder<sqlx::Postgres>, value: &serde_json::Value) {
     match value {
         serde_json::Value::Null => {
-            query_builder.push("NULL");
+            builder.push("NULL");
         }
         serde_json::Value::Bool(b) => {
-            query_builder.push_bind(*b);
+            builder.query.push_bind(*b);
+            builder.rendered.push_str(if *b { "TRUE" } else { "FALSE" });
         }
         serde_json::Value::Number(n) => {
             if let Some(i) = n.as_i64() {
-                query_builder.push_bind(i);
+                builder.query.push_bind(i);
             } else if let Some(f) = n.as_f64() {
-                query_builder.push_bind(f);
+                builder.query.push_bind(f);
             } else {
-                query_builder.push_bind(n.to_string());
+                builder.query.push_bind(n.to_string());
             }
+            builder.rendered.push_str(&n.to_string());
         }
         serde_json::Value::String(s) => {
-            query_builder.push_bind(s.clone());
+            builder.query.push_bind(s.clone());
+            builder.rendered.push_str(&quote_literal(s, false));
         }
         serde_json::Value::Array(_) | serde_json::Value::Object(_) => {
             // For JSON arrays/objects, bind as JSON string
-            query_builder.push_bind(serde_json::to_string(value).unwrap_or_default());
+            let json = serde_json::to_string(value).unwrap_or_default();
+            builder.rendered.push_str(&quote_literal(&json, false));
+            builder.query.push_bind(json);
         }
     }
 }
@@ -342,56 +1045,421 @@ fn quote_identifier_mysql(identifier: &str) -> String {
     format!("`{}`", identifier.replace('`', "``"))
 }
 
-/// Build WHERE clause with proper NULL handling using bind parameters
+/// Declared DATA_TYPE of each column, used to bind values with the right type
+async fn get_mysql_column_types(
+    pool: &sqlx::MySqlPool,
+    table_name: &str,
+) -> AppResult<HashMap<String, String>> {
+    let rows = sqlx::query(
+        "SELECT COLUMN_NAME AS column_name, DATA_TYPE AS data_type FROM INFORMATION_SCHEMA.COLUMNS
+         WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = ?"
+    )
+    .bind(table_name)
+    .fetch_all(pool)
+    .await?;
+
+    let mut column_types = HashMap::with_capacity(rows.len());
+    for row in rows {
+        let column_name: String = row.try_get("column_name")?;
+        let data_type: String = row.try_get("data_type")?;
+        column_types.insert(column_name, data_type.to_lowercase());
+    }
+    Ok(column_types)
+}
+
+/// Build WHERE clause with proper NULL handling using bind parameters.
+/// With `match_original_values`, every comparable non-key column must also still hold its loaded value.
 fn build_where_clause_with_binds_mysql(
-    query_builder: &mut QueryBuilder<sqlx::MySql>,
-    primary_keys: &[String],
+    builder: &mut StatementBuilder<sqlx::MySql>,
+    key_columns: &[String],
     row_data: &serde_json::Map<String, serde_json::Value>,
-) {
+    column_types: &HashMap<String, String>,
+    match_original_values: bool,
+) -> AppResult<()> {
     let mut first = true;
-    for pk in primary_keys {
+    for pk in key_columns {
         if !first {
-            query_builder.push(" AND ");
+            builder.push(" AND ");
         }
         first = false;
 
         let value = row_data.get(pk).unwrap_or(&serde_json::Value::Null);
-        query_builder.push(quote_identifier_mysql(pk));
+        builder.push(&quote_identifier_mysql(pk));
 
         // Use IS NULL for null values
         if value.is_null() {
-            query_builder.push(" IS NULL");
+            builder.push(" IS NULL");
         } else {
-            query_builder.push(" = ");
-            push_json_value_mysql(query_builder, value);
+            builder.push(" = ");
+            push_json_value_mysql(builder, column_types.get(pk).map(String::as_str), value)?;
         }
     }
+
+    if match_original_values {
+        for (column, value) in row_data {
+            if key_columns.contains(column) {
+                continue;
+            }
+            let Some(column_type) = column_types.get(column) else { continue };
+            if !is_comparable_mysql_type(column_type) {
+                continue;
+            }
+
+            // <=> is MySQL's NULL-safe equality
+            builder.push(" AND ");
+            builder.push(&quote_identifier_mysql(column));
+            builder.push(" <=> ");
+            push_json_value_mysql(builder, Some(column_type), value)?;
+        }
+    }
+    Ok(())
 }
 
-/// Push a JSON value as a bind parameter for MySQL
-fn push_json_value_mysql(query_builder: &mut QueryBuilder<sqlx::MySql>, value: &serde_json::Value) {
-    match value {
-        serde_json::Value::Null => {
-            query_builder.push("NULL");
+/// Types whose grid representation round-trips exactly (blobs are truncated for display)
+fn is_comparable_mysql_type(column_type: &str) -> bool {
+    !matches!(
+        column_type,
+        "json" | "float" | "double" | "binary" | "varbinary" | "tinyblob" | "blob" | "mediumblob"
+            | "longblob" | "geometry" | "point" | "linestring" | "polygon" | "multipoint"
+            | "multilinestring" | "multipolygon" | "geometrycollection"
+    )
+}
+
+/// Push a JSON value as a bind parameter for MySQL, converting it according to the column type
+fn push_json_value_mysql(
+    builder: &mut StatementBuilder<sqlx::MySql>,
+    column_type: Option<&str>,
+    value: &serde_json::Value,
+) -> AppResult<()> {
+    match (column_type, value) {
+        (_, serde_json::Value::Null) => {
+            builder.push("NULL");
         }
-        serde_json::Value::Bool(b) => {
+        (Some(ty @ ("binary" | "varbinary" | "tinyblob" | "blob" | "mediumblob" | "longblob")), _) => {
+            let bytes = decode_hex_bytes(ty, value)?;
+            builder.rendered.push_str(&format!("0x{}", hex::encode(&bytes)));
+            builder.query.push_bind(bytes);
+        }
+        // Bind exact numerics as text so MySQL converts them without a lossy f64 round trip
+        (Some("decimal"), serde_json::Value::Number(_) | serde_json::Value::String(_)) => {
+            let text = json_value_to_text(value);
+            builder.rendered.push_str(&quote_literal(&text, true));
+            builder.query.push_bind(text);
+        }
+        (_, serde_json::Value::Bool(b)) => {
             // MySQL uses 1/0 for boolean
-            query_builder.push_bind(if *b { 1i32 } else { 0i32 });
+            let flag = if *b { 1i32 } else { 0i32 };
+            builder.query.push_bind(flag);
+            builder.rendered.push_str(&flag.to_string());
         }
-        serde_json::Value::Number(n) => {
+        (_, serde_json::Value::Number(n)) => {
             if let Some(i) = n.as_i64() {
-                query_builder.push_bind(i);
+                builder.query.push_bind(i);
             } else if let Some(f) = n.as_f64() {
-                query_builder.push_bind(f);
+                builder.query.push_bind(f);
             } else {
-                query_builder.push_bind(n.to_string());
+                builder.query.push_bind(n.to_string());
             }
+            builder.rendered.push_str(&n.to_string());
         }
-        serde_json::Value::String(s) => {
-            query_builder.push_bind(s.clone());
+        (_, serde_json::Value::String(s)) => {
+            builder.query.push_bind(s.clone());
+            builder.rendered.push_str(&quote_literal(s, true));
         }
-        serde_json::Value::Array(_) | serde_json::Value::Object(_) => {
-            query_builder.push_bind(serde_json::to_string(value).unwrap_or_default());
+        (_, serde_json::Value::Array(_) | serde_json::Value::Object(_)) => {
+            let json = serde_json::to_string(value).unwrap_or_default();
+            builder.rendered.push_str(&quote_literal(&json, true));
+            builder.query.push_bind(json);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn request(primary_key_columns: &[&str], row_identifier: RowIdentifier) -> CommitRequest {
+        CommitRequest {
+            connection_id: "conn".to_string(),
+            table_name: "order_lines".to_string(),
+            primary_key_columns: primary_key_columns.iter().map(|c| c.to_string()).collect(),
+            changes: DataGridChanges { edits: vec![], deletes: vec![0], inserts: vec![] },
+            original_rows: vec![],
+            dry_run: true,
+            check_conflicts: false,
+            row_identifier,
+            record_undo: false,
+        }
+    }
+
+    fn row(value: serde_json::Value) -> serde_json::Map<String, serde_json::Value> {
+        value.as_object().unwrap().clone()
+    }
+
+    fn column_types() -> HashMap<String, String> {
+        [("order_id", "integer"), ("line_no", "integer"), ("note", "text"), ("payload", "json")]
+            .into_iter()
+            .map(|(c, t)| (c.to_string(), t.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn inverse_restores_old_values_removes_inserts_and_reinserts_deletes() {
+        let mut committed = request(&["order_id", "line_no"], RowIdentifier::PrimaryKey);
+        committed.original_rows = vec![
+            row(json!({"order_id": 1, "line_no": 1, "note": "gone"})),
+            row(json!({"order_id": 1, "line_no": 2, "note": "old"})),
+        ];
+        committed.changes = DataGridChanges {
+            edits: vec![
+                CellEdit {
+                    row_index: 1,
+                    column_name: "note".to_string(),
+                    old_value: json!("old"),
+                    new_value: json!("new"),
+                },
+                CellEdit {
+                    row_index: 1,
+                    column_name: "line_no".to_string(),
+                    old_value: json!(2),
+                    new_value: json!(5),
+                },
+            ],
+            deletes: vec![0],
+            inserts: vec![RowInsert {
+                temp_id: "t1".to_string(),
+                row_data: row(json!({"order_id": 2, "note": "added"})),
+            }],
+        };
+        let inserted = row(json!({"order_id": 2, "line_no": 9, "note": "added"}));
+
+        let inverse = inverse_request(&committed, std::slice::from_ref(&inserted)).unwrap();
+        assert!(inverse.check_conflicts);
+        assert!(!inverse.record_undo);
+
+        // The inserted row is deleted by its generated key
+        assert_eq!(inverse.changes.deletes, vec![0]);
+        assert_eq!(inverse.original_rows[0], inserted);
+
+        // The edited row is found by its committed key and gets the exact old values back
+        assert_eq!(
+            inverse.original_rows[1],
+            row(json!({"order_id": 1, "line_no": 5, "note": "new"}))
+        );
+        let restored: Vec<_> = inverse
+            .changes
+            .edits
+            .iter()
+            .map(|e| (e.row_index, e.column_name.as_str(), e.new_value.clone()))
+            .collect();
+        assert_eq!(restored, vec![(1, "note", json!("old")), (1, "line_no", json!(2))]);
+
+        // The deleted row goes back with every loaded value
+        assert_eq!(inverse.changes.inserts.len(), 1);
+        assert_eq!(inverse.changes.inserts[0].row_data, committed.original_rows[0]);
+
+        // Without the generated key an inserted row can't be found again
+        let unknown_key = row(json!({"order_id": 2, "note": "added"}));
+        assert!(inverse_request(&committed, &[unknown_key.clone()]).is_err());
+        assert_eq!(
+            mysql_inserted_row(&committed, &unknown_key, 9)["line_no"],
+            json!(9)
+        );
+
+        // ctid changes on update, so reverts of ctid-matched rows match on values
+        let mut by_ctid = request(&[], RowIdentifier::Ctid);
+        by_ctid.original_rows = vec![row(json!({"ctid": "(0,1)", "note": "x"}))];
+        by_ctid.changes = DataGridChanges { edits: vec![], deletes: vec![0], inserts: vec![] };
+        let inverse = inverse_request(&by_ctid, &[]).unwrap();
+        assert_eq!(inverse.row_identifier, RowIdentifier::FullRow);
+        assert_eq!(inverse.changes.inserts[0].row_data, row(json!({"note": "x"})));
+    }
+
+    #[test]
+    fn composite_primary_key_matches_every_key_column() {
+        let row_data = row(json!({"order_id": 1, "line_no": 2, "note": "x"}));
+        let request = request(&["order_id", "line_no"], RowIdentifier::PrimaryKey);
+        let columns =
+            row_match_columns(&request, &row_data, &column_types(), is_comparable_postgres_type, true).unwrap();
+
+        let mut postgres: StatementBuilder<sqlx::Postgres> = StatementBuilder::new(String::new());
+        build_where_clause_with_binds_postgres(&mut postgres, &columns, &row_data, &column_types(), false).unwrap();
+        assert_eq!(postgres.rendered, r#""order_id" = '1'::integer AND "line_no" = '2'::integer"#);
+
+        let mut mysql: StatementBuilder<sqlx::MySql> = StatementBuilder::new(String::new());
+        build_where_clause_with_binds_mysql(&mut mysql, &columns, &row_data, &HashMap::new(), false).unwrap();
+        assert_eq!(mysql.rendered, "`order_id` = 1 AND `line_no` = 2");
+
+        let partial = row(json!({"order_id": 1}));
+        assert!(matches!(
+            row_match_columns(&request, &partial, &column_types(), is_comparable_postgres_type, true),
+            Err(AppError::ValidationError(_))
+        ));
+    }
+
+    #[test]
+    fn tables_without_primary_key_need_an_explicit_row_identifier() {
+        let row_data = row(json!({"ctid": "(0,3)", "note": null, "payload": {"a": 1}}));
+
+        assert!(matches!(
+            row_match_columns(
+                &request(&[], RowIdentifier::PrimaryKey),
+                &row_data,
+                &column_types(),
+                is_comparable_postgres_type,
+                true
+            ),
+            Err(AppError::NoPrimaryKey(_))
+        ));
+
+        // JSON can't be compared reliably and ctid has no declared type here
+        let full_row = row_match_columns(
+            &request(&[], RowIdentifier::FullRow),
+            &row_data,
+            &column_types(),
+            is_comparable_postgres_type,
+            true,
+        )
+        .unwrap();
+        assert_eq!(full_row, vec!["note".to_string()]);
+
+        let ctid = request(&[], RowIdentifier::Ctid);
+        assert_eq!(
+            row_match_columns(&ctid, &row_data, &column_types(), is_comparable_postgres_type, true).unwrap(),
+            vec![CTID_COLUMN.to_string()]
+        );
+        assert!(row_match_columns(&ctid, &row_data, &column_types(), is_comparable_mysql_type, false).is_err());
+
+        assert!(ensure_single_row(RowIdentifier::FullRow, 2, 0).is_err());
+        assert!(ensure_single_row(RowIdentifier::FullRow, 1, 0).is_ok());
+    }
+
+    /// Database error carrying only a SQLSTATE, like the ones PostgreSQL reports
+    #[derive(Debug)]
+    struct SqlState(&'static str);
+
+    impl std::fmt::Display for SqlState {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "SQLSTATE {}", self.0)
+        }
+    }
+
+    impl std::error::Error for SqlState {}
+
+    impl sqlx::error::DatabaseError for SqlState {
+        fn message(&self) -> &str {
+            self.0
+        }
+
+        fn code(&self) -> Option<std::borrow::Cow<'_, str>> {
+            Some(self.0.into())
+        }
+
+        fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) {
+            self
         }
+
+        fn into_error(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+            self
+        }
+
+        fn kind(&self) -> sqlx::error::ErrorKind {
+            sqlx::error::ErrorKind::Other
+        }
+    }
+
+    fn failing_with(code: &'static str) -> CommitAttemptError {
+        CommitAttemptError::Database(sqlx::Error::Database(Box::new(SqlState(code))))
+    }
+
+    /// Runs against the server in `DATASPEAK_TEST_POSTGRES_URL`; skipped when it is unset
+    #[tokio::test]
+    async fn too_long_varchar_edit_is_rejected() {
+        let Ok(url) = std::env::var("DATASPEAK_TEST_POSTGRES_URL") else {
+            eprintln!("DATASPEAK_TEST_POSTGRES_URL not set; skipping");
+            return;
+        };
+        let pool = sqlx::PgPool::connect(&url).await.unwrap();
+        let schema = format!("dataspeak_commit_{}", uuid::Uuid::new_v4().simple());
+        sqlx::query(&format!("CREATE SCHEMA {}", schema)).execute(&pool).await.unwrap();
+        sqlx::query(&format!("CREATE TABLE {}.codes (id integer PRIMARY KEY, code varchar(3))", schema))
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(&format!("INSERT INTO {}.codes VALUES (1, 'abc')", schema))
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let column_types = get_postgres_column_types(&pool, &schema, "codes").await.unwrap();
+        assert_eq!(column_types["code"], "character varying");
+
+        let mut edit = request(&["id"], RowIdentifier::PrimaryKey);
+        edit.dry_run = false;
+        edit.original_rows = vec![row(json!({"id": 1, "code": "abc"}))];
+        edit.changes = DataGridChanges {
+            edits: vec![CellEdit {
+                row_index: 0,
+                column_name: "code".to_string(),
+                old_value: json!("abc"),
+                new_value: json!("abcdef"),
+            }],
+            deletes: vec![],
+            inserts: vec![],
+        };
+        let quoted_table = format!("{}.codes", schema);
+        let result = commit_postgres_attempt(&pool, &edit, &column_types, &quoted_table).await;
+
+        let stored: String = sqlx::query_scalar(&format!("SELECT code FROM {}.codes WHERE id = 1", schema))
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        sqlx::query(&format!("DROP SCHEMA {} CASCADE", schema)).execute(&pool).await.unwrap();
+
+        assert!(result.is_err(), "the edit must fail instead of being truncated");
+        assert_eq!(stored, "abc");
+    }
+
+    #[tokio::test]
+    async fn deadlocks_are_retried_and_other_errors_are_not() {
+        let mut calls = 0;
+        let result = retry_transient_commit(|| {
+            calls += 1;
+            let outcome = if calls < 3 {
+                Err(failing_with("40P01"))
+            } else {
+                Ok(build_commit_result(false, 1, 0, 0, vec![]))
+            };
+            async move { outcome }
+        })
+        .await
+        .unwrap();
+        assert_eq!(result.attempts, 3);
+        assert!(result.message.ends_with("after 3 attempts"));
+
+        let mut calls = 0;
+        let err = retry_transient_commit(|| {
+            calls += 1;
+            async { Err(failing_with("23505")) }
+        })
+        .await;
+        assert!(err.is_err());
+        assert_eq!(calls, 1);
+
+        let mut calls = 0;
+        let err = retry_transient_commit(|| {
+            calls += 1;
+            async { Err(failing_with("40P01")) }
+        })
+        .await;
+        assert!(err.is_err());
+        assert_eq!(calls, MAX_COMMIT_ATTEMPTS);
     }
 }
@@ -0,0 +1,265 @@
+use crate::db::connection::{ConnectionManager, DatabaseType};
+use crate::db::query::{self, ColumnMetadata};
+use crate::error::{AppError, AppResult};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use sqlx::mysql::MySqlRow;
+use sqlx::{Postgres, Transaction};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, Mutex, RwLock};
+use tokio::task::JoinHandle;
+
+/// Cursors that have not been fetched from for this long are closed automatically
+const CURSOR_IDLE_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// How often the idle watchdog checks a cursor
+const CURSOR_IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Number of MySQL rows buffered ahead of the consumer by the streaming task
+const MYSQL_PREFETCH_ROWS: usize = 1000;
+
+/// A page of rows read from an open cursor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CursorPage {
+    pub cursor_id: String,
+    pub columns: Vec<String>,
+    pub column_metadata: Vec<ColumnMetadata>,
+    pub rows: Vec<serde_json::Map<String, serde_json::Value>>,
+    pub row_count: usize,
+    /// True once the underlying result set has no more rows
+    pub exhausted: bool,
+    pub execution_time_ms: u128,
+}
+
+enum CursorKind {
+    /// Server-side cursor declared inside a held transaction
+    Postgres {
+        tx: Transaction<'static, Postgres>,
+        name: String,
+    },
+    /// Streaming fetch running on a dedicated connection, buffered through a channel
+    MySql {
+        rows: mpsc::Receiver<AppResult<MySqlRow>>,
+        task: JoinHandle<()>,
+    },
+    Closed,
+}
+
+struct Cursor {
+    kind: CursorKind,
+    last_used: Instant,
+    exhausted: bool,
+}
+
+// Global registry of open cursors
+lazy_static::lazy_static! {
+    static ref CURSORS: Arc<RwLock<HashMap<String, Arc<Mutex<Cursor>>>>> = Arc::new(RwLock::new(HashMap::new()));
+}
+
+/// Open a forward-only cursor over `query` and return its handle
+pub async fn open_cursor(
+    manager: &ConnectionManager,
+    connection_id: &str,
+    query: &str,
+) -> AppResult<String> {
+    let conn = manager.get_connection(connection_id)?;
+    conn.ensure_query_allowed(query)?;
+    let query = query.trim().trim_end_matches(';').to_string();
+
+    if query.is_empty() {
+        return Err(AppError::ValidationError("Cannot open a cursor for an empty query".to_string()));
+    }
+
+    let cursor_id = uuid::Uuid::new_v4().to_string();
+
+    let kind = match conn.database_type {
+        DatabaseType::PostgreSQL => {
+            let pool = manager.get_pool_postgres(connection_id).await?;
+            let mut tx = pool.begin().await?;
+
+            // Cursor names must be plain identifiers, so derive one from the UUID
+            let name = format!("dataspeak_cursor_{}", cursor_id.replace('-', ""));
+            let declare = format!("DECLARE {} NO SCROLL CURSOR FOR {}", name, query);
+            sqlx::raw_sql(&declare).execute(&mut *tx).await?;
+
+            CursorKind::Postgres { tx, name }
+        }
+        DatabaseType::MariaDB | DatabaseType::MySQL => {
+            let pool = manager.get_pool_mysql(connection_id).await?;
+            let (sender, receiver) = mpsc::channel(MYSQL_PREFETCH_ROWS);
+
+            // The task owns the connection and only reads ahead as far as the
+            // channel allows, so memory stays bounded regardless of result size
+            let task = tokio::spawn(async move {
+                let mut conn = match pool.acquire().await {
+                    Ok(conn) => conn,
+                    Err(e) => {
+                        let _ = sender.send(Err(AppError::from(e))).await;
+                        return;
+                    }
+                };
+
+                let mut stream = sqlx::query(&query).fetch(&mut *conn);
+                while let Some(row) = stream.next().await {
+                    let failed = row.is_err();
+                    if sender.send(row.map_err(AppError::from)).await.is_err() || failed {
+                        break;
+                    }
+                }
+            });
+
+            CursorKind::MySql { rows: receiver, task }
+        }
+    };
+
+    let cursor = Arc::new(Mutex::new(Cursor {
+        kind,
+        last_used: Instant::now(),
+        exhausted: false,
+    }));
+
+    {
+        let mut cursors = CURSORS.write().await;
+        cursors.insert(cursor_id.clone(), cursor);
+    }
+
+    spawn_idle_watchdog(cursor_id.clone());
+
+    Ok(cursor_id)
+}
+
+/// Fetch the next `count` rows from an open cursor
+pub async fn fetch_cursor(cursor_id: &str, count: usize) -> AppResult<CursorPage> {
+    if count == 0 {
+        return Err(AppError::ValidationError("Fetch count must be greater than zero".to_string()));
+    }
+
+    let cursor = get_cursor(cursor_id).await?;
+    let mut cursor = cursor.lock().await;
+    let start = Instant::now();
+    cursor.last_used = Instant::now();
+
+    if cursor.exhausted {
+        return Ok(CursorPage {
+            cursor_id: cursor_id.to_string(),
+            columns: vec![],
+            column_metadata: vec![],
+            rows: vec![],
+            row_count: 0,
+            exhausted: true,
+            execution_time_ms: 0,
+        });
+    }
+
+    let (columns, column_metadata, rows) = match &mut cursor.kind {
+        CursorKind::Postgres { tx, name } => {
+            let fetch = format!("FETCH FORWARD {} FROM {}", count, name);
+            let pg_rows = sqlx::raw_sql(&fetch).fetch_all(&mut **tx).await?;
+
+            let (columns, column_metadata) = pg_rows
+                .first()
+                .map(query::columns_from_row)
+                .unwrap_or_default();
+
+            let mut rows = Vec::with_capacity(pg_rows.len());
+            for row in &pg_rows {
+                rows.push(query::postgres_row_to_json(row)?);
+            }
+            (columns, column_metadata, rows)
+        }
+        CursorKind::MySql { rows: receiver, .. } => {
+            let mut mysql_rows = Vec::with_capacity(count);
+            while mysql_rows.len() < count {
+                match receiver.recv().await {
+                    Some(row) => mysql_rows.push(row?),
+                    None => break,
+                }
+            }
+
+            let (columns, column_metadata) = mysql_rows
+                .first()
+                .map(query::columns_from_row)
+                .unwrap_or_default();
+
+            let mut rows = Vec::with_capacity(mysql_rows.len());
+            for row in &mysql_rows {
+                rows.push(query::mysql_row_to_json(row)?);
+            }
+            (columns, column_metadata, rows)
+        }
+        CursorKind::Closed => {
+            return Err(AppError::QueryError(format!("Cursor '{}' is closed", cursor_id)));
+        }
+    };
+
+    cursor.exhausted = rows.len() < count;
+    cursor.last_used = Instant::now();
+
+    Ok(CursorPage {
+        cursor_id: cursor_id.to_string(),
+        columns,
+        column_metadata,
+        row_count: rows.len(),
+        rows,
+        exhausted: cursor.exhausted,
+        execution_time_ms: start.elapsed().as_millis(),
+    })
+}
+
+/// Close a cursor and release its transaction/connection
+pub async fn close_cursor(cursor_id: &str) -> AppResult<()> {
+    let cursor = {
+        let mut cursors = CURSORS.write().await;
+        cursors.remove(cursor_id)
+    }
+    .ok_or_else(|| AppError::QueryError(format!("Cursor '{}' not found", cursor_id)))?;
+
+    let mut cursor = cursor.lock().await;
+
+    match std::mem::replace(&mut cursor.kind, CursorKind::Closed) {
+        CursorKind::Postgres { mut tx, name } => {
+            // Closing is best-effort: rolling back the transaction drops the cursor anyway
+            let _ = sqlx::raw_sql(&format!("CLOSE {}", name)).execute(&mut *tx).await;
+            tx.rollback().await?;
+        }
+        CursorKind::MySql { rows, task } => {
+            drop(rows);
+            task.abort();
+        }
+        CursorKind::Closed => {}
+    }
+
+    Ok(())
+}
+
+async fn get_cursor(cursor_id: &str) -> AppResult<Arc<Mutex<Cursor>>> {
+    let cursors = CURSORS.read().await;
+    cursors
+        .get(cursor_id)
+        .cloned()
+        .ok_or_else(|| AppError::QueryError(format!("Cursor '{}' not found", cursor_id)))
+}
+
+/// Close the cursor once it has been idle longer than CURSOR_IDLE_TIMEOUT
+fn spawn_idle_watchdog(cursor_id: String) {
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(CURSOR_IDLE_CHECK_INTERVAL).await;
+
+            let Ok(cursor) = get_cursor(&cursor_id).await else {
+                // Already closed by the user
+                break;
+            };
+
+            let idle = cursor.lock().await.last_used.elapsed();
+            if idle >= CURSOR_IDLE_TIMEOUT {
+                if let Err(e) = close_cursor(&cursor_id).await {
+                    eprintln!("Failed to close idle cursor {}: {}", cursor_id, e);
+                }
+                break;
+            }
+        }
+    });
+}
@@ -0,0 +1,125 @@
+use crate::db::connection::{ConnectionManager, DatabaseType};
+use crate::error::AppResult;
+use serde::{Deserialize, Serialize};
+
+/// One-shot snapshot of a connection's server, for support requests.
+/// Each field is gathered separately; a failed lookup leaves it `None` and is listed in `errors`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectionDiagnostics {
+    pub connection_id: String,
+    pub database_type: DatabaseType,
+    pub server_version: Option<String>,
+    /// Size of the current database (data and indexes) in bytes
+    pub database_size_bytes: Option<i64>,
+    /// Base tables in the current database, excluding system schemas
+    pub table_count: Option<i64>,
+    /// Client connections open on the server
+    pub active_connections: Option<i64>,
+    /// Lookups that failed, as "field: error"
+    pub errors: Vec<String>,
+}
+
+/// Gather server version, database size, table count and connection count for a connection
+pub async fn connection_diagnostics(
+    manager: &ConnectionManager,
+    connection_id: &str,
+) -> AppResult<ConnectionDiagnostics> {
+    let conn = manager.get_connection(connection_id)?;
+    let mut diagnostics = ConnectionDiagnostics {
+        connection_id: connection_id.to_string(),
+        database_type: conn.database_type.clone(),
+        server_version: None,
+        database_size_bytes: None,
+        table_count: None,
+        active_connections: None,
+        errors: Vec::new(),
+    };
+
+    match conn.database_type {
+        DatabaseType::PostgreSQL => {
+            let pool = manager.get_pool_postgres(connection_id).await?;
+            let (version, size, tables, active) = tokio::join!(
+                sqlx::query_scalar::<_, String>("SELECT version()").fetch_one(&pool),
+                sqlx::query_scalar::<_, i64>("SELECT pg_database_size(current_database())").fetch_one(&pool),
+                sqlx::query_scalar::<_, i64>(
+                    "SELECT COUNT(*) FROM information_schema.tables
+                     WHERE table_type = 'BASE TABLE'
+                       AND table_schema NOT IN ('pg_catalog', 'information_schema')"
+                )
+                .fetch_one(&pool),
+                sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM pg_stat_activity WHERE datname IS NOT NULL")
+                    .fetch_one(&pool),
+            );
+            diagnostics.server_version = diagnostics.record("server_version", version);
+            diagnostics.database_size_bytes = diagnostics.record("database_size_bytes", size);
+            diagnostics.table_count = diagnostics.record("table_count", tables);
+            diagnostics.active_connections = diagnostics.record("active_connections", active);
+        }
+        DatabaseType::MariaDB | DatabaseType::MySQL => {
+            let pool = manager.get_pool_mysql(connection_id).await?;
+            let (version, size, tables, active) = tokio::join!(
+                sqlx::query_scalar::<_, String>("SELECT VERSION()").fetch_one(&pool),
+                sqlx::query_scalar::<_, i64>(
+                    "SELECT CAST(COALESCE(SUM(data_length + index_length), 0) AS SIGNED)
+                     FROM information_schema.tables WHERE table_schema = DATABASE()"
+                )
+                .fetch_one(&pool),
+                sqlx::query_scalar::<_, i64>(
+                    "SELECT COUNT(*) FROM information_schema.tables
+                     WHERE table_schema = DATABASE() AND table_type = 'BASE TABLE'"
+                )
+                .fetch_one(&pool),
+                sqlx::query_as::<_, (String, String)>("SHOW STATUS LIKE 'Threads_connected'").fetch_one(&pool),
+            );
+            let active = active.map_err(|e| e.to_string()).and_then(|(_, value)| {
+                value.trim().parse::<i64>().map_err(|e| format!("unexpected value '{}': {}", value, e))
+            });
+            diagnostics.server_version = diagnostics.record("server_version", version);
+            diagnostics.database_size_bytes = diagnostics.record("database_size_bytes", size);
+            diagnostics.table_count = diagnostics.record("table_count", tables);
+            diagnostics.active_connections = diagnostics.record("active_connections", active);
+        }
+    }
+
+    Ok(diagnostics)
+}
+
+impl ConnectionDiagnostics {
+    /// Keep a lookup's value, or note its error and leave the field empty
+    fn record<T, E: std::fmt::Display>(&mut self, field: &str, result: Result<T, E>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(e) => {
+                self.errors.push(format!("{}: {}", field, e));
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failed_lookups_are_listed_without_losing_the_rest() {
+        let mut diagnostics = ConnectionDiagnostics {
+            connection_id: "conn".to_string(),
+            database_type: DatabaseType::MySQL,
+            server_version: None,
+            database_size_bytes: None,
+            table_count: None,
+            active_connections: None,
+            errors: Vec::new(),
+        };
+
+        diagnostics.server_version = diagnostics.record("server_version", Ok::<_, String>("8.0.36".to_string()));
+        diagnostics.active_connections =
+            diagnostics.record("active_connections", "n/a".parse::<i64>());
+
+        assert_eq!(diagnostics.server_version.as_deref(), Some("8.0.36"));
+        assert_eq!(diagnostics.active_connections, None);
+        assert_eq!(diagnostics.errors.len(), 1);
+        assert!(diagnostics.errors[0].starts_with("active_connections: "));
+    }
+}
@@ -1,8 +1,12 @@
 pub mod connection;
+pub mod ssh_tunnel;
 pub mod schema;
 pub mod query;
+pub mod cursor;
+pub mod risk;
 pub mod erd;
 pub mod commit;
 pub mod clear;
+pub mod diagnostics;
 pub mod keywords;
 pub mod syntax_highlight;
@@ -1,13 +1,22 @@
-use crate::db::connection::{ConnectionManager, DatabaseType};
-use crate::error::AppResult;
+use crate::ai::cache::{cache_key, ResponseCache};
+use crate::db::connection::{Connection, ConnectionManager, DatabaseType};
+use crate::db::schema::parse_mysql_enum_values;
+use crate::db::syntax_highlight;
+use crate::error::{AppError, AppResult};
 use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime};
+use futures::{Stream, StreamExt};
 use serde::{Deserialize, Serialize};
-use sqlx::postgres::types::{Oid, PgInterval};
+use sqlx::postgres::types::{Oid, PgInterval, PgRange};
 use sqlx::types::ipnetwork::IpNetwork;
 use sqlx::{Column, Row, TypeInfo, ValueRef};
 use std::collections::HashMap;
 use std::net::IpAddr;
-use std::time::Instant;
+use std::ops::Bound;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use tokio::sync::RwLock;
+use tokio_util::sync::CancellationToken;
 
 /// Quote an identifier for PostgreSQL (uses double quotes)
 fn quote_identifier_postgres(identifier: &str) -> String {
@@ -20,13 +29,66 @@ fn quote_identifier_mysql(identifier: &str) -> String {
 }
 
 /// Quote an identifier based on database type
-fn quote_identifier(identifier: &str, db_type: &DatabaseType) -> String {
+pub(crate) fn quote_identifier(identifier: &str, db_type: &DatabaseType) -> String {
     match db_type {
         DatabaseType::PostgreSQL => quote_identifier_postgres(identifier),
         DatabaseType::MariaDB | DatabaseType::MySQL => quote_identifier_mysql(identifier),
     }
 }
 
+/// Split a `schema.table` reference; bare names have no schema
+pub(crate) fn split_qualified_table(table_name: &str) -> (Option<&str>, &str) {
+    match table_name.split_once('.') {
+        Some((schema, table)) if !schema.is_empty() && !table.is_empty() => (Some(schema), table),
+        _ => (None, table_name),
+    }
+}
+
+/// Quote a table name; PostgreSQL names may be schema-qualified (`sales.orders` -> `"sales"."orders"`)
+pub(crate) fn quote_table(table_name: &str, db_type: &DatabaseType) -> String {
+    match (db_type, split_qualified_table(table_name)) {
+        (DatabaseType::PostgreSQL, (Some(schema), table)) => format!(
+            "{}.{}",
+            quote_identifier_postgres(schema),
+            quote_identifier_postgres(table)
+        ),
+        _ => quote_identifier(table_name, db_type),
+    }
+}
+
+/// Schema and bare name of a PostgreSQL table. A `schema.table` reference names its
+/// schema; otherwise the first schema on the search path containing the table wins.
+pub(crate) async fn resolve_postgres_table<'a>(
+    pool: &sqlx::PgPool,
+    table_name: &'a str,
+    search_path: &[String],
+) -> (String, &'a str) {
+    let (schema, table) = split_qualified_table(table_name);
+    if let Some(schema) = schema {
+        return (schema.to_string(), table);
+    }
+
+    let first = search_path.first().cloned().unwrap_or_else(|| "public".to_string());
+    if search_path.len() <= 1 {
+        return (first, table);
+    }
+
+    let found: Option<String> = sqlx::query_scalar(
+        "SELECT table_schema::TEXT FROM information_schema.tables
+         WHERE table_name = $1 AND table_schema::TEXT = ANY($2::TEXT[])
+         ORDER BY array_position($2::TEXT[], table_schema::TEXT)
+         LIMIT 1",
+    )
+    .bind(table)
+    .bind(search_path)
+    .fetch_optional(pool)
+    .await
+    .ok()
+    .flatten();
+
+    (found.unwrap_or(first), table)
+}
+
 /// Convert float to JSON, handling special values (NaN, Infinity)
 /// serde_json::Number::from_f64() returns None for NaN/Infinity, so we
 /// represent them as special string values for data integrity
@@ -81,38 +143,1608 @@ impl TableMetadata {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QueryResult {
     pub columns: Vec<String>,
+    #[serde(default)]
     pub column_metadata: Vec<ColumnMetadata>,
     pub rows: Vec<serde_json::Map<String, serde_json::Value>>,
     pub row_count: usize,
     pub execution_time_ms: u128,
+    /// Per-column aggregates over `rows`, only computed when requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<HashMap<String, ColumnSummary>>,
+}
+
+/// Footer aggregates for one result column
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ColumnSummary {
+    /// Non-null values
+    pub count: usize,
+    /// Sum, average, minimum and maximum are only set for numeric columns with at least
+    /// one numeric value
+    pub sum: Option<f64>,
+    pub avg: Option<f64>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+/// Whether a result column type holds plain numbers that can be summed
+/// (`INT4`, `numeric(10,2)`, `INT UNSIGNED`, ...). Money, bit and boolean types are not.
+fn is_numeric_data_type(data_type: &str) -> bool {
+    let upper = data_type.to_uppercase();
+    let base = upper.split('(').next().unwrap_or_default().trim();
+    let base = base.strip_suffix("UNSIGNED").unwrap_or(base).trim();
+    matches!(
+        base,
+        "INT2" | "INT4" | "INT8" | "SMALLINT" | "INT" | "INTEGER" | "BIGINT" | "TINYINT" | "MEDIUMINT"
+            | "FLOAT4" | "FLOAT8" | "REAL" | "FLOAT" | "DOUBLE" | "DOUBLE PRECISION" | "NUMERIC" | "DECIMAL"
+    )
+}
+
+/// Number in a result cell; DECIMAL/NUMERIC values arrive as strings to keep their precision
+fn cell_number(value: &serde_json::Value) -> Option<f64> {
+    match value {
+        serde_json::Value::Number(n) => n.as_f64(),
+        serde_json::Value::String(s) => s.trim().parse::<f64>().ok().filter(|f| f.is_finite()),
+        _ => None,
+    }
+}
+
+impl QueryResult {
+    /// Aggregates over the fetched rows, keyed by column name. Every column gets a count of
+    /// its non-null values; columns whose metadata type is numeric also get sum, average,
+    /// minimum and maximum over the values that parse as numbers.
+    pub fn summarize(&self) -> HashMap<String, ColumnSummary> {
+        self.columns
+            .iter()
+            .map(|column| {
+                let numeric = self
+                    .column_metadata
+                    .iter()
+                    .find(|meta| &meta.name == column)
+                    .is_some_and(|meta| is_numeric_data_type(&meta.data_type));
+                let values = self
+                    .rows
+                    .iter()
+                    .filter_map(|row| row.get(column))
+                    .filter(|value| !value.is_null());
+
+                let mut summary = ColumnSummary::default();
+                let mut numbers = 0usize;
+                for value in values {
+                    summary.count += 1;
+                    let Some(number) = cell_number(value).filter(|_| numeric) else {
+                        continue;
+                    };
+                    numbers += 1;
+                    summary.sum = Some(summary.sum.unwrap_or(0.0) + number);
+                    summary.min = Some(summary.min.map_or(number, |min| min.min(number)));
+                    summary.max = Some(summary.max.map_or(number, |max| max.max(number)));
+                }
+                summary.avg = summary.sum.map(|sum| sum / numbers as f64);
+                (column.clone(), summary)
+            })
+            .collect()
+    }
+
+    /// Attach `summarize()` as the result's summary
+    pub fn with_summary(mut self) -> Self {
+        self.summary = Some(self.summarize());
+        self
+    }
+}
+
+/// A row present in both results whose non-key values differ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RowChange {
+    pub before: serde_json::Map<String, serde_json::Value>,
+    pub after: serde_json::Map<String, serde_json::Value>,
+    /// Columns whose values differ, in result column order
+    pub changed_columns: Vec<String>,
+}
+
+/// Row-level differences between two query results.
+/// Rows are listed in the order they appear in their source result.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryDiff {
+    pub key_columns: Vec<String>,
+    /// Rows only in the second result
+    pub added: Vec<serde_json::Map<String, serde_json::Value>>,
+    /// Rows only in the first result
+    pub removed: Vec<serde_json::Map<String, serde_json::Value>>,
+    pub changed: Vec<RowChange>,
+    pub unchanged_count: usize,
+}
+
+/// Compare two results, aligning rows by `key_columns`.
+///
+/// Rows with a NULL in any key column can't be identified (NULL never equals NULL),
+/// so they are matched on their full contents instead and reported as added/removed
+/// rather than changed. With no key columns every row is matched that way.
+/// Duplicate keys are paired in order of appearance.
+pub fn diff_query_results(
+    before: &QueryResult,
+    after: &QueryResult,
+    key_columns: &[String],
+) -> AppResult<QueryDiff> {
+    for key in key_columns {
+        if !before.columns.contains(key) || !after.columns.contains(key) {
+            return Err(AppError::ValidationError(format!(
+                "Key column '{}' must be present in both results",
+                key
+            )));
+        }
+    }
+
+    let mut columns = before.columns.clone();
+    for column in &after.columns {
+        if !columns.contains(column) {
+            columns.push(column.clone());
+        }
+    }
+
+    // Canonical text for a list of cells so rows can be hashed
+    let encode = |row: &serde_json::Map<String, serde_json::Value>, cols: &[String]| {
+        let cells: Vec<Option<&serde_json::Value>> = cols.iter().map(|c| row.get(c)).collect();
+        serde_json::to_string(&cells).unwrap_or_default()
+    };
+    let row_key = |row: &serde_json::Map<String, serde_json::Value>| {
+        let has_null_key = key_columns
+            .iter()
+            .any(|c| matches!(row.get(c), None | Some(serde_json::Value::Null)));
+        if key_columns.is_empty() || has_null_key {
+            None
+        } else {
+            Some(encode(row, key_columns))
+        }
+    };
+
+    // Index the second result by key, or by full contents for unkeyed rows
+    let mut after_by_key: HashMap<String, std::collections::VecDeque<usize>> = HashMap::new();
+    let mut after_by_content: HashMap<String, std::collections::VecDeque<usize>> = HashMap::new();
+    for (idx, row) in after.rows.iter().enumerate() {
+        match row_key(row) {
+            Some(key) => after_by_key.entry(key).or_default().push_back(idx),
+            None => after_by_content.entry(encode(row, &columns)).or_default().push_back(idx),
+        }
+    }
+
+    let mut matched = vec![false; after.rows.len()];
+    let mut removed = Vec::new();
+    let mut changed = Vec::new();
+    let mut unchanged_count = 0;
+
+    for row in &before.rows {
+        let (index, lookup) = match row_key(row) {
+            Some(key) => (&mut after_by_key, key),
+            None => (&mut after_by_content, encode(row, &columns)),
+        };
+        let Some(after_idx) = index.get_mut(&lookup).and_then(|queue| queue.pop_front()) else {
+            removed.push(row.clone());
+            continue;
+        };
+        matched[after_idx] = true;
+
+        let after_row = &after.rows[after_idx];
+        let changed_columns: Vec<String> = columns
+            .iter()
+            .filter(|c| row.get(*c) != after_row.get(*c))
+            .cloned()
+            .collect();
+        if changed_columns.is_empty() {
+            unchanged_count += 1;
+        } else {
+            changed.push(RowChange {
+                before: row.clone(),
+                after: after_row.clone(),
+                changed_columns,
+            });
+        }
+    }
+
+    let added = after
+        .rows
+        .iter()
+        .zip(&matched)
+        .filter(|(_, matched)| !**matched)
+        .map(|(row, _)| row.clone())
+        .collect();
+
+    Ok(QueryDiff {
+        key_columns: key_columns.to_vec(),
+        added,
+        removed,
+        changed,
+        unchanged_count,
+    })
+}
+
+/// How `pivot_query_result` fills a cell that several rows map to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PivotAggregate {
+    /// Reject the pivot when an index/pivot pair occurs more than once
+    #[default]
+    Error,
+    First,
+    Last,
+    /// Non-null values
+    Count,
+    /// Numeric aggregates skip nulls and values that are not numbers
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+impl PivotAggregate {
+    fn combine(self, values: &[&serde_json::Value]) -> serde_json::Value {
+        let number = |n: f64| serde_json::Number::from_f64(n).map_or(serde_json::Value::Null, serde_json::Value::Number);
+        let numbers = || values.iter().filter_map(|v| cell_number(v));
+        match self {
+            Self::Error | Self::First => values.first().map_or(serde_json::Value::Null, |v| (*v).clone()),
+            Self::Last => values.last().map_or(serde_json::Value::Null, |v| (*v).clone()),
+            Self::Count => values.iter().filter(|v| !v.is_null()).count().into(),
+            Self::Sum => numbers().reduce(|a, b| a + b).map_or(serde_json::Value::Null, number),
+            Self::Avg => {
+                let count = numbers().count();
+                numbers()
+                    .reduce(|a, b| a + b)
+                    .map_or(serde_json::Value::Null, |sum| number(sum / count as f64))
+            }
+            Self::Min => numbers().reduce(f64::min).map_or(serde_json::Value::Null, number),
+            Self::Max => numbers().reduce(f64::max).map_or(serde_json::Value::Null, number),
+        }
+    }
 }
 
+/// Turn a long result wide: one row per distinct `index_column` value and one column per
+/// distinct `pivot_column` value, holding `value_column`. Rows and pivot columns follow
+/// the order in which their values first appear, so the query's ORDER BY decides the
+/// layout. Pivot values become headers as the grid shows them (a NULL pivot value is
+/// the `NULL` column). Missing combinations are NULL; pairs occurring more than once
+/// are combined with `aggregate`.
+pub fn pivot_query_result(
+    result: &QueryResult,
+    index_column: &str,
+    pivot_column: &str,
+    value_column: &str,
+    aggregate: PivotAggregate,
+) -> AppResult<QueryResult> {
+    for column in [index_column, pivot_column, value_column] {
+        if !result.columns.iter().any(|c| c == column) {
+            return Err(AppError::ValidationError(format!("Column '{}' is not in the result", column)));
+        }
+    }
+    if index_column == pivot_column {
+        return Err(AppError::ValidationError(
+            "The index and pivot columns must be different".to_string(),
+        ));
+    }
+
+    let null = serde_json::Value::Null;
+    let mut index_values: Vec<&serde_json::Value> = Vec::new();
+    let mut index_positions: HashMap<String, usize> = HashMap::new();
+    let mut headers: Vec<String> = Vec::new();
+    let mut header_positions: HashMap<String, usize> = HashMap::new();
+    let mut cells: HashMap<(usize, usize), Vec<&serde_json::Value>> = HashMap::new();
+
+    for row in &result.rows {
+        let index_value = row.get(index_column).unwrap_or(&null);
+        let row_pos = *index_positions.entry(index_value.to_string()).or_insert_with(|| {
+            index_values.push(index_value);
+            index_values.len() - 1
+        });
+
+        let header = grid_cell_text(row.get(pivot_column));
+        let column_pos = match header_positions.get(&header) {
+            Some(&pos) => pos,
+            None => {
+                headers.push(header.clone());
+                header_positions.insert(header.clone(), headers.len() - 1);
+                headers.len() - 1
+            }
+        };
+
+        let values = cells.entry((row_pos, column_pos)).or_default();
+        if aggregate == PivotAggregate::Error && !values.is_empty() {
+            return Err(AppError::ValidationError(format!(
+                "More than one row has {} = {} and {} = {}; choose an aggregate to combine them",
+                index_column,
+                grid_cell_text(Some(index_value)),
+                pivot_column,
+                header
+            )));
+        }
+        values.push(row.get(value_column).unwrap_or(&null));
+    }
+
+    if headers.iter().any(|h| h == index_column) {
+        return Err(AppError::ValidationError(format!(
+            "Pivot value '{}' would duplicate the index column",
+            index_column
+        )));
+    }
+
+    let rows: Vec<serde_json::Map<String, serde_json::Value>> = index_values
+        .iter()
+        .enumerate()
+        .map(|(row_pos, index_value)| {
+            let mut row = serde_json::Map::new();
+            row.insert(index_column.to_string(), (*index_value).clone());
+            for (column_pos, header) in headers.iter().enumerate() {
+                let value = cells
+                    .get(&(row_pos, column_pos))
+                    .map_or(serde_json::Value::Null, |values| aggregate.combine(values));
+                row.insert(header.clone(), value);
+            }
+            row
+        })
+        .collect();
+
+    let source_metadata = |column: &str| result.column_metadata.iter().find(|m| m.name == column);
+    let value_metadata = source_metadata(value_column);
+    let mut column_metadata = vec![source_metadata(index_column).cloned().unwrap_or_else(|| ColumnMetadata {
+        name: index_column.to_string(),
+        data_type: "TEXT".to_string(),
+        enum_values: None,
+        foreign_key: None,
+    })];
+    column_metadata.extend(headers.iter().map(|header| {
+        let data_type = match aggregate {
+            PivotAggregate::Count => "INT8".to_string(),
+            PivotAggregate::Sum | PivotAggregate::Avg | PivotAggregate::Min | PivotAggregate::Max => {
+                "FLOAT8".to_string()
+            }
+            PivotAggregate::Error | PivotAggregate::First | PivotAggregate::Last => {
+                value_metadata.map_or_else(|| "TEXT".to_string(), |m| m.data_type.clone())
+            }
+        };
+        ColumnMetadata {
+            name: header.clone(),
+            data_type,
+            enum_values: None,
+            foreign_key: None,
+        }
+    }));
+
+    let mut columns = vec![index_column.to_string()];
+    columns.extend(headers);
+
+    Ok(QueryResult {
+        columns,
+        column_metadata,
+        row_count: rows.len(),
+        rows,
+        execution_time_ms: result.execution_time_ms,
+        summary: None,
+    })
+}
+
+/// Text format for copying a result set to the clipboard
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResultTextFormat {
+    /// Tab-separated, for pasting into spreadsheets
+    Tsv,
+    Csv,
+    /// GitHub-flavored table
+    Markdown,
+    /// Array of row objects with their original JSON values
+    Json,
+}
+
+/// Serialize an already loaded result with a header row. Cells read as in the grid:
+/// NULL as `NULL`, strings as-is, anything else (numbers, JSON) in its JSON form.
+pub fn serialize_query_result(result: &QueryResult, format: ResultTextFormat) -> AppResult<String> {
+    let cell_rows = || {
+        result
+            .rows
+            .iter()
+            .map(|row| result.columns.iter().map(|column| grid_cell_text(row.get(column))))
+    };
+
+    match format {
+        ResultTextFormat::Tsv => {
+            let mut out = String::new();
+            let header: Vec<String> = result.columns.iter().map(|c| tsv_cell(c)).collect();
+            out.push_str(&header.join("\t"));
+            out.push('\n');
+            for cells in cell_rows() {
+                let cells: Vec<String> = cells.map(|cell| tsv_cell(&cell)).collect();
+                out.push_str(&cells.join("\t"));
+                out.push('\n');
+            }
+            Ok(out)
+        }
+        ResultTextFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(Vec::new());
+            writer.write_record(&result.columns)?;
+            for cells in cell_rows() {
+                writer.write_record(cells)?;
+            }
+            let bytes = writer
+                .into_inner()
+                .map_err(|e| AppError::Other(format!("Failed to write CSV: {}", e)))?;
+            String::from_utf8(bytes).map_err(|e| AppError::Other(format!("Failed to write CSV: {}", e)))
+        }
+        ResultTextFormat::Markdown => {
+            let mut out = String::new();
+            let header: Vec<String> = result.columns.iter().map(|c| markdown_cell(c)).collect();
+            out.push_str(&format!("| {} |\n", header.join(" | ")));
+            out.push_str(&format!("|{}\n", " --- |".repeat(result.columns.len())));
+            for cells in cell_rows() {
+                let cells: Vec<String> = cells.map(|cell| markdown_cell(&cell)).collect();
+                out.push_str(&format!("| {} |\n", cells.join(" | ")));
+            }
+            Ok(out)
+        }
+        ResultTextFormat::Json => Ok(serde_json::to_string_pretty(&result.rows)?),
+    }
+}
+
+/// A cell's text as the result grid shows it
+pub(crate) fn grid_cell_text(value: Option<&serde_json::Value>) -> String {
+    match value {
+        None | Some(serde_json::Value::Null) => "NULL".to_string(),
+        Some(serde_json::Value::String(s)) => s.clone(),
+        Some(value) => value.to_string(),
+    }
+}
+
+/// Escape the characters that would split a TSV cell or row
+fn tsv_cell(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+/// Keep cell text on one line and stop pipes from splitting the column
+pub(crate) fn markdown_cell(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace(['\r', '\n'], " ")
+}
+
+/// Page size when neither the caller nor the connection sets one
+pub const DEFAULT_QUERY_LIMIT: i32 = 50;
+
+/// Run a query with pagination. `params` are bound in order to the query's
+/// `$1`/`?` placeholders, typed by their JSON type. Without an explicit `limit`
+/// the connection's `default_limit` applies.
 pub async fn execute_query(
     manager: &ConnectionManager,
     connection_id: &str,
     query: &str,
-    limit: i32,
+    params: &[serde_json::Value],
+    limit: Option<i32>,
     offset: i32,
+    timeout_ms: u64,
+) -> AppResult<QueryResult> {
+    execute_query_with_token(manager, connection_id, query, params, limit, offset, timeout_ms, None).await
+}
+
+/// Like `execute_query`, but while it runs the query's server session is registered
+/// under `query_token` so `cancel_query` can stop just this statement
+#[allow(clippy::too_many_arguments)]
+pub async fn execute_query_with_token(
+    manager: &ConnectionManager,
+    connection_id: &str,
+    query: &str,
+    params: &[serde_json::Value],
+    limit: Option<i32>,
+    offset: i32,
+    timeout_ms: u64,
+    query_token: Option<&str>,
 ) -> AppResult<QueryResult> {
     let conn = manager.get_connection(connection_id)?;
+    conn.ensure_query_allowed(query)?;
     let start = Instant::now();
 
-    // Add pagination to query only if not already present
-    let query_upper = query.to_uppercase();
-    let paginated_query = if query_upper.contains("LIMIT") {
-        // Query already has LIMIT, use as-is
-        query.trim_end_matches(';').to_string()
+    let paginated_query = paginate_query(query, resolve_limit(&conn, limit), offset);
+
+    let execution = async {
+        match conn.database_type {
+            DatabaseType::PostgreSQL => {
+                execute_postgres_query(manager, connection_id, &paginated_query, params, timeout_ms, query_token)
+                    .await
+            }
+            DatabaseType::MariaDB | DatabaseType::MySQL => {
+                execute_mysql_query(manager, connection_id, &paginated_query, params, query_token).await
+            }
+        }
+    };
+
+    // A timeout of 0 means no limit
+    let result = if timeout_ms == 0 {
+        execution.await?
     } else {
-        // Add LIMIT/OFFSET
-        format!("{} LIMIT {} OFFSET {}", query.trim_end_matches(';'), limit, offset)
+        tokio::time::timeout(Duration::from_millis(timeout_ms), execution)
+            .await
+            .map_err(|_| {
+                AppError::QueryTimeout(format!("Query exceeded the {} ms timeout", timeout_ms))
+            })??
     };
 
-    let result = match conn.database_type {
+    let execution_time_ms = start.elapsed().as_millis();
+
+    Ok(QueryResult {
+        columns: result.0,
+        column_metadata: result.1,
+        rows: result.2,
+        row_count: result.3,
+        execution_time_ms,
+        summary: None,
+    })
+}
+
+/// Server session running a tracked query
+struct RunningQuery {
+    connection_id: String,
+    /// `pg_backend_pid()` on PostgreSQL, `CONNECTION_ID()` on MySQL/MariaDB
+    backend_id: u64,
+    cancelled: bool,
+}
+
+lazy_static::lazy_static! {
+    /// In-flight queries keyed by their client-supplied token
+    static ref RUNNING_QUERIES: std::sync::Mutex<HashMap<String, RunningQuery>> =
+        std::sync::Mutex::new(HashMap::new());
+}
+
+fn running_queries() -> std::sync::MutexGuard<'static, HashMap<String, RunningQuery>> {
+    RUNNING_QUERIES.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Registry entry for a running query, removed when the guard drops
+#[derive(Default)]
+struct RunningQueryGuard {
+    token: Option<String>,
+}
+
+impl RunningQueryGuard {
+    fn register(token: &str, connection_id: &str, backend_id: u64) -> Self {
+        running_queries().insert(
+            token.to_string(),
+            RunningQuery {
+                connection_id: connection_id.to_string(),
+                backend_id,
+                cancelled: false,
+            },
+        );
+        Self { token: Some(token.to_string()) }
+    }
+
+    /// Whether `cancel_query` was called for this query
+    fn was_cancelled(&self) -> bool {
+        self.token
+            .as_ref()
+            .and_then(|token| running_queries().get(token).map(|query| query.cancelled))
+            .unwrap_or(false)
+    }
+}
+
+impl Drop for RunningQueryGuard {
+    fn drop(&mut self) {
+        if let Some(token) = &self.token {
+            running_queries().remove(token);
+        }
+    }
+}
+
+/// Pooled PostgreSQL connection running under a session-level `statement_timeout`.
+/// A session setting (rather than SET LOCAL) needs no transaction block, so VACUUM and
+/// CREATE INDEX CONCURRENTLY still run. The timeout is reset before the connection is
+/// reused; if that never happens, e.g. the query future was dropped, the connection
+/// is closed instead of going back to the pool.
+struct TimedPgConnection {
+    conn: sqlx::pool::PoolConnection<sqlx::Postgres>,
+    timeout_set: bool,
+}
+
+impl TimedPgConnection {
+    async fn acquire(pool: &sqlx::PgPool, timeout_ms: u64) -> AppResult<Self> {
+        let mut timed = Self { conn: pool.acquire().await?, timeout_set: timeout_ms > 0 };
+        if timed.timeout_set {
+            sqlx::query(&format!("SET statement_timeout = {}", timeout_ms))
+                .execute(&mut *timed.conn)
+                .await?;
+        }
+        Ok(timed)
+    }
+
+    async fn reset(&mut self) -> AppResult<()> {
+        if self.timeout_set {
+            sqlx::query("RESET statement_timeout").execute(&mut *self.conn).await?;
+            self.timeout_set = false;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for TimedPgConnection {
+    fn drop(&mut self) {
+        if self.timeout_set {
+            self.conn.close_on_drop();
+        }
+    }
+}
+
+fn query_cancelled_error() -> AppError {
+    AppError::OperationCancelled("Query cancelled by user".to_string())
+}
+
+/// Cancel the query started with `query_token`. Only that statement is stopped: the
+/// cancel is sent on a separate connection, so the session and its pool stay usable.
+pub async fn cancel_query(manager: &ConnectionManager, query_token: &str) -> AppResult<()> {
+    let (connection_id, backend_id) = {
+        let mut running = running_queries();
+        let query = running
+            .get_mut(query_token)
+            .ok_or_else(|| AppError::Other("No running query found for this token".to_string()))?;
+        query.cancelled = true;
+        (query.connection_id.clone(), query.backend_id)
+    };
+
+    use sqlx::Connection as _;
+
+    // A one-off connection, since every pooled connection may be busy (one of them
+    // with the very query being cancelled)
+    match manager.get_connection(&connection_id)?.database_type {
         DatabaseType::PostgreSQL => {
-            execute_postgres_query(manager, connection_id, &paginated_query).await?
+            let mut conn = manager.connect_postgres_once(&connection_id).await?;
+            let cancelled = sqlx::query("SELECT pg_cancel_backend($1)")
+                .bind(backend_id as i32)
+                .execute(&mut conn)
+                .await;
+            conn.close().await.ok();
+            cancelled?;
         }
         DatabaseType::MariaDB | DatabaseType::MySQL => {
-            execute_mysql_query(manager, connection_id, &paginated_query).await?
+            let mut conn = manager.connect_mysql_once(&connection_id).await?;
+            let killed = sqlx::query(&format!("KILL QUERY {}", backend_id))
+                .execute(&mut conn)
+                .await;
+            conn.close().await.ok();
+            killed?;
+        }
+    }
+    Ok(())
+}
+
+/// Maximum cached read query results
+const QUERY_RESULT_CACHE_SIZE: usize = 64;
+
+lazy_static::lazy_static! {
+    static ref QUERY_RESULT_CACHE: ResponseCache = ResponseCache::new(QUERY_RESULT_CACHE_SIZE);
+    /// Per-connection counter mixed into cache keys; bumping it orphans older results
+    static ref QUERY_CACHE_GENERATIONS: std::sync::Mutex<HashMap<String, u64>> =
+        std::sync::Mutex::new(HashMap::new());
+}
+
+/// Like `execute_query`, but a read-only query repeated within `cache_ttl` with the
+/// same SQL (ignoring whitespace, keyword case and comments), params and page
+/// returns the earlier result. A zero TTL always runs the query.
+#[allow(clippy::too_many_arguments)]
+pub async fn execute_query_cached(
+    manager: &ConnectionManager,
+    connection_id: &str,
+    query: &str,
+    params: &[serde_json::Value],
+    limit: Option<i32>,
+    offset: i32,
+    timeout_ms: u64,
+    cache_ttl: Duration,
+) -> AppResult<QueryResult> {
+    let conn = manager.get_connection(connection_id)?;
+    if cache_ttl.is_zero() || crate::ai::sanitizer::validate_sql(query, &conn.database_type).is_err() {
+        return execute_query(manager, connection_id, query, params, limit, offset, timeout_ms).await;
+    }
+
+    let generation = QUERY_CACHE_GENERATIONS
+        .lock()
+        .ok()
+        .and_then(|generations| generations.get(connection_id).copied())
+        .unwrap_or(0);
+    let key = cache_key(&[
+        connection_id,
+        &generation.to_string(),
+        &syntax_highlight::normalize_sql(query, &conn.database_type),
+        &serde_json::to_string(params)?,
+        &format!("{}:{}", resolve_limit(&conn, limit), offset),
+    ]);
+
+    if let Some(result) = QUERY_RESULT_CACHE
+        .get(key, cache_ttl)
+        .and_then(|cached| serde_json::from_str::<QueryResult>(&cached).ok())
+    {
+        return Ok(result);
+    }
+
+    let result = execute_query(manager, connection_id, query, params, limit, offset, timeout_ms).await?;
+    if let Ok(serialized) = serde_json::to_string(&result) {
+        QUERY_RESULT_CACHE.insert(key, serialized);
+    }
+    Ok(result)
+}
+
+/// Stop serving cached results for a connection after its data or target changes.
+/// Also forgets whether it has PostGIS, since DDL may have created or dropped it.
+pub fn invalidate_query_cache(connection_id: &str) {
+    if let Ok(mut generations) = QUERY_CACHE_GENERATIONS.lock() {
+        *generations.entry(connection_id.to_string()).or_insert(0) += 1;
+    }
+    if let Ok(mut installed) = POSTGIS_INSTALLED.lock() {
+        installed.remove(connection_id);
+    }
+}
+
+/// "query-started" event, sent once the result columns are known and before rows are fetched
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryStarted {
+    pub query_id: String,
+    /// Empty when the statement couldn't be described up front
+    pub columns: Vec<String>,
+    pub column_metadata: Vec<ColumnMetadata>,
+}
+
+/// "query-finished" event once the rows have been delivered
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryFinished {
+    pub query_id: String,
+    pub row_count: usize,
+    pub execution_time_ms: u128,
+}
+
+/// Run a query like `execute_query`, reporting through events so the frontend can show
+/// the header right away: "query-started" with the described columns, "query-rows" with
+/// the full result, then "query-finished", or "query-error" on failure.
+#[allow(clippy::too_many_arguments)]
+pub async fn execute_query_with_events(
+    app: &AppHandle,
+    manager: &ConnectionManager,
+    connection_id: &str,
+    query_id: &str,
+    query: &str,
+    params: &[serde_json::Value],
+    limit: Option<i32>,
+    offset: i32,
+    timeout_ms: u64,
+) -> AppResult<QueryResult> {
+    let columns = describe_query_columns(manager, connection_id, query, limit, offset).await;
+    app.emit(
+        "query-started",
+        QueryStarted {
+            query_id: query_id.to_string(),
+            columns: columns.iter().map(|c| c.name.clone()).collect(),
+            column_metadata: columns,
+        },
+    )
+    .ok();
+
+    match execute_query_with_token(
+        manager,
+        connection_id,
+        query,
+        params,
+        limit,
+        offset,
+        timeout_ms,
+        Some(query_id),
+    )
+    .await
+    {
+        Ok(result) => {
+            app.emit(
+                "query-rows",
+                serde_json::json!({ "query_id": query_id, "result": &result }),
+            )
+            .ok();
+            app.emit(
+                "query-finished",
+                QueryFinished {
+                    query_id: query_id.to_string(),
+                    row_count: result.row_count,
+                    execution_time_ms: result.execution_time_ms,
+                },
+            )
+            .ok();
+            Ok(result)
+        }
+        Err(e) => {
+            app.emit(
+                "query-error",
+                serde_json::json!({ "query_id": query_id, "error": e.to_string() }),
+            )
+            .ok();
+            Err(e)
+        }
+    }
+}
+
+/// Result columns of the paginated query from a prepare-only describe. Best effort:
+/// any failure yields no columns and is reported when the query itself runs.
+async fn describe_query_columns(
+    manager: &ConnectionManager,
+    connection_id: &str,
+    query: &str,
+    limit: Option<i32>,
+    offset: i32,
+) -> Vec<ColumnMetadata> {
+    use sqlx::Executor;
+
+    let Ok(conn) = manager.get_connection(connection_id) else {
+        return Vec::new();
+    };
+    if conn.ensure_query_allowed(query).is_err() {
+        return Vec::new();
+    }
+    let paginated_query = paginate_query(query, resolve_limit(&conn, limit), offset);
+
+    let described: Option<Vec<(String, String)>> = match conn.database_type {
+        DatabaseType::PostgreSQL => match manager.get_pool_postgres(connection_id).await {
+            Ok(pool) => pool.describe(&paginated_query).await.ok().map(|d| {
+                d.columns()
+                    .iter()
+                    .map(|c| (c.name().to_string(), c.type_info().name().to_string()))
+                    .collect()
+            }),
+            Err(_) => None,
+        },
+        DatabaseType::MariaDB | DatabaseType::MySQL => match manager.get_pool_mysql(connection_id).await {
+            Ok(pool) => pool.describe(&paginated_query).await.ok().map(|d| {
+                d.columns()
+                    .iter()
+                    .map(|c| (c.name().to_string(), c.type_info().name().to_string()))
+                    .collect()
+            }),
+            Err(_) => None,
+        },
+    };
+
+    described
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(name, data_type)| ColumnMetadata {
+            name,
+            data_type,
+            enum_values: None,
+            foreign_key: None,
+        })
+        .collect()
+}
+
+/// Page size for a query: the caller's, else the connection's default
+fn resolve_limit(conn: &Connection, limit: Option<i32>) -> i32 {
+    limit
+        .filter(|l| *l > 0)
+        .or_else(|| conn.default_limit.and_then(|l| i32::try_from(l).ok()))
+        .unwrap_or(DEFAULT_QUERY_LIMIT)
+}
+
+/// Add LIMIT/OFFSET to a query so it returns the same rows as running it raw, one page
+/// at a time. Queries that already limit themselves and single-row aggregates are left
+/// alone; grouped and windowed queries are paged as a subselect so the clause can't
+/// collide with their own trailing clauses.
+fn paginate_query(query: &str, limit: i32, offset: i32) -> String {
+    let query = syntax_highlight::trim_statement_end(query);
+    let shape = syntax_highlight::statement_shape(query);
+    if shape.is_limited() || shape.is_single_row_aggregate() {
+        query.to_string()
+    } else if shape.has_group_by || shape.has_window {
+        format!("SELECT * FROM ({}\n) AS _page LIMIT {} OFFSET {}", query, limit, offset)
+    } else {
+        format!("{} LIMIT {} OFFSET {}", query, limit, offset)
+    }
+}
+
+/// Bind positional parameters to a PostgreSQL query by JSON type: integers as
+/// BIGINT, other numbers as DOUBLE PRECISION, strings as TEXT, null as NULL,
+/// arrays and objects as JSONB
+fn bind_postgres_params<'q>(
+    mut query: sqlx::query::Query<'q, sqlx::Postgres, sqlx::postgres::PgArguments>,
+    params: &'q [serde_json::Value],
+) -> sqlx::query::Query<'q, sqlx::Postgres, sqlx::postgres::PgArguments> {
+    for param in params {
+        query = match param {
+            serde_json::Value::Null => query.bind(None::<String>),
+            serde_json::Value::Bool(b) => query.bind(*b),
+            serde_json::Value::Number(n) => match n.as_i64() {
+                Some(i) => query.bind(i),
+                None => query.bind(n.as_f64()),
+            },
+            serde_json::Value::String(s) => query.bind(s.as_str()),
+            other => query.bind(sqlx::types::Json(other)),
+        };
+    }
+    query
+}
+
+/// Bind positional parameters to a MySQL query by JSON type; arrays and
+/// objects are sent as their JSON text
+fn bind_mysql_params<'q>(
+    mut query: sqlx::query::Query<'q, sqlx::MySql, sqlx::mysql::MySqlArguments>,
+    params: &'q [serde_json::Value],
+) -> sqlx::query::Query<'q, sqlx::MySql, sqlx::mysql::MySqlArguments> {
+    for param in params {
+        query = match param {
+            serde_json::Value::Null => query.bind(None::<String>),
+            serde_json::Value::Bool(b) => query.bind(*b),
+            serde_json::Value::Number(n) => match n.as_i64() {
+                Some(i) => query.bind(i),
+                None => query.bind(n.as_f64()),
+            },
+            serde_json::Value::String(s) => query.bind(s.as_str()),
+            other => query.bind(other.to_string()),
+        };
+    }
+    query
+}
+
+/// Retrieve the execution plan for a SELECT query as JSON without running it
+pub async fn explain_query(
+    manager: &ConnectionManager,
+    connection_id: &str,
+    query: &str,
+) -> AppResult<serde_json::Value> {
+    let conn = manager.get_connection(connection_id)?;
+
+    // Only read queries may be explained; EXPLAIN on DML is one ANALYZE away from running it
+    crate::ai::sanitizer::validate_sql(query, &conn.database_type)?;
+
+    let query = query.trim().trim_end_matches(';');
+
+    match conn.database_type {
+        DatabaseType::PostgreSQL => {
+            let pool = manager.get_pool_postgres(connection_id).await?;
+            let plan: serde_json::Value = sqlx::query_scalar(&format!("EXPLAIN (FORMAT JSON) {}", query))
+                .fetch_one(&pool)
+                .await?;
+            Ok(plan)
+        }
+        DatabaseType::MySQL => {
+            let pool = manager.get_pool_mysql(connection_id).await?;
+            let plan: String = sqlx::query_scalar(&format!("EXPLAIN FORMAT=JSON {}", query))
+                .fetch_one(&pool)
+                .await?;
+            Ok(serde_json::from_str(&plan)?)
+        }
+        DatabaseType::MariaDB => {
+            // FORMAT=JSON is not available on every MariaDB version, so use the
+            // tabular plan and return each row as an object
+            let pool = manager.get_pool_mysql(connection_id).await?;
+            let rows = sqlx::query(&format!("EXPLAIN {}", query)).fetch_all(&pool).await?;
+
+            let mut plan = Vec::with_capacity(rows.len());
+            for row in &rows {
+                plan.push(serde_json::Value::Object(mysql_row_to_json(row)?));
+            }
+            Ok(serde_json::Value::Array(plan))
+        }
+    }
+}
+
+/// Whether a statement produces a result set (as opposed to a row count)
+fn statement_returns_rows(statement: &str) -> bool {
+    let keyword: String = statement
+        .trim_start()
+        .chars()
+        .take_while(|c| c.is_ascii_alphabetic())
+        .collect::<String>()
+        .to_uppercase();
+    matches!(
+        keyword.as_str(),
+        "SELECT" | "WITH" | "SHOW" | "EXPLAIN" | "VALUES" | "TABLE" | "DESCRIBE" | "DESC"
+    ) || statement.to_uppercase().contains("RETURNING")
+}
+
+/// Run a script of `;`-separated statements in order inside a single transaction.
+/// Unlike `execute_query`, no LIMIT is added. Statements that return rows produce a
+/// full result; other statements produce an empty result whose `row_count` is the
+/// number of affected rows. Any failure rolls back the whole batch (MySQL DDL
+/// commits implicitly and cannot be rolled back).
+pub async fn execute_query_batch(
+    manager: &ConnectionManager,
+    connection_id: &str,
+    script: &str,
+    timeout_ms: u64,
+) -> AppResult<Vec<QueryResult>> {
+    let conn = manager.get_connection(connection_id)?;
+    let statements = syntax_highlight::split_statements(script);
+    if statements.is_empty() {
+        return Err(AppError::ValidationError("The batch contains no statements".to_string()));
+    }
+    for statement in &statements {
+        conn.ensure_query_allowed(statement)?;
+    }
+
+    let execution = async {
+        match conn.database_type {
+            DatabaseType::PostgreSQL => {
+                let pool = manager.get_pool_postgres(connection_id).await?;
+                execute_postgres_batch(&pool, &statements).await
+            }
+            DatabaseType::MariaDB | DatabaseType::MySQL => {
+                let pool = manager.get_pool_mysql(connection_id).await?;
+                execute_mysql_batch(&pool, &statements).await
+            }
+        }
+    };
+
+    // Dropping the future on timeout drops the open transaction, which rolls it back
+    if timeout_ms == 0 {
+        execution.await
+    } else {
+        tokio::time::timeout(Duration::from_millis(timeout_ms), execution)
+            .await
+            .map_err(|_| {
+                AppError::QueryTimeout(format!("Batch exceeded the {} ms timeout", timeout_ms))
+            })?
+    }
+}
+
+fn batch_statement_error(index: usize, err: impl std::fmt::Display) -> AppError {
+    AppError::QueryError(format!(
+        "Statement {} failed, batch rolled back: {}",
+        index + 1,
+        err
+    ))
+}
+
+async fn execute_postgres_batch(
+    pool: &sqlx::PgPool,
+    statements: &[String],
+) -> AppResult<Vec<QueryResult>> {
+    let mut tx = pool.begin().await?;
+    let mut results = Vec::with_capacity(statements.len());
+
+    for (idx, statement) in statements.iter().enumerate() {
+        let start = Instant::now();
+        let result = if statement_returns_rows(statement) {
+            let rows = sqlx::query(statement)
+                .fetch_all(&mut *tx)
+                .await
+                .map_err(|e| batch_statement_error(idx, e))?;
+            let (columns, column_metadata) = rows.first().map(columns_from_row).unwrap_or_default();
+            let rows = rows
+                .iter()
+                .map(postgres_row_to_json)
+                .collect::<AppResult<Vec<_>>>()?;
+            QueryResult {
+                columns,
+                column_metadata,
+                row_count: rows.len(),
+                rows,
+                execution_time_ms: start.elapsed().as_millis(),
+                summary: None,
+            }
+        } else {
+            let done = sqlx::query(statement)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| batch_statement_error(idx, e))?;
+            QueryResult {
+                columns: Vec::new(),
+                column_metadata: Vec::new(),
+                rows: Vec::new(),
+                row_count: done.rows_affected() as usize,
+                execution_time_ms: start.elapsed().as_millis(),
+                summary: None,
+            }
+        };
+        results.push(result);
+    }
+
+    tx.commit().await?;
+    Ok(results)
+}
+
+async fn execute_mysql_batch(
+    pool: &sqlx::MySqlPool,
+    statements: &[String],
+) -> AppResult<Vec<QueryResult>> {
+    let mut tx = pool.begin().await?;
+    let mut results = Vec::with_capacity(statements.len());
+
+    for (idx, statement) in statements.iter().enumerate() {
+        let start = Instant::now();
+        let result = if statement_returns_rows(statement) {
+            let rows = sqlx::query(statement)
+                .fetch_all(&mut *tx)
+                .await
+                .map_err(|e| batch_statement_error(idx, e))?;
+            let (columns, column_metadata) = rows.first().map(columns_from_row).unwrap_or_default();
+            let rows = rows
+                .iter()
+                .map(mysql_row_to_json)
+                .collect::<AppResult<Vec<_>>>()?;
+            QueryResult {
+                columns,
+                column_metadata,
+                row_count: rows.len(),
+                rows,
+                execution_time_ms: start.elapsed().as_millis(),
+                summary: None,
+            }
+        } else {
+            let done = sqlx::query(statement)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| batch_statement_error(idx, e))?;
+            QueryResult {
+                columns: Vec::new(),
+                column_metadata: Vec::new(),
+                rows: Vec::new(),
+                row_count: done.rows_affected() as usize,
+                execution_time_ms: start.elapsed().as_millis(),
+                summary: None,
+            }
+        };
+        results.push(result);
+    }
+
+    tx.commit().await?;
+    Ok(results)
+}
+
+/// Aggregate statistics for a single column
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnProfile {
+    pub table_name: String,
+    pub column_name: String,
+    pub data_type: String,
+    pub row_count: i64,
+    pub null_count: i64,
+    pub distinct_count: i64,
+    /// MIN/MAX rendered as text; None for types without an ordering or an empty column
+    pub min: Option<String>,
+    pub max: Option<String>,
+    /// Only computed for numeric columns
+    pub avg: Option<f64>,
+    pub stddev: Option<f64>,
+}
+
+/// Which aggregates a column type supports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ProfileKind {
+    /// Counts, MIN/MAX, AVG and STDDEV
+    Numeric,
+    /// Counts and MIN/MAX (text, temporal, enum, uuid, ...)
+    Ordered,
+    /// Counts only (boolean, json, binary, arrays, spatial, ...)
+    Unordered,
+}
+
+fn profile_kind(data_type: &str, is_enum: bool) -> ProfileKind {
+    let data_type = data_type.to_lowercase();
+    // "double precision", "timestamp with time zone", "numeric(10,2)" -> leading word
+    let base = data_type.split(['(', ' ']).next().unwrap_or("");
+
+    match base {
+        "smallint" | "integer" | "int" | "bigint" | "tinyint" | "mediumint" | "decimal"
+        | "numeric" | "real" | "float" | "double" | "smallserial" | "serial" | "bigserial" => {
+            ProfileKind::Numeric
+        }
+        "character" | "char" | "varchar" | "text" | "tinytext" | "mediumtext" | "longtext"
+        | "citext" | "name" | "date" | "time" | "timestamp" | "datetime" | "year" | "interval"
+        | "uuid" | "inet" | "cidr" | "macaddr" | "enum" | "money" => ProfileKind::Ordered,
+        _ if is_enum => ProfileKind::Ordered,
+        _ => ProfileKind::Unordered,
+    }
+}
+
+/// Build the single aggregate query profiling `quoted_column`.
+/// MIN/MAX are cast to text and AVG/STDDEV to double so every dialect decodes uniformly.
+fn build_profile_query(
+    quoted_table: &str,
+    quoted_column: &str,
+    kind: ProfileKind,
+    db_type: &DatabaseType,
+) -> String {
+    let is_postgres = matches!(db_type, DatabaseType::PostgreSQL);
+
+    // Postgres has no equality operator for json, xml, point, ...; compare their text form
+    let distinct_expr = if is_postgres && kind == ProfileKind::Unordered {
+        format!("{}::text", quoted_column)
+    } else {
+        quoted_column.to_string()
+    };
+
+    let mut select = vec![
+        "COUNT(*) AS row_count".to_string(),
+        format!("COUNT(*) - COUNT({}) AS null_count", quoted_column),
+        format!("COUNT(DISTINCT {}) AS distinct_count", distinct_expr),
+    ];
+
+    if kind != ProfileKind::Unordered {
+        if is_postgres {
+            select.push(format!("MIN({})::text AS min_value", quoted_column));
+            select.push(format!("MAX({})::text AS max_value", quoted_column));
+        } else {
+            select.push(format!("CAST(MIN({}) AS CHAR) AS min_value", quoted_column));
+            select.push(format!("CAST(MAX({}) AS CHAR) AS max_value", quoted_column));
+        }
+    }
+
+    if kind == ProfileKind::Numeric {
+        if is_postgres {
+            select.push(format!("AVG({})::float8 AS avg_value", quoted_column));
+            select.push(format!("STDDEV_SAMP({})::float8 AS stddev_value", quoted_column));
+        } else {
+            // Adding a float literal turns MySQL's DECIMAL AVG into DOUBLE
+            select.push(format!("AVG({}) + 0E0 AS avg_value", quoted_column));
+            select.push(format!("STDDEV_SAMP({}) AS stddev_value", quoted_column));
+        }
+    }
+
+    format!("SELECT {} FROM {}", select.join(", "), quoted_table)
+}
+
+/// Profile a column: row, null and distinct counts, MIN/MAX for ordered types,
+/// and AVG/STDDEV for numeric types, all computed in one query.
+/// The column's type is looked up in `schema`, which also validates both names.
+pub async fn profile_column(
+    manager: &ConnectionManager,
+    connection_id: &str,
+    schema: &crate::db::schema::Schema,
+    table_name: &str,
+    column_name: &str,
+    timeout_ms: u64,
+) -> AppResult<ColumnProfile> {
+    let conn = manager.get_connection(connection_id)?;
+
+    let table = schema
+        .tables
+        .iter()
+        .find(|t| t.qualified_name() == table_name)
+        .or_else(|| schema.tables.iter().find(|t| t.name == table_name))
+        .ok_or_else(|| AppError::ValidationError(format!("Table '{}' not found", table_name)))?;
+    let column = table
+        .columns
+        .iter()
+        .find(|c| c.name == column_name)
+        .ok_or_else(|| {
+            AppError::ValidationError(format!(
+                "Column '{}' not found in table '{}'",
+                column_name, table_name
+            ))
+        })?;
+
+    let kind = profile_kind(&column.data_type, column.enum_values.is_some());
+    let query = build_profile_query(
+        &quote_table(&table.qualified_name(), &conn.database_type),
+        &quote_identifier(column_name, &conn.database_type),
+        kind,
+        &conn.database_type,
+    );
+
+    let execution = async {
+        match conn.database_type {
+            DatabaseType::PostgreSQL => {
+                let pool = manager.get_pool_postgres(connection_id).await?;
+                let row = sqlx::query(&query).fetch_one(&pool).await?;
+                read_profile_row(&row, kind)
+            }
+            DatabaseType::MariaDB | DatabaseType::MySQL => {
+                let pool = manager.get_pool_mysql(connection_id).await?;
+                let row = sqlx::query(&query).fetch_one(&pool).await?;
+                read_profile_row(&row, kind)
+            }
+        }
+    };
+
+    let (row_count, null_count, distinct_count, min, max, avg, stddev) = if timeout_ms == 0 {
+        execution.await?
+    } else {
+        tokio::time::timeout(Duration::from_millis(timeout_ms), execution)
+            .await
+            .map_err(|_| {
+                AppError::QueryTimeout(format!("Query exceeded the {} ms timeout", timeout_ms))
+            })??
+    };
+
+    Ok(ColumnProfile {
+        table_name: table_name.to_string(),
+        column_name: column_name.to_string(),
+        data_type: column.data_type.clone(),
+        row_count,
+        null_count,
+        distinct_count,
+        min,
+        max,
+        avg,
+        stddev,
+    })
+}
+
+type ProfileRow = (i64, i64, i64, Option<String>, Option<String>, Option<f64>, Option<f64>);
+
+fn read_profile_row<'r, R>(row: &'r R, kind: ProfileKind) -> AppResult<ProfileRow>
+where
+    R: Row,
+    for<'a> &'a str: sqlx::ColumnIndex<R>,
+    i64: sqlx::Decode<'r, R::Database> + sqlx::Type<R::Database>,
+    String: sqlx::Decode<'r, R::Database> + sqlx::Type<R::Database>,
+    f64: sqlx::Decode<'r, R::Database> + sqlx::Type<R::Database>,
+{
+    let row_count: i64 = row.try_get("row_count")?;
+    let null_count: i64 = row.try_get("null_count")?;
+    let distinct_count: i64 = row.try_get("distinct_count")?;
+
+    let (min, max) = if kind == ProfileKind::Unordered {
+        (None, None)
+    } else {
+        (row.try_get("min_value")?, row.try_get("max_value")?)
+    };
+    let (avg, stddev) = if kind == ProfileKind::Numeric {
+        (row.try_get("avg_value")?, row.try_get("stddev_value")?)
+    } else {
+        (None, None)
+    };
+
+    Ok((row_count, null_count, distinct_count, min, max, avg, stddev))
+}
+
+/// A batch of rows emitted on the "query-row-batch" event while streaming
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryRowBatch {
+    pub stream_id: String,
+    pub batch_index: usize,
+    /// Column names and metadata are only sent with the first batch
+    pub columns: Vec<String>,
+    pub column_metadata: Vec<ColumnMetadata>,
+    pub rows: Vec<serde_json::Map<String, serde_json::Value>>,
+}
+
+/// Final "query-stream-complete" event once a stream ends or is cancelled
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryStreamComplete {
+    pub stream_id: String,
+    pub row_count: usize,
+    pub execution_time_ms: u128,
+    pub cancelled: bool,
+}
+
+// Global streaming query cancellation tokens, keyed by stream id
+lazy_static::lazy_static! {
+    static ref QUERY_STREAM_TOKENS: Arc<RwLock<HashMap<String, CancellationToken>>> = Arc::new(RwLock::new(HashMap::new()));
+}
+
+/// Execute a query and emit its rows in batches instead of buffering the full result.
+/// Rows are converted with the same logic as `execute_query`, and no LIMIT is added.
+pub async fn execute_query_streaming(
+    app: AppHandle,
+    manager: &ConnectionManager,
+    connection_id: &str,
+    stream_id: &str,
+    query: &str,
+    batch_size: usize,
+) -> AppResult<QueryStreamComplete> {
+    if batch_size == 0 {
+        return Err(AppError::ValidationError("Batch size must be greater than zero".to_string()));
+    }
+
+    let conn = manager.get_connection(connection_id)?;
+    conn.ensure_query_allowed(query)?;
+    let query = query.trim().trim_end_matches(';');
+    let start = Instant::now();
+
+    // Create and register cancellation token
+    let cancel_token = CancellationToken::new();
+    {
+        let mut tokens = QUERY_STREAM_TOKENS.write().await;
+        tokens.insert(stream_id.to_string(), cancel_token.clone());
+    }
+
+    // Errors, including failing to get a pool, fall through to the token cleanup
+    let result = async {
+        match conn.database_type {
+            DatabaseType::PostgreSQL => {
+                let pool = manager.get_pool_postgres(connection_id).await?;
+                let rows = sqlx::query(query).fetch(&pool);
+                emit_row_batches(&app, stream_id, rows, batch_size, &cancel_token, postgres_row_to_json).await
+            }
+            DatabaseType::MariaDB | DatabaseType::MySQL => {
+                let pool = manager.get_pool_mysql(connection_id).await?;
+                let rows = sqlx::query(query).fetch(&pool);
+                emit_row_batches(&app, stream_id, rows, batch_size, &cancel_token, mysql_row_to_json).await
+            }
+        }
+    }
+    .await;
+
+    // Clean up token
+    {
+        let mut tokens = QUERY_STREAM_TOKENS.write().await;
+        tokens.remove(stream_id);
+    }
+
+    let (row_count, cancelled) = result?;
+
+    let complete = QueryStreamComplete {
+        stream_id: stream_id.to_string(),
+        row_count,
+        execution_time_ms: start.elapsed().as_millis(),
+        cancelled,
+    };
+    app.emit("query-stream-complete", &complete).ok();
+
+    Ok(complete)
+}
+
+/// Cancel an in-flight streaming query
+pub async fn cancel_query_stream(stream_id: &str) -> AppResult<()> {
+    let tokens = QUERY_STREAM_TOKENS.read().await;
+    if let Some(token) = tokens.get(stream_id) {
+        token.cancel();
+        Ok(())
+    } else {
+        Err(AppError::Other("No active query stream found for this id".to_string()))
+    }
+}
+
+/// Drain a row stream, emitting a "query-row-batch" event every `batch_size` rows.
+/// Returns the number of rows emitted and whether the stream was cancelled.
+async fn emit_row_batches<R, S>(
+    app: &AppHandle,
+    stream_id: &str,
+    mut rows: S,
+    batch_size: usize,
+    cancel_token: &CancellationToken,
+    to_json: fn(&R) -> AppResult<serde_json::Map<String, serde_json::Value>>,
+) -> AppResult<(usize, bool)>
+where
+    R: Row,
+    S: Stream<Item = Result<R, sqlx::Error>> + Unpin,
+{
+    let mut batch = Vec::with_capacity(batch_size);
+    let mut columns = None;
+    let mut batch_index = 0;
+    let mut row_count = 0;
+
+    loop {
+        let next = tokio::select! {
+            _ = cancel_token.cancelled() => return Ok((row_count, true)),
+            next = rows.next() => next,
+        };
+
+        let Some(row) = next else {
+            break;
+        };
+        let row = row?;
+
+        if batch_index == 0 && columns.is_none() {
+            columns = Some(columns_from_row(&row));
+        }
+        batch.push(to_json(&row)?);
+
+        if batch.len() >= batch_size {
+            row_count += batch.len();
+            emit_batch(app, stream_id, batch_index, columns.take(), std::mem::take(&mut batch));
+            batch_index += 1;
+        }
+    }
+
+    if !batch.is_empty() {
+        row_count += batch.len();
+        emit_batch(app, stream_id, batch_index, columns.take(), batch);
+    }
+
+    Ok((row_count, false))
+}
+
+fn emit_batch(
+    app: &AppHandle,
+    stream_id: &str,
+    batch_index: usize,
+    columns: Option<(Vec<String>, Vec<ColumnMetadata>)>,
+    rows: Vec<serde_json::Map<String, serde_json::Value>>,
+) {
+    // Only the first batch carries the column definitions
+    let (columns, column_metadata) = columns.unwrap_or_default();
+
+    app.emit(
+        "query-row-batch",
+        QueryRowBatch {
+            stream_id: stream_id.to_string(),
+            batch_index,
+            columns,
+            column_metadata,
+            rows,
+        },
+    )
+    .ok();
+}
+
+/// Exact name of a filter column, checked against the table's introspected columns so
+/// only real column names ever reach the SQL
+pub(crate) fn resolve_filter_column(
+    schema: &crate::db::schema::Schema,
+    table_name: &str,
+    column: &str,
+) -> AppResult<String> {
+    let table = schema
+        .find_table(table_name)
+        .ok_or_else(|| AppError::ValidationError(format!("Unknown table '{}'", table_name)))?;
+    table
+        .columns
+        .iter()
+        .find(|c| c.name == column)
+        .or_else(|| table.columns.iter().find(|c| c.name.eq_ignore_ascii_case(column)))
+        .map(|c| c.name.clone())
+        .ok_or_else(|| {
+            AppError::ValidationError(format!(
+                "Column '{}' does not exist in table '{}'",
+                column, table_name
+            ))
+        })
+}
+
+/// `SELECT *` for one page of a table, optionally filtered on `column = value`. The value is
+/// never written into the SQL: it is returned for binding, or a null becomes `IS NULL`.
+fn table_page_query(
+    db_type: &DatabaseType,
+    quoted_table: &str,
+    filter: Option<(&str, serde_json::Value)>,
+    limit: i32,
+    offset: i32,
+) -> (String, Option<serde_json::Value>) {
+    let Some((column, value)) = filter else {
+        let q = format!("SELECT * FROM {} LIMIT {} OFFSET {}", quoted_table, limit, offset);
+        return (q, None);
+    };
+
+    let quoted_column = quote_identifier(column, db_type);
+    if value.is_null() {
+        // NULL requires IS NULL, not = $1
+        let q = format!(
+            "SELECT * FROM {} WHERE {} IS NULL LIMIT {} OFFSET {}",
+            quoted_table, quoted_column, limit, offset
+        );
+        return (q, None);
+    }
+
+    let placeholder = match db_type {
+        DatabaseType::PostgreSQL => "$1",
+        DatabaseType::MariaDB | DatabaseType::MySQL => "?",
+    };
+    let q = format!(
+        "SELECT * FROM {} WHERE {} = {} LIMIT {} OFFSET {}",
+        quoted_table, quoted_column, placeholder, limit, offset
+    );
+    (q, Some(value))
+}
+
+pub async fn execute_table_query(
+    manager: &ConnectionManager,
+    connection_id: &str,
+    schema: &crate::db::schema::Schema,
+    table_name: &str,
+    filter_column: Option<String>,
+    filter_value: Option<serde_json::Value>,
+    limit: i32,
+    offset: i32,
+) -> AppResult<QueryResult> {
+    let conn = manager.get_connection(connection_id)?;
+    let start = Instant::now();
+
+    // Quote table name to prevent SQL injection
+    let quoted_table = quote_table(table_name, &conn.database_type);
+
+    let filter_column = match (filter_column, &filter_value) {
+        (Some(column), Some(_)) => Some(resolve_filter_column(schema, table_name, &column)?),
+        _ => None,
+    };
+    let filter = filter_column.as_deref().zip(filter_value);
+    let (query, bind_value) = table_page_query(&conn.database_type, &quoted_table, filter, limit, offset);
+
+    let result = match &conn.database_type {
+        DatabaseType::PostgreSQL => {
+            execute_postgres_table_query(manager, connection_id, table_name, &query, bind_value).await?
+        }
+        DatabaseType::MariaDB | DatabaseType::MySQL => {
+            execute_mysql_table_query(manager, connection_id, table_name, &query, bind_value).await?
         }
     };
 
@@ -124,118 +1756,290 @@ pub async fn execute_query(
         rows: result.2,
         row_count: result.3,
         execution_time_ms,
+        summary: None,
     })
 }
 
-pub async fn execute_table_query(
+/// Rows fetched when a table is previewed from the sidebar
+pub const PREVIEW_ROW_LIMIT: i32 = 50;
+
+/// First rows of a table for a sidebar preview, using the same filter support as
+/// `execute_table_query`. Previews are not user queries and are never added to query
+/// history. An empty preview still reports the table's columns, taken from the schema.
+pub async fn preview_table(
     manager: &ConnectionManager,
     connection_id: &str,
+    schema: &crate::db::schema::Schema,
     table_name: &str,
     filter_column: Option<String>,
     filter_value: Option<serde_json::Value>,
     limit: i32,
-    offset: i32,
 ) -> AppResult<QueryResult> {
+    let mut result = execute_table_query(
+        manager,
+        connection_id,
+        schema,
+        table_name,
+        filter_column,
+        filter_value,
+        limit.max(1),
+        0,
+    )
+    .await?;
+
+    if result.columns.is_empty() {
+        let table = schema
+            .tables
+            .iter()
+            .find(|t| t.qualified_name() == table_name)
+            .or_else(|| schema.tables.iter().find(|t| t.name == table_name));
+        if let Some(table) = table {
+            (result.columns, result.column_metadata) = column_metadata_from_schema(table);
+        }
+    }
+
+    Ok(result)
+}
+
+/// Column names and metadata for a table as introspected, for results without rows
+fn column_metadata_from_schema(table: &crate::db::schema::Table) -> (Vec<String>, Vec<ColumnMetadata>) {
+    table
+        .columns
+        .iter()
+        .map(|col| {
+            let foreign_key = match (&col.foreign_key_table, &col.foreign_key_column) {
+                (Some(table), Some(column)) if col.is_foreign_key => Some(ForeignKeyMetadata {
+                    referenced_table: table.clone(),
+                    referenced_column: column.clone(),
+                }),
+                _ => None,
+            };
+            (
+                col.name.clone(),
+                ColumnMetadata {
+                    name: col.name.clone(),
+                    data_type: col.data_type.clone(),
+                    enum_values: col.enum_values.clone(),
+                    foreign_key,
+                },
+            )
+        })
+        .unzip()
+}
+
+/// Result of a keyset-paginated query, with the cursor value for the next page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeysetQueryResult {
+    #[serde(flatten)]
+    pub result: QueryResult,
+    /// Ordering column value of the last row; pass back as `last_value` to get the next page
+    pub last_value: Option<serde_json::Value>,
+}
+
+/// Page through a table by an ordering column instead of OFFSET, so deep pages stay fast.
+/// The ordering column should be unique (e.g. a primary key); rows sharing a value
+/// across a page boundary would otherwise be skipped.
+pub async fn execute_query_keyset(
+    manager: &ConnectionManager,
+    connection_id: &str,
+    table_name: &str,
+    order_column: &str,
+    descending: bool,
+    last_value: Option<serde_json::Value>,
+    limit: i32,
+) -> AppResult<KeysetQueryResult> {
     let conn = manager.get_connection(connection_id)?;
     let start = Instant::now();
 
-    // Quote table name to prevent SQL injection
-    let quoted_table = quote_identifier(table_name, &conn.database_type);
+    let quoted_table = quote_table(table_name, &conn.database_type);
+    let quoted_column = quote_identifier(order_column, &conn.database_type);
+    let (comparison, direction) = if descending { ("<", "DESC") } else { (">", "ASC") };
+    let last_value = last_value.filter(|v| !v.is_null());
+
+    let result = match conn.database_type {
+        DatabaseType::PostgreSQL => {
+            let pool = manager.get_pool_postgres(connection_id).await?;
+            let (table_schema, bare_table) =
+                resolve_postgres_table(&pool, table_name, &conn.search_path()).await;
+
+            // Validate the column against the catalog and get its type so the
+            // bound cursor value can be cast to it
+            let column_type: Option<String> = sqlx::query_scalar(
+                "SELECT pg_catalog.format_type(a.atttypid, a.atttypmod)
+                 FROM pg_attribute a
+                 JOIN pg_class c ON c.oid = a.attrelid
+                 JOIN pg_namespace n ON n.oid = c.relnamespace
+                 WHERE n.nspname = $3 AND c.relname = $1 AND a.attname = $2
+                   AND a.attnum > 0 AND NOT a.attisdropped"
+            )
+            .bind(bare_table)
+            .bind(order_column)
+            .bind(&table_schema)
+            .fetch_optional(&pool)
+            .await?;
+
+            let column_type = column_type.ok_or_else(|| {
+                AppError::ValidationError(format!(
+                    "Column '{}' does not exist on table '{}'",
+                    order_column, table_name
+                ))
+            })?;
+
+            let rows = if let Some(value) = last_value {
+                let query = format!(
+                    "SELECT * FROM {} WHERE {} {} CAST($1 AS {}) ORDER BY {} {} LIMIT {}",
+                    quoted_table, quoted_column, comparison, column_type, quoted_column, direction, limit
+                );
+                let text = match value {
+                    serde_json::Value::String(s) => s,
+                    other => other.to_string(),
+                };
+                sqlx::query(&query).bind(text).fetch_all(&pool).await?
+            } else {
+                let query = format!(
+                    "SELECT * FROM {} ORDER BY {} {} LIMIT {}",
+                    quoted_table, quoted_column, direction, limit
+                );
+                sqlx::query(&query).fetch_all(&pool).await?
+            };
+
+            let (fk_result, enum_result) = tokio::join!(
+                get_postgres_fk_metadata(&pool, bare_table, &table_schema),
+                get_postgres_enum_values(&pool, bare_table, &table_schema)
+            );
+
+            let metadata = TableMetadata {
+                foreign_keys: fk_result.unwrap_or_default(),
+                enum_values: enum_result.unwrap_or_default(),
+            };
+
+            process_postgres_rows(rows, metadata).await?
+        }
+        DatabaseType::MariaDB | DatabaseType::MySQL => {
+            let pool = manager.get_pool_mysql(connection_id).await?;
+
+            let column_exists: i64 = sqlx::query_scalar(
+                "SELECT COUNT(*) FROM INFORMATION_SCHEMA.COLUMNS
+                 WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = ? AND COLUMN_NAME = ?"
+            )
+            .bind(table_name)
+            .bind(order_column)
+            .fetch_one(&pool)
+            .await?;
+
+            if column_exists == 0 {
+                return Err(AppError::ValidationError(format!(
+                    "Column '{}' does not exist on table '{}'",
+                    order_column, table_name
+                )));
+            }
+
+            let rows = if let Some(value) = last_value {
+                let query = format!(
+                    "SELECT * FROM {} WHERE {} {} ? ORDER BY {} {} LIMIT {}",
+                    quoted_table, quoted_column, comparison, quoted_column, direction, limit
+                );
+                match value {
+                    serde_json::Value::Bool(b) => sqlx::query(&query).bind(b).fetch_all(&pool).await?,
+                    serde_json::Value::Number(n) => {
+                        if let Some(i) = n.as_i64() {
+                            sqlx::query(&query).bind(i).fetch_all(&pool).await?
+                        } else if let Some(f) = n.as_f64() {
+                            sqlx::query(&query).bind(f).fetch_all(&pool).await?
+                        } else {
+                            sqlx::query(&query).bind(n.to_string()).fetch_all(&pool).await?
+                        }
+                    }
+                    serde_json::Value::String(s) => sqlx::query(&query).bind(s).fetch_all(&pool).await?,
+                    other => sqlx::query(&query).bind(other.to_string()).fetch_all(&pool).await?,
+                }
+            } else {
+                let query = format!(
+                    "SELECT * FROM {} ORDER BY {} {} LIMIT {}",
+                    quoted_table, quoted_column, direction, limit
+                );
+                sqlx::query(&query).fetch_all(&pool).await?
+            };
+
+            let database_name: (String,) = sqlx::query_as("SELECT DATABASE()")
+                .fetch_one(&pool)
+                .await?;
+            let db_name = &database_name.0;
+
+            let (fk_result, enum_result) = tokio::join!(
+                get_mysql_fk_metadata(&pool, table_name, db_name),
+                get_mysql_enum_values(&pool, table_name, db_name)
+            );
 
-    // Execute with parameterized filter if provided
-    let result = match &conn.database_type {
-        DatabaseType::PostgreSQL => {
-            execute_postgres_table_query(
-                manager, connection_id, &quoted_table, table_name,
-                filter_column, filter_value, limit, offset
-            ).await?
-        }
-        DatabaseType::MariaDB | DatabaseType::MySQL => {
-            execute_mysql_table_query(
-                manager, connection_id, &quoted_table, table_name,
-                filter_column, filter_value, limit, offset
-            ).await?
+            let metadata = TableMetadata {
+                foreign_keys: fk_result.unwrap_or_default(),
+                enum_values: enum_result.unwrap_or_default(),
+            };
+
+            process_mysql_rows(rows, metadata).await?
         }
     };
 
-    let execution_time_ms = start.elapsed().as_millis();
-
-    Ok(QueryResult {
-        columns: result.0,
-        column_metadata: result.1,
-        rows: result.2,
-        row_count: result.3,
-        execution_time_ms,
+    let (columns, column_metadata, rows, row_count) = result;
+    let next_last_value = rows.last().and_then(|row| row.get(order_column).cloned());
+
+    Ok(KeysetQueryResult {
+        result: QueryResult {
+            columns,
+            column_metadata,
+            rows,
+            row_count,
+            execution_time_ms: start.elapsed().as_millis(),
+            summary: None,
+        },
+        last_value: next_last_value,
     })
 }
 
-/// Execute a PostgreSQL table query with parameterized filter
+/// Execute a PostgreSQL table query built by `table_page_query`
 async fn execute_postgres_table_query(
     manager: &ConnectionManager,
     connection_id: &str,
-    quoted_table: &str,
     raw_table_name: &str,
-    filter_column: Option<String>,
-    filter_value: Option<serde_json::Value>,
-    limit: i32,
-    offset: i32,
+    query: &str,
+    bind_value: Option<serde_json::Value>,
 ) -> AppResult<(Vec<String>, Vec<ColumnMetadata>, Vec<serde_json::Map<String, serde_json::Value>>, usize)> {
     let pool = manager.get_pool_postgres(connection_id).await?;
 
-    // Build query with parameterized filter
-    let (query, bind_value) = if let (Some(column), Some(value)) = (filter_column, filter_value) {
-        let quoted_column = quote_identifier_postgres(&column);
-        if value.is_null() {
-            // NULL requires IS NULL, not = $1
-            let q = format!(
-                "SELECT * FROM {} WHERE {} IS NULL LIMIT {} OFFSET {}",
-                quoted_table, quoted_column, limit, offset
-            );
-            (q, None)
-        } else {
-            let q = format!(
-                "SELECT * FROM {} WHERE {} = $1 LIMIT {} OFFSET {}",
-                quoted_table, quoted_column, limit, offset
-            );
-            (q, Some(value))
-        }
-    } else {
-        let q = format!("SELECT * FROM {} LIMIT {} OFFSET {}", quoted_table, limit, offset);
-        (q, None)
-    };
-
     // Execute with or without bind parameter
     let rows = if let Some(val) = bind_value {
         // Convert JSON value to appropriate bind type
         match val {
             serde_json::Value::Bool(b) => {
-                sqlx::query(&query).bind(b).fetch_all(&pool).await?
+                sqlx::query(query).bind(b).fetch_all(&pool).await?
             }
             serde_json::Value::Number(n) => {
                 if let Some(i) = n.as_i64() {
-                    sqlx::query(&query).bind(i).fetch_all(&pool).await?
+                    sqlx::query(query).bind(i).fetch_all(&pool).await?
                 } else if let Some(f) = n.as_f64() {
-                    sqlx::query(&query).bind(f).fetch_all(&pool).await?
+                    sqlx::query(query).bind(f).fetch_all(&pool).await?
                 } else {
-                    sqlx::query(&query).bind(n.to_string()).fetch_all(&pool).await?
+                    sqlx::query(query).bind(n.to_string()).fetch_all(&pool).await?
                 }
             }
             serde_json::Value::String(s) => {
-                sqlx::query(&query).bind(s).fetch_all(&pool).await?
+                sqlx::query(query).bind(s).fetch_all(&pool).await?
             }
             _ => {
                 // For arrays/objects, bind as JSON string
-                sqlx::query(&query).bind(val.to_string()).fetch_all(&pool).await?
+                sqlx::query(query).bind(val.to_string()).fetch_all(&pool).await?
             }
         }
     } else {
-        sqlx::query(&query).fetch_all(&pool).await?
+        sqlx::query(query).fetch_all(&pool).await?
     };
 
     // Fetch FK and enum metadata in parallel
+    let search_path = manager.get_connection(connection_id)?.search_path();
+    let (table_schema, bare_table) = resolve_postgres_table(&pool, raw_table_name, &search_path).await;
     let (fk_result, enum_result) = tokio::join!(
-        get_postgres_fk_metadata(&pool, raw_table_name, "public"),
-        get_postgres_enum_values(&pool, raw_table_name, "public")
+        get_postgres_fk_metadata(&pool, bare_table, &table_schema),
+        get_postgres_enum_values(&pool, bare_table, &table_schema)
     );
 
     let metadata = TableMetadata {
@@ -246,64 +2050,40 @@ async fn execute_postgres_table_query(
     process_postgres_rows(rows, metadata).await
 }
 
-/// Execute a MySQL table query with parameterized filter
+/// Execute a MySQL table query built by `table_page_query`
 async fn execute_mysql_table_query(
     manager: &ConnectionManager,
     connection_id: &str,
-    quoted_table: &str,
     raw_table_name: &str,
-    filter_column: Option<String>,
-    filter_value: Option<serde_json::Value>,
-    limit: i32,
-    offset: i32,
+    query: &str,
+    bind_value: Option<serde_json::Value>,
 ) -> AppResult<(Vec<String>, Vec<ColumnMetadata>, Vec<serde_json::Map<String, serde_json::Value>>, usize)> {
     let pool = manager.get_pool_mysql(connection_id).await?;
 
-    // Build query with parameterized filter
-    let (query, bind_value) = if let (Some(column), Some(value)) = (filter_column, filter_value) {
-        let quoted_column = quote_identifier_mysql(&column);
-        if value.is_null() {
-            let q = format!(
-                "SELECT * FROM {} WHERE {} IS NULL LIMIT {} OFFSET {}",
-                quoted_table, quoted_column, limit, offset
-            );
-            (q, None)
-        } else {
-            let q = format!(
-                "SELECT * FROM {} WHERE {} = ? LIMIT {} OFFSET {}",
-                quoted_table, quoted_column, limit, offset
-            );
-            (q, Some(value))
-        }
-    } else {
-        let q = format!("SELECT * FROM {} LIMIT {} OFFSET {}", quoted_table, limit, offset);
-        (q, None)
-    };
-
     // Execute with or without bind parameter
     let rows = if let Some(val) = bind_value {
         match val {
             serde_json::Value::Bool(b) => {
-                sqlx::query(&query).bind(b).fetch_all(&pool).await?
+                sqlx::query(query).bind(b).fetch_all(&pool).await?
             }
             serde_json::Value::Number(n) => {
                 if let Some(i) = n.as_i64() {
-                    sqlx::query(&query).bind(i).fetch_all(&pool).await?
+                    sqlx::query(query).bind(i).fetch_all(&pool).await?
                 } else if let Some(f) = n.as_f64() {
-                    sqlx::query(&query).bind(f).fetch_all(&pool).await?
+                    sqlx::query(query).bind(f).fetch_all(&pool).await?
                 } else {
-                    sqlx::query(&query).bind(n.to_string()).fetch_all(&pool).await?
+                    sqlx::query(query).bind(n.to_string()).fetch_all(&pool).await?
                 }
             }
             serde_json::Value::String(s) => {
-                sqlx::query(&query).bind(s).fetch_all(&pool).await?
+                sqlx::query(query).bind(s).fetch_all(&pool).await?
             }
             _ => {
-                sqlx::query(&query).bind(val.to_string()).fetch_all(&pool).await?
+                sqlx::query(query).bind(val.to_string()).fetch_all(&pool).await?
             }
         }
     } else {
-        sqlx::query(&query).fetch_all(&pool).await?
+        sqlx::query(query).fetch_all(&pool).await?
     };
 
     // Get database name and fetch FK/enum metadata in parallel
@@ -519,9 +2299,7 @@ fn convert_postgres_value_ex(row: &sqlx::postgres::PgRow, idx: usize, col_type:
             .unwrap_or(serde_json::Value::Null),
         // Range types (int4range, int8range, daterange, tsrange, tstzrange, numrange)
         "INT4RANGE" | "INT8RANGE" | "DATERANGE" | "TSRANGE" | "TSTZRANGE" | "NUMRANGE" => {
-            row.try_get::<String, _>(idx)
-                .map(serde_json::Value::String)
-                .unwrap_or(serde_json::Value::Null)
+            pg_range_to_json(row, idx, col_type)
         }
         // OID type (PostgreSQL object identifier)
         "OID" => row.try_get::<Oid, _>(idx)
@@ -544,43 +2322,37 @@ fn convert_postgres_value_ex(row: &sqlx::postgres::PgRow, idx: usize, col_type:
         "TEXT" | "VARCHAR" | "CHAR" | "BPCHAR" | "NAME" => row.try_get::<String, _>(idx)
             .map(serde_json::Value::String)
             .unwrap_or(serde_json::Value::Null),
-        // PostgreSQL array types - convert to proper JSON arrays
-        "_INT2" => row.try_get::<Vec<i16>, _>(idx)
-            .map(|v| serde_json::to_value(v).unwrap_or(serde_json::Value::Null))
-            .unwrap_or(serde_json::Value::Null),
-        "_INT4" => row.try_get::<Vec<i32>, _>(idx)
-            .map(|v| serde_json::to_value(v).unwrap_or(serde_json::Value::Null))
-            .unwrap_or(serde_json::Value::Null),
-        "_INT8" => row.try_get::<Vec<i64>, _>(idx)
-            .map(|v| serde_json::to_value(v).unwrap_or(serde_json::Value::Null))
-            .unwrap_or(serde_json::Value::Null),
-        "_FLOAT4" => row.try_get::<Vec<f32>, _>(idx)
-            .map(|v| serde_json::to_value(v).unwrap_or(serde_json::Value::Null))
-            .unwrap_or(serde_json::Value::Null),
-        "_FLOAT8" => row.try_get::<Vec<f64>, _>(idx)
-            .map(|v| serde_json::to_value(v).unwrap_or(serde_json::Value::Null))
-            .unwrap_or(serde_json::Value::Null),
-        "_TEXT" | "_VARCHAR" | "_BPCHAR" => row.try_get::<Vec<String>, _>(idx)
-            .map(|v| serde_json::to_value(v).unwrap_or(serde_json::Value::Null))
-            .unwrap_or(serde_json::Value::Null),
-        "_BOOL" => row.try_get::<Vec<bool>, _>(idx)
-            .map(|v| serde_json::to_value(v).unwrap_or(serde_json::Value::Null))
-            .unwrap_or(serde_json::Value::Null),
-        "_UUID" => row.try_get::<Vec<uuid::Uuid>, _>(idx)
-            .map(|v| serde_json::to_value(v.iter().map(|u| u.to_string()).collect::<Vec<_>>()).unwrap_or(serde_json::Value::Null))
-            .unwrap_or(serde_json::Value::Null),
-        "_NUMERIC" => row.try_get::<Vec<rust_decimal::Decimal>, _>(idx)
-            .map(|v| serde_json::to_value(v.iter().map(|d| d.to_string()).collect::<Vec<_>>()).unwrap_or(serde_json::Value::Null))
-            .unwrap_or(serde_json::Value::Null),
-        "_DATE" => row.try_get::<Vec<chrono::NaiveDate>, _>(idx)
-            .map(|v| serde_json::to_value(v.iter().map(|d| d.to_string()).collect::<Vec<_>>()).unwrap_or(serde_json::Value::Null))
-            .unwrap_or(serde_json::Value::Null),
-        "_TIMESTAMP" => row.try_get::<Vec<chrono::NaiveDateTime>, _>(idx)
-            .map(|v| serde_json::to_value(v.iter().map(|d| d.to_string()).collect::<Vec<_>>()).unwrap_or(serde_json::Value::Null))
-            .unwrap_or(serde_json::Value::Null),
-        "_TIMESTAMPTZ" => row.try_get::<Vec<chrono::DateTime<chrono::Utc>>, _>(idx)
-            .map(|v| serde_json::to_value(v.iter().map(|d| d.to_rfc3339()).collect::<Vec<_>>()).unwrap_or(serde_json::Value::Null))
-            .unwrap_or(serde_json::Value::Null),
+        // PostgreSQL array types - convert to proper JSON arrays, keeping NULL elements.
+        // sqlx names built-in array types `INT4[]`; `_int4` is the catalog name.
+        "_INT2" | "INT2[]" => pg_array_to_json::<i16>(row, idx, col_type, |v| v.into()),
+        "_INT4" | "INT4[]" => pg_array_to_json::<i32>(row, idx, col_type, |v| v.into()),
+        "_INT8" | "INT8[]" => pg_array_to_json::<i64>(row, idx, col_type, |v| v.into()),
+        "_FLOAT4" | "FLOAT4[]" => pg_array_to_json::<f32>(row, idx, col_type, |v| float_to_json(v as f64)),
+        "_FLOAT8" | "FLOAT8[]" => pg_array_to_json::<f64>(row, idx, col_type, float_to_json),
+        "_TEXT" | "TEXT[]" | "_VARCHAR" | "VARCHAR[]" | "_BPCHAR" | "BPCHAR[]" | "_NAME" | "NAME[]" => {
+            pg_array_to_json::<String>(row, idx, col_type, serde_json::Value::String)
+        }
+        "_BOOL" | "BOOL[]" => pg_array_to_json::<bool>(row, idx, col_type, serde_json::Value::Bool),
+        "_UUID" | "UUID[]" => {
+            pg_array_to_json::<uuid::Uuid>(row, idx, col_type, |u| serde_json::Value::String(u.to_string()))
+        }
+        "_NUMERIC" | "NUMERIC[]" => pg_array_to_json::<rust_decimal::Decimal>(row, idx, col_type, |d| {
+            serde_json::Value::String(d.to_string())
+        }),
+        "_DATE" | "DATE[]" => pg_array_to_json::<chrono::NaiveDate>(row, idx, col_type, |d| {
+            serde_json::Value::String(d.to_string())
+        }),
+        "_TIMESTAMP" | "TIMESTAMP[]" => pg_array_to_json::<chrono::NaiveDateTime>(row, idx, col_type, |d| {
+            serde_json::Value::String(d.to_string())
+        }),
+        "_TIMESTAMPTZ" | "TIMESTAMPTZ[]" => {
+            pg_array_to_json::<chrono::DateTime<chrono::Utc>>(row, idx, col_type, |d| {
+                serde_json::Value::String(d.to_rfc3339())
+            })
+        }
+        "_JSON" | "JSON[]" | "_JSONB" | "JSONB[]" => {
+            pg_array_to_json::<serde_json::Value>(row, idx, col_type, |v| v)
+        }
         "BYTEA" => row.try_get::<Vec<u8>, _>(idx)
             .map(|bytes| serde_json::Value::String(format!("0x{}", hex::encode(bytes))))
             .unwrap_or(serde_json::Value::Null),
@@ -594,6 +2366,16 @@ fn convert_postgres_value_ex(row: &sqlx::postgres::PgRow, idx: usize, col_type:
                 serde_json::Value::Null
             }
         }
+        // pgvector embeddings, shortened for display
+        "vector" | "VECTOR" => row
+            .try_get_raw(idx)
+            .ok()
+            .and_then(|raw| <&[u8] as sqlx::Decode<sqlx::Postgres>>::decode(raw).ok())
+            .and_then(decode_pgvector)
+            .map(|values| serde_json::Value::String(format_vector_preview(&values)))
+            .unwrap_or_else(|| serde_json::Value::String("<vector>".to_string())),
+        // Arrays of other types (enums, domains, ...) keep PostgreSQL's text form
+        other if other.starts_with('_') || other.ends_with("[]") => pg_array_text(row, idx, other),
         _ => {
             if let Ok(val) = row.try_get::<String, _>(idx) {
                 serde_json::Value::String(val)
@@ -610,12 +2392,229 @@ fn convert_postgres_value_ex(row: &sqlx::postgres::PgRow, idx: usize, col_type:
     }
 }
 
+/// Decode a non-NULL PostgreSQL array as a JSON array, mapping NULL elements to
+/// JSON null. Values that don't decode as `T` fall back to the text form.
+fn pg_array_to_json<'r, T>(
+    row: &'r sqlx::postgres::PgRow,
+    idx: usize,
+    col_type: &str,
+    to_json: impl Fn(T) -> serde_json::Value,
+) -> serde_json::Value
+where
+    Vec<Option<T>>: sqlx::Decode<'r, sqlx::Postgres> + sqlx::Type<sqlx::Postgres>,
+{
+    match row.try_get::<Vec<Option<T>>, _>(idx) {
+        Ok(items) => serde_json::Value::Array(
+            items
+                .into_iter()
+                .map(|item| item.map_or(serde_json::Value::Null, &to_json))
+                .collect(),
+        ),
+        Err(_) => pg_array_text(row, idx, col_type),
+    }
+}
+
+/// An array without a JSON mapping in PostgreSQL's `{a,"b c",NULL}` literal form.
+/// Elements are read as text, which works for text-like element types (enums, citext, ...).
+fn pg_array_text(row: &sqlx::postgres::PgRow, idx: usize, col_type: &str) -> serde_json::Value {
+    match row.try_get_unchecked::<Vec<Option<String>>, _>(idx) {
+        Ok(items) => serde_json::Value::String(format_pg_array_literal(&items)),
+        Err(_) => serde_json::Value::String(format!("<unsupported: {}>", col_type)),
+    }
+}
+
+/// Format elements as a PostgreSQL array literal, quoting where the parser requires it
+fn format_pg_array_literal(items: &[Option<String>]) -> String {
+    let elements: Vec<String> = items
+        .iter()
+        .map(|item| match item {
+            None => "NULL".to_string(),
+            Some(value) => {
+                let needs_quotes = value.is_empty()
+                    || value.eq_ignore_ascii_case("NULL")
+                    || value
+                        .chars()
+                        .any(|c| matches!(c, ',' | '{' | '}' | '"' | '\\') || c.is_whitespace());
+                if needs_quotes {
+                    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+                } else {
+                    value.clone()
+                }
+            }
+        })
+        .collect();
+    format!("{{{}}}", elements.join(","))
+}
+
+/// Set in the first byte of a binary range value when the range is empty
+const PG_RANGE_EMPTY: u8 = 0x01;
+
+/// A range in PostgreSQL's canonical text form: `[1,10)`, `[1,)`, `(,)` or `empty`
+fn pg_range_to_json(row: &sqlx::postgres::PgRow, idx: usize, col_type: &str) -> serde_json::Value {
+    let unsupported = || serde_json::Value::String(format!("<unsupported: {}>", col_type));
+    let Some(bytes) = row
+        .try_get_raw(idx)
+        .ok()
+        .and_then(|raw| <&[u8] as sqlx::Decode<sqlx::Postgres>>::decode(raw).ok())
+    else {
+        return unsupported();
+    };
+
+    // Text results already hold the canonical form; binary ones start with a flags byte
+    if let Some(text) = std::str::from_utf8(bytes).ok().filter(|t| t.starts_with(['[', '(', 'e'])) {
+        return serde_json::Value::String(text.to_string());
+    }
+    if bytes.first().is_some_and(|flags| flags & PG_RANGE_EMPTY != 0) {
+        return serde_json::Value::String("empty".to_string());
+    }
+
+    let text = match col_type {
+        "INT4RANGE" => row.try_get::<PgRange<i32>, _>(idx).map(|r| format_pg_range(&r, i32::to_string)),
+        "INT8RANGE" => row.try_get::<PgRange<i64>, _>(idx).map(|r| format_pg_range(&r, i64::to_string)),
+        "NUMRANGE" => row
+            .try_get::<PgRange<rust_decimal::Decimal>, _>(idx)
+            .map(|r| format_pg_range(&r, rust_decimal::Decimal::to_string)),
+        "DATERANGE" => row.try_get::<PgRange<NaiveDate>, _>(idx).map(|r| format_pg_range(&r, NaiveDate::to_string)),
+        "TSRANGE" => row
+            .try_get::<PgRange<NaiveDateTime>, _>(idx)
+            .map(|r| format_pg_range(&r, NaiveDateTime::to_string)),
+        "TSTZRANGE" => row
+            .try_get::<PgRange<DateTime<chrono::Utc>>, _>(idx)
+            .map(|r| format_pg_range(&r, DateTime::to_rfc3339)),
+        _ => return unsupported(),
+    };
+    text.map(serde_json::Value::String).unwrap_or_else(|_| unsupported())
+}
+
+/// Format a non-empty range; a missing bound is left blank and always exclusive, as PostgreSQL prints it
+fn format_pg_range<T>(range: &PgRange<T>, to_text: impl Fn(&T) -> String) -> String {
+    let bound = |value: &T| quote_pg_range_bound(to_text(value));
+    let (open, lower) = match &range.start {
+        Bound::Included(value) => ('[', bound(value)),
+        Bound::Excluded(value) => ('(', bound(value)),
+        Bound::Unbounded => ('(', String::new()),
+    };
+    let (upper, close) = match &range.end {
+        Bound::Included(value) => (bound(value), ']'),
+        Bound::Excluded(value) => (bound(value), ')'),
+        Bound::Unbounded => (String::new(), ')'),
+    };
+    format!("{}{},{}{}", open, lower, upper, close)
+}
+
+/// Double-quote a bound containing characters the range parser treats specially, e.g. the space in a timestamp
+fn quote_pg_range_bound(text: String) -> String {
+    let needs_quotes =
+        text.is_empty() || text.chars().any(|c| c.is_whitespace() || matches!(c, '"' | '\\' | ',' | '(' | ')' | '[' | ']'));
+    if needs_quotes {
+        format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        text
+    }
+}
+
+/// Leading pgvector components shown before the rest are elided
+const VECTOR_PREVIEW_LEN: usize = 6;
+
+/// Decode a pgvector value from its binary form (u16 dimensions, u16 unused, then
+/// big-endian f32s) or its text form (`[1,2,3]`)
+fn decode_pgvector(bytes: &[u8]) -> Option<Vec<f32>> {
+    if let Some(text) = std::str::from_utf8(bytes).ok().filter(|t| t.starts_with('[')) {
+        let inner = text.trim().strip_prefix('[')?.strip_suffix(']')?;
+        if inner.trim().is_empty() {
+            return Some(Vec::new());
+        }
+        return inner.split(',').map(|v| v.trim().parse::<f32>().ok()).collect();
+    }
+
+    let dimensions = u16::from_be_bytes([*bytes.first()?, *bytes.get(1)?]) as usize;
+    let data = bytes.get(4..)?;
+    if data.len() != dimensions * 4 {
+        return None;
+    }
+    Some(
+        data.chunks_exact(4)
+            .map(|c| f32::from_be_bytes([c[0], c[1], c[2], c[3]]))
+            .collect(),
+    )
+}
+
+/// `[0.12, 0.34, ... +1530 more]`
+fn format_vector_preview(values: &[f32]) -> String {
+    let shown: Vec<String> = values
+        .iter()
+        .take(VECTOR_PREVIEW_LEN)
+        .map(|v| format!("{:.4}", v).trim_end_matches('0').trim_end_matches('.').to_string())
+        .collect();
+    if values.len() > VECTOR_PREVIEW_LEN {
+        format!("[{}, ... +{} more]", shown.join(", "), values.len() - VECTOR_PREVIEW_LEN)
+    } else {
+        format!("[{}]", shown.join(", "))
+    }
+}
+
 /// Wrapper for backward compatibility - assumes non-enum column
 #[inline]
 fn convert_postgres_value(row: &sqlx::postgres::PgRow, idx: usize, col_type: &str) -> serde_json::Value {
     convert_postgres_value_ex(row, idx, col_type, false)
 }
 
+/// Convert a full PostgreSQL row into a JSON object keyed by column name
+pub(crate) fn postgres_row_to_json(
+    row: &sqlx::postgres::PgRow,
+) -> AppResult<serde_json::Map<String, serde_json::Value>> {
+    let mut row_map = serde_json::Map::with_capacity(row.columns().len());
+
+    for (idx, column) in row.columns().iter().enumerate() {
+        let col_name = column.name().to_string();
+
+        // Check if the value is NULL first
+        let raw_value = row.try_get_raw(idx)?;
+        let value = if raw_value.is_null() {
+            serde_json::Value::Null
+        } else {
+            convert_postgres_value(row, idx, column.type_info().name())
+        };
+        row_map.insert(col_name, value);
+    }
+
+    Ok(row_map)
+}
+
+/// Convert a full MySQL row into a JSON object keyed by column name
+pub(crate) fn mysql_row_to_json(
+    row: &sqlx::mysql::MySqlRow,
+) -> AppResult<serde_json::Map<String, serde_json::Value>> {
+    let mut row_map = serde_json::Map::with_capacity(row.columns().len());
+
+    for (idx, column) in row.columns().iter().enumerate() {
+        let col_name = column.name().to_string();
+
+        // Check if the value is NULL first
+        let raw_value = row.try_get_raw(idx)?;
+        let value = if raw_value.is_null() {
+            serde_json::Value::Null
+        } else {
+            convert_mysql_value(row, idx, column.type_info().name())
+        };
+        row_map.insert(col_name, value);
+    }
+
+    Ok(row_map)
+}
+
+/// Build column names and basic metadata (no FK/enum lookups) from a row
+pub(crate) fn columns_from_row<R: Row>(row: &R) -> (Vec<String>, Vec<ColumnMetadata>) {
+    row.columns()
+        .iter()
+        .map(|col| {
+            let name = col.name().to_string();
+            let data_type = col.type_info().name().to_string();
+            (name.clone(), TableMetadata::default().get_column_metadata(&name, data_type))
+        })
+        .unzip()
+}
+
 /// Convert a MySQL value to JSON based on column type
 fn convert_mysql_value(row: &sqlx::mysql::MySqlRow, idx: usize, col_type: &str) -> serde_json::Value {
     match col_type {
@@ -738,19 +2737,62 @@ async fn execute_postgres_query(
     manager: &ConnectionManager,
     connection_id: &str,
     query: &str,
+    params: &[serde_json::Value],
+    timeout_ms: u64,
+    query_token: Option<&str>,
 ) -> AppResult<(Vec<String>, Vec<ColumnMetadata>, Vec<serde_json::Map<String, serde_json::Value>>, usize)> {
     let pool = manager.get_pool_postgres(connection_id).await?;
 
-    let rows = sqlx::query(query).fetch_all(&pool).await?;
+    // PostGIS columns would otherwise arrive as unreadable EWKB
+    let original_query = query;
+    let rewritten = postgis_as_text(&pool, connection_id, query).await;
+    let query = rewritten.as_deref().unwrap_or(query);
+
+    // A server-side statement timeout lets the server abort the work instead of
+    // only abandoning the future
+    let mut conn = TimedPgConnection::acquire(&pool, timeout_ms).await?;
+
+    let running = match query_token {
+        Some(token) => {
+            let pid: i32 = sqlx::query_scalar("SELECT pg_backend_pid()")
+                .fetch_one(&mut *conn.conn)
+                .await?;
+            RunningQueryGuard::register(token, connection_id, pid as u64)
+        }
+        None => RunningQueryGuard::default(),
+    };
 
-    // Try to extract table name and get FK metadata
-    let fk_map = if let Some(table_name) = extract_table_name(query) {
-        // Default to 'public' schema
-        get_postgres_fk_metadata(&pool, &table_name, "public")
-            .await
-            .unwrap_or_default()
+    let fetched = bind_postgres_params(sqlx::query(query), params).fetch_all(&mut *conn.conn).await;
+    let reset = conn.reset().await;
+    drop(conn);
+    let rows = match fetched {
+        Ok(rows) => rows,
+        // 57014 = query_canceled, raised by pg_cancel_backend or when statement_timeout fires
+        Err(sqlx::Error::Database(e)) if e.code().as_deref() == Some("57014") => {
+            if running.was_cancelled() {
+                return Err(query_cancelled_error());
+            }
+            return Err(AppError::QueryTimeout(format!(
+                "Query exceeded the {} ms statement timeout",
+                timeout_ms
+            )));
+        }
+        Err(e) => return Err(e.into()),
+    };
+    drop(running);
+    reset?;
+
+    // Try to extract table name and get FK and enum metadata
+    let (fk_map, enum_map) = if let Some(table_name) = extract_table_name(original_query) {
+        let search_path = manager.get_connection(connection_id)?.search_path();
+        let (table_schema, bare_table) = resolve_postgres_table(&pool, &table_name, &search_path).await;
+        let (fk_result, enum_result) = tokio::join!(
+            get_postgres_fk_metadata(&pool, bare_table, &table_schema),
+            get_postgres_enum_values(&pool, bare_table, &table_schema)
+        );
+        (fk_result.unwrap_or_default(), enum_result.unwrap_or_default())
     } else {
-        HashMap::new()
+        (HashMap::new(), HashMap::new())
     };
 
     // Get column names and metadata from first row, or try to get column info even with no rows
@@ -759,10 +2801,11 @@ async fn execute_postgres_query(
             let name = col.name().to_string();
             let data_type = col.type_info().name().to_string();
             let foreign_key = fk_map.get(&name).cloned();
+            let enum_values = enum_map.get(&name).cloned();
             (name.clone(), ColumnMetadata {
                 name,
                 data_type,
-                enum_values: None, // PostgreSQL enums would need schema query
+                enum_values,
                 foreign_key,
             })
         }).collect();
@@ -770,16 +2813,17 @@ async fn execute_postgres_query(
          cols.into_iter().map(|(_, meta)| meta).collect())
     } else {
         // No rows, try to prepare the query to get column metadata
-        match sqlx::query(query).fetch_optional(&pool).await {
+        match bind_postgres_params(sqlx::query(query), params).fetch_optional(&pool).await {
             Ok(Some(row)) => {
                 let cols: Vec<_> = row.columns().iter().map(|col| {
                     let name = col.name().to_string();
                     let data_type = col.type_info().name().to_string();
                     let foreign_key = fk_map.get(&name).cloned();
+                    let enum_values = enum_map.get(&name).cloned();
                     (name.clone(), ColumnMetadata {
                         name,
                         data_type,
-                        enum_values: None,
+                        enum_values,
                         foreign_key,
                     })
                 }).collect();
@@ -798,31 +2842,110 @@ async fn execute_postgres_query(
     }
 
     // Convert rows to JSON using the centralized conversion function
-    let mut result_rows = Vec::new();
-
+    let mut result_rows = Vec::with_capacity(rows.len());
     for row in &rows {
-        let mut row_map = serde_json::Map::new();
+        result_rows.push(postgres_row_to_json(row)?);
+    }
 
-        for (idx, column) in row.columns().iter().enumerate() {
-            let col_name = column.name().to_string();
-            let col_type = column.type_info().name();
+    Ok((columns, column_metadata, result_rows, rows.len()))
+}
 
-            // Check if the value is NULL first
-            let raw_value = row.try_get_raw(idx)?;
-            if raw_value.is_null() {
-                row_map.insert(col_name, serde_json::Value::Null);
-                continue;
-            }
+lazy_static::lazy_static! {
+    /// Whether each connection's database has the postgis extension
+    static ref POSTGIS_INSTALLED: std::sync::Mutex<HashMap<String, bool>> =
+        std::sync::Mutex::new(HashMap::new());
+}
 
-            // Use the centralized conversion function
-            let value = convert_postgres_value(row, idx, col_type);
-            row_map.insert(col_name, value);
-        }
+/// Whether the postgis extension is installed, checked once per connection.
+/// A failed check isn't cached and counts as not installed.
+async fn has_postgis(pool: &sqlx::PgPool, connection_id: &str) -> bool {
+    if let Some(installed) = POSTGIS_INSTALLED
+        .lock()
+        .ok()
+        .and_then(|installed| installed.get(connection_id).copied())
+    {
+        return installed;
+    }
 
-        result_rows.push(row_map);
+    let Ok(installed) = sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS (SELECT 1 FROM pg_extension WHERE extname = 'postgis')",
+    )
+    .fetch_one(pool)
+    .await
+    else {
+        return false;
+    };
+
+    if let Ok(mut cache) = POSTGIS_INSTALLED.lock() {
+        cache.insert(connection_id.to_string(), installed);
     }
+    installed
+}
 
-    Ok((columns, column_metadata, result_rows, rows.len()))
+/// Rewrite a read query so its PostGIS geometry/geography columns come back as WKT.
+/// The result columns are found by describing the statement, so `SELECT *` works too.
+/// Returns None (run the query unchanged) for non-SELECT statements, databases without
+/// PostGIS, queries without PostGIS columns, or when describing fails.
+async fn postgis_as_text(pool: &sqlx::PgPool, connection_id: &str, query: &str) -> Option<String> {
+    use sqlx::Executor;
+
+    let head = query.trim_start().to_uppercase();
+    let is_read = head.starts_with("SELECT")
+        || (head.starts_with("WITH") && crate::ai::sanitizer::validate_sql(query, &DatabaseType::PostgreSQL).is_ok());
+    if !is_read || !has_postgis(pool, connection_id).await {
+        return None;
+    }
+
+    let described = pool.describe(query).await.ok()?;
+    let columns: Vec<(String, bool)> = described
+        .columns()
+        .iter()
+        .map(|col| (col.name().to_string(), is_postgis_type(col.type_info().name())))
+        .collect();
+
+    wrap_postgis_columns(query, &columns)
+}
+
+fn is_postgis_type(type_name: &str) -> bool {
+    type_name.eq_ignore_ascii_case("geometry") || type_name.eq_ignore_ascii_case("geography")
+}
+
+/// Select every column of `query` through a subquery, wrapping the flagged ones in
+/// ST_AsText. Rows are numbered in the order the query returns them and the outer
+/// query sorts on that number, so the query's own ORDER BY still holds. None when no
+/// column is flagged, or when duplicate/unnamed columns couldn't be referenced from
+/// the outer query.
+fn wrap_postgis_columns(query: &str, columns: &[(String, bool)]) -> Option<String> {
+    if !columns.iter().any(|(_, postgis)| *postgis) {
+        return None;
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    if !columns
+        .iter()
+        .all(|(name, _)| !name.is_empty() && name != "?column?" && name != "_row_order" && seen.insert(name))
+    {
+        return None;
+    }
+
+    let select_list: Vec<String> = columns
+        .iter()
+        .map(|(name, postgis)| {
+            let quoted = quote_identifier_postgres(name);
+            if *postgis {
+                format!("ST_AsText(_q.{}) AS {}", quoted, quoted)
+            } else {
+                format!("_q.{}", quoted)
+            }
+        })
+        .collect();
+
+    // The newline keeps a trailing `--` comment from swallowing the closing parenthesis
+    Some(format!(
+        "SELECT {} FROM (SELECT _i.*, row_number() OVER () AS _row_order FROM ({}\n) AS _i) AS _q ORDER BY _q._row_order",
+        select_list.join(", "),
+        query.trim().trim_end_matches(';')
+    ))
 }
 
 // Helper function to get foreign key metadata for PostgreSQL
@@ -834,7 +2957,8 @@ async fn get_postgres_fk_metadata(
     let fk_query = r#"
         SELECT
             kcu.column_name,
-            ccu.table_name AS referenced_table,
+            CASE WHEN ccu.table_schema = 'public' THEN ccu.table_name::TEXT
+                 ELSE ccu.table_schema || '.' || ccu.table_name END AS referenced_table,
             ccu.column_name AS referenced_column
         FROM information_schema.table_constraints AS tc
         JOIN information_schema.key_column_usage AS kcu
@@ -842,7 +2966,7 @@ async fn get_postgres_fk_metadata(
           AND tc.table_schema = kcu.table_schema
         JOIN information_schema.constraint_column_usage AS ccu
           ON ccu.constraint_name = tc.constraint_name
-          AND ccu.table_schema = tc.table_schema
+          AND ccu.constraint_schema = tc.constraint_schema
         WHERE tc.constraint_type = 'FOREIGN KEY'
           AND tc.table_name = $1
           AND tc.table_schema = $2
@@ -926,6 +3050,7 @@ async fn get_postgres_enum_values(
             e.enumlabel as enum_value
         FROM information_schema.columns c
         JOIN pg_type t ON c.udt_name = t.typname
+        JOIN pg_namespace tn ON tn.oid = t.typnamespace AND tn.nspname = c.udt_schema
         JOIN pg_enum e ON t.oid = e.enumtypid
         WHERE c.table_name = $1
           AND c.table_schema = $2
@@ -952,46 +3077,6 @@ async fn get_postgres_enum_values(
     Ok(enum_map)
 }
 
-/// Parse MySQL enum/set definition like enum('val1','val2','escaped''quote')
-/// Handles escaped quotes ('') within values
-fn parse_mysql_enum_values(column_type: &str) -> Vec<String> {
-    let Some(start) = column_type.find('(') else { return vec![] };
-    let Some(end) = column_type.rfind(')') else { return vec![] };
-    if start >= end { return vec![] }
-
-    let inner = &column_type[start + 1..end];
-    let mut values = Vec::new();
-    let mut current = String::new();
-    let mut in_quote = false;
-    let mut chars = inner.chars().peekable();
-
-    while let Some(c) = chars.next() {
-        match c {
-            '\'' if !in_quote => in_quote = true,
-            '\'' if in_quote => {
-                // Check for escaped quote ('')
-                if chars.peek() == Some(&'\'') {
-                    chars.next();
-                    current.push('\'');
-                } else {
-                    in_quote = false;
-                }
-            }
-            ',' if !in_quote => {
-                if !current.is_empty() {
-                    values.push(std::mem::take(&mut current));
-                }
-            }
-            _ if in_quote => current.push(c),
-            _ => {} // Skip whitespace outside quotes
-        }
-    }
-    if !current.is_empty() {
-        values.push(current);
-    }
-    values
-}
-
 async fn get_mysql_enum_values(
     pool: &sqlx::MySqlPool,
     table_name: &str,
@@ -1032,10 +3117,12 @@ fn extract_table_name(query: &str) -> Option<String> {
             .trim_matches(|c| c == '`' || c == '"' || c == '\'' || c == ';')
             .to_string();
 
-        // Don't include schema prefix, just table name
-        if let Some(dot_idx) = table_name.rfind('.') {
-            return Some(table_name[dot_idx + 1..].to_string());
-        }
+        // Keep a schema prefix (PostgreSQL resolves it); quotes are stripped per part
+        let table_name = table_name
+            .split('.')
+            .map(|part| part.trim_matches(|c| c == '`' || c == '"'))
+            .collect::<Vec<_>>()
+            .join(".");
 
         return Some(table_name);
     }
@@ -1047,10 +3134,31 @@ async fn execute_mysql_query(
     manager: &ConnectionManager,
     connection_id: &str,
     query: &str,
+    params: &[serde_json::Value],
+    query_token: Option<&str>,
 ) -> AppResult<(Vec<String>, Vec<ColumnMetadata>, Vec<serde_json::Map<String, serde_json::Value>>, usize)> {
     let pool = manager.get_pool_mysql(connection_id).await?;
 
-    let rows = sqlx::query(query).fetch_all(&pool).await?;
+    // A dedicated connection, so its CONNECTION_ID() identifies the session to KILL QUERY
+    let mut session = pool.acquire().await?;
+    let running = match query_token {
+        Some(token) => {
+            let id: u64 = sqlx::query_scalar("SELECT CONNECTION_ID()")
+                .fetch_one(&mut *session)
+                .await?;
+            RunningQueryGuard::register(token, connection_id, id)
+        }
+        None => RunningQueryGuard::default(),
+    };
+
+    let rows = match bind_mysql_params(sqlx::query(query), params).fetch_all(&mut *session).await {
+        Ok(rows) => rows,
+        // KILL QUERY surfaces as 1317 (query execution was interrupted)
+        Err(_) if running.was_cancelled() => return Err(query_cancelled_error()),
+        Err(e) => return Err(e.into()),
+    };
+    drop(running);
+    drop(session);
 
     // Get current database name for FK queries
     let database_name: (String,) = sqlx::query_as("SELECT DATABASE()")
@@ -1058,13 +3166,16 @@ async fn execute_mysql_query(
         .await?;
     let database_name = database_name.0;
 
-    // Try to extract table name and get FK metadata
-    let fk_map = if let Some(table_name) = extract_table_name(query) {
-        get_mysql_fk_metadata(&pool, &table_name, &database_name)
-            .await
-            .unwrap_or_default()
+    // Try to extract table name and get FK and enum metadata
+    let (fk_map, enum_map) = if let Some(table_name) = extract_table_name(query) {
+        let (_, table_name) = split_qualified_table(&table_name);
+        let (fk_result, enum_result) = tokio::join!(
+            get_mysql_fk_metadata(&pool, table_name, &database_name),
+            get_mysql_enum_values(&pool, table_name, &database_name)
+        );
+        (fk_result.unwrap_or_default(), enum_result.unwrap_or_default())
     } else {
-        HashMap::new()
+        (HashMap::new(), HashMap::new())
     };
 
     // Get column names and metadata from first row, or try to get column info even with no rows
@@ -1073,10 +3184,11 @@ async fn execute_mysql_query(
             let name = col.name().to_string();
             let data_type = col.type_info().name().to_string();
             let foreign_key = fk_map.get(&name).cloned();
+            let enum_values = enum_map.get(&name).cloned();
             (name.clone(), ColumnMetadata {
                 name,
                 data_type,
-                enum_values: None, // MySQL enums would need SHOW COLUMNS query
+                enum_values,
                 foreign_key,
             })
         }).collect();
@@ -1084,16 +3196,17 @@ async fn execute_mysql_query(
          cols.into_iter().map(|(_, meta)| meta).collect())
     } else {
         // No rows, try to prepare the query to get column metadata
-        match sqlx::query(query).fetch_optional(&pool).await {
+        match bind_mysql_params(sqlx::query(query), params).fetch_optional(&pool).await {
             Ok(Some(row)) => {
                 let cols: Vec<_> = row.columns().iter().map(|col| {
                     let name = col.name().to_string();
                     let data_type = col.type_info().name().to_string();
                     let foreign_key = fk_map.get(&name).cloned();
+                    let enum_values = enum_map.get(&name).cloned();
                     (name.clone(), ColumnMetadata {
                         name,
                         data_type,
-                        enum_values: None,
+                        enum_values,
                         foreign_key,
                     })
                 }).collect();
@@ -1112,29 +3225,362 @@ async fn execute_mysql_query(
     }
 
     // Convert rows to JSON using the centralized conversion function
-    let mut result_rows = Vec::new();
-
+    let mut result_rows = Vec::with_capacity(rows.len());
     for row in &rows {
-        let mut row_map = serde_json::Map::new();
+        result_rows.push(mysql_row_to_json(row)?);
+    }
 
-        for (idx, column) in row.columns().iter().enumerate() {
-            let col_name = column.name().to_string();
-            let col_type = column.type_info().name();
+    Ok((columns, column_metadata, result_rows, rows.len()))
+}
 
-            // Check if the value is NULL first
-            let raw_value = row.try_get_raw(idx)?;
-            if raw_value.is_null() {
-                row_map.insert(col_name, serde_json::Value::Null);
-                continue;
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
 
-            // Use the centralized conversion function
-            let value = convert_mysql_value(row, idx, col_type);
-            row_map.insert(col_name, value);
+    #[test]
+    fn test_pgvector_decode_and_preview() {
+        let mut binary = vec![0, 8, 0, 0];
+        for i in 0..8 {
+            binary.extend_from_slice(&(i as f32 * 0.5).to_be_bytes());
         }
+        let values = decode_pgvector(&binary).unwrap();
+        assert_eq!(values.len(), 8);
+        assert_eq!(format_vector_preview(&values), "[0, 0.5, 1, 1.5, 2, 2.5, ... +2 more]");
 
-        result_rows.push(row_map);
+        assert_eq!(decode_pgvector(b"[0.12,0.34]").unwrap(), vec![0.12, 0.34]);
+        assert_eq!(format_vector_preview(&[0.12, -0.34]), "[0.12, -0.34]");
+        assert!(decode_pgvector(&[0, 2, 0, 0, 1]).is_none());
     }
 
-    Ok((columns, column_metadata, result_rows, rows.len()))
+    #[test]
+    fn test_paginate_query() {
+        assert_eq!(
+            paginate_query("SELECT 'no LIMIT here';", 50, 100),
+            "SELECT 'no LIMIT here' LIMIT 50 OFFSET 100"
+        );
+        assert_eq!(
+            paginate_query("SELECT * FROM t -- newest first\n", 10, 0),
+            "SELECT * FROM t LIMIT 10 OFFSET 0"
+        );
+        assert_eq!(
+            paginate_query("SELECT * FROM (SELECT * FROM t LIMIT 5) s", 10, 0),
+            "SELECT * FROM (SELECT * FROM t LIMIT 5) s LIMIT 10 OFFSET 0"
+        );
+        assert_eq!(paginate_query("SELECT * FROM t LIMIT 5;", 10, 0), "SELECT * FROM t LIMIT 5");
+        assert_eq!(paginate_query("SELECT COUNT(*) FROM huge_table", 10, 0), "SELECT COUNT(*) FROM huge_table");
+        assert_eq!(
+            paginate_query("SELECT g, count(*) FROM t GROUP BY g ORDER BY 2 DESC", 10, 20),
+            "SELECT * FROM (SELECT g, count(*) FROM t GROUP BY g ORDER BY 2 DESC\n) AS _page LIMIT 10 OFFSET 20"
+        );
+        assert_eq!(
+            paginate_query("SELECT * FROM t ORDER BY id FETCH FIRST 5 ROWS ONLY", 10, 0),
+            "SELECT * FROM t ORDER BY id FETCH FIRST 5 ROWS ONLY"
+        );
+    }
+
+    fn result(rows: Vec<serde_json::Value>) -> QueryResult {
+        let rows: Vec<_> = rows
+            .into_iter()
+            .map(|row| row.as_object().cloned().unwrap())
+            .collect();
+        QueryResult {
+            columns: vec!["id".to_string(), "name".to_string()],
+            column_metadata: Vec::new(),
+            row_count: rows.len(),
+            rows,
+            execution_time_ms: 0,
+            summary: None,
+        }
+    }
+
+    #[test]
+    fn diff_aligns_rows_by_key() {
+        let before = result(vec![
+            json!({"id": 1, "name": "a"}),
+            json!({"id": 2, "name": "b"}),
+            json!({"id": 3, "name": "c"}),
+        ]);
+        let after = result(vec![
+            json!({"id": 4, "name": "d"}),
+            json!({"id": 3, "name": "c"}),
+            json!({"id": 2, "name": "B"}),
+        ]);
+
+        let diff = diff_query_results(&before, &after, &["id".to_string()]).unwrap();
+        assert_eq!(diff.removed, vec![json!({"id": 1, "name": "a"}).as_object().cloned().unwrap()]);
+        assert_eq!(diff.added, vec![json!({"id": 4, "name": "d"}).as_object().cloned().unwrap()]);
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].changed_columns, vec!["name".to_string()]);
+        assert_eq!(diff.unchanged_count, 1);
+    }
+
+    #[test]
+    fn diff_matches_null_keys_by_content() {
+        let before = result(vec![json!({"id": null, "name": "x"}), json!({"id": null, "name": "y"})]);
+        let after = result(vec![json!({"id": null, "name": "y"}), json!({"id": null, "name": "z"})]);
+
+        let diff = diff_query_results(&before, &after, &["id".to_string()]).unwrap();
+        assert_eq!(diff.unchanged_count, 1);
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.added.len(), 1);
+        assert!(diff.changed.is_empty());
+
+        assert!(diff_query_results(&before, &after, &["missing".to_string()]).is_err());
+    }
+
+    #[test]
+    fn profile_query_picks_aggregates_by_type() {
+        assert_eq!(profile_kind("double precision", false), ProfileKind::Numeric);
+        assert_eq!(profile_kind("character varying", false), ProfileKind::Ordered);
+        assert_eq!(profile_kind("USER-DEFINED", true), ProfileKind::Ordered);
+        assert_eq!(profile_kind("jsonb", false), ProfileKind::Unordered);
+
+        let pg = build_profile_query("\"t\"", "\"c\"", ProfileKind::Unordered, &DatabaseType::PostgreSQL);
+        assert!(pg.contains("COUNT(DISTINCT \"c\"::text)"));
+        assert!(!pg.contains("MIN("));
+
+        let mysql = build_profile_query("`t`", "`c`", ProfileKind::Ordered, &DatabaseType::MySQL);
+        assert!(mysql.contains("CAST(MAX(`c`) AS CHAR)"));
+        assert!(!mysql.contains("AVG("));
+    }
+
+    #[test]
+    fn test_schema_qualified_table_names() {
+        assert_eq!(split_qualified_table("sales.orders"), (Some("sales"), "orders"));
+        assert_eq!(split_qualified_table("orders"), (None, "orders"));
+        assert_eq!(quote_table("sales.orders", &DatabaseType::PostgreSQL), "\"sales\".\"orders\"");
+        assert_eq!(quote_table("orders", &DatabaseType::PostgreSQL), "\"orders\"");
+        assert_eq!(quote_table("a.b", &DatabaseType::MySQL), "`a.b`");
+        assert_eq!(
+            extract_table_name("SELECT * FROM \"sales\".\"orders\" WHERE id = 1").as_deref(),
+            Some("sales.orders")
+        );
+    }
+
+    #[test]
+    fn test_column_metadata_from_schema() {
+        use crate::db::schema::{ColumnInfo, Table};
+        let table = Table::fixture(
+            "orders",
+            vec![
+                ColumnInfo::fixture("id").with_type("integer"),
+                ColumnInfo::fixture("user_id").with_type("integer").references("users", "id"),
+            ],
+        );
+
+        let (columns, metadata) = column_metadata_from_schema(&table);
+        assert_eq!(columns, vec!["id", "user_id"]);
+        assert!(metadata[0].foreign_key.is_none());
+        let fk = metadata[1].foreign_key.as_ref().unwrap();
+        assert_eq!((fk.referenced_table.as_str(), fk.referenced_column.as_str()), ("users", "id"));
+    }
+
+    #[test]
+    fn table_filter_is_validated_quoted_and_bound() {
+        use crate::db::schema::{ColumnInfo, Table};
+        let schema = crate::db::schema::Schema {
+            database_name: "shop".to_string(),
+            tables: vec![Table::fixture("orders", vec![ColumnInfo::fixture("id"), ColumnInfo::fixture("order")])],
+            routines: vec![],
+        };
+
+        assert_eq!(resolve_filter_column(&schema, "orders", "ORDER").unwrap(), "order");
+        assert!(resolve_filter_column(&schema, "orders", "id = 1 OR 1=1 --").is_err());
+        assert!(resolve_filter_column(&schema, "missing", "id").is_err());
+
+        let value = json!("O'Brien");
+        let (sql, bind) = table_page_query(
+            &DatabaseType::PostgreSQL,
+            "\"orders\"",
+            Some(("order", value.clone())),
+            50,
+            0,
+        );
+        assert_eq!(sql, "SELECT * FROM \"orders\" WHERE \"order\" = $1 LIMIT 50 OFFSET 0");
+        assert_eq!(bind, Some(value.clone()));
+
+        let (sql, bind) = table_page_query(&DatabaseType::MySQL, "`orders`", Some(("order", value)), 50, 100);
+        assert_eq!(sql, "SELECT * FROM `orders` WHERE `order` = ? LIMIT 50 OFFSET 100");
+        assert!(bind.is_some());
+
+        let (sql, bind) = table_page_query(&DatabaseType::MySQL, "`orders`", Some(("order", json!(null))), 10, 0);
+        assert_eq!(sql, "SELECT * FROM `orders` WHERE `order` IS NULL LIMIT 10 OFFSET 0");
+        assert!(bind.is_none());
+    }
+
+    #[test]
+    fn test_wrap_postgis_columns() {
+        let columns = vec![("id".to_string(), false), ("geom".to_string(), true)];
+        assert_eq!(
+            wrap_postgis_columns("SELECT * FROM parcels ORDER BY id DESC;", &columns).unwrap(),
+            "SELECT _q.\"id\", ST_AsText(_q.\"geom\") AS \"geom\" FROM (SELECT _i.*, row_number() OVER () AS _row_order \
+             FROM (SELECT * FROM parcels ORDER BY id DESC\n) AS _i) AS _q ORDER BY _q._row_order"
+        );
+
+        let plain = vec![("id".to_string(), false)];
+        assert!(wrap_postgis_columns("SELECT id FROM parcels", &plain).is_none());
+
+        let duplicated = vec![("id".to_string(), false), ("id".to_string(), false), ("geom".to_string(), true)];
+        assert!(wrap_postgis_columns("SELECT a.id, b.id, a.geom FROM a, b", &duplicated).is_none());
+    }
+
+    #[test]
+    fn pg_array_literal_quotes_and_keeps_nulls() {
+        let items = vec![
+            Some("plain".to_string()),
+            None,
+            Some("NULL".to_string()),
+            Some("a,b".to_string()),
+            Some("say \"hi\"".to_string()),
+            Some(String::new()),
+        ];
+        assert_eq!(
+            format_pg_array_literal(&items),
+            r#"{plain,NULL,"NULL","a,b","say \"hi\"",""}"#
+        );
+        assert_eq!(format_pg_array_literal(&[]), "{}");
+    }
+
+    #[test]
+    fn summary_skips_nulls_and_non_numeric_values() {
+        let meta = |name: &str, data_type: &str| ColumnMetadata {
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            enum_values: None,
+            foreign_key: None,
+        };
+        let result = QueryResult {
+            columns: vec!["qty".to_string(), "price".to_string(), "code".to_string()],
+            column_metadata: vec![meta("qty", "INT4"), meta("price", "numeric(10,2)"), meta("code", "TEXT")],
+            rows: vec![
+                json!({"qty": 2, "price": "10.50", "code": "7"}).as_object().unwrap().clone(),
+                json!({"qty": null, "price": "NaN", "code": null}).as_object().unwrap().clone(),
+                json!({"qty": 4, "price": "-0.50", "code": "x"}).as_object().unwrap().clone(),
+            ],
+            row_count: 3,
+            execution_time_ms: 0,
+            summary: None,
+        }
+        .with_summary();
+        let summary = result.summary.unwrap();
+
+        let qty = &summary["qty"];
+        assert_eq!((qty.count, qty.sum, qty.avg), (2, Some(6.0), Some(3.0)));
+        assert_eq!((qty.min, qty.max), (Some(2.0), Some(4.0)));
+
+        // "NaN" is counted as a value but left out of the arithmetic
+        let price = &summary["price"];
+        assert_eq!((price.count, price.sum, price.avg), (3, Some(10.0), Some(5.0)));
+        assert_eq!((price.min, price.max), (Some(-0.5), Some(10.5)));
+
+        // Digits in a text column are not numbers
+        assert_eq!(summary["code"], ColumnSummary { count: 2, ..Default::default() });
+        assert!(is_numeric_data_type("int unsigned"));
+        assert!(!is_numeric_data_type("MONEY"));
+    }
+
+    #[test]
+    fn pivot_orders_columns_by_first_appearance_and_fills_gaps_with_null() {
+        let long = QueryResult {
+            columns: vec!["region".to_string(), "month".to_string(), "sales".to_string()],
+            column_metadata: vec![],
+            rows: vec![
+                json!({"region": "north", "month": "Feb", "sales": 5}).as_object().unwrap().clone(),
+                json!({"region": "north", "month": "Jan", "sales": 3}).as_object().unwrap().clone(),
+                json!({"region": "south", "month": "Jan", "sales": "7.5"}).as_object().unwrap().clone(),
+                json!({"region": null, "month": null, "sales": 1}).as_object().unwrap().clone(),
+            ],
+            row_count: 4,
+            execution_time_ms: 0,
+            summary: None,
+        };
+
+        let wide = pivot_query_result(&long, "region", "month", "sales", PivotAggregate::Error).unwrap();
+        assert_eq!(wide.columns, vec!["region", "Feb", "Jan", "NULL"]);
+        assert_eq!(wide.row_count, 3);
+        assert_eq!(
+            serde_json::Value::Object(wide.rows[1].clone()),
+            json!({"region": "south", "Feb": null, "Jan": "7.5", "NULL": null})
+        );
+        assert_eq!(wide.rows[2]["region"], json!(null));
+        assert_eq!(wide.column_metadata.len(), wide.columns.len());
+
+        let mut duplicated = long.clone();
+        duplicated
+            .rows
+            .push(json!({"region": "north", "month": "Jan", "sales": 4}).as_object().unwrap().clone());
+        let err = pivot_query_result(&duplicated, "region", "month", "sales", PivotAggregate::Error).unwrap_err();
+        assert!(err.to_string().contains("region = north and month = Jan"));
+
+        let summed = pivot_query_result(&duplicated, "region", "month", "sales", PivotAggregate::Sum).unwrap();
+        assert_eq!(summed.rows[0]["Jan"], json!(7.0));
+        let counted = pivot_query_result(&duplicated, "region", "month", "sales", PivotAggregate::Count).unwrap();
+        assert_eq!(counted.rows[0]["Jan"], json!(2));
+        assert_eq!(counted.rows[1]["Feb"], json!(null));
+
+        assert!(pivot_query_result(&long, "region", "region", "sales", PivotAggregate::Error).is_err());
+        assert!(pivot_query_result(&long, "region", "month", "missing", PivotAggregate::Error).is_err());
+    }
+
+    #[test]
+    fn results_serialize_for_the_clipboard_without_breaking_cells() {
+        let result = QueryResult {
+            columns: vec!["id".to_string(), "note".to_string(), "extra".to_string()],
+            column_metadata: vec![],
+            rows: vec![
+                json!({"id": 1, "note": "tab\there\nline two", "extra": null}).as_object().unwrap().clone(),
+                json!({"id": 2, "note": "a|b, \"c\"", "extra": {"k": true}}).as_object().unwrap().clone(),
+            ],
+            row_count: 2,
+            execution_time_ms: 0,
+            summary: None,
+        };
+
+        assert_eq!(
+            serialize_query_result(&result, ResultTextFormat::Tsv).unwrap(),
+            "id\tnote\textra\n1\ttab\\there\\nline two\tNULL\n2\ta|b, \"c\"\t{\"k\":true}\n"
+        );
+        assert_eq!(
+            serialize_query_result(&result, ResultTextFormat::Csv).unwrap(),
+            "id,note,extra\n1,\"tab\there\nline two\",NULL\n2,\"a|b, \"\"c\"\"\",\"{\"\"k\"\":true}\"\n"
+        );
+        assert_eq!(
+            serialize_query_result(&result, ResultTextFormat::Markdown).unwrap(),
+            "| id | note | extra |\n| --- | --- | --- |\n| 1 | tab\there line two | NULL |\n| 2 | a\\|b, \"c\" | {\"k\":true} |\n"
+        );
+        let json: serde_json::Value =
+            serde_json::from_str(&serialize_query_result(&result, ResultTextFormat::Json).unwrap()).unwrap();
+        assert_eq!(json[1]["extra"]["k"], json!(true));
+    }
+
+    #[test]
+    fn pg_ranges_format_like_postgres() {
+        let range = |start, end| PgRange { start, end };
+        assert_eq!(format_pg_range(&range(Bound::Included(1), Bound::Excluded(10)), i32::to_string), "[1,10)");
+        assert_eq!(format_pg_range(&range(Bound::Included(1), Bound::Unbounded), i32::to_string), "[1,)");
+        assert_eq!(format_pg_range(&range(Bound::Unbounded, Bound::Included(5)), i32::to_string), "(,5]");
+        assert_eq!(format_pg_range(&range(Bound::<i32>::Unbounded, Bound::Unbounded), i32::to_string), "(,)");
+
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap().and_hms_opt(9, 30, 0).unwrap();
+        assert_eq!(
+            format_pg_range(&range(Bound::Included(start), Bound::Unbounded), NaiveDateTime::to_string),
+            r#"["2024-01-01 09:30:00",)"#
+        );
+        assert_eq!(quote_pg_range_bound(r#"a"b"#.to_string()), r#""a\"b""#);
+    }
+
+    #[test]
+    fn running_query_is_tracked_until_its_guard_drops() {
+        let guard = RunningQueryGuard::register("tab-1", "conn", 4242);
+        assert!(!guard.was_cancelled());
+
+        running_queries().get_mut("tab-1").unwrap().cancelled = true;
+        assert!(guard.was_cancelled());
+        assert_eq!(running_queries().get("tab-1").unwrap().backend_id, 4242);
+
+        drop(guard);
+        assert!(!running_queries().contains_key("tab-1"));
+        assert!(!RunningQueryGuard::default().was_cancelled());
+    }
 }
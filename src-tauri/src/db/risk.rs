@@ -0,0 +1,524 @@
+use crate::db::connection::{ConnectionManager, DatabaseType};
+use crate::db::query::{resolve_postgres_table, split_qualified_table};
+use crate::db::syntax_highlight::{split_statements, statement_shape, tokenize_plain, TokenType};
+use crate::error::AppResult;
+use serde::{Deserialize, Serialize};
+use sqlparser::ast::{
+    JoinConstraint, JoinOperator, Query, SelectItem, SetExpr, Statement, TableFactor,
+};
+use sqlparser::dialect::{MySqlDialect, PostgreSqlDialect};
+use sqlparser::parser::Parser;
+use sqlx::Row;
+use std::collections::HashMap;
+
+/// Tables with at least this many (estimated) rows are considered large
+const LARGE_TABLE_ROWS: f64 = 100_000.0;
+
+/// Tables with at least this many columns are considered wide
+const WIDE_TABLE_COLUMNS: i64 = 30;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RiskSeverity {
+    Info,
+    Warning,
+    Critical,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryRiskWarning {
+    /// Stable identifier for the rule (e.g. "cross_join", "missing_limit")
+    pub code: String,
+    pub severity: RiskSeverity,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryRiskReport {
+    pub warnings: Vec<QueryRiskWarning>,
+    /// Planner row estimate for the whole query, when EXPLAIN succeeded
+    pub estimated_rows: Option<f64>,
+}
+
+/// Facts gathered from the parsed query, before consulting the database
+#[derive(Debug, Default)]
+struct StaticAnalysis {
+    warnings: Vec<QueryRiskWarning>,
+    tables: Vec<String>,
+    star_tables: Vec<String>,
+    has_limit: bool,
+    is_query: bool,
+}
+
+/// Statistics for a referenced table
+struct TableStats {
+    estimated_rows: f64,
+    column_count: i64,
+}
+
+/// Analyze a query for patterns that are likely to be expensive before it runs
+pub async fn analyze_query_risk(
+    manager: &ConnectionManager,
+    connection_id: &str,
+    query: &str,
+) -> AppResult<QueryRiskReport> {
+    let conn = manager.get_connection(connection_id)?;
+    let mut analysis = analyze_statement(query, &conn.database_type);
+
+    if !analysis.is_query {
+        return Ok(QueryRiskReport {
+            warnings: analysis.warnings,
+            estimated_rows: None,
+        });
+    }
+
+    let sql = query.trim().trim_end_matches(';');
+    let (estimated_rows, table_stats) = match conn.database_type {
+        DatabaseType::PostgreSQL => {
+            let pool = manager.get_pool_postgres(connection_id).await?;
+            let estimate = explain_postgres(&pool, sql).await.ok();
+            let stats = postgres_table_stats(&pool, &analysis.tables, &conn.search_path())
+                .await
+                .unwrap_or_default();
+            (estimate, stats)
+        }
+        DatabaseType::MariaDB | DatabaseType::MySQL => {
+            let pool = manager.get_pool_mysql(connection_id).await?;
+            let estimate = explain_mysql(&pool, sql).await.ok();
+            let stats = mysql_table_stats(&pool, &analysis.tables).await.unwrap_or_default();
+            (estimate, stats)
+        }
+    };
+
+    // Unbounded reads of large tables
+    if !analysis.has_limit {
+        let large_tables: Vec<&String> = analysis
+            .tables
+            .iter()
+            .filter(|t| {
+                table_stats
+                    .get(*t)
+                    .map(|s| s.estimated_rows >= LARGE_TABLE_ROWS)
+                    .unwrap_or(false)
+            })
+            .collect();
+
+        if !large_tables.is_empty() || estimated_rows.unwrap_or(0.0) >= LARGE_TABLE_ROWS {
+            let subject = if large_tables.is_empty() {
+                "the result".to_string()
+            } else {
+                large_tables
+                    .iter()
+                    .map(|t| t.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            analysis.warnings.push(QueryRiskWarning {
+                code: "missing_limit".to_string(),
+                severity: RiskSeverity::Warning,
+                message: format!(
+                    "Query has no LIMIT and {} is large{}",
+                    subject,
+                    estimated_rows
+                        .map(|r| format!(" (~{} rows estimated)", r.round() as u64))
+                        .unwrap_or_default()
+                ),
+            });
+        }
+    }
+
+    // SELECT * against wide tables
+    for table in &analysis.star_tables {
+        if let Some(stats) = table_stats.get(table) {
+            if stats.column_count >= WIDE_TABLE_COLUMNS {
+                analysis.warnings.push(QueryRiskWarning {
+                    code: "select_star_wide_table".to_string(),
+                    severity: RiskSeverity::Info,
+                    message: format!(
+                        "SELECT * on '{}' returns {} columns; consider selecting only the columns you need",
+                        table, stats.column_count
+                    ),
+                });
+            }
+        }
+    }
+
+    // Attach the planner estimate to cross join warnings so the impact is visible
+    if let Some(rows) = estimated_rows {
+        for warning in analysis.warnings.iter_mut().filter(|w| w.code == "cross_join") {
+            warning.message.push_str(&format!(" (~{} rows estimated)", rows.round() as u64));
+        }
+    }
+
+    Ok(QueryRiskReport {
+        warnings: analysis.warnings,
+        estimated_rows,
+    })
+}
+
+/// Parse the query and collect structural warnings (join conditions, wildcards, LIMIT)
+fn analyze_statement(query: &str, db_type: &DatabaseType) -> StaticAnalysis {
+    let mut analysis = StaticAnalysis::default();
+
+    let parsed = match db_type {
+        DatabaseType::PostgreSQL => Parser::parse_sql(&PostgreSqlDialect {}, query),
+        DatabaseType::MariaDB | DatabaseType::MySQL => Parser::parse_sql(&MySqlDialect {}, query),
+    };
+
+    match parsed {
+        Ok(statements) => {
+            // Only single read queries are explained; anything else gets structural checks only
+            analysis.is_query = statements.len() == 1 && matches!(statements[0], Statement::Query(_));
+            analysis.has_limit = true;
+
+            for statement in &statements {
+                if let Statement::Query(query) = statement {
+                    analysis.has_limit &= query.limit.is_some() || query.fetch.is_some();
+                    walk_query(query, &mut analysis);
+                }
+            }
+        }
+        Err(e) => {
+            analysis.warnings.push(QueryRiskWarning {
+                code: "unparsed".to_string(),
+                severity: RiskSeverity::Info,
+                message: format!("Query could not be fully analyzed, so only keyword checks ran: {}", e),
+            });
+            scan_tokens(query, &mut analysis);
+        }
+    }
+
+    analysis.tables.sort();
+    analysis.tables.dedup();
+    analysis.star_tables.sort();
+    analysis.star_tables.dedup();
+    analysis
+}
+
+/// Keyword-level fallback for queries the parser rejects, such as dialect extensions
+/// it doesn't know: tables after FROM/JOIN, CROSS JOIN, `SELECT *` and LIMIT
+fn scan_tokens(query: &str, analysis: &mut StaticAnalysis) {
+    let tokens: Vec<_> = tokenize_plain(query)
+        .into_iter()
+        .filter(|t| t.token_type != TokenType::Comment && !t.value.trim().is_empty())
+        .collect();
+    let words: Vec<String> = tokens.iter().map(|t| t.value.to_uppercase()).collect();
+    let word = |i: usize| words.get(i).map(String::as_str);
+
+    let writes = words.iter().any(|w| matches!(w.as_str(), "INSERT" | "UPDATE" | "DELETE" | "MERGE"));
+    analysis.is_query = split_statements(query).len() == 1
+        && (word(0) == Some("SELECT") || (word(0) == Some("WITH") && !writes));
+    analysis.has_limit = statement_shape(query).is_limited();
+
+    let name_at = |i: usize| {
+        tokens
+            .get(i)
+            .filter(|t| t.token_type == TokenType::Text && t.value.starts_with(|c: char| c.is_alphabetic() || c == '_' || c == '"' || c == '`'))
+            .map(|t| t.value.trim_matches(['"', '`']).to_string())
+    };
+
+    let mut tables = Vec::new();
+    for i in 0..tokens.len() {
+        match word(i) {
+            Some("FROM") | Some("JOIN") => {
+                if let Some(name) = name_at(i + 1) {
+                    match (word(i + 2), name_at(i + 3)) {
+                        (Some("."), Some(table)) => tables.push(format!("{}.{}", name, table)),
+                        _ => tables.push(name),
+                    }
+                }
+            }
+            Some("CROSS") if word(i + 1) == Some("JOIN") => analysis.warnings.push(QueryRiskWarning {
+                code: "cross_join".to_string(),
+                severity: RiskSeverity::Warning,
+                message: "CROSS JOIN produces a cartesian product".to_string(),
+            }),
+            _ => {}
+        }
+    }
+
+    if (0..words.len()).any(|i| word(i) == Some("SELECT") && word(i + 1) == Some("*")) {
+        analysis.star_tables.extend(tables.iter().cloned());
+    }
+    analysis.tables.extend(tables);
+}
+
+fn walk_query(query: &Query, analysis: &mut StaticAnalysis) {
+    if let Some(with) = &query.with {
+        for cte in &with.cte_tables {
+            walk_query(&cte.query, analysis);
+        }
+    }
+    walk_set_expr(&query.body, analysis);
+}
+
+fn walk_set_expr(body: &SetExpr, analysis: &mut StaticAnalysis) {
+    match body {
+        SetExpr::Select(select) => {
+            let mut select_tables = Vec::new();
+
+            for table_with_joins in &select.from {
+                collect_table(&table_with_joins.relation, &mut select_tables, analysis);
+
+                for join in &table_with_joins.joins {
+                    let left = select_tables.last().cloned().unwrap_or_else(|| "?".to_string());
+                    collect_table(&join.relation, &mut select_tables, analysis);
+                    let right = table_name(&join.relation).unwrap_or_else(|| "subquery".to_string());
+
+                    match &join.join_operator {
+                        JoinOperator::CrossJoin => analysis.warnings.push(QueryRiskWarning {
+                            code: "cross_join".to_string(),
+                            severity: RiskSeverity::Warning,
+                            message: format!(
+                                "CROSS JOIN between '{}' and '{}' produces a cartesian product",
+                                left, right
+                            ),
+                        }),
+                        JoinOperator::Inner(JoinConstraint::None) => {
+                            analysis.warnings.push(QueryRiskWarning {
+                                code: "cross_join".to_string(),
+                                severity: RiskSeverity::Critical,
+                                message: format!(
+                                    "JOIN between '{}' and '{}' has no ON/USING condition and produces a cartesian product",
+                                    left, right
+                                ),
+                            })
+                        }
+                        _ => {}
+                    }
+                }
+            }
+
+            // Comma-separated FROM list without any WHERE clause
+            if select.from.len() > 1 && select.selection.is_none() {
+                let names: Vec<String> = select
+                    .from
+                    .iter()
+                    .map(|t| table_name(&t.relation).unwrap_or_else(|| "subquery".to_string()))
+                    .collect();
+                analysis.warnings.push(QueryRiskWarning {
+                    code: "cross_join".to_string(),
+                    severity: RiskSeverity::Critical,
+                    message: format!(
+                        "Tables {} are listed without a WHERE condition and produce a cartesian product",
+                        names.join(", ")
+                    ),
+                });
+            }
+
+            if select.projection.iter().any(|item| matches!(item, SelectItem::Wildcard(_))) {
+                analysis.star_tables.extend(select_tables.iter().cloned());
+            }
+
+            analysis.tables.extend(select_tables);
+        }
+        SetExpr::Query(query) => walk_query(query, analysis),
+        SetExpr::SetOperation { left, right, .. } => {
+            walk_set_expr(left, analysis);
+            walk_set_expr(right, analysis);
+        }
+        _ => {}
+    }
+}
+
+fn collect_table(factor: &TableFactor, tables: &mut Vec<String>, analysis: &mut StaticAnalysis) {
+    match factor {
+        TableFactor::Table { .. } => {
+            if let Some(name) = table_name(factor) {
+                tables.push(name);
+            }
+        }
+        TableFactor::Derived { subquery, .. } => walk_query(subquery, analysis),
+        _ => {}
+    }
+}
+
+/// Table name for a table factor, keeping a `schema.table` qualifier (catalog prefix dropped)
+fn table_name(factor: &TableFactor) -> Option<String> {
+    match factor {
+        TableFactor::Table { name, .. } => {
+            let parts: Vec<&str> = name.0.iter().rev().take(2).map(|ident| ident.value.as_str()).collect();
+            match parts.as_slice() {
+                [table, schema] => Some(format!("{}.{}", schema, table)),
+                [table] => Some(table.to_string()),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Ask the PostgreSQL planner for the estimated row count of a query
+async fn explain_postgres(pool: &sqlx::PgPool, sql: &str) -> AppResult<f64> {
+    let plan: serde_json::Value = sqlx::query_scalar(&format!("EXPLAIN (FORMAT JSON) {}", sql))
+        .fetch_one(pool)
+        .await?;
+
+    Ok(plan[0]["Plan"]["Plan Rows"].as_f64().unwrap_or(0.0))
+}
+
+/// Ask the MySQL optimizer for row estimates; nested-loop joins multiply per-table rows
+async fn explain_mysql(pool: &sqlx::MySqlPool, sql: &str) -> AppResult<f64> {
+    let rows = sqlx::query(&format!("EXPLAIN {}", sql)).fetch_all(pool).await?;
+
+    let mut estimate = 1.0;
+    for row in &rows {
+        let table_rows = row
+            .try_get::<Option<u64>, _>("rows")
+            .map(|v| v.map(|n| n as f64))
+            .or_else(|_| row.try_get::<Option<i64>, _>("rows").map(|v| v.map(|n| n as f64)))
+            .unwrap_or(None);
+        if let Some(n) = table_rows {
+            estimate *= n.max(1.0);
+        }
+    }
+
+    Ok(estimate)
+}
+
+/// Statistics keyed by the table names as the query wrote them; unqualified names
+/// resolve through the connection's schema search path
+async fn postgres_table_stats(
+    pool: &sqlx::PgPool,
+    tables: &[String],
+    search_path: &[String],
+) -> AppResult<HashMap<String, TableStats>> {
+    let mut stats = HashMap::with_capacity(tables.len());
+    for name in tables {
+        let (schema, table) = resolve_postgres_table(pool, name, search_path).await;
+        let row = sqlx::query(
+            r#"
+            SELECT
+                c.reltuples::FLOAT8 as estimated_rows,
+                (SELECT COUNT(*) FROM pg_attribute a
+                 WHERE a.attrelid = c.oid AND a.attnum > 0 AND NOT a.attisdropped) as column_count
+            FROM pg_class c
+            JOIN pg_namespace n ON n.oid = c.relnamespace
+            WHERE n.nspname = $1
+                AND c.relkind IN ('r', 'p', 'v', 'm')
+                AND c.relname = $2
+            "#,
+        )
+        .bind(&schema)
+        .bind(table)
+        .fetch_optional(pool)
+        .await?;
+
+        if let Some(row) = row {
+            stats.insert(
+                name.clone(),
+                TableStats {
+                    // reltuples is -1 for tables that have never been analyzed
+                    estimated_rows: row.try_get::<f64, _>("estimated_rows")?.max(0.0),
+                    column_count: row.try_get("column_count")?,
+                },
+            );
+        }
+    }
+
+    Ok(stats)
+}
+
+async fn mysql_table_stats(
+    pool: &sqlx::MySqlPool,
+    tables: &[String],
+) -> AppResult<HashMap<String, TableStats>> {
+    let rows = sqlx::query(
+        r#"
+        SELECT
+            t.TABLE_NAME as table_name,
+            t.TABLE_ROWS as estimated_rows,
+            (SELECT COUNT(*) FROM information_schema.COLUMNS c
+             WHERE c.TABLE_SCHEMA = t.TABLE_SCHEMA AND c.TABLE_NAME = t.TABLE_NAME) as column_count
+        FROM information_schema.TABLES t
+        WHERE t.TABLE_SCHEMA = DATABASE()
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut stats = HashMap::new();
+    for row in rows {
+        let name: String = row.try_get("table_name")?;
+        if !tables.iter().any(|t| split_qualified_table(t).1.eq_ignore_ascii_case(&name)) {
+            continue;
+        }
+
+        let estimated_rows = row
+            .try_get::<Option<u64>, _>("estimated_rows")
+            .ok()
+            .flatten()
+            .unwrap_or(0) as f64;
+        let column_count: i64 = row.try_get("column_count")?;
+
+        // Key by the name as written in the query so lookups line up
+        let key = tables
+            .iter()
+            .find(|t| split_qualified_table(t).1.eq_ignore_ascii_case(&name))
+            .cloned()
+            .unwrap_or(name);
+        stats.insert(key, TableStats { estimated_rows, column_count });
+    }
+
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codes(query: &str) -> Vec<String> {
+        analyze_statement(query, &DatabaseType::PostgreSQL)
+            .warnings
+            .into_iter()
+            .map(|w| w.code)
+            .collect()
+    }
+
+    #[test]
+    fn test_comma_join_without_where_is_flagged() {
+        assert_eq!(codes("SELECT * FROM users, orders"), vec!["cross_join"]);
+        assert!(codes("SELECT * FROM users u, orders o WHERE u.id = o.user_id").is_empty());
+    }
+
+    #[test]
+    fn test_join_without_condition_is_flagged() {
+        assert_eq!(codes("SELECT * FROM users CROSS JOIN orders"), vec!["cross_join"]);
+        assert!(codes("SELECT * FROM users JOIN orders ON users.id = orders.user_id").is_empty());
+    }
+
+    #[test]
+    fn test_collects_tables_and_limit() {
+        let analysis = analyze_statement(
+            "SELECT * FROM users WHERE id IN (SELECT user_id FROM orders) LIMIT 10",
+            &DatabaseType::PostgreSQL,
+        );
+        assert!(analysis.is_query);
+        assert!(analysis.has_limit);
+        assert_eq!(analysis.star_tables, vec!["users".to_string()]);
+
+        let analysis = analyze_statement("SELECT id FROM users", &DatabaseType::MySQL);
+        assert!(!analysis.has_limit);
+        assert!(analysis.star_tables.is_empty());
+    }
+
+    #[test]
+    fn test_repeated_tables_are_deduplicated() {
+        let analysis = analyze_statement(
+            "SELECT * FROM users a JOIN orders o ON o.user_id = a.id JOIN users b ON b.id = o.user_id",
+            &DatabaseType::PostgreSQL,
+        );
+        assert_eq!(analysis.tables, vec!["orders".to_string(), "users".to_string()]);
+    }
+
+    #[test]
+    fn test_unparsed_query_falls_back_to_token_scan() {
+        let analysis = analyze_statement("SELECT * FROM sales.orders CROSS JOIN users WHERE", &DatabaseType::PostgreSQL);
+        let codes: Vec<&str> = analysis.warnings.iter().map(|w| w.code.as_str()).collect();
+        assert_eq!(codes, vec!["unparsed", "cross_join"]);
+        assert!(analysis.is_query);
+        assert!(!analysis.has_limit);
+        assert_eq!(analysis.tables, vec!["sales.orders".to_string(), "users".to_string()]);
+        assert_eq!(analysis.star_tables, analysis.tables);
+    }
+}
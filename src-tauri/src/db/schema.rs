@@ -11,10 +11,235 @@ use std::time::Duration;
 /// Timeout for loading individual table metadata (30 seconds)
 const TABLE_QUERY_TIMEOUT: Duration = Duration::from_secs(30);
 
+/// Upper bound on routines loaded for the AI schema context
+const ROUTINE_LIMIT: i64 = 500;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Schema {
     pub database_name: String,
     pub tables: Vec<Table>,
+    /// Stored functions and procedures; only loaded when enabled in settings
+    #[serde(default)]
+    pub routines: Vec<RoutineInfo>,
+}
+
+impl Schema {
+    /// Hash of the schema structure (tables, columns, types, keys, indexes, routines).
+    /// Changes whenever DDL or an import alters the schema, so cached LLM output
+    /// tied to the old shape is not reused.
+    pub fn fingerprint(&self) -> String {
+        use std::hash::{Hash, Hasher};
+
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        self.database_name.hash(&mut hasher);
+        for table in &self.tables {
+            table.name.hash(&mut hasher);
+            table.schema.hash(&mut hasher);
+            table.is_view.hash(&mut hasher);
+            for col in &table.columns {
+                col.name.hash(&mut hasher);
+                col.data_type.hash(&mut hasher);
+                col.is_nullable.hash(&mut hasher);
+                col.is_primary_key.hash(&mut hasher);
+                col.foreign_key_table.hash(&mut hasher);
+                col.foreign_key_column.hash(&mut hasher);
+                col.enum_values.hash(&mut hasher);
+            }
+            for idx in &table.indexes {
+                idx.name.hash(&mut hasher);
+                idx.columns.hash(&mut hasher);
+            }
+        }
+        for routine in &self.routines {
+            routine.prompt_signature().hash(&mut hasher);
+        }
+        format!("{:016x}", hasher.finish())
+    }
+
+    /// Look up a table by its qualified name, falling back to the bare name (case-insensitive)
+    pub fn find_table(&self, name: &str) -> Option<&Table> {
+        self.tables
+            .iter()
+            .find(|t| t.qualified_name().eq_ignore_ascii_case(name))
+            .or_else(|| self.tables.iter().find(|t| t.name.eq_ignore_ascii_case(name)))
+    }
+
+    /// Routine section for LLM prompts (empty when no routines were loaded)
+    pub fn prompt_routines(&self) -> String {
+        if self.routines.is_empty() {
+            return String::new();
+        }
+
+        let mut output = String::from("\nRoutines (callable from SQL):\n");
+        for routine in &self.routines {
+            output.push_str(&format!("  - {}\n", routine.prompt_signature()));
+        }
+        output
+    }
+}
+
+/// Text format for an entity-relationship diagram
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ErDiagramFormat {
+    /// Graphviz `digraph` with one record node per table
+    Dot,
+    /// Mermaid `erDiagram`
+    Mermaid,
+}
+
+impl Schema {
+    /// Entity-relationship diagram with every table as a node and each foreign key
+    /// column as an edge to the table it references. Tables without relationships
+    /// are still listed; column labels carry PK/FK markers.
+    pub fn er_diagram(&self, format: ErDiagramFormat) -> String {
+        match format {
+            ErDiagramFormat::Dot => self.er_diagram_dot(),
+            ErDiagramFormat::Mermaid => self.er_diagram_mermaid(),
+        }
+    }
+
+    fn er_diagram_dot(&self) -> String {
+        let mut output = String::from("digraph schema {\n    rankdir=LR;\n    node [shape=record];\n");
+
+        for table in &self.tables {
+            let columns: String = table
+                .columns
+                .iter()
+                .map(|col| {
+                    let label = format!("{}: {}{}", col.name, col.data_type, key_markers(col, " "));
+                    format!("{}\\l", escape_dot_record(&label))
+                })
+                .collect();
+            output.push_str(&format!(
+                "    \"{}\" [label=\"{{{}|{}}}\"];\n",
+                escape_dot_id(&table.qualified_name()),
+                escape_dot_record(&table.qualified_name()),
+                columns
+            ));
+        }
+
+        for table in &self.tables {
+            for col in &table.columns {
+                if let Some(target) = &col.foreign_key_table {
+                    let label = match &col.foreign_key_column {
+                        Some(target_col) => format!("{} -> {}", col.name, target_col),
+                        None => col.name.clone(),
+                    };
+                    output.push_str(&format!(
+                        "    \"{}\" -> \"{}\" [label=\"{}\"];\n",
+                        escape_dot_id(&table.qualified_name()),
+                        escape_dot_id(target),
+                        escape_dot_id(&label)
+                    ));
+                }
+            }
+        }
+
+        output.push_str("}\n");
+        output
+    }
+
+    fn er_diagram_mermaid(&self) -> String {
+        let mut output = String::from("erDiagram\n");
+
+        for table in &self.tables {
+            let entity = mermaid_identifier(&table.qualified_name());
+            if table.columns.is_empty() {
+                output.push_str(&format!("    {}\n", entity));
+                continue;
+            }
+            output.push_str(&format!("    {} {{\n", entity));
+            for col in &table.columns {
+                output.push_str(&format!(
+                    "        {} {}{}\n",
+                    mermaid_identifier(&col.data_type),
+                    mermaid_identifier(&col.name),
+                    key_markers(col, ", ")
+                ));
+            }
+            output.push_str("    }\n");
+        }
+
+        for table in &self.tables {
+            for col in &table.columns {
+                if let Some(target) = &col.foreign_key_table {
+                    // Many rows reference one parent; a nullable key makes the parent optional
+                    let parent = if col.is_nullable { "o|" } else { "||" };
+                    output.push_str(&format!(
+                        "    {} }}o--{} {} : \"{}\"\n",
+                        mermaid_identifier(&table.qualified_name()),
+                        parent,
+                        mermaid_identifier(target),
+                        col.name.replace('"', "'")
+                    ));
+                }
+            }
+        }
+
+        output
+    }
+}
+
+/// ` PK`, ` FK` or ` PK<sep>FK` suffix for a column label
+fn key_markers(col: &ColumnInfo, separator: &str) -> String {
+    let mut markers = Vec::new();
+    if col.is_primary_key {
+        markers.push("PK");
+    }
+    if col.is_foreign_key {
+        markers.push("FK");
+    }
+    if markers.is_empty() {
+        String::new()
+    } else {
+        format!(" {}", markers.join(separator))
+    }
+}
+
+/// Escape a quoted DOT identifier or edge label
+fn escape_dot_id(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Escape text inside a DOT record label, where braces, bars and angle brackets are structural
+fn escape_dot_record(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '\\' | '"' | '{' | '}' | '|' | '<' | '>') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Mermaid entity, type and attribute names are single words; other characters become `_`
+fn mermaid_identifier(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| if c.is_alphanumeric() || matches!(c, '_' | '-' | '(' | ')' | '[' | ']') { c } else { '_' })
+        .collect()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoutineInfo {
+    pub name: String,
+    pub schema: Option<String>,
+    pub routine_type: String,    // FUNCTION, PROCEDURE
+    pub return_type: Option<String>,
+    pub parameters: String,      // e.g. "user_id integer, since date"
+}
+
+impl RoutineInfo {
+    /// Signature for LLM prompts, e.g. `FUNCTION total_spent(user_id integer) RETURNS numeric`
+    pub fn prompt_signature(&self) -> String {
+        let mut signature = format!("{} {}({})", self.routine_type, self.name, self.parameters);
+        if let Some(return_type) = &self.return_type {
+            signature.push_str(&format!(" RETURNS {}", return_type));
+        }
+        signature
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,6 +258,74 @@ pub struct Table {
     pub indexes: Vec<IndexInfo>,
     pub triggers: Vec<TriggerInfo>,
     pub constraints: Vec<ConstraintInfo>,
+    /// True for views and materialized views (read-only)
+    #[serde(default)]
+    pub is_view: bool,
+    /// `COMMENT ON TABLE` / `TABLE_COMMENT` text
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+impl Table {
+    /// Name to reference the table by in SQL: `schema.table` outside PostgreSQL's `public`
+    pub fn qualified_name(&self) -> String {
+        match self.schema.as_deref() {
+            Some(schema) if schema != "public" => format!("{}.{}", schema, self.name),
+            _ => self.name.clone(),
+        }
+    }
+
+    /// Table heading for LLM prompts, marking views as read-only
+    pub fn prompt_label(&self) -> String {
+        if self.is_view {
+            format!("{} (VIEW, read-only)", self.qualified_name())
+        } else {
+            self.qualified_name()
+        }
+    }
+
+    /// ` -- comment` suffix for the table heading in LLM prompts; empty without a comment
+    pub fn prompt_comment(&self) -> String {
+        prompt_comment(self.comment.as_deref())
+    }
+
+    /// Compact index summary for LLM prompts, e.g. `PK(id); UNIQUE(email); orders_user_date(user_id, created_at)`.
+    /// Columns are listed in index order so the leading column is visible.
+    pub fn prompt_indexes(&self) -> String {
+        self.indexes
+            .iter()
+            .filter(|idx| !idx.columns.is_empty())
+            .map(|idx| {
+                let columns = idx.columns.join(", ");
+                if idx.is_primary {
+                    format!("PK({})", columns)
+                } else if idx.is_unique {
+                    format!("UNIQUE {}({})", idx.name, columns)
+                } else {
+                    format!("{}({})", idx.name, columns)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+#[cfg(test)]
+impl Table {
+    /// Test fixture: a plain table with `columns` and no schema, indexes or constraints
+    pub(crate) fn fixture(name: &str, columns: Vec<ColumnInfo>) -> Self {
+        Self {
+            name: name.to_string(),
+            schema: None,
+            row_count: None,
+            columns,
+            indexes: vec![],
+            triggers: vec![],
+            constraints: vec![],
+            is_view: false,
+            comment: None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -46,6 +339,108 @@ pub struct ColumnInfo {
     pub foreign_key_column: Option<String>,
     pub default_value: Option<String>,
     pub character_maximum_length: Option<i32>,
+    /// Allowed values for enum-typed columns
+    #[serde(default)]
+    pub enum_values: Option<Vec<String>>,
+    /// `COMMENT ON COLUMN` / `COLUMN_COMMENT` text
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+/// Comment text longer than this is cut short in LLM prompts
+const PROMPT_COMMENT_LIMIT: usize = 200;
+
+/// ` -- comment` on a single line, shortened to `PROMPT_COMMENT_LIMIT` characters
+fn prompt_comment(comment: Option<&str>) -> String {
+    let Some(comment) = comment else {
+        return String::new();
+    };
+    let single_line = comment.split_whitespace().collect::<Vec<_>>().join(" ");
+    if single_line.is_empty() {
+        return String::new();
+    }
+    if single_line.chars().count() > PROMPT_COMMENT_LIMIT {
+        let shortened: String = single_line.chars().take(PROMPT_COMMENT_LIMIT).collect();
+        format!(" -- {}...", shortened.trim_end())
+    } else {
+        format!(" -- {}", single_line)
+    }
+}
+
+/// Databases report a missing comment as NULL (PostgreSQL) or an empty string (MySQL)
+fn non_empty_comment(comment: Option<String>) -> Option<String> {
+    comment.filter(|c| !c.trim().is_empty())
+}
+
+/// Enum values listed inline in LLM prompts before the list is elided
+const PROMPT_ENUM_VALUE_LIMIT: usize = 20;
+
+impl ColumnInfo {
+    /// Column type for LLM prompts, listing allowed values for enum columns
+    pub fn prompt_type(&self) -> String {
+        match &self.enum_values {
+            Some(values) if !values.is_empty() => {
+                let mut listed: Vec<String> = values
+                    .iter()
+                    .take(PROMPT_ENUM_VALUE_LIMIT)
+                    .map(|v| format!("'{}'", v.replace('\'', "''")))
+                    .collect();
+                if values.len() > PROMPT_ENUM_VALUE_LIMIT {
+                    listed.push("...".to_string());
+                }
+                format!("{} IN ({})", self.data_type, listed.join(", "))
+            }
+            _ => self.data_type.clone(),
+        }
+    }
+
+    /// ` -- comment` suffix for the column's line in LLM prompts; empty without a comment
+    pub fn prompt_comment(&self) -> String {
+        prompt_comment(self.comment.as_deref())
+    }
+}
+
+/// Test fixture builder: `ColumnInfo::fixture("user_id").with_type("integer").references("users", "id")`
+#[cfg(test)]
+impl ColumnInfo {
+    /// A nullable `text` column that is not part of any key
+    pub(crate) fn fixture(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            data_type: "text".to_string(),
+            is_nullable: true,
+            is_primary_key: false,
+            is_foreign_key: false,
+            foreign_key_table: None,
+            foreign_key_column: None,
+            default_value: None,
+            character_maximum_length: None,
+            enum_values: None,
+            comment: None,
+        }
+    }
+
+    pub(crate) fn with_type(mut self, data_type: &str) -> Self {
+        self.data_type = data_type.to_string();
+        self
+    }
+
+    pub(crate) fn not_null(mut self) -> Self {
+        self.is_nullable = false;
+        self
+    }
+
+    pub(crate) fn primary_key(mut self) -> Self {
+        self.is_primary_key = true;
+        self
+    }
+
+    pub(crate) fn references(mut self, table: &str, column: &str) -> Self {
+        self.is_foreign_key = true;
+        self.foreign_key_table = Some(table.to_string());
+        self.foreign_key_column = Some(column.to_string());
+        self
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -74,19 +469,52 @@ pub struct ConstraintInfo {
     pub referenced_columns: Option<Vec<String>>,
 }
 
+/// How long an introspected schema is reused before the database is queried again
+pub const SCHEMA_CACHE_TTL: Duration = Duration::from_secs(300);
+
+/// Schema for a connection, served from the connection manager's cache when fresh.
+/// A cache miss introspects the database and streams `schema-load-progress` events.
 pub async fn get_schema(
     manager: &ConnectionManager,
     connection_id: &str,
     app: &AppHandle,
 ) -> AppResult<Schema> {
+    if let Some(schema) = manager.cached_schema(connection_id, SCHEMA_CACHE_TTL) {
+        return Ok(schema);
+    }
+
     let conn = manager.get_connection(connection_id)?;
 
-    match conn.database_type {
-        DatabaseType::PostgreSQL => get_postgres_schema(manager, connection_id, &conn, app).await,
+    let schema = match conn.database_type {
+        DatabaseType::PostgreSQL => get_postgres_schema(manager, connection_id, &conn, app).await?,
         DatabaseType::MariaDB | DatabaseType::MySQL => {
-            get_mysql_schema(manager, connection_id, &conn, app).await
+            get_mysql_schema(manager, connection_id, &conn, app).await?
         }
-    }
+    };
+
+    manager.cache_schema(connection_id, schema.clone());
+    Ok(schema)
+}
+
+/// ER diagram text for a connection's (cached) schema
+pub async fn generate_er_diagram(
+    manager: &ConnectionManager,
+    connection_id: &str,
+    app: &AppHandle,
+    format: ErDiagramFormat,
+) -> AppResult<String> {
+    let schema = get_schema(manager, connection_id, app).await?;
+    Ok(schema.er_diagram(format))
+}
+
+/// Discard any cached schema and introspect the database again
+pub async fn refresh_schema(
+    manager: &ConnectionManager,
+    connection_id: &str,
+    app: &AppHandle,
+) -> AppResult<Schema> {
+    manager.invalidate_schema(connection_id);
+    get_schema(manager, connection_id, app).await
 }
 
 async fn get_postgres_schema(
@@ -97,18 +525,32 @@ async fn get_postgres_schema(
 ) -> AppResult<Schema> {
     let pool = manager.get_pool_postgres(connection_id).await?;
 
-    // Get all tables in public schema
+    // Get all tables, views and materialized views in the configured schemas
+    // (materialized views are not listed in information_schema)
     let tables_query = r#"
         SELECT
-            t.table_name,
-            t.table_schema
+            t.table_name::TEXT as table_name,
+            t.table_schema::TEXT as table_schema,
+            CASE WHEN t.table_type = 'VIEW' THEN 'view' ELSE 'table' END as relation_kind,
+            obj_description(format('%I.%I', t.table_schema, t.table_name)::regclass, 'pg_class') as table_comment
         FROM information_schema.tables t
-        WHERE t.table_schema = 'public'
-        AND t.table_type = 'BASE TABLE'
-        ORDER BY t.table_name
+        WHERE t.table_schema = ANY($1)
+        AND t.table_type IN ('BASE TABLE', 'VIEW')
+        UNION ALL
+        SELECT
+            m.matviewname::TEXT as table_name,
+            m.schemaname::TEXT as table_schema,
+            'materialized_view' as relation_kind,
+            obj_description(format('%I.%I', m.schemaname, m.matviewname)::regclass, 'pg_class') as table_comment
+        FROM pg_matviews m
+        WHERE m.schemaname = ANY($1)
+        ORDER BY table_schema, table_name
     "#;
 
-    let table_rows = sqlx::query(tables_query).fetch_all(&pool).await?;
+    let table_rows = sqlx::query(tables_query)
+        .bind(conn.search_path())
+        .fetch_all(&pool)
+        .await?;
     let total_tables = table_rows.len();
     let loaded_count = Arc::new(AtomicUsize::new(0));
 
@@ -119,6 +561,8 @@ async fn get_postgres_schema(
             let pool = pool.clone();
             let table_name: String = table_row.try_get("table_name").unwrap();
             let table_schema: String = table_row.try_get("table_schema").unwrap();
+            let relation_kind: String = table_row.try_get("relation_kind").unwrap();
+            let comment = non_empty_comment(table_row.try_get("table_comment").ok().flatten());
             let app_handle = app.clone();
             let loaded_count = Arc::clone(&loaded_count);
 
@@ -127,9 +571,20 @@ async fn get_postgres_schema(
 
                 // Wrap all table metadata queries in a timeout
                 let result = tokio::time::timeout(TABLE_QUERY_TIMEOUT, async {
-                    // Get accurate row count using COUNT(*)
-                    let row_count = get_postgres_row_count(&pool, &table_schema, &table_name).await?;
-                    let columns = get_postgres_columns(&pool, &table_schema, &table_name).await?;
+                    let is_view = relation_kind != "table";
+
+                    // Get accurate row count using COUNT(*); views would have to be
+                    // fully evaluated for that, so leave their count unknown
+                    let row_count = if is_view {
+                        None
+                    } else {
+                        get_postgres_row_count(&pool, &table_schema, &table_name).await?
+                    };
+                    let columns = if relation_kind == "materialized_view" {
+                        get_postgres_matview_columns(&pool, &table_schema, &table_name).await?
+                    } else {
+                        get_postgres_columns(&pool, &table_schema, &table_name).await?
+                    };
                     let indexes = get_postgres_indexes(&pool, &table_schema, &table_name).await?;
                     let triggers = get_postgres_triggers(&pool, &table_schema, &table_name).await?;
                     let constraints = get_postgres_constraints(&pool, &table_schema, &table_name).await?;
@@ -142,6 +597,8 @@ async fn get_postgres_schema(
                         indexes,
                         triggers,
                         constraints,
+                        is_view,
+                        comment,
                     })
                 })
                 .await;
@@ -184,9 +641,78 @@ async fn get_postgres_schema(
     Ok(Schema {
         database_name: conn.default_database.clone(),
         tables,
+        routines: Vec::new(),
     })
 }
 
+/// Load stored functions and procedures for a connection
+pub async fn get_routines(
+    manager: &ConnectionManager,
+    connection_id: &str,
+) -> AppResult<Vec<RoutineInfo>> {
+    let conn = manager.get_connection(connection_id)?;
+
+    match conn.database_type {
+        DatabaseType::PostgreSQL => {
+            get_postgres_routines(manager, connection_id, &conn.search_path()).await
+        }
+        DatabaseType::MariaDB | DatabaseType::MySQL => {
+            get_mysql_routines(manager, connection_id, &conn.default_database).await
+        }
+    }
+}
+
+async fn get_postgres_routines(
+    manager: &ConnectionManager,
+    connection_id: &str,
+    search_path: &[String],
+) -> AppResult<Vec<RoutineInfo>> {
+    let pool = manager.get_pool_postgres(connection_id).await?;
+
+    // information_schema.routines lists what the user may call; pg_proc supplies
+    // readable argument and result types. Extension-owned functions are skipped.
+    let query = r#"
+        SELECT
+            p.proname::TEXT as routine_name,
+            n.nspname::TEXT as routine_schema,
+            r.routine_type::TEXT as routine_type,
+            pg_get_function_arguments(p.oid) as parameters,
+            CASE WHEN p.prokind = 'p' THEN NULL ELSE pg_get_function_result(p.oid) END as return_type
+        FROM pg_proc p
+        JOIN pg_namespace n ON n.oid = p.pronamespace
+        JOIN information_schema.routines r
+            ON r.specific_schema = n.nspname
+            AND r.specific_name = p.proname || '_' || p.oid
+        WHERE n.nspname = ANY($2)
+            AND p.prokind IN ('f', 'p')
+            AND NOT EXISTS (
+                SELECT 1 FROM pg_depend d
+                WHERE d.classid = 'pg_proc'::regclass AND d.objid = p.oid AND d.deptype = 'e'
+            )
+        ORDER BY p.proname
+        LIMIT $1
+    "#;
+
+    let rows = sqlx::query(query)
+        .bind(ROUTINE_LIMIT)
+        .bind(search_path)
+        .fetch_all(&pool)
+        .await?;
+
+    let mut routines = Vec::new();
+    for row in rows {
+        routines.push(RoutineInfo {
+            name: row.try_get("routine_name")?,
+            schema: row.try_get("routine_schema").ok(),
+            routine_type: row.try_get("routine_type")?,
+            return_type: row.try_get("return_type").ok().flatten(),
+            parameters: row.try_get("parameters").unwrap_or_default(),
+        });
+    }
+
+    Ok(routines)
+}
+
 async fn get_postgres_row_count(
     pool: &sqlx::PgPool,
     schema: &str,
@@ -222,13 +748,16 @@ async fn get_postgres_columns(
             CASE WHEN pk.column_name IS NOT NULL THEN true ELSE false END as is_primary_key,
             CASE WHEN fk.column_name IS NOT NULL THEN true ELSE false END as is_foreign_key,
             fk.foreign_table_name,
-            fk.foreign_column_name
+            fk.foreign_column_name,
+            en.enum_values,
+            col_description(format('%I.%I', c.table_schema, c.table_name)::regclass, c.ordinal_position::INT) as column_comment
         FROM information_schema.columns c
         LEFT JOIN (
             SELECT ku.column_name
             FROM information_schema.table_constraints tc
             JOIN information_schema.key_column_usage ku
                 ON tc.constraint_name = ku.constraint_name
+                AND tc.constraint_schema = ku.constraint_schema
             WHERE tc.constraint_type = 'PRIMARY KEY'
                 AND tc.table_schema = $1
                 AND tc.table_name = $2
@@ -236,17 +765,32 @@ async fn get_postgres_columns(
         LEFT JOIN (
             SELECT
                 kcu.column_name,
-                ccu.table_name AS foreign_table_name,
+                CASE WHEN ccu.table_schema = 'public' THEN ccu.table_name::TEXT
+                     ELSE ccu.table_schema || '.' || ccu.table_name END AS foreign_table_name,
                 ccu.column_name AS foreign_column_name
             FROM information_schema.table_constraints AS tc
             JOIN information_schema.key_column_usage AS kcu
                 ON tc.constraint_name = kcu.constraint_name
+                AND tc.constraint_schema = kcu.constraint_schema
             JOIN information_schema.constraint_column_usage AS ccu
                 ON ccu.constraint_name = tc.constraint_name
+                AND ccu.constraint_schema = tc.constraint_schema
             WHERE tc.constraint_type = 'FOREIGN KEY'
                 AND tc.table_schema = $1
                 AND tc.table_name = $2
         ) fk ON c.column_name = fk.column_name
+        LEFT JOIN (
+            SELECT
+                tn.nspname AS type_schema,
+                t.typname AS type_name,
+                array_agg(e.enumlabel::TEXT ORDER BY e.enumsortorder) AS enum_values
+            FROM pg_type t
+            JOIN pg_namespace tn ON tn.oid = t.typnamespace
+            JOIN pg_enum e ON e.enumtypid = t.oid
+            GROUP BY tn.nspname, t.typname
+        ) en ON c.data_type = 'USER-DEFINED'
+            AND en.type_schema = c.udt_schema
+            AND en.type_name = c.udt_name
         WHERE c.table_schema = $1
             AND c.table_name = $2
         ORDER BY c.ordinal_position
@@ -271,6 +815,57 @@ async fn get_postgres_columns(
             foreign_key_column: row.try_get("foreign_column_name").ok(),
             default_value: row.try_get("column_default").ok(),
             character_maximum_length: row.try_get("character_maximum_length").ok(),
+            enum_values: row.try_get("enum_values").ok().flatten(),
+            comment: non_empty_comment(row.try_get("column_comment").ok().flatten()),
+        });
+    }
+
+    Ok(columns)
+}
+
+/// Materialized views are absent from information_schema.columns, so read pg_attribute
+async fn get_postgres_matview_columns(
+    pool: &sqlx::PgPool,
+    schema: &str,
+    table: &str,
+) -> AppResult<Vec<ColumnInfo>> {
+    let query = r#"
+        SELECT
+            a.attname::TEXT as column_name,
+            format_type(a.atttypid, a.atttypmod) as data_type,
+            NOT a.attnotnull as is_nullable,
+            col_description(c.oid, a.attnum) as column_comment
+        FROM pg_attribute a
+        JOIN pg_class c ON c.oid = a.attrelid
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE n.nspname = $1
+            AND c.relname = $2
+            AND a.attnum > 0
+            AND NOT a.attisdropped
+        ORDER BY a.attnum
+    "#;
+
+    let rows = sqlx::query(query)
+        .bind(schema)
+        .bind(table)
+        .fetch_all(pool)
+        .await?;
+
+    let mut columns = Vec::new();
+
+    for row in rows {
+        columns.push(ColumnInfo {
+            name: row.try_get("column_name")?,
+            data_type: row.try_get("data_type")?,
+            is_nullable: row.try_get("is_nullable")?,
+            is_primary_key: false,
+            is_foreign_key: false,
+            foreign_key_table: None,
+            foreign_key_column: None,
+            default_value: None,
+            character_maximum_length: None,
+            enum_values: None,
+            comment: non_empty_comment(row.try_get("column_comment").ok().flatten()),
         });
     }
 
@@ -289,7 +884,7 @@ async fn get_postgres_indexes(
             ix.indisunique as is_unique,
             ix.indisprimary as is_primary,
             am.amname as index_type,
-            COALESCE(array_agg(a.attname::TEXT ORDER BY array_position(ix.indkey, a.attnum)), ARRAY[]::TEXT[]) as columns
+            COALESCE(array_agg(a.attname::TEXT ORDER BY k.ord), ARRAY[]::TEXT[]) as columns
         FROM pg_indexes i
         JOIN pg_class c ON c.relname = i.tablename
         JOIN pg_namespace n ON n.oid = c.relnamespace AND n.nspname = i.schemaname
@@ -298,7 +893,8 @@ async fn get_postgres_indexes(
         )
         JOIN pg_class ic ON ic.oid = ix.indexrelid
         JOIN pg_am am ON am.oid = ic.relam
-        JOIN pg_attribute a ON a.attrelid = c.oid AND a.attnum = ANY(ix.indkey)
+        CROSS JOIN LATERAL unnest(ix.indkey::int2[]) WITH ORDINALITY AS k(attnum, ord)
+        JOIN pg_attribute a ON a.attrelid = c.oid AND a.attnum = k.attnum
         WHERE i.schemaname = $1
             AND i.tablename = $2
         GROUP BY i.indexname, i.indexdef, ix.indisunique, ix.indisprimary, am.amname
@@ -379,7 +975,8 @@ async fn get_postgres_constraints(
             tc.constraint_name,
             tc.constraint_type,
             COALESCE(array_agg(DISTINCT kcu.column_name::TEXT ORDER BY kcu.column_name::TEXT) FILTER (WHERE kcu.column_name IS NOT NULL), ARRAY[]::TEXT[]) as columns,
-            ccu.table_name as referenced_table,
+            CASE WHEN ccu.table_schema = 'public' THEN ccu.table_name::TEXT
+                 ELSE ccu.table_schema || '.' || ccu.table_name END as referenced_table,
             array_agg(DISTINCT ccu.column_name::TEXT ORDER BY ccu.column_name::TEXT) FILTER (WHERE ccu.column_name IS NOT NULL) as referenced_columns
         FROM information_schema.table_constraints tc
         LEFT JOIN information_schema.key_column_usage kcu
@@ -387,10 +984,10 @@ async fn get_postgres_constraints(
             AND tc.table_schema = kcu.table_schema
         LEFT JOIN information_schema.constraint_column_usage ccu
             ON tc.constraint_name = ccu.constraint_name
-            AND tc.table_schema = ccu.table_schema
+            AND tc.constraint_schema = ccu.constraint_schema
         WHERE tc.table_schema = $1
             AND tc.table_name = $2
-        GROUP BY tc.constraint_name, tc.constraint_type, ccu.table_name
+        GROUP BY tc.constraint_name, tc.constraint_type, ccu.table_schema, ccu.table_name
         ORDER BY tc.constraint_name
     "#;
 
@@ -428,7 +1025,7 @@ async fn get_mysql_schema(
 
     // Get all tables with approximate row counts from information_schema
     // TABLE_ROWS is an estimate but much faster than COUNT(*)
-    let tables_query = "SELECT table_name, table_rows FROM information_schema.tables WHERE table_schema = ? AND table_type = 'BASE TABLE' ORDER BY table_name";
+    let tables_query = "SELECT table_name, table_rows, table_type, table_comment FROM information_schema.tables WHERE table_schema = ? AND table_type IN ('BASE TABLE', 'VIEW') ORDER BY table_name";
 
     let table_rows = sqlx::query(tables_query)
         .bind(&conn.default_database)
@@ -445,7 +1042,19 @@ async fn get_mysql_schema(
             let pool = pool.clone();
             let database = conn.default_database.clone();
             let table_name: String = table_row.try_get("table_name").unwrap();
-            let row_count: Option<i64> = table_row.try_get::<Option<u64>, _>("table_rows").ok().flatten().map(|v| v as i64);
+            let is_view = table_row.try_get::<String, _>("table_type").map(|t| t == "VIEW").unwrap_or(false);
+            // Views have no TABLE_ROWS estimate
+            let row_count: Option<i64> = if is_view {
+                None
+            } else {
+                table_row.try_get::<Option<u64>, _>("table_rows").ok().flatten().map(|v| v as i64)
+            };
+            // MySQL fills TABLE_COMMENT of views with the literal "VIEW"
+            let comment = if is_view {
+                None
+            } else {
+                non_empty_comment(table_row.try_get("table_comment").ok().flatten())
+            };
             let app_handle = app.clone();
             let loaded_count = Arc::clone(&loaded_count);
 
@@ -467,6 +1076,8 @@ async fn get_mysql_schema(
                         indexes,
                         triggers,
                         constraints,
+                        is_view,
+                        comment,
                     })
                 })
                 .await;
@@ -509,9 +1120,66 @@ async fn get_mysql_schema(
     Ok(Schema {
         database_name: conn.default_database.clone(),
         tables,
+        routines: Vec::new(),
     })
 }
 
+async fn get_mysql_routines(
+    manager: &ConnectionManager,
+    connection_id: &str,
+    database: &str,
+) -> AppResult<Vec<RoutineInfo>> {
+    let pool = manager.get_pool_mysql(connection_id).await?;
+
+    let query = r#"
+        SELECT
+            CAST(r.ROUTINE_NAME AS CHAR) as routine_name,
+            CAST(r.ROUTINE_TYPE AS CHAR) as routine_type,
+            CAST(r.DTD_IDENTIFIER AS CHAR) as return_type,
+            CAST((
+                SELECT GROUP_CONCAT(
+                    CONCAT_WS(' ', p.PARAMETER_MODE, p.PARAMETER_NAME, p.DTD_IDENTIFIER)
+                    ORDER BY p.ORDINAL_POSITION SEPARATOR ', '
+                )
+                FROM information_schema.PARAMETERS p
+                WHERE p.SPECIFIC_SCHEMA = r.ROUTINE_SCHEMA
+                    AND p.SPECIFIC_NAME = r.SPECIFIC_NAME
+                    AND p.ORDINAL_POSITION > 0
+            ) AS CHAR) as parameters
+        FROM information_schema.ROUTINES r
+        WHERE r.ROUTINE_SCHEMA = ?
+        ORDER BY r.ROUTINE_NAME
+        LIMIT ?
+        "#;
+
+    let rows = sqlx::query(query)
+        .bind(database)
+        .bind(ROUTINE_LIMIT)
+        .fetch_all(&pool)
+        .await?;
+
+    let mut routines = Vec::new();
+    for row in rows {
+        let routine_type: String = row.try_get("routine_type")?;
+        // DTD_IDENTIFIER is only set for functions
+        let return_type = if routine_type == "FUNCTION" {
+            row.try_get("return_type").ok().flatten()
+        } else {
+            None
+        };
+
+        routines.push(RoutineInfo {
+            name: row.try_get("routine_name")?,
+            schema: Some(database.to_string()),
+            routine_type,
+            return_type,
+            parameters: row.try_get::<Option<String>, _>("parameters").ok().flatten().unwrap_or_default(),
+        });
+    }
+
+    Ok(routines)
+}
+
 async fn get_mysql_columns(
     pool: &sqlx::MySqlPool,
     database: &str,
@@ -525,6 +1193,8 @@ async fn get_mysql_columns(
             c.COLUMN_DEFAULT as column_default,
             c.CHARACTER_MAXIMUM_LENGTH as character_maximum_length,
             c.COLUMN_KEY as column_key,
+            c.COLUMN_TYPE as column_type,
+            c.COLUMN_COMMENT as column_comment,
             k.REFERENCED_TABLE_NAME as foreign_table_name,
             k.REFERENCED_COLUMN_NAME as foreign_column_name
         FROM information_schema.COLUMNS c
@@ -559,10 +1229,20 @@ async fn get_mysql_columns(
 
     for row in rows {
         let column_key: String = row.try_get("column_key").unwrap_or_default();
+        let data_type: String = row.try_get("data_type")?;
+
+        // COLUMN_TYPE carries the member list, e.g. enum('a','b') or set('x','y')
+        let enum_values = if data_type.eq_ignore_ascii_case("enum") || data_type.eq_ignore_ascii_case("set") {
+            row.try_get::<String, _>("column_type")
+                .ok()
+                .map(|column_type| parse_mysql_enum_values(&column_type))
+        } else {
+            None
+        };
 
         columns.push(ColumnInfo {
             name: row.try_get("column_name")?,
-            data_type: row.try_get("data_type")?,
+            data_type,
             is_nullable: row.try_get::<String, _>("is_nullable")? == "YES",
             is_primary_key: column_key == "PRI",
             is_foreign_key: row.try_get::<Option<String>, _>("foreign_table_name")?.is_some(),
@@ -570,12 +1250,53 @@ async fn get_mysql_columns(
             foreign_key_column: row.try_get("foreign_column_name").ok(),
             default_value: row.try_get("column_default").ok(),
             character_maximum_length: row.try_get::<Option<u64>, _>("character_maximum_length")?.map(|v| v as i32),
+            enum_values,
+            comment: non_empty_comment(row.try_get("column_comment").ok().flatten()),
         });
     }
 
     Ok(columns)
 }
 
+/// Parse MySQL enum/set definition like enum('val1','val2','escaped''quote').
+/// Quoted members may contain commas, doubled quotes ('') and backslash escapes.
+pub(crate) fn parse_mysql_enum_values(column_type: &str) -> Vec<String> {
+    let Some(start) = column_type.find('(') else { return vec![] };
+    let Some(end) = column_type.rfind(')') else { return vec![] };
+    if start >= end { return vec![] }
+
+    let inner = &column_type[start + 1..end];
+    let mut values = Vec::new();
+    let mut current = String::new();
+    let mut in_quote = false;
+    let mut chars = inner.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' if !in_quote => in_quote = true,
+            '\'' if in_quote => {
+                // Check for escaped quote ('')
+                if chars.peek() == Some(&'\'') {
+                    chars.next();
+                    current.push('\'');
+                } else {
+                    // Closing quote ends the member, which may be an empty string
+                    in_quote = false;
+                    values.push(std::mem::take(&mut current));
+                }
+            }
+            '\\' if in_quote => {
+                if let Some(escaped) = chars.next() {
+                    current.push(escaped);
+                }
+            }
+            _ if in_quote => current.push(c),
+            _ => {} // Skip commas and whitespace outside quotes
+        }
+    }
+    values
+}
+
 async fn get_mysql_indexes(
     pool: &sqlx::MySqlPool,
     database: &str,
@@ -711,3 +1432,122 @@ async fn get_mysql_constraints(
 
     Ok(constraints)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mysql_enum_values_handles_commas_and_quotes() {
+        assert_eq!(
+            parse_mysql_enum_values("enum('a,b','c''d')"),
+            vec!["a,b".to_string(), "c'd".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_parse_mysql_enum_values_set_and_empty_member() {
+        assert_eq!(
+            parse_mysql_enum_values("set('', 'x', 'y\\\\z')"),
+            vec!["".to_string(), "x".to_string(), "y\\z".to_string()]
+        );
+        assert!(parse_mysql_enum_values("varchar(255)").is_empty());
+    }
+
+    #[test]
+    fn test_prompt_indexes_keeps_composite_column_order() {
+        let index = |name: &str, columns: &[&str], is_unique: bool, is_primary: bool| IndexInfo {
+            name: name.to_string(),
+            columns: columns.iter().map(|c| c.to_string()).collect(),
+            is_unique,
+            is_primary,
+            index_type: None,
+        };
+        let mut table = Table::fixture("orders", vec![]);
+        table.indexes = vec![
+            index("orders_pkey", &["id"], true, true),
+            index("orders_user_created", &["user_id", "created_at"], false, false),
+            index("orders_ref", &["reference"], true, false),
+        ];
+
+        assert_eq!(
+            table.prompt_indexes(),
+            "PK(id); orders_user_created(user_id, created_at); UNIQUE orders_ref(reference)"
+        );
+    }
+
+    #[test]
+    fn test_prompt_label_qualifies_non_public_schemas() {
+        let mut table = Table::fixture("orders", vec![]);
+        table.schema = Some("public".to_string());
+        assert_eq!(table.prompt_label(), "orders");
+
+        table.schema = Some("sales".to_string());
+        table.is_view = true;
+        assert_eq!(table.prompt_label(), "sales.orders (VIEW, read-only)");
+    }
+
+    #[test]
+    fn test_prompt_comment_is_single_line_and_bounded() {
+        assert_eq!(prompt_comment(None), "");
+        assert_eq!(prompt_comment(Some("  ")), "");
+        assert_eq!(prompt_comment(Some("Amount in cents,\n  excluding tax")), " -- Amount in cents, excluding tax");
+
+        let long = "x".repeat(PROMPT_COMMENT_LIMIT + 50);
+        let shortened = prompt_comment(Some(&long));
+        assert!(shortened.ends_with("..."));
+        assert_eq!(shortened.len(), " -- ".len() + PROMPT_COMMENT_LIMIT + 3);
+
+        assert_eq!(non_empty_comment(Some(String::new())), None);
+        assert_eq!(non_empty_comment(Some("Customer orders".to_string())).as_deref(), Some("Customer orders"));
+    }
+
+    #[test]
+    fn test_er_diagram_marks_keys_and_keeps_unrelated_tables() {
+        let column = |name: &str| ColumnInfo::fixture(name).with_type("integer").not_null();
+        let schema = Schema {
+            database_name: "shop".to_string(),
+            tables: vec![
+                Table::fixture("users", vec![column("id").primary_key()]),
+                Table::fixture(
+                    "orders",
+                    vec![column("id").primary_key(), column("user_id").references("users", "id")],
+                ),
+                Table::fixture("settings", vec![]),
+            ],
+            routines: vec![],
+        };
+
+        let mermaid = schema.er_diagram(ErDiagramFormat::Mermaid);
+        assert!(mermaid.starts_with("erDiagram\n"));
+        assert!(mermaid.contains("        integer user_id FK\n"));
+        assert!(mermaid.contains("    orders }o--|| users : \"user_id\"\n"));
+        assert!(mermaid.contains("    settings\n"));
+
+        let dot = schema.er_diagram(ErDiagramFormat::Dot);
+        assert!(dot.contains("\"users\" [label=\"{users|id: integer PK\\l}\"];"));
+        assert!(dot.contains("\"orders\" -> \"users\" [label=\"user_id -> id\"];"));
+        assert!(dot.contains("\"settings\" [label=\"{settings|}\"];"));
+    }
+
+    #[test]
+    fn test_routine_prompt_signature() {
+        let function = RoutineInfo {
+            name: "total_spent".to_string(),
+            schema: Some("public".to_string()),
+            routine_type: "FUNCTION".to_string(),
+            return_type: Some("numeric".to_string()),
+            parameters: "user_id integer".to_string(),
+        };
+        assert_eq!(function.prompt_signature(), "FUNCTION total_spent(user_id integer) RETURNS numeric");
+
+        let procedure = RoutineInfo {
+            name: "archive_orders".to_string(),
+            schema: None,
+            routine_type: "PROCEDURE".to_string(),
+            return_type: None,
+            parameters: String::new(),
+        };
+        assert_eq!(procedure.prompt_signature(), "PROCEDURE archive_orders()");
+    }
+}
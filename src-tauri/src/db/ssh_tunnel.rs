@@ -0,0 +1,301 @@
+use crate::db::connection::{Connection, DatabaseType};
+use crate::error::{AppError, AppResult};
+use russh::client;
+use russh_keys::key;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+use tokio_rustls::rustls;
+
+/// Default SSH port when the connection does not specify one
+const DEFAULT_SSH_PORT: u16 = 22;
+
+/// Interval for SSH keepalive messages so idle tunnels are not dropped by the bastion
+const SSH_KEEPALIVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Verifies the bastion's host key against the fingerprint pinned on the connection,
+/// or else the user's `~/.ssh/known_hosts`
+struct TunnelClient {
+    host: String,
+    port: u16,
+    pinned_fingerprint: Option<String>,
+    /// Why the host key was refused, reported instead of russh's generic error
+    rejection: Arc<Mutex<Option<String>>>,
+}
+
+impl TunnelClient {
+    fn reject(&self, reason: String) -> Result<bool, russh::Error> {
+        if let Ok(mut rejection) = self.rejection.lock() {
+            *rejection = Some(reason);
+        }
+        Ok(false)
+    }
+}
+
+#[async_trait::async_trait]
+impl client::Handler for TunnelClient {
+    type Error = russh::Error;
+
+    async fn check_server_key(
+        &mut self,
+        server_public_key: &key::PublicKey,
+    ) -> Result<bool, Self::Error> {
+        let fingerprint = server_public_key.fingerprint();
+
+        if let Some(pinned) = self.pinned_fingerprint.as_deref() {
+            let pinned = pinned.trim();
+            let pinned = pinned.strip_prefix("SHA256:").unwrap_or(pinned);
+            if pinned == fingerprint {
+                return Ok(true);
+            }
+            return self.reject(format!(
+                "host key SHA256:{} does not match the pinned fingerprint SHA256:{}",
+                fingerprint, pinned
+            ));
+        }
+
+        match russh_keys::check_known_hosts(&self.host, self.port, server_public_key) {
+            Ok(true) => Ok(true),
+            Ok(false) => self.reject(format!(
+                "host key SHA256:{} is not in ~/.ssh/known_hosts; add the host there or pin its fingerprint",
+                fingerprint
+            )),
+            Err(russh_keys::Error::KeyChanged { line }) => self.reject(format!(
+                "host key SHA256:{} does not match ~/.ssh/known_hosts line {}; the bastion may be impersonated",
+                fingerprint, line
+            )),
+            Err(e) => self.reject(format!("could not check ~/.ssh/known_hosts: {}", e)),
+        }
+    }
+}
+
+/// A local port forwarded through an SSH session to the database host.
+/// The tunnel stays open until this value is dropped.
+pub struct SshTunnel {
+    pub local_port: u16,
+    session: Arc<client::Handle<TunnelClient>>,
+    listener_task: JoinHandle<()>,
+}
+
+impl SshTunnel {
+    /// Open an SSH session to the bastion and forward a dynamically chosen
+    /// local port to the connection's database host/port
+    pub async fn open(conn: &Connection) -> AppResult<Self> {
+        let ssh_host = conn
+            .ssh_host
+            .as_deref()
+            .filter(|h| !h.is_empty())
+            .ok_or_else(|| AppError::ConnectionError("SSH host is not configured".to_string()))?;
+        let ssh_port = conn.ssh_port.unwrap_or(DEFAULT_SSH_PORT);
+        let ssh_user = conn
+            .ssh_user
+            .as_deref()
+            .filter(|u| !u.is_empty())
+            .ok_or_else(|| AppError::ConnectionError("SSH user is not configured".to_string()))?;
+
+        let config = Arc::new(client::Config {
+            keepalive_interval: Some(SSH_KEEPALIVE_INTERVAL),
+            ..Default::default()
+        });
+
+        let handler = TunnelClient {
+            host: ssh_host.to_string(),
+            port: ssh_port,
+            pinned_fingerprint: conn.ssh_host_key_fingerprint.clone().filter(|f| !f.trim().is_empty()),
+            rejection: Arc::new(Mutex::new(None)),
+        };
+        let rejection = Arc::clone(&handler.rejection);
+
+        let mut session = client::connect(config, (ssh_host, ssh_port), handler)
+            .await
+            .map_err(|e| {
+                let reason = rejection.lock().ok().and_then(|r| r.clone());
+                AppError::ConnectionError(format!(
+                    "Failed to connect to SSH host {}:{}: {}",
+                    ssh_host,
+                    ssh_port,
+                    reason.unwrap_or_else(|| e.to_string())
+                ))
+            })?;
+
+        let authenticated = if let Some(key_path) = conn.ssh_key_path.as_deref().filter(|p| !p.is_empty()) {
+            // The SSH password doubles as the key passphrase for encrypted keys
+            let key_pair = russh_keys::load_secret_key(key_path, conn.ssh_password.as_deref())
+                .map_err(|e| {
+                    AppError::ConnectionError(format!(
+                        "Failed to load SSH private key '{}': {}",
+                        key_path, e
+                    ))
+                })?;
+
+            session
+                .authenticate_publickey(ssh_user, Arc::new(key_pair))
+                .await
+                .map_err(|e| AppError::ConnectionError(format!("SSH key authentication error: {}", e)))?
+        } else if let Some(password) = conn.ssh_password.as_deref() {
+            session
+                .authenticate_password(ssh_user, password)
+                .await
+                .map_err(|e| AppError::ConnectionError(format!("SSH password authentication error: {}", e)))?
+        } else {
+            return Err(AppError::ConnectionError(
+                "SSH tunnel requires a private key path or password".to_string(),
+            ));
+        };
+
+        if !authenticated {
+            let method = if conn.ssh_key_path.as_deref().is_some_and(|p| !p.is_empty()) {
+                "the private key was rejected"
+            } else {
+                "the password was rejected"
+            };
+            return Err(AppError::ConnectionError(format!(
+                "SSH authentication failed for {}@{}: {}",
+                ssh_user, ssh_host, method
+            )));
+        }
+
+        // Port 0 lets the OS pick a free local port
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await?;
+        let local_port = listener.local_addr()?.port();
+
+        let session = Arc::new(session);
+        let forward_session = Arc::clone(&session);
+        let db_host = conn.host.clone();
+        let db_port = conn.port;
+
+        let listener_task = tokio::spawn(async move {
+            loop {
+                let (mut socket, peer) = match listener.accept().await {
+                    Ok(accepted) => accepted,
+                    Err(e) => {
+                        eprintln!("SSH tunnel listener error: {}", e);
+                        break;
+                    }
+                };
+
+                let session = Arc::clone(&forward_session);
+                let db_host = db_host.clone();
+
+                tokio::spawn(async move {
+                    let channel = match session
+                        .channel_open_direct_tcpip(
+                            db_host.clone(),
+                            db_port as u32,
+                            peer.ip().to_string(),
+                            peer.port() as u32,
+                        )
+                        .await
+                    {
+                        Ok(channel) => channel,
+                        Err(e) => {
+                            eprintln!("Failed to open SSH channel to {}:{}: {}", db_host, db_port, e);
+                            return;
+                        }
+                    };
+
+                    let mut stream = channel.into_stream();
+                    let _ = tokio::io::copy_bidirectional(&mut socket, &mut stream).await;
+                });
+            }
+        });
+
+        Ok(Self {
+            local_port,
+            session,
+            listener_task,
+        })
+    }
+
+    /// Whether the underlying SSH session has been closed (e.g. bastion restart)
+    pub fn is_closed(&self) -> bool {
+        self.session.is_closed()
+    }
+}
+
+/// Check that the database behind a tunnel presents a certificate valid for `server_name`.
+/// Pools reach a tunneled server as 127.0.0.1, which its certificate doesn't name, so they
+/// can only verify the chain; this handshake checks the configured host name as well.
+pub async fn verify_tls_server_name(
+    local_port: u16,
+    server_name: &str,
+    db_type: &DatabaseType,
+    root_cert: Option<&str>,
+) -> AppResult<()> {
+    let mut stream = TcpStream::connect(("127.0.0.1", local_port)).await?;
+
+    // Ask the server to switch to TLS the way each protocol does before its handshake
+    match db_type {
+        DatabaseType::PostgreSQL => {
+            // SSLRequest: message length 8, request code 80877103
+            stream.write_all(&[0, 0, 0, 8, 0x04, 0xd2, 0x16, 0x2f]).await?;
+            let mut reply = [0u8; 1];
+            stream.read_exact(&mut reply).await?;
+            if reply[0] != b'S' {
+                return Err(AppError::ConnectionError(
+                    "The database behind the SSH tunnel does not accept TLS".to_string(),
+                ));
+            }
+        }
+        DatabaseType::MariaDB | DatabaseType::MySQL => {
+            // Skip the server greeting, then send an SSLRequest packet (sequence 1) with
+            // CLIENT_PROTOCOL_41 | CLIENT_SSL | CLIENT_SECURE_CONNECTION
+            let mut header = [0u8; 4];
+            stream.read_exact(&mut header).await?;
+            let mut greeting = vec![0u8; u32::from_le_bytes([header[0], header[1], header[2], 0]) as usize];
+            stream.read_exact(&mut greeting).await?;
+
+            let capabilities: u32 = 0x0200 | 0x0800 | 0x8000;
+            let mut packet = vec![32, 0, 0, 1];
+            packet.extend_from_slice(&capabilities.to_le_bytes());
+            packet.extend_from_slice(&(16u32 * 1024 * 1024).to_le_bytes());
+            packet.push(33); // utf8_general_ci
+            packet.extend_from_slice(&[0u8; 23]);
+            stream.write_all(&packet).await?;
+        }
+    }
+
+    let mut roots = rustls::RootCertStore::empty();
+    match root_cert.filter(|c| !c.is_empty()) {
+        Some(path) => {
+            let pem = std::fs::read(path)
+                .map_err(|e| AppError::ConnectionError(format!("Failed to read root certificate '{}': {}", path, e)))?;
+            for cert in rustls_pemfile::certs(&mut pem.as_slice()) {
+                let cert = cert.map_err(|e| {
+                    AppError::ConnectionError(format!("Invalid root certificate '{}': {}", path, e))
+                })?;
+                roots.add(cert).map_err(|e| {
+                    AppError::ConnectionError(format!("Invalid root certificate '{}': {}", path, e))
+                })?;
+            }
+        }
+        None => roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned()),
+    }
+
+    let config = rustls::ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .map_err(|e| AppError::ConnectionError(format!("Failed to set up TLS: {}", e)))?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    let name = rustls::pki_types::ServerName::try_from(server_name.to_string())
+        .map_err(|e| AppError::ConnectionError(format!("Invalid TLS server name '{}': {}", server_name, e)))?;
+
+    tokio_rustls::TlsConnector::from(Arc::new(config))
+        .connect(name, stream)
+        .await
+        .map_err(|e| {
+            AppError::ConnectionError(format!(
+                "The database certificate is not valid for '{}': {}",
+                server_name, e
+            ))
+        })?;
+    Ok(())
+}
+
+impl Drop for SshTunnel {
+    fn drop(&mut self) {
+        self.listener_task.abort();
+    }
+}
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 
+use crate::db::connection::DatabaseType;
 use crate::db::keywords::SqlKeyword;
 use crate::db::schema::Schema;
 
@@ -8,10 +9,16 @@ use crate::db::schema::Schema;
 pub struct HighlightConfig {
     pub keywords: Vec<SqlKeyword>,
     pub schema: Option<Schema>,
+    /// Prefix for generated CSS classes (`<prefix>-keyword-dml`); defaults to `sql`
+    #[serde(default)]
+    pub class_prefix: Option<String>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-enum TokenType {
+const DEFAULT_CLASS_PREFIX: &str = "sql";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenType {
     KeywordDml,         // SELECT, INSERT, UPDATE, DELETE
     KeywordClause,      // FROM, WHERE, JOIN, ORDER, GROUP
     KeywordReserved,    // Other reserved keywords
@@ -24,22 +31,215 @@ enum TokenType {
     String,
     Number,
     Operator,
+    ParenOpen,
+    ParenClose,
     Comment,
     Text,
 }
 
-struct Token {
-    token_type: TokenType,
-    value: String,
+impl TokenType {
+    /// CSS class suffix, appended to the configured prefix
+    fn class_suffix(&self) -> Option<&'static str> {
+        match self {
+            TokenType::KeywordDml => Some("keyword-dml"),
+            TokenType::KeywordClause => Some("keyword-clause"),
+            TokenType::KeywordReserved => Some("keyword-reserved"),
+            TokenType::KeywordUnreserved => Some("keyword-unreserved"),
+            TokenType::KeywordType => Some("keyword-type"),
+            TokenType::KeywordFunction => Some("keyword-function"),
+            TokenType::KeywordCommon => Some("keyword-common"),
+            TokenType::Table => Some("table"),
+            TokenType::Column => Some("column"),
+            TokenType::String => Some("string"),
+            TokenType::Number => Some("number"),
+            TokenType::Operator | TokenType::ParenOpen | TokenType::ParenClose => Some("operator"),
+            TokenType::Comment => Some("comment"),
+            TokenType::Text => None,
+        }
+    }
+}
+
+/// A highlighted fragment of the input. `start`/`end` are byte offsets into the SQL text;
+/// the tokens cover the input contiguously.
+#[derive(Debug, Clone, Serialize)]
+pub struct Token {
+    #[serde(rename = "type")]
+    pub token_type: TokenType,
+    pub value: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Append a token positioned right after the previous one
+fn push_token(tokens: &mut Vec<Token>, token_type: TokenType, value: String) {
+    let start = tokens.last().map(|t| t.end).unwrap_or(0);
+    let end = start + value.len();
+    tokens.push(Token {
+        token_type,
+        value,
+        start,
+        end,
+    });
+}
+
+/// Tokenize without keyword or schema lookups, for structural analysis
+pub(crate) fn tokenize_plain(sql: &str) -> Vec<Token> {
+    let config = HighlightConfig {
+        keywords: Vec::new(),
+        schema: None,
+        class_prefix: None,
+    };
+    tokenize_sql(sql, &config)
+}
+
+/// Aggregate functions that collapse their input to one row without GROUP BY
+const AGGREGATE_FUNCTIONS: &[&str] = &[
+    "COUNT", "SUM", "AVG", "MIN", "MAX", "STRING_AGG", "ARRAY_AGG", "JSON_AGG", "JSONB_AGG",
+    "JSON_OBJECT_AGG", "JSONB_OBJECT_AGG", "JSON_ARRAYAGG", "JSON_OBJECTAGG", "GROUP_CONCAT",
+    "BOOL_AND", "BOOL_OR", "EVERY", "BIT_AND", "BIT_OR", "BIT_XOR", "STDDEV", "STDDEV_POP",
+    "STDDEV_SAMP", "VARIANCE", "VAR_POP", "VAR_SAMP",
+];
+
+/// Clauses found outside any parentheses, i.e. belonging to the outermost query rather
+/// than a subquery, CTE body, string or comment
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct StatementShape {
+    pub has_limit: bool,
+    /// `OFFSET n` or `FETCH FIRST/NEXT`, the standard alternatives to LIMIT
+    pub has_offset_or_fetch: bool,
+    pub has_group_by: bool,
+    /// A window function (`... OVER (...)`)
+    pub has_window: bool,
+    /// A call to an aggregate function such as COUNT or SUM
+    pub has_aggregate: bool,
+    /// UNION, INTERSECT or EXCEPT
+    pub has_set_operation: bool,
+}
+
+impl StatementShape {
+    /// The query already limits or pages its own rows
+    pub fn is_limited(&self) -> bool {
+        self.has_limit || self.has_offset_or_fetch
+    }
+
+    /// Aggregates without GROUP BY, windows or set operations return exactly one row
+    pub fn is_single_row_aggregate(&self) -> bool {
+        self.has_aggregate && !self.has_group_by && !self.has_window && !self.has_set_operation
+    }
+}
+
+/// Classify the outermost query's clauses from its tokens
+pub fn statement_shape(sql: &str) -> StatementShape {
+    let mut shape = StatementShape::default();
+    let mut depth = 0usize;
+    let mut previous_word = String::new();
+
+    for token in tokenize_plain(sql) {
+        match token.token_type {
+            TokenType::ParenOpen => depth += 1,
+            TokenType::ParenClose => depth = depth.saturating_sub(1),
+            TokenType::Text | TokenType::KeywordFunction if depth == 0 => {
+                if !token.value.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+                    continue;
+                }
+                let word = token.value.to_uppercase();
+                match word.as_str() {
+                    "LIMIT" => shape.has_limit = true,
+                    "OFFSET" | "FETCH" => shape.has_offset_or_fetch = true,
+                    "BY" if previous_word == "GROUP" => shape.has_group_by = true,
+                    "OVER" => shape.has_window = true,
+                    "UNION" | "INTERSECT" | "EXCEPT" => shape.has_set_operation = true,
+                    _ if token.token_type == TokenType::KeywordFunction
+                        && AGGREGATE_FUNCTIONS.contains(&word.as_str()) =>
+                    {
+                        shape.has_aggregate = true;
+                    }
+                    _ => {}
+                }
+                previous_word = word;
+            }
+            _ => {}
+        }
+    }
+
+    shape
+}
+
+/// The statement without trailing semicolons, comments and whitespace, so that
+/// clauses appended to it aren't swallowed by a `--` comment
+pub fn trim_statement_end(sql: &str) -> &str {
+    let end = tokenize_plain(sql)
+        .iter()
+        .rev()
+        .find(|t| {
+            t.token_type != TokenType::Comment
+                && !(t.token_type == TokenType::Operator && t.value == ";")
+                && !t.value.trim().is_empty()
+        })
+        .map(|t| t.end)
+        .unwrap_or(0);
+    &sql[..end]
+}
+
+/// Canonical form of a statement for cache keys: comments and surrounding whitespace
+/// dropped and tokens separated by single spaces. On PostgreSQL, which folds unquoted
+/// identifiers, tokens are also uppercased outside string literals and quoted
+/// identifiers; MySQL table names can be case-sensitive, so their case is kept there.
+pub fn normalize_sql(sql: &str, db_type: &DatabaseType) -> String {
+    let fold_case = matches!(db_type, DatabaseType::PostgreSQL);
+    tokenize_plain(trim_statement_end(sql))
+        .into_iter()
+        .filter(|t| t.token_type != TokenType::Comment && !t.value.trim().is_empty())
+        .map(|t| {
+            if !fold_case || t.token_type == TokenType::String || t.value.starts_with(['"', '`']) {
+                t.value
+            } else {
+                t.value.to_uppercase()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
 }
 
 /// Tokenize SQL text with syntax highlighting
 pub fn highlight_sql(sql: &str, config: &HighlightConfig) -> String {
     let tokens = tokenize_sql(sql, config);
-    tokens_to_html(&tokens)
+    let paren_groups = match_parens(&tokens);
+    let prefix = config.class_prefix.as_deref().unwrap_or(DEFAULT_CLASS_PREFIX);
+    tokens_to_html(&tokens, &paren_groups, prefix)
 }
 
-fn tokenize_sql(sql: &str, config: &HighlightConfig) -> Vec<Token> {
+/// Split a script into statements on `;`, ignoring semicolons inside strings,
+/// quoted identifiers and comments. Empty and comment-only statements are dropped.
+pub fn split_statements(sql: &str) -> Vec<String> {
+    let mut statements = Vec::new();
+    let mut current = String::new();
+    let mut has_code = false;
+
+    for token in tokenize_plain(sql) {
+        if token.token_type == TokenType::Operator && token.value == ";" {
+            if has_code {
+                statements.push(current.trim().to_string());
+            }
+            current.clear();
+            has_code = false;
+            continue;
+        }
+
+        if token.token_type != TokenType::Comment && !token.value.trim().is_empty() {
+            has_code = true;
+        }
+        current.push_str(&token.value);
+    }
+
+    if has_code {
+        statements.push(current.trim().to_string());
+    }
+
+    statements
+}
+
+pub fn tokenize_sql(sql: &str, config: &HighlightConfig) -> Vec<Token> {
     let mut tokens = Vec::new();
 
     // Build lookup maps
@@ -74,10 +274,7 @@ fn tokenize_sql(sql: &str, config: &HighlightConfig) -> Vec<Token> {
                 comment.push(chars[i]);
                 i += 1;
             }
-            tokens.push(Token {
-                token_type: TokenType::Comment,
-                value: comment,
-            });
+            push_token(&mut tokens, TokenType::Comment, comment);
             continue;
         }
 
@@ -93,10 +290,7 @@ fn tokenize_sql(sql: &str, config: &HighlightConfig) -> Vec<Token> {
                 }
                 i += 1;
             }
-            tokens.push(Token {
-                token_type: TokenType::Comment,
-                value: comment,
-            });
+            push_token(&mut tokens, TokenType::Comment, comment);
             continue;
         }
 
@@ -123,10 +317,7 @@ fn tokenize_sql(sql: &str, config: &HighlightConfig) -> Vec<Token> {
                     i += 1;
                 }
             }
-            tokens.push(Token {
-                token_type: TokenType::String,
-                value: string,
-            });
+            push_token(&mut tokens, TokenType::String, string);
             continue;
         }
 
@@ -145,10 +336,7 @@ fn tokenize_sql(sql: &str, config: &HighlightConfig) -> Vec<Token> {
                     i += 1;
                 }
             }
-            tokens.push(Token {
-                token_type: TokenType::Text,
-                value: string,
-            });
+            push_token(&mut tokens, TokenType::Text, string);
             continue;
         }
 
@@ -159,10 +347,7 @@ fn tokenize_sql(sql: &str, config: &HighlightConfig) -> Vec<Token> {
                 num.push(chars[i]);
                 i += 1;
             }
-            tokens.push(Token {
-                token_type: TokenType::Number,
-                value: num,
-            });
+            push_token(&mut tokens, TokenType::Number, num);
             continue;
         }
 
@@ -170,18 +355,9 @@ fn tokenize_sql(sql: &str, config: &HighlightConfig) -> Vec<Token> {
         if ch.is_alphabetic() || ch == '_' {
             // Try to match alias.column pattern
             if let Some((alias, column, length)) = try_match_aliased_column(&chars, i, &column_set) {
-                tokens.push(Token {
-                    token_type: TokenType::Text,
-                    value: alias,
-                });
-                tokens.push(Token {
-                    token_type: TokenType::Operator,
-                    value: ".".to_string(),
-                });
-                tokens.push(Token {
-                    token_type: TokenType::Column,
-                    value: column,
-                });
+                push_token(&mut tokens, TokenType::Text, alias);
+                push_token(&mut tokens, TokenType::Operator, ".".to_string());
+                push_token(&mut tokens, TokenType::Column, column);
                 i += length;
                 continue;
             }
@@ -216,6 +392,8 @@ fn tokenize_sql(sql: &str, config: &HighlightConfig) -> Vec<Token> {
                         }
                     }
                 }
+            } else if is_function_call(&chars, i, &tokens) {
+                TokenType::KeywordFunction
             } else if table_set.contains(&upper_word) {
                 TokenType::Table
             } else if column_set.contains(&upper_word) {
@@ -224,10 +402,7 @@ fn tokenize_sql(sql: &str, config: &HighlightConfig) -> Vec<Token> {
                 TokenType::Text
             };
 
-            tokens.push(Token {
-                token_type,
-                value: word,
-            });
+            push_token(&mut tokens, token_type, word);
             continue;
         }
 
@@ -235,36 +410,53 @@ fn tokenize_sql(sql: &str, config: &HighlightConfig) -> Vec<Token> {
         if i + 1 < chars.len() {
             let two_char = format!("{}{}", ch, chars[i + 1]);
             if matches!(two_char.as_str(), "<>" | "!=" | "<=" | ">=" | "||") {
-                tokens.push(Token {
-                    token_type: TokenType::Operator,
-                    value: two_char,
-                });
+                push_token(&mut tokens, TokenType::Operator, two_char);
                 i += 2;
                 continue;
             }
         }
 
+        // Parentheses (paired up later by match_parens)
+        if ch == '(' || ch == ')' {
+            let token_type = if ch == '(' { TokenType::ParenOpen } else { TokenType::ParenClose };
+            push_token(&mut tokens, token_type, ch.to_string());
+            i += 1;
+            continue;
+        }
+
         // Single-character operators
-        if matches!(ch, '=' | '<' | '>' | '+' | '-' | '*' | '/' | '%' | '(' | ')' | ',' | ';' | '.') {
-            tokens.push(Token {
-                token_type: TokenType::Operator,
-                value: ch.to_string(),
-            });
+        if matches!(ch, '=' | '<' | '>' | '+' | '-' | '*' | '/' | '%' | ',' | ';' | '.') {
+            push_token(&mut tokens, TokenType::Operator, ch.to_string());
             i += 1;
             continue;
         }
 
         // Everything else (whitespace, punctuation, etc.)
-        tokens.push(Token {
-            token_type: TokenType::Text,
-            value: ch.to_string(),
-        });
+        push_token(&mut tokens, TokenType::Text, ch.to_string());
         i += 1;
     }
 
     tokens
 }
 
+/// Whether the identifier ending at `end` is a function call: the next non-whitespace
+/// character is `(` and the identifier doesn't follow FROM/JOIN/INTO, where `name (`
+/// is a table (or column list) rather than a call.
+fn is_function_call(chars: &[char], end: usize, tokens: &[Token]) -> bool {
+    let next = chars[end..].iter().find(|c| !c.is_whitespace());
+    if next != Some(&'(') {
+        return false;
+    }
+
+    let previous = tokens
+        .iter()
+        .rev()
+        .find(|t| t.token_type != TokenType::Comment && !t.value.trim().is_empty());
+    !previous.is_some_and(|t| {
+        matches!(t.value.to_uppercase().as_str(), "FROM" | "JOIN" | "INTO")
+    })
+}
+
 /// Try to match alias.column pattern
 fn try_match_aliased_column(
     chars: &[char],
@@ -309,27 +501,56 @@ fn try_match_aliased_column(
     }
 }
 
-fn tokens_to_html(tokens: &[Token]) -> String {
+/// Pair up parentheses by nesting depth. Each matched pair shares a group id
+/// (numbered in order of the opening paren); unbalanced parens get None.
+/// Parens inside strings, quoted identifiers and comments are never Paren tokens,
+/// so they are ignored here.
+fn match_parens(tokens: &[Token]) -> Vec<Option<usize>> {
+    let mut groups = vec![None; tokens.len()];
+    let mut open_stack: Vec<(usize, usize)> = Vec::new();
+    let mut next_group = 0;
+
+    for (idx, token) in tokens.iter().enumerate() {
+        match token.token_type {
+            TokenType::ParenOpen => {
+                open_stack.push((idx, next_group));
+                next_group += 1;
+            }
+            TokenType::ParenClose => {
+                if let Some((open_idx, group)) = open_stack.pop() {
+                    groups[open_idx] = Some(group);
+                    groups[idx] = Some(group);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    groups
+}
+
+fn tokens_to_html(tokens: &[Token], paren_groups: &[Option<usize>], prefix: &str) -> String {
     let mut html = String::new();
 
-    for token in tokens {
+    for (idx, token) in tokens.iter().enumerate() {
         let escaped = escape_html(&token.value);
 
-        let wrapped = match token.token_type {
-            TokenType::KeywordDml => format!("<span class=\"sql-keyword-dml\">{}</span>", escaped),
-            TokenType::KeywordClause => format!("<span class=\"sql-keyword-clause\">{}</span>", escaped),
-            TokenType::KeywordReserved => format!("<span class=\"sql-keyword-reserved\">{}</span>", escaped),
-            TokenType::KeywordUnreserved => format!("<span class=\"sql-keyword-unreserved\">{}</span>", escaped),
-            TokenType::KeywordType => format!("<span class=\"sql-keyword-type\">{}</span>", escaped),
-            TokenType::KeywordFunction => format!("<span class=\"sql-keyword-function\">{}</span>", escaped),
-            TokenType::KeywordCommon => format!("<span class=\"sql-keyword-common\">{}</span>", escaped),
-            TokenType::Table => format!("<span class=\"sql-table\">{}</span>", escaped),
-            TokenType::Column => format!("<span class=\"sql-column\">{}</span>", escaped),
-            TokenType::String => format!("<span class=\"sql-string\">{}</span>", escaped),
-            TokenType::Number => format!("<span class=\"sql-number\">{}</span>", escaped),
-            TokenType::Operator => format!("<span class=\"sql-operator\">{}</span>", escaped),
-            TokenType::Comment => format!("<span class=\"sql-comment\">{}</span>", escaped),
-            TokenType::Text => escaped,
+        let wrapped = match (&token.token_type, token.token_type.class_suffix()) {
+            (TokenType::ParenOpen | TokenType::ParenClose, _) => match paren_groups.get(idx).copied().flatten() {
+                Some(group) => format!(
+                    "<span class=\"{p}-operator {p}-paren\" data-paren-group=\"{}\">{}</span>",
+                    group,
+                    escaped,
+                    p = prefix
+                ),
+                None => format!(
+                    "<span class=\"{p}-operator {p}-paren-unmatched\">{}</span>",
+                    escaped,
+                    p = prefix
+                ),
+            },
+            (_, Some(suffix)) => format!("<span class=\"{}-{}\">{}</span>", prefix, suffix, escaped),
+            (_, None) => escaped,
         };
 
         html.push_str(&wrapped);
@@ -361,9 +582,174 @@ mod tests {
                 },
             ],
             schema: None,
+            class_prefix: None,
         };
 
         let html = highlight_sql("SELECT * FROM users", &config);
         assert!(html.contains("sql-keyword"));
     }
+
+    #[test]
+    fn test_split_statements_respects_quotes_and_comments() {
+        let script = "INSERT INTO t VALUES ('a;b');\n-- done; really\nUPDATE t SET \"x;y\" = 1 /* ; */;\n;\n-- trailing comment";
+        assert_eq!(
+            split_statements(script),
+            vec![
+                "INSERT INTO t VALUES ('a;b')".to_string(),
+                "-- done; really\nUPDATE t SET \"x;y\" = 1 /* ; */".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_top_level_limit_and_statement_end() {
+        assert!(statement_shape("SELECT * FROM t limit 10;").has_limit);
+        assert!(!statement_shape("SELECT 'no LIMIT here'").has_limit);
+        assert!(!statement_shape("SELECT * FROM (SELECT * FROM t LIMIT 5) s -- LIMIT").has_limit);
+        assert!(!statement_shape("WITH x AS (SELECT 1 LIMIT 1) SELECT * FROM x").has_limit);
+
+        assert_eq!(trim_statement_end("SELECT 1; -- done\n"), "SELECT 1");
+        assert_eq!(trim_statement_end("SELECT ';' /* c */ ;;"), "SELECT ';'");
+    }
+
+    #[test]
+    fn test_statement_shape_aggregates_and_windows() {
+        let shape = statement_shape("SELECT COUNT(*) FROM huge_table");
+        assert!(shape.is_single_row_aggregate());
+
+        let shape = statement_shape("SELECT status, count(*) FROM t GROUP BY status HAVING count(*) > 1");
+        assert!(shape.has_group_by && !shape.is_single_row_aggregate());
+
+        let shape = statement_shape("SELECT id, SUM(x) OVER (PARTITION BY g) FROM t");
+        assert!(shape.has_window && !shape.is_single_row_aggregate());
+
+        // Aggregates inside a subquery don't make the outer query single-row
+        assert!(!statement_shape("SELECT * FROM (SELECT count(*) AS n FROM t) s").has_aggregate);
+        assert!(statement_shape("SELECT * FROM t ORDER BY id FETCH FIRST 5 ROWS ONLY").is_limited());
+        assert!(!statement_shape("SELECT count(*) FROM a UNION ALL SELECT count(*) FROM b")
+            .is_single_row_aggregate());
+    }
+
+    #[test]
+    fn test_paren_groups_nested_and_unmatched() {
+        let config = HighlightConfig {
+            keywords: vec![],
+            schema: None,
+            class_prefix: None,
+        };
+
+        let sql = "SELECT (a + (b)) ')' /* ( */ FROM (x))";
+        let tokens = tokenize_sql(sql, &config);
+        let groups = match_parens(&tokens);
+        let parens: Vec<(&str, Option<usize>)> = tokens
+            .iter()
+            .zip(&groups)
+            .filter(|(t, _)| matches!(t.token_type, TokenType::ParenOpen | TokenType::ParenClose))
+            .map(|(t, g)| (t.value.as_str(), *g))
+            .collect();
+
+        assert_eq!(
+            parens,
+            vec![
+                ("(", Some(0)),
+                ("(", Some(1)),
+                (")", Some(1)),
+                (")", Some(0)),
+                ("(", Some(2)),
+                (")", Some(2)),
+                (")", None),
+            ]
+        );
+
+        let html = highlight_sql(sql, &config);
+        assert!(html.contains("data-paren-group=\"1\""));
+        assert_eq!(html.matches("sql-paren-unmatched").count(), 1);
+    }
+
+    #[test]
+    fn test_class_prefix_and_token_offsets() {
+        let mut config = HighlightConfig {
+            keywords: vec![SqlKeyword {
+                word: "SELECT".to_string(),
+                category: "reserved".to_string(),
+                description: None,
+            }],
+            schema: None,
+            class_prefix: None,
+        };
+
+        let sql = "SELECT 'é' (1)";
+        let default_html = highlight_sql(sql, &config);
+        assert!(default_html.contains("class=\"sql-keyword-dml\""));
+        assert!(default_html.contains("class=\"sql-operator sql-paren\""));
+
+        config.class_prefix = Some("hl".to_string());
+        let html = highlight_sql(sql, &config);
+        assert!(html.contains("class=\"hl-keyword-dml\""));
+        assert!(html.contains("class=\"hl-operator hl-paren\""));
+        assert!(!html.contains("sql-"));
+
+        let tokens = tokenize_sql(sql, &config);
+        for token in &tokens {
+            assert_eq!(&sql[token.start..token.end], token.value);
+        }
+        let string = tokens.iter().find(|t| t.token_type == TokenType::String).unwrap();
+        assert_eq!((string.start, string.end), (7, 11));
+        assert_eq!(tokens.last().unwrap().end, sql.len());
+
+        let json = serde_json::to_value(&tokens[0]).unwrap();
+        assert_eq!(json["type"], "keyword_dml");
+    }
+
+    #[test]
+    fn test_function_call_detection() {
+        let config = HighlightConfig {
+            keywords: vec![],
+            schema: Some(Schema {
+                database_name: "shop".to_string(),
+                tables: vec![crate::db::schema::Table::fixture("orders", vec![])],
+                routines: vec![],
+            }),
+            class_prefix: None,
+        };
+
+        let sql = "SELECT my_fn (a), orders FROM orders (x) JOIN orders(y) WHERE b (c)";
+        let kinds: Vec<(&str, TokenType)> = tokenize_sql(sql, &config)
+            .iter()
+            .filter(|t| t.value.chars().all(|c| c.is_alphanumeric() || c == '_'))
+            .map(|t| (&sql[t.start..t.end], t.token_type.clone()))
+            .collect();
+
+        assert_eq!(kinds[1], ("my_fn", TokenType::KeywordFunction));
+        assert_eq!(kinds[3], ("orders", TokenType::Table));
+        assert_eq!(kinds[5], ("orders", TokenType::Table));
+        assert_eq!(kinds[8], ("orders", TokenType::Table));
+        assert_eq!(kinds[11], ("b", TokenType::KeywordFunction));
+    }
+
+    #[test]
+    fn normalize_sql_ignores_layout_and_keyword_case_only() {
+        let pg = DatabaseType::PostgreSQL;
+        let key = normalize_sql("SELECT name\n  FROM users -- active only\nWHERE city = 'Oslo';", &pg);
+        assert_eq!(key, normalize_sql("select   NAME from USERS where CITY = 'Oslo'", &pg));
+        assert_ne!(key, normalize_sql("SELECT name FROM users WHERE city = 'oslo'", &pg));
+        assert_ne!(
+            normalize_sql("SELECT \"Name\" FROM users", &pg),
+            normalize_sql("SELECT \"name\" FROM users", &pg)
+        );
+    }
+
+    #[test]
+    fn normalize_sql_keeps_identifier_case_on_mysql() {
+        // `users` and `USERS` are different tables on a case-sensitive MySQL server
+        let mysql = DatabaseType::MySQL;
+        assert_ne!(
+            normalize_sql("SELECT * FROM users", &mysql),
+            normalize_sql("SELECT * FROM USERS", &mysql)
+        );
+        assert_eq!(
+            normalize_sql("SELECT * FROM users -- all\n;", &mysql),
+            normalize_sql("SELECT  *\nFROM users", &mysql)
+        );
+    }
 }
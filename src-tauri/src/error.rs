@@ -12,6 +12,9 @@ pub enum AppError {
     #[error("Query error: {0}")]
     QueryError(String),
 
+    #[error("Query timed out: {0}")]
+    QueryTimeout(String),
+
     #[error("IO error: {0}")]
     IoError(String),
 
@@ -48,6 +51,15 @@ pub enum AppError {
     #[error("Validation error: {0}")]
     ValidationError(String),
 
+    #[error("Concurrent modification: {0}")]
+    ConcurrentModification(String),
+
+    #[error("Read-only connection: {0}")]
+    ReadOnlyConnection(String),
+
+    #[error("No primary key: {0}")]
+    NoPrimaryKey(String),
+
     #[error("{0}")]
     Other(String),
 }
@@ -82,6 +94,12 @@ impl From<zip::result::ZipError> for AppError {
     }
 }
 
+impl From<rust_xlsxwriter::XlsxError> for AppError {
+    fn from(err: rust_xlsxwriter::XlsxError) -> Self {
+        AppError::ImportExportError(err.to_string())
+    }
+}
+
 impl From<csv::Error> for AppError {
     fn from(err: csv::Error) -> Self {
         AppError::ImportExportError(err.to_string())
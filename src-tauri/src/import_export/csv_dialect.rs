@@ -0,0 +1,282 @@
+use crate::error::{AppError, AppResult};
+use csv::{QuoteStyle, ReaderBuilder, Terminator, WriterBuilder};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::io::{BufRead, Read};
+
+pub const DEFAULT_CSV_DELIMITER: u8 = b',';
+pub const DEFAULT_CSV_QUOTE: u8 = b'"';
+
+pub fn default_csv_delimiter() -> u8 {
+    DEFAULT_CSV_DELIMITER
+}
+
+pub fn default_csv_quote() -> u8 {
+    DEFAULT_CSV_QUOTE
+}
+
+pub fn default_has_headers() -> bool {
+    true
+}
+
+/// Line ending written after each CSV record
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CsvLineTerminator {
+    #[default]
+    Lf,
+    Crlf,
+}
+
+/// Text encoding of imported CSV files; exports are always written as UTF-8
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CsvEncoding {
+    #[default]
+    Utf8,
+    /// ISO-8859-1: every byte is the code point with the same value
+    Latin1,
+    /// Latin-1 with printable characters (€, curly quotes, dashes) in 0x80-0x9F, as Excel writes
+    Windows1252,
+}
+
+/// Windows-1252 characters for bytes 0x80-0x9F; unassigned bytes map to the C1 control of the same value
+const WINDOWS_1252_HIGH: [char; 32] = [
+    '\u{20AC}', '\u{0081}', '\u{201A}', '\u{0192}', '\u{201E}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{02C6}', '\u{2030}', '\u{0160}', '\u{2039}', '\u{0152}', '\u{008D}', '\u{017D}', '\u{008F}',
+    '\u{0090}', '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{02DC}', '\u{2122}', '\u{0161}', '\u{203A}', '\u{0153}', '\u{009D}', '\u{017E}', '\u{0178}',
+];
+
+impl CsvEncoding {
+    fn decode_byte(self, byte: u8) -> char {
+        match (self, byte) {
+            (Self::Windows1252, 0x80..=0x9F) => WINDOWS_1252_HIGH[(byte - 0x80) as usize],
+            _ => char::from(byte),
+        }
+    }
+}
+
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Presents a CSV file to the parser as UTF-8: drops a leading UTF-8 byte order mark, which
+/// would otherwise end up in the first header name, and transcodes single-byte encodings.
+/// A BOM means the file is UTF-8 whatever encoding was chosen.
+pub struct DecodingReader<R> {
+    inner: R,
+    encoding: CsvEncoding,
+    checked_bom: bool,
+    /// Transcoded bytes not handed out yet, starting at `decoded_pos`
+    decoded: Vec<u8>,
+    decoded_pos: usize,
+}
+
+impl<R: BufRead> DecodingReader<R> {
+    pub fn new(inner: R, encoding: CsvEncoding) -> Self {
+        Self {
+            inner,
+            encoding,
+            checked_bom: false,
+            decoded: Vec::new(),
+            decoded_pos: 0,
+        }
+    }
+}
+
+impl<R: BufRead> Read for DecodingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if !self.checked_bom {
+            self.checked_bom = true;
+            if self.inner.fill_buf()?.starts_with(UTF8_BOM) {
+                self.inner.consume(UTF8_BOM.len());
+                self.encoding = CsvEncoding::Utf8;
+            }
+        }
+        if self.encoding == CsvEncoding::Utf8 {
+            return self.inner.read(buf);
+        }
+
+        if self.decoded_pos == self.decoded.len() {
+            let encoding = self.encoding;
+            let raw = self.inner.fill_buf()?;
+            if raw.is_empty() {
+                return Ok(0);
+            }
+            let consumed = raw.len();
+            self.decoded = raw.iter().map(|&byte| encoding.decode_byte(byte)).collect::<String>().into_bytes();
+            self.decoded_pos = 0;
+            self.inner.consume(consumed);
+        }
+
+        let n = buf.len().min(self.decoded.len() - self.decoded_pos);
+        buf[..n].copy_from_slice(&self.decoded[self.decoded_pos..self.decoded_pos + n]);
+        self.decoded_pos += n;
+        Ok(n)
+    }
+}
+
+/// Accept a delimiter or quote as a byte value (`59`) or a one-character string (`";"`, `"\t"`)
+pub fn deserialize_csv_byte<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u8, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum CsvByte {
+        Byte(u8),
+        Text(String),
+    }
+
+    match CsvByte::deserialize(deserializer)? {
+        CsvByte::Byte(byte) => Ok(byte),
+        CsvByte::Text(text) => match text.as_bytes() {
+            [byte] => Ok(*byte),
+            _ => Err(serde::de::Error::custom(format!(
+                "CSV delimiter and quote must be a single-byte character, got {:?}",
+                text
+            ))),
+        },
+    }
+}
+
+/// How CSV files are split into fields and records
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CsvDialect {
+    pub delimiter: u8,
+    pub quote: u8,
+    pub has_headers: bool,
+    pub line_terminator: CsvLineTerminator,
+    /// Only read by import
+    pub encoding: CsvEncoding,
+}
+
+impl Default for CsvDialect {
+    fn default() -> Self {
+        Self {
+            delimiter: DEFAULT_CSV_DELIMITER,
+            quote: DEFAULT_CSV_QUOTE,
+            has_headers: true,
+            line_terminator: CsvLineTerminator::Lf,
+            encoding: CsvEncoding::Utf8,
+        }
+    }
+}
+
+impl CsvDialect {
+    /// Reject bytes the csv crate would accept but that make files unreadable: letters,
+    /// digits, line breaks, other control characters and non-ASCII bytes
+    pub fn validate(&self) -> AppResult<()> {
+        for (name, byte) in [("delimiter", self.delimiter), ("quote character", self.quote)] {
+            let usable = byte == b'\t' || byte == b' ' || (byte.is_ascii_graphic() && !byte.is_ascii_alphanumeric());
+            if !usable {
+                return Err(AppError::ValidationError(format!(
+                    "Invalid CSV {} {:?}: use a punctuation character, space or tab",
+                    name,
+                    char::from(byte)
+                )));
+            }
+        }
+        if self.delimiter == self.quote {
+            return Err(AppError::ValidationError(format!(
+                "CSV delimiter and quote character must differ (both are {:?})",
+                char::from(self.delimiter)
+            )));
+        }
+        Ok(())
+    }
+
+    /// Writer settings; non-numeric fields are always quoted so empty strings stay
+    /// distinct from unquoted empty fields on import. Callers skip the header row
+    /// themselves when `has_headers` is off.
+    pub fn writer_builder(&self) -> WriterBuilder {
+        let mut builder = WriterBuilder::new();
+        builder
+            .quote_style(QuoteStyle::NonNumeric)
+            .delimiter(self.delimiter)
+            .quote(self.quote)
+            .terminator(match self.line_terminator {
+                CsvLineTerminator::Lf => Terminator::Any(b'\n'),
+                CsvLineTerminator::Crlf => Terminator::CRLF,
+            });
+        builder
+    }
+
+    /// Reader settings; any of `\r`, `\n` or `\r\n` ends a record
+    pub fn reader_builder(&self) -> ReaderBuilder {
+        let mut builder = ReaderBuilder::new();
+        builder
+            .delimiter(self.delimiter)
+            .quote(self.quote)
+            .has_headers(self.has_headers);
+        builder
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Options {
+        #[serde(default = "default_csv_delimiter", deserialize_with = "deserialize_csv_byte")]
+        delimiter: u8,
+    }
+
+    #[test]
+    fn delimiters_parse_from_bytes_or_characters_and_bad_ones_are_rejected() {
+        let parse = |json: &str| serde_json::from_str::<Options>(json).map(|o| o.delimiter);
+        assert_eq!(parse("{}").unwrap(), b',');
+        assert_eq!(parse(r#"{"delimiter":59}"#).unwrap(), b';');
+        assert_eq!(parse(r#"{"delimiter":"\t"}"#).unwrap(), b'\t');
+        assert!(parse(r#"{"delimiter":";;"}"#).is_err());
+        assert!(parse(r#"{"delimiter":"é"}"#).is_err());
+
+        let dialect = |delimiter, quote| CsvDialect { delimiter, quote, ..CsvDialect::default() };
+        assert!(CsvDialect::default().validate().is_ok());
+        assert!(dialect(b'|', b'\'').validate().is_ok());
+        assert!(dialect(b'\n', b'"').validate().is_err());
+        assert!(dialect(b'a', b'"').validate().is_err());
+        assert!(dialect(b'"', b'"').validate().is_err());
+    }
+
+    #[test]
+    fn writer_applies_dialect() {
+        let dialect = CsvDialect {
+            delimiter: b';',
+            quote: b'\'',
+            has_headers: true,
+            line_terminator: CsvLineTerminator::Crlf,
+            ..CsvDialect::default()
+        };
+        let mut writer = dialect.writer_builder().from_writer(Vec::new());
+        writer.write_record(["id", "it's"]).unwrap();
+        writer.write_record(["1", ""]).unwrap();
+        let written = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(written, "'id';'it''s'\r\n1;''\r\n");
+
+        let mut reader = dialect.reader_builder().from_reader(written.as_bytes());
+        let record = reader.records().next().unwrap().unwrap();
+        assert_eq!(record.iter().collect::<Vec<_>>(), vec!["1", ""]);
+    }
+
+    fn decode(raw: &[u8], encoding: CsvEncoding) -> String {
+        let mut decoded = String::new();
+        // A tiny buffer makes the reader refill several times
+        let inner = std::io::BufReader::with_capacity(4, raw);
+        DecodingReader::new(inner, encoding).read_to_string(&mut decoded).unwrap();
+        decoded
+    }
+
+    #[test]
+    fn decoding_reader_strips_bom_and_transcodes() {
+        let bom_csv = b"\xEF\xBB\xBFid,name\n1,caf\xC3\xA9\n";
+        assert_eq!(decode(bom_csv, CsvEncoding::Utf8), "id,name\n1,caf\u{e9}\n");
+        // The BOM wins over a mistaken single-byte choice
+        assert_eq!(decode(bom_csv, CsvEncoding::Windows1252), "id,name\n1,caf\u{e9}\n");
+
+        let mut reader = CsvDialect::default()
+            .reader_builder()
+            .from_reader(DecodingReader::new(&bom_csv[..], CsvEncoding::Utf8));
+        assert_eq!(reader.headers().unwrap().get(0), Some("id"));
+
+        let ansi = b"caf\xE9 \x80\x93\x94";
+        assert_eq!(decode(ansi, CsvEncoding::Windows1252), "caf\u{e9} \u{20ac}\u{201c}\u{201d}");
+        assert_eq!(decode(ansi, CsvEncoding::Latin1), "caf\u{e9} \u{80}\u{93}\u{94}");
+    }
+}
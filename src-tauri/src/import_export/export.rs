@@ -1,13 +1,19 @@
 use crate::db::connection::{ConnectionManager, DatabaseType};
+use crate::db::schema::Schema;
 use crate::error::{AppError, AppResult};
-use csv::Writer;
+use crate::import_export::csv_dialect::{
+    default_csv_delimiter, default_csv_quote, default_has_headers, deserialize_csv_byte, CsvDialect,
+    CsvLineTerminator,
+};
+use crate::import_export::export_manifest::{options_fingerprint, ExportManifest};
+use crate::import_export::masking::MaskStrategy;
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use sqlx::types::ipnetwork;
 use std::collections::HashMap;
 use std::fs::{self, File};
 use std::io::{BufWriter, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tauri::{AppHandle, Emitter};
 use tokio::sync::RwLock;
@@ -41,12 +47,274 @@ lazy_static::lazy_static! {
     static ref EXPORT_TOKENS: Arc<RwLock<HashMap<String, CancellationToken>>> = Arc::new(RwLock::new(HashMap::new()));
 }
 
+/// Output file format for exported tables
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    /// One JSON array of objects per table
+    Json,
+    /// Newline-delimited JSON, one object per line
+    Ndjson,
+    /// Excel workbook with one sheet per table
+    Xlsx,
+    /// Typed columnar file per table (Arrow types, Decimal128 for fixed-point)
+    Parquet,
+    /// Batched INSERT statements per table, loaded after schema.sql
+    #[serde(rename = "sql")]
+    SqlDump,
+}
+
+impl ExportFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+            ExportFormat::Ndjson => "ndjson",
+            ExportFormat::Xlsx => "xlsx",
+            ExportFormat::Parquet => "parquet",
+            // Keeps a table named "schema" from overwriting schema.sql
+            ExportFormat::SqlDump => "data.sql",
+        }
+    }
+}
+
+/// How a formatted value is represented in typed (JSON/XLSX) output
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ValueKind {
+    Integer,
+    Float,
+    /// Arbitrary precision numbers; kept as text in JSON to preserve precision
+    Decimal,
+    Bool,
+    Json,
+    Date,
+    DateTime,
+    Time,
+    /// Hex encoded bytes (`\x..` for PostgreSQL, `0x..` for MySQL)
+    Binary,
+    /// MySQL spatial value exported as WKT
+    Spatial,
+    Text,
+}
+
+/// Maximum sheet name length allowed by Excel
+const XLSX_MAX_SHEET_NAME_LEN: usize = 31;
+
+/// Maximum number of rows in an Excel worksheet (including the header row)
+const XLSX_MAX_ROWS: usize = 1_048_576;
+
+/// Maximum number of characters in an Excel cell
+const XLSX_MAX_CELL_CHARS: usize = 32_767;
+
+/// Formatted rows for a single table, ready to be written out
+struct TableData {
+    column_names: Vec<String>,
+    kinds: Vec<ValueKind>,
+    /// Precision and scale for DECIMAL columns, used for typed (Parquet) output
+    decimal_specs: Vec<Option<DecimalSpec>>,
+    records: Vec<Vec<String>>,
+}
+
+/// Precision and scale of a fixed-point column
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct DecimalSpec {
+    precision: u8,
+    scale: i8,
+}
+
+impl DecimalSpec {
+    /// Only precisions that fit Arrow's Decimal128 (at most 38 digits) are kept
+    fn new(precision: Option<i32>, scale: Option<i32>) -> Option<Self> {
+        let precision = u8::try_from(precision?).ok()?;
+        let scale = i8::try_from(scale.unwrap_or(0)).ok()?;
+        if precision == 0 || precision > 38 || scale < 0 || scale as u8 > precision {
+            return None;
+        }
+        Some(Self { precision, scale })
+    }
+}
+
+/// Rows per multi-row INSERT statement in SQL dumps
+const SQL_DUMP_BATCH_ROWS: usize = 500;
+
+/// Rows per Parquet row group / Arrow record batch
+const PARQUET_BATCH_ROWS: usize = 65_536;
+
+/// Column naming the source table of each record in a combined export
+const COMBINED_TABLE_COLUMN: &str = "__table";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExportOptions {
     pub connection_id: String,
     pub tables: Vec<String>,
     pub output_dir: String,
     pub create_zip: bool,
+    #[serde(default)]
+    pub format: ExportFormat,
+    /// Columns to redact, keyed by "table.column"
+    #[serde(default)]
+    pub mask_columns: HashMap<String, MaskStrategy>,
+    /// Row filter applied to every table without an entry in `table_filters`
+    #[serde(default)]
+    pub where_clause: Option<String>,
+    #[serde(default)]
+    pub max_rows: Option<usize>,
+    /// Per-table filters, keyed by table name; override `where_clause`/`max_rows`
+    #[serde(default)]
+    pub table_filters: HashMap<String, TableFilter>,
+    /// Columns to export per table, in output order; tables without an entry export every column
+    #[serde(default)]
+    pub column_selection: HashMap<String, Vec<String>>,
+    /// Write every table into one CSV/NDJSON file, tagging each record with a
+    /// `__table` column. CSV headers are the union of all tables' columns.
+    #[serde(default)]
+    pub combine_into_single_file: bool,
+    /// CSV field separator, e.g. `;` or a tab; defaults to a comma
+    #[serde(default = "default_csv_delimiter", deserialize_with = "deserialize_csv_byte")]
+    pub delimiter: u8,
+    /// CSV quote character; defaults to a double quote
+    #[serde(default = "default_csv_quote", deserialize_with = "deserialize_csv_byte")]
+    pub quote: u8,
+    /// Write a header row with the column names
+    #[serde(default = "default_has_headers")]
+    pub has_headers: bool,
+    #[serde(default)]
+    pub line_terminator: CsvLineTerminator,
+    /// Continue an interrupted export to the same output path, skipping tables whose
+    /// files were completed; only for exports that write one file per table
+    #[serde(default)]
+    pub resume: bool,
+    /// Only write `schema.sql`, no table data
+    #[serde(default)]
+    pub schema_only: bool,
+    /// Only write table data, no `schema.sql`
+    #[serde(default)]
+    pub data_only: bool,
+}
+
+/// Subset of a table's rows to export
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TableFilter {
+    /// SQL condition without the WHERE keyword, e.g. `status = 'active'`
+    #[serde(default)]
+    pub where_clause: Option<String>,
+    #[serde(default)]
+    pub max_rows: Option<usize>,
+}
+
+impl TableFilter {
+    /// ` WHERE (...) LIMIT n` suffix for the export SELECT; the condition is validated first
+    fn sql_suffix(&self, db_type: &DatabaseType) -> AppResult<String> {
+        let mut suffix = String::new();
+        if let Some(clause) = self.where_clause.as_deref().filter(|c| !c.trim().is_empty()) {
+            crate::ai::sanitizer::validate_where_clause(clause, db_type)?;
+            suffix.push_str(&format!(" WHERE ({})", clause.trim()));
+        }
+        if let Some(max_rows) = self.max_rows {
+            suffix.push_str(&format!(" LIMIT {}", max_rows));
+        }
+        Ok(suffix)
+    }
+}
+
+impl ExportOptions {
+    fn csv_dialect(&self) -> CsvDialect {
+        CsvDialect {
+            delimiter: self.delimiter,
+            quote: self.quote,
+            has_headers: self.has_headers,
+            line_terminator: self.line_terminator,
+            ..CsvDialect::default()
+        }
+    }
+
+    /// Whether each table gets its own file, so progress can be tracked per table
+    fn writes_table_files(&self) -> bool {
+        !self.schema_only
+            && !self.combine_into_single_file
+            && !(self.format == ExportFormat::Xlsx && !self.create_zip)
+    }
+
+    /// Reject combinations of options that can't be exported together
+    fn validate_scope(&self) -> AppResult<()> {
+        if self.schema_only && self.data_only {
+            return Err(AppError::ValidationError(
+                "Choose either a schema-only or a data-only export, not both".to_string(),
+            ));
+        }
+
+        if self.combine_into_single_file && !matches!(self.format, ExportFormat::Csv | ExportFormat::Ndjson) {
+            return Err(AppError::ValidationError(
+                "Only CSV and NDJSON exports can be combined into a single file".to_string(),
+            ));
+        }
+
+        if self.resume && !self.writes_table_files() {
+            return Err(AppError::ValidationError(
+                "Only exports that write one file per table can be resumed".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Steps reported in progress events: one per table, or just the schema file
+    fn progress_total(&self) -> usize {
+        if self.schema_only {
+            1
+        } else {
+            self.tables.len()
+        }
+    }
+
+    /// Identifies the options a resumable export was started with; `resume` itself is left out
+    fn fingerprint(&self) -> AppResult<String> {
+        options_fingerprint(&ExportOptions { resume: false, ..self.clone() })
+    }
+
+    /// Resolve `column_selection` against the schema so a misspelled column fails
+    /// before any file is written
+    fn resolve_column_selection(&self, schema: &Schema) -> AppResult<HashMap<String, Vec<String>>> {
+        self.column_selection
+            .iter()
+            .map(|(table_name, columns)| {
+                if !self.tables.contains(table_name) {
+                    return Err(AppError::ValidationError(format!(
+                        "Columns were selected for '{}', which is not being exported",
+                        table_name
+                    )));
+                }
+                if columns.is_empty() {
+                    return Err(AppError::ValidationError(format!(
+                        "Select at least one column to export from '{}'",
+                        table_name
+                    )));
+                }
+
+                let mut resolved: Vec<String> = Vec::with_capacity(columns.len());
+                for column in columns {
+                    let name = crate::db::query::resolve_filter_column(schema, table_name, column)?;
+                    if resolved.contains(&name) {
+                        return Err(AppError::ValidationError(format!(
+                            "Column '{}' is selected more than once for '{}'",
+                            name, table_name
+                        )));
+                    }
+                    resolved.push(name);
+                }
+                Ok((table_name.clone(), resolved))
+            })
+            .collect()
+    }
+
+    /// Filter for one table: its own entry if present, otherwise the global one
+    fn filter_for(&self, table_name: &str) -> TableFilter {
+        self.table_filters.get(table_name).cloned().unwrap_or_else(|| TableFilter {
+            where_clause: self.where_clause.clone(),
+            max_rows: self.max_rows,
+        })
+    }
 }
 
 pub async fn export_tables(
@@ -57,6 +325,28 @@ pub async fn export_tables(
     use futures::stream::{self, StreamExt};
     use tokio::sync::Mutex;
 
+    // Reject a bad filter before any files are written
+    let conn = manager.get_connection(&options.connection_id)?;
+    let db_type = conn.database_type.clone();
+    let table_filters: HashMap<String, String> = options
+        .tables
+        .iter()
+        .map(|table| Ok((table.clone(), options.filter_for(table).sql_suffix(&db_type)?)))
+        .collect::<AppResult<_>>()?;
+
+    let csv_dialect = options.csv_dialect();
+    csv_dialect.validate()?;
+
+    options.validate_scope()?;
+    let fingerprint = options.fingerprint()?;
+
+    let column_selection = if options.column_selection.is_empty() || options.schema_only {
+        HashMap::new()
+    } else {
+        let schema = crate::db::schema::get_schema(manager, &options.connection_id, &app).await?;
+        options.resolve_column_selection(&schema)?
+    };
+
     // Create and register cancellation token
     let cancel_token = CancellationToken::new();
     let export_id = options.connection_id.clone();
@@ -70,17 +360,19 @@ pub async fn export_tables(
         // For ZIP: user selected path is the final ZIP location
         let zip_path = PathBuf::from(&options.output_dir);
 
-        // Create a temporary directory for CSV files
+        // Create a temporary directory for CSV files, named after the ZIP so a
+        // resumed export finds the files of the interrupted one
         let parent = zip_path.parent()
             .ok_or_else(|| AppError::IoError("Invalid output path".to_string()))?;
-        let temp_dir_name = format!(
-            ".dataspeak_export_{}",
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs()
-        );
-        let temp_dir = parent.join(temp_dir_name);
+        let zip_name = zip_path
+            .file_name()
+            .ok_or_else(|| AppError::IoError("Invalid output path".to_string()))?
+            .to_string_lossy();
+        let temp_dir = parent.join(format!(".dataspeak_export_{}.partial", zip_name));
+        if !options.resume {
+            // Leftovers of an earlier run would otherwise end up in the archive
+            fs::remove_dir_all(&temp_dir).ok();
+        }
         fs::create_dir_all(&temp_dir).map_err(|e| {
             AppError::IoError(format!("Failed to create temporary directory: {}", e))
         })?;
@@ -95,10 +387,22 @@ pub async fn export_tables(
         (output_path.clone(), output_path)
     };
 
-    let conn = manager.get_connection(&options.connection_id)?;
-    let db_type = conn.database_type.clone();
-    let table_names = options.tables.clone();
-    let total_tables = table_names.len();
+    // Tables finished by an earlier run with the same options
+    let manifest = if options.writes_table_files() {
+        let manifest = if options.resume {
+            ExportManifest::load(&temp_dir, &fingerprint)
+        } else {
+            None
+        };
+        let manifest = manifest.unwrap_or_else(|| ExportManifest::new(fingerprint));
+        manifest.save(&temp_dir)?;
+        Some(Arc::new(std::sync::Mutex::new(manifest)))
+    } else {
+        None
+    };
+
+    let table_names = if options.schema_only { Vec::new() } else { options.tables.clone() };
+    let total_tables = options.progress_total();
 
     // Emit start event
     app.emit(
@@ -119,20 +423,53 @@ pub async fn export_tables(
     let connection_id = options.connection_id.clone();
 
     // Export schema first
-    app.emit(
-        "export-progress",
-        ExportProgress {
-            table_name: String::new(),
-            current: 0,
-            total: total_tables,
-            status: "Exporting database schema...".to_string(),
-            cancelled: false,
-        },
-    )
-    .ok();
-
     let schema_path = temp_dir.join("schema.sql");
-    export_schema(manager, &connection_id, &schema_path, &db_type, &app).await?;
+    if !options.data_only {
+        app.emit(
+            "export-progress",
+            ExportProgress {
+                table_name: String::new(),
+                current: 0,
+                total: total_tables,
+                status: "Exporting database schema...".to_string(),
+                cancelled: false,
+            },
+        )
+        .ok();
+
+        export_schema(manager, &connection_id, &schema_path, &db_type, &app).await?;
+    }
+
+    let export_name = if conn.default_database.is_empty() {
+        "export".to_string()
+    } else {
+        conn.default_database.clone()
+    };
+
+    // Without a ZIP, all tables go into a single workbook with one sheet per table
+    let shared_workbook = if options.format == ExportFormat::Xlsx && !options.create_zip && !options.schema_only {
+        Some(Arc::new(std::sync::Mutex::new(XlsxWorkbook::new())))
+    } else {
+        None
+    };
+
+    // Tables are fetched in parallel but appended to the combined file one at a time
+    let combined_path = temp_dir.join(format!("{}.{}", export_name, options.format.extension()));
+    let combined_writer = if options.combine_into_single_file && !options.schema_only {
+        let columns = if options.format == ExportFormat::Csv {
+            combined_csv_columns(manager, &connection_id, &db_type, &options.tables, &column_selection).await?
+        } else {
+            Vec::new()
+        };
+        Some(Arc::new(std::sync::Mutex::new(CombinedWriter::create(
+            &combined_path,
+            options.format,
+            columns,
+            &csv_dialect,
+        )?)))
+    } else {
+        None
+    };
 
     // Export tables in parallel with concurrency limit
     let results: Vec<AppResult<()>> = stream::iter(table_names.into_iter())
@@ -144,6 +481,14 @@ pub async fn export_tables(
             let app = app_handle.clone();
             let total = total_tables;
             let cancel_token = cancel_token.clone();
+            let format = options.format;
+            let csv_dialect = &csv_dialect;
+            let workbook = shared_workbook.clone();
+            let combined = combined_writer.clone();
+            let manifest = manifest.clone();
+            let masks = &options.mask_columns;
+            let filter = table_filters.get(&table_name).cloned().unwrap_or_default();
+            let columns = column_selection.get(&table_name).cloned();
 
             async move {
                 // Check for cancellation
@@ -151,16 +496,62 @@ pub async fn export_tables(
                     return Err(AppError::OperationCancelled("Export cancelled by user".to_string()));
                 }
 
+                let already_exported = match &manifest {
+                    Some(shared) => shared
+                        .lock()
+                        .map_err(|e| AppError::ImportExportError(format!("Failed to lock export manifest: {}", e)))?
+                        .is_completed(&table_name, &temp_dir),
+                    None => false,
+                };
+                if already_exported {
+                    let mut count = completed.lock().await;
+                    *count += 1;
+                    let current = *count;
+                    drop(count);
+
+                    app.emit(
+                        "export-progress",
+                        ExportProgress {
+                            table_name: table_name.clone(),
+                            current,
+                            total,
+                            status: format!("Skipped table (already exported): {}", table_name),
+                            cancelled: false,
+                        },
+                    )
+                    .ok();
+                    return Ok(());
+                }
+
                 // Export the table
-                let result = export_table_to_csv(
+                let result = export_table(
                     manager,
                     &connection_id,
                     &table_name,
                     &temp_dir,
                     &db_type,
+                    format,
+                    csv_dialect,
+                    masks,
+                    &filter,
+                    columns.as_deref(),
+                    workbook.as_deref(),
+                    combined.as_deref(),
                 )
                 .await;
 
+                // Only a fully written file is recorded
+                let result = match (result, &manifest) {
+                    (Ok(()), Some(shared)) => {
+                        let mut manifest = shared
+                            .lock()
+                            .map_err(|e| AppError::ImportExportError(format!("Failed to lock export manifest: {}", e)))?;
+                        manifest.mark_completed(&table_name, &table_file_path(&temp_dir, &table_name, format))?;
+                        manifest.save(&temp_dir)
+                    }
+                    (result, _) => result,
+                };
+
                 // Update progress
                 let mut count = completed.lock().await;
                 *count += 1;
@@ -173,7 +564,13 @@ pub async fn export_tables(
                         table_name: table_name.clone(),
                         current,
                         total,
-                        status: format!("Exported table: {}", table_name),
+                        status: if workbook.is_some() {
+                            format!("Added sheet for table: {}", table_name)
+                        } else if combined.is_some() {
+                            format!("Appended table: {}", table_name)
+                        } else {
+                            format!("Exported table: {}", table_name)
+                        },
                         cancelled: false,
                     },
                 )
@@ -220,8 +617,40 @@ pub async fn export_tables(
         return Err(AppError::OperationCancelled("Export cancelled by user".to_string()));
     }
 
+    if let Some(writer) = &combined_writer {
+        writer
+            .lock()
+            .map_err(|e| AppError::ImportExportError(format!("Failed to lock combined file: {}", e)))?
+            .finish()?;
+    }
+
+    // Every table is done, so there is nothing left to resume
+    if manifest.is_some() {
+        ExportManifest::remove(&temp_dir);
+    }
+
     // Create ZIP if requested
-    let result_path = if options.create_zip {
+    let result_path = if let Some(workbook) = shared_workbook {
+        app.emit(
+            "export-progress",
+            ExportProgress {
+                table_name: String::new(),
+                current: total_tables,
+                total: total_tables,
+                status: "Writing Excel workbook...".to_string(),
+                cancelled: false,
+            },
+        )
+        .ok();
+
+        let workbook_path = final_path.join(format!("{}.xlsx", export_name));
+        workbook
+            .lock()
+            .map_err(|e| AppError::ImportExportError(format!("Failed to lock workbook: {}", e)))?
+            .save(&workbook_path)?;
+
+        workbook_path.to_string_lossy().to_string()
+    } else if options.create_zip {
         app.emit(
             "export-progress",
             ExportProgress {
@@ -241,6 +670,10 @@ pub async fn export_tables(
         fs::remove_dir_all(&temp_dir).ok();
 
         final_path.to_string_lossy().to_string()
+    } else if options.schema_only {
+        schema_path.to_string_lossy().to_string()
+    } else if combined_writer.is_some() {
+        combined_path.to_string_lossy().to_string()
     } else {
         final_path.to_string_lossy().to_string()
     };
@@ -278,111 +711,1048 @@ pub async fn cancel_export(connection_id: String) -> AppResult<()> {
     }
 }
 
-async fn export_table_to_csv(
+#[allow(clippy::too_many_arguments)]
+async fn export_table(
     manager: &ConnectionManager,
     connection_id: &str,
     table_name: &str,
     output_path: &PathBuf,
     db_type: &DatabaseType,
+    format: ExportFormat,
+    csv_dialect: &CsvDialect,
+    masks: &HashMap<String, MaskStrategy>,
+    filter: &str,
+    columns: Option<&[String]>,
+    workbook: Option<&std::sync::Mutex<XlsxWorkbook>>,
+    combined: Option<&std::sync::Mutex<CombinedWriter>>,
 ) -> AppResult<()> {
-    match db_type {
+    let mut data = match db_type {
         DatabaseType::PostgreSQL => {
-            export_postgres_table(manager, connection_id, table_name, output_path).await
+            fetch_postgres_table(manager, connection_id, table_name, filter, columns).await?
         }
         DatabaseType::MariaDB | DatabaseType::MySQL => {
-            export_mysql_table(manager, connection_id, table_name, output_path).await
+            fetch_mysql_table(manager, connection_id, table_name, filter, columns).await?
         }
+    };
+    apply_column_masks(&mut data, table_name, masks)?;
+
+    if let Some(shared) = combined {
+        return shared
+            .lock()
+            .map_err(|e| AppError::ImportExportError(format!("Failed to lock combined file: {}", e)))?
+            .write_table(table_name, &data);
     }
+
+    write_table_file(output_path, table_name, data, db_type, format, csv_dialect, workbook)
 }
 
-async fn export_postgres_table(
+/// Union of the selected tables' columns, in first-seen order, for a combined CSV header
+async fn combined_csv_columns(
     manager: &ConnectionManager,
     connection_id: &str,
+    db_type: &DatabaseType,
+    tables: &[String],
+    column_selection: &HashMap<String, Vec<String>>,
+) -> AppResult<Vec<String>> {
+    let mut columns: Vec<String> = Vec::new();
+    for table_name in tables {
+        let table_columns: Vec<String> = match column_selection.get(table_name) {
+            Some(selected) => selected.clone(),
+            None => match db_type {
+                DatabaseType::PostgreSQL => {
+                    let pool = manager.get_pool_postgres(connection_id).await?;
+                    let search_path = manager.get_connection(connection_id)?.search_path();
+                    let (table_schema, bare_table) =
+                        crate::db::query::resolve_postgres_table(&pool, table_name, &search_path).await;
+                    sqlx::query_scalar(
+                        "SELECT column_name::TEXT FROM information_schema.columns
+                         WHERE table_name = $1 AND table_schema = $2
+                         ORDER BY ordinal_position",
+                    )
+                    .bind(bare_table)
+                    .bind(&table_schema)
+                    .fetch_all(&pool)
+                    .await?
+                }
+                DatabaseType::MariaDB | DatabaseType::MySQL => {
+                    let pool = manager.get_pool_mysql(connection_id).await?;
+                    sqlx::query_scalar(
+                        "SELECT COLUMN_NAME FROM INFORMATION_SCHEMA.COLUMNS
+                         WHERE TABLE_NAME = ? AND TABLE_SCHEMA = DATABASE()
+                         ORDER BY ORDINAL_POSITION",
+                    )
+                    .bind(table_name)
+                    .fetch_all(&pool)
+                    .await?
+                }
+            },
+        };
+
+        for column in table_columns {
+            if !columns.contains(&column) {
+                columns.push(column);
+            }
+        }
+    }
+    Ok(columns)
+}
+
+/// Output file shared by every table of a combined export
+enum CombinedSink {
+    Csv(csv::Writer<File>),
+    Ndjson(BufWriter<File>),
+}
+
+/// Appends each table's records to one file, prefixed with the source table name
+struct CombinedWriter {
+    /// Union of all tables' columns (CSV only); a table's missing columns are written as NULL
+    columns: Vec<String>,
+    sink: CombinedSink,
+}
+
+impl CombinedWriter {
+    fn create(path: &Path, format: ExportFormat, columns: Vec<String>, csv_dialect: &CsvDialect) -> AppResult<Self> {
+        let file = File::create(path).map_err(|e| {
+            AppError::IoError(format!("Failed to create combined export file: {}", e))
+        })?;
+
+        let sink = if format == ExportFormat::Csv {
+            let mut writer = csv_dialect.writer_builder().from_writer(file);
+            if csv_dialect.has_headers {
+                let header = std::iter::once(COMBINED_TABLE_COLUMN).chain(columns.iter().map(String::as_str));
+                writer.write_record(header).map_err(|e| {
+                    AppError::IoError(format!("Failed to write CSV header: {}", e))
+                })?;
+            }
+            CombinedSink::Csv(writer)
+        } else {
+            CombinedSink::Ndjson(BufWriter::with_capacity(256 * 1024, file))
+        };
+
+        Ok(Self { columns, sink })
+    }
+
+    fn write_table(&mut self, table_name: &str, data: &TableData) -> AppResult<()> {
+        if data.column_names.iter().any(|name| name == COMBINED_TABLE_COLUMN) {
+            return Err(AppError::ValidationError(format!(
+                "Table '{}' has a '{}' column and cannot be exported into a combined file",
+                table_name, COMBINED_TABLE_COLUMN
+            )));
+        }
+
+        match &mut self.sink {
+            CombinedSink::Csv(writer) => {
+                // Columns added after the header was written would be silently dropped
+                if let Some(extra) = data.column_names.iter().find(|name| !self.columns.contains(name)) {
+                    return Err(AppError::ImportExportError(format!(
+                        "Column '{}' of table '{}' is missing from the combined CSV header",
+                        extra, table_name
+                    )));
+                }
+                let positions: Vec<Option<usize>> = self
+                    .columns
+                    .iter()
+                    .map(|column| data.column_names.iter().position(|name| name == column))
+                    .collect();
+
+                for record in &data.records {
+                    let row = std::iter::once(table_name).chain(
+                        positions
+                            .iter()
+                            .map(|idx| idx.map_or(CSV_NULL_MARKER, |idx| record[idx].as_str())),
+                    );
+                    writer.write_record(row).map_err(|e| {
+                        AppError::IoError(format!("Failed to write CSV row: {}", e))
+                    })?;
+                }
+            }
+            CombinedSink::Ndjson(writer) => {
+                for record in &data.records {
+                    let mut object = serde_json::Map::new();
+                    object.insert(COMBINED_TABLE_COLUMN.to_string(), table_name.into());
+                    for ((name, kind), value) in data.column_names.iter().zip(&data.kinds).zip(record) {
+                        object.insert(name.clone(), formatted_to_json(value.clone(), *kind));
+                    }
+                    serde_json::to_writer(&mut *writer, &object)?;
+                    writer.write_all(b"\n")?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> AppResult<()> {
+        match &mut self.sink {
+            CombinedSink::Csv(writer) => writer.flush(),
+            CombinedSink::Ndjson(writer) => writer.flush(),
+        }
+        .map_err(|e| AppError::IoError(format!("Failed to flush combined export file: {}", e)))
+    }
+}
+
+/// Redact masked columns in place before the table is written.
+/// Columns whose masked values no longer match their type are exported as text.
+fn apply_column_masks(
+    data: &mut TableData,
     table_name: &str,
-    output_path: &PathBuf,
+    masks: &HashMap<String, MaskStrategy>,
+) -> AppResult<()> {
+    let prefix = format!("{}.", table_name);
+    for (key, strategy) in masks {
+        let Some(column) = key.strip_prefix(&prefix) else {
+            continue;
+        };
+        // A typo must not silently export the column unmasked
+        let idx = data
+            .column_names
+            .iter()
+            .position(|name| name == column)
+            .ok_or_else(|| {
+                AppError::ValidationError(format!(
+                    "Masked column '{}' does not exist in table '{}'",
+                    column, table_name
+                ))
+            })?;
+
+        let integer = data.kinds[idx] == ValueKind::Integer;
+        for record in &mut data.records {
+            let value = &mut record[idx];
+            if value != CSV_NULL_MARKER {
+                *value = strategy
+                    .mask(value, integer)
+                    .unwrap_or_else(|| CSV_NULL_MARKER.to_string());
+            }
+        }
+
+        let keeps_type = *strategy == MaskStrategy::Null || (integer && strategy.keeps_integer_type());
+        if !keeps_type {
+            data.kinds[idx] = ValueKind::Text;
+            data.decimal_specs[idx] = None;
+        }
+    }
+    Ok(())
+}
+
+/// File a table is written to in a per-table export
+fn table_file_path(output_path: &Path, table_name: &str, format: ExportFormat) -> PathBuf {
+    output_path.join(format!("{}.{}", table_name, format.extension()))
+}
+
+/// Write formatted records for a table in the requested format
+fn write_table_file(
+    output_path: &Path,
+    table_name: &str,
+    data: TableData,
+    db_type: &DatabaseType,
+    format: ExportFormat,
+    csv_dialect: &CsvDialect,
+    workbook: Option<&std::sync::Mutex<XlsxWorkbook>>,
+) -> AppResult<()> {
+    let file_path = table_file_path(output_path, table_name, format);
+
+    match format {
+        ExportFormat::Csv => write_csv_file(&file_path, &data.column_names, data.records, csv_dialect),
+        ExportFormat::Json | ExportFormat::Ndjson => write_json_file(
+            &file_path,
+            &data.column_names,
+            &data.kinds,
+            data.records,
+            format == ExportFormat::Ndjson,
+        ),
+        ExportFormat::Parquet => write_parquet_file(&file_path, &data),
+        ExportFormat::SqlDump => write_sql_dump_file(&file_path, table_name, &data, db_type),
+        ExportFormat::Xlsx => match workbook {
+            Some(shared) => shared
+                .lock()
+                .map_err(|e| AppError::ImportExportError(format!("Failed to lock workbook: {}", e)))?
+                .add_table_sheet(table_name, &data),
+            None => {
+                let mut workbook = XlsxWorkbook::new();
+                workbook.add_table_sheet(table_name, &data)?;
+                workbook.save(&file_path)
+            }
+        },
+    }
+}
+
+/// Excel workbook that tracks sheet names so table names map to unique, valid sheets
+struct XlsxWorkbook {
+    workbook: rust_xlsxwriter::Workbook,
+    sheet_names: Vec<String>,
+}
+
+impl XlsxWorkbook {
+    fn new() -> Self {
+        Self {
+            workbook: rust_xlsxwriter::Workbook::new(),
+            sheet_names: Vec::new(),
+        }
+    }
+
+    fn add_table_sheet(&mut self, table_name: &str, data: &TableData) -> AppResult<()> {
+        use rust_xlsxwriter::Format;
+
+        if data.records.len() >= XLSX_MAX_ROWS {
+            return Err(AppError::ImportExportError(format!(
+                "Table '{}' has {} rows, which exceeds Excel's limit of {} rows per sheet",
+                table_name,
+                data.records.len(),
+                XLSX_MAX_ROWS - 1
+            )));
+        }
+
+        let sheet_name = unique_sheet_name(table_name, &self.sheet_names);
+        self.sheet_names.push(sheet_name.clone());
+
+        let header_format = Format::new().set_bold();
+        let date_format = Format::new().set_num_format("yyyy-mm-dd");
+        let datetime_format = Format::new().set_num_format("yyyy-mm-dd hh:mm:ss");
+        let time_format = Format::new().set_num_format("hh:mm:ss");
+
+        let worksheet = self.workbook.add_worksheet();
+        worksheet.set_name(&sheet_name)?;
+
+        for (col, name) in data.column_names.iter().enumerate() {
+            worksheet.write_string_with_format(0, col as u16, name, &header_format)?;
+        }
+
+        for (row_idx, record) in data.records.iter().enumerate() {
+            let row = (row_idx + 1) as u32;
+
+            for (col_idx, (value, kind)) in record.iter().zip(&data.kinds).enumerate() {
+                let col = col_idx as u16;
+
+                // NULLs are left as empty cells
+                if value == CSV_NULL_MARKER {
+                    continue;
+                }
+
+                match kind {
+                    ValueKind::Integer | ValueKind::Float | ValueKind::Decimal => {
+                        match value.parse::<f64>() {
+                            Ok(number) if number.is_finite() => {
+                                worksheet.write_number(row, col, number)?;
+                            }
+                            _ => {
+                                worksheet.write_string(row, col, value)?;
+                            }
+                        }
+                    }
+                    ValueKind::Bool => match value.as_str() {
+                        "true" | "t" | "1" => {
+                            worksheet.write_boolean(row, col, true)?;
+                        }
+                        "false" | "f" | "0" => {
+                            worksheet.write_boolean(row, col, false)?;
+                        }
+                        _ => {
+                            worksheet.write_string(row, col, value)?;
+                        }
+                    },
+                    ValueKind::Date => match chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+                        Ok(date) => {
+                            worksheet.write_datetime_with_format(row, col, &date, &date_format)?;
+                        }
+                        Err(_) => {
+                            worksheet.write_string(row, col, value)?;
+                        }
+                    },
+                    ValueKind::DateTime => match parse_export_datetime(value) {
+                        Some(datetime) => {
+                            worksheet.write_datetime_with_format(row, col, &datetime, &datetime_format)?;
+                        }
+                        None => {
+                            worksheet.write_string(row, col, value)?;
+                        }
+                    },
+                    ValueKind::Time => match chrono::NaiveTime::parse_from_str(value, "%H:%M:%S%.f") {
+                        Ok(time) => {
+                            worksheet.write_datetime_with_format(row, col, &time, &time_format)?;
+                        }
+                        Err(_) => {
+                            worksheet.write_string(row, col, value)?;
+                        }
+                    },
+                    ValueKind::Json | ValueKind::Binary | ValueKind::Spatial | ValueKind::Text => {
+                        if value.chars().count() > XLSX_MAX_CELL_CHARS {
+                            let truncated: String = value.chars().take(XLSX_MAX_CELL_CHARS).collect();
+                            worksheet.write_string(row, col, &truncated)?;
+                        } else {
+                            worksheet.write_string(row, col, value)?;
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn save(&mut self, path: &Path) -> AppResult<()> {
+        self.workbook.save(path)?;
+        Ok(())
+    }
+}
+
+/// Parse the timestamp formats produced by the value formatters
+fn parse_export_datetime(value: &str) -> Option<chrono::NaiveDateTime> {
+    chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f")
+        .or_else(|_| chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f"))
+        .ok()
+        .or_else(|| {
+            chrono::DateTime::parse_from_rfc3339(value)
+                .ok()
+                .map(|dt| dt.naive_utc())
+        })
+}
+
+/// Build a valid Excel sheet name for a table, unique (case-insensitively) among `existing`
+fn unique_sheet_name(table_name: &str, existing: &[String]) -> String {
+    let cleaned: String = table_name
+        .chars()
+        .map(|c| match c {
+            '[' | ']' | ':' | '*' | '?' | '/' | '\\' => '_',
+            _ => c,
+        })
+        .collect();
+    let cleaned = cleaned.trim_matches('\'');
+    let base = if cleaned.is_empty() { "Sheet" } else { cleaned };
+
+    let is_taken = |name: &str| existing.iter().any(|e| e.eq_ignore_ascii_case(name));
+
+    let candidate: String = base.chars().take(XLSX_MAX_SHEET_NAME_LEN).collect();
+    if !is_taken(&candidate) {
+        return candidate;
+    }
+
+    let mut counter = 2;
+    loop {
+        let suffix = format!("~{}", counter);
+        let prefix: String = base
+            .chars()
+            .take(XLSX_MAX_SHEET_NAME_LEN - suffix.len())
+            .collect();
+        let candidate = format!("{}{}", prefix, suffix);
+        if !is_taken(&candidate) {
+            return candidate;
+        }
+        counter += 1;
+    }
+}
+
+fn write_csv_file(
+    csv_path: &PathBuf,
+    column_names: &[String],
+    records: Vec<Vec<String>>,
+    csv_dialect: &CsvDialect,
+) -> AppResult<()> {
+    let file = File::create(csv_path).map_err(|e| {
+        AppError::IoError(format!("Failed to create CSV file: {}", e))
+    })?;
+
+    // Quoting non-numeric fields writes empty strings as "", which import keeps
+    // distinct from an unquoted empty field (NULL by default)
+    let mut writer = csv_dialect.writer_builder().from_writer(file);
+
+    if csv_dialect.has_headers {
+        writer.write_record(column_names).map_err(|e| {
+            AppError::IoError(format!("Failed to write CSV header: {}", e))
+        })?;
+    }
+
+    // Write all records (csv crate handles escaping automatically)
+    for record in records {
+        writer.write_record(&record).map_err(|e| {
+            AppError::IoError(format!("Failed to write CSV row: {}", e))
+        })?;
+    }
+
+    writer.flush().map_err(|e| {
+        AppError::IoError(format!("Failed to flush CSV: {}", e))
+    })?;
+
+    Ok(())
+}
+
+fn write_json_file(
+    json_path: &PathBuf,
+    column_names: &[String],
+    kinds: &[ValueKind],
+    records: Vec<Vec<String>>,
+    newline_delimited: bool,
+) -> AppResult<()> {
+    let file = File::create(json_path).map_err(|e| {
+        AppError::IoError(format!("Failed to create JSON file: {}", e))
+    })?;
+    let mut writer = BufWriter::with_capacity(256 * 1024, file);
+
+    if !newline_delimited {
+        writer.write_all(b"[")?;
+    }
+
+    for (row_idx, record) in records.into_iter().enumerate() {
+        let object: serde_json::Map<String, serde_json::Value> = column_names
+            .iter()
+            .zip(kinds)
+            .zip(record)
+            .map(|((name, kind), value)| (name.clone(), formatted_to_json(value, *kind)))
+            .collect();
+
+        if newline_delimited {
+            serde_json::to_writer(&mut writer, &object)?;
+            writer.write_all(b"\n")?;
+        } else {
+            if row_idx > 0 {
+                writer.write_all(b",")?;
+            }
+            writer.write_all(b"\n  ")?;
+            serde_json::to_writer(&mut writer, &object)?;
+        }
+    }
+
+    if !newline_delimited {
+        writer.write_all(b"\n]\n")?;
+    }
+
+    writer.flush().map_err(|e| {
+        AppError::IoError(format!("Failed to flush JSON: {}", e))
+    })?;
+
+    Ok(())
+}
+
+/// Write a table as Parquet, mapping each column's value kind to an Arrow type.
+/// NULL markers become Arrow nulls; values that do not parse as their column
+/// type fail the export rather than being silently dropped.
+fn write_parquet_file(parquet_path: &Path, data: &TableData) -> AppResult<()> {
+    use arrow_schema::{Field, Schema};
+    use parquet::arrow::ArrowWriter;
+    use parquet::basic::Compression;
+    use parquet::file::properties::WriterProperties;
+
+    let fields: Vec<Field> = data
+        .column_names
+        .iter()
+        .zip(&data.kinds)
+        .zip(&data.decimal_specs)
+        .map(|((name, kind), spec)| Field::new(name, arrow_type(*kind, *spec), true))
+        .collect();
+    let schema = Arc::new(Schema::new(fields));
+
+    let file = File::create(parquet_path).map_err(|e| {
+        AppError::IoError(format!("Failed to create Parquet file: {}", e))
+    })?;
+    let props = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+    let mut writer = ArrowWriter::try_new(file, schema.clone(), Some(props))
+        .map_err(|e| AppError::ImportExportError(format!("Failed to create Parquet writer: {}", e)))?;
+
+    for chunk in data.records.chunks(PARQUET_BATCH_ROWS) {
+        let columns = (0..data.column_names.len())
+            .map(|col| {
+                build_arrow_column(
+                    chunk,
+                    col,
+                    data.kinds[col],
+                    data.decimal_specs[col],
+                    &data.column_names[col],
+                )
+            })
+            .collect::<AppResult<Vec<_>>>()?;
+
+        let batch = arrow_array::RecordBatch::try_new(schema.clone(), columns)
+            .map_err(|e| AppError::ImportExportError(format!("Failed to build Parquet batch: {}", e)))?;
+        writer
+            .write(&batch)
+            .map_err(|e| AppError::ImportExportError(format!("Failed to write Parquet batch: {}", e)))?;
+    }
+
+    writer
+        .close()
+        .map_err(|e| AppError::ImportExportError(format!("Failed to finalize Parquet file: {}", e)))?;
+
+    Ok(())
+}
+
+/// Write a table as batched multi-row INSERT statements that a psql/mysql client can replay
+/// after schema.sql. Each table is loaded in one transaction.
+fn write_sql_dump_file(
+    sql_path: &Path,
+    table_name: &str,
+    data: &TableData,
+    db_type: &DatabaseType,
 ) -> AppResult<()> {
+    let write_err = |e: std::io::Error| AppError::IoError(format!("Failed to write SQL dump: {}", e));
+    let quote_identifier = match db_type {
+        DatabaseType::PostgreSQL => quote_identifier_postgres,
+        DatabaseType::MariaDB | DatabaseType::MySQL => quote_identifier_mysql,
+    };
+
+    let file = File::create(sql_path).map_err(|e| {
+        AppError::IoError(format!("Failed to create SQL dump file: {}", e))
+    })?;
+    let mut writer = BufWriter::with_capacity(256 * 1024, file);
+
+    writeln!(writer, "-- Data for table {}", table_name).map_err(write_err)?;
+    writeln!(writer, "-- Generated by DataSpeak; load schema.sql first
+").map_err(write_err)?;
+    match db_type {
+        DatabaseType::PostgreSQL => {
+            // Literals below assume backslashes are not escape characters
+            writeln!(writer, "SET standard_conforming_strings = on;").map_err(write_err)?;
+            writeln!(writer, "BEGIN;
+").map_err(write_err)?;
+        }
+        DatabaseType::MariaDB | DatabaseType::MySQL => {
+            // Tables may be loaded in any order relative to their foreign keys
+            writeln!(writer, "SET FOREIGN_KEY_CHECKS = 0;").map_err(write_err)?;
+            writeln!(writer, "START TRANSACTION;
+").map_err(write_err)?;
+        }
+    }
+
+    if !data.records.is_empty() {
+        // Schema-qualified names are quoted per part, matching schema.sql
+        let insert_prefix = format!(
+            "INSERT INTO {} ({}) VALUES",
+            crate::db::query::quote_table(table_name, db_type),
+            data.column_names
+                .iter()
+                .map(|name| quote_identifier(name))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+
+        for batch in data.records.chunks(SQL_DUMP_BATCH_ROWS) {
+            writeln!(writer, "{}", insert_prefix).map_err(write_err)?;
+            for (row_idx, record) in batch.iter().enumerate() {
+                let values: Vec<String> = record
+                    .iter()
+                    .zip(&data.kinds)
+                    .map(|(value, kind)| sql_literal(value, *kind, db_type))
+                    .collect();
+                let terminator = if row_idx + 1 == batch.len() { ";" } else { "," };
+                writeln!(writer, "({}){}", values.join(", "), terminator).map_err(write_err)?;
+            }
+            writeln!(writer).map_err(write_err)?;
+        }
+    }
+
+    writeln!(writer, "COMMIT;").map_err(write_err)?;
+    if !matches!(db_type, DatabaseType::PostgreSQL) {
+        writeln!(writer, "SET FOREIGN_KEY_CHECKS = 1;").map_err(write_err)?;
+    }
+
+    writer.flush().map_err(|e| {
+        AppError::IoError(format!("Failed to flush SQL dump file: {}", e))
+    })?;
+
+    Ok(())
+}
+
+/// Render a formatted value as a SQL literal for the target dialect
+fn sql_literal(value: &str, kind: ValueKind, db_type: &DatabaseType) -> String {
+    if value == CSV_NULL_MARKER {
+        return "NULL".to_string();
+    }
+
+    let is_postgres = matches!(db_type, DatabaseType::PostgreSQL);
+    let quote = |text: &str| {
+        if is_postgres {
+            format!("'{}'", text.replace('\'', "''"))
+        } else {
+            let mut quoted = String::with_capacity(text.len() + 2);
+            quoted.push('\'');
+            for c in text.chars() {
+                match c {
+                    '\'' => quoted.push_str("''"),
+                    '\\' => quoted.push_str("\\\\"),
+                    '\0' => quoted.push_str("\\0"),
+                    '\x1a' => quoted.push_str("\\Z"),
+                    _ => quoted.push(c),
+                }
+            }
+            quoted.push('\'');
+            quoted
+        }
+    };
+
+    match kind {
+        ValueKind::Integer if value.parse::<i128>().is_ok() => value.to_string(),
+        // NaN and Infinity must be quoted to be parsed as floats
+        ValueKind::Float if value.parse::<f64>().is_ok_and(|v| v.is_finite()) => value.to_string(),
+        ValueKind::Decimal if value.parse::<rust_decimal::Decimal>().is_ok() => value.to_string(),
+        ValueKind::Bool => match (value, is_postgres) {
+            ("true" | "t" | "1", true) => "TRUE".to_string(),
+            ("false" | "f" | "0", true) => "FALSE".to_string(),
+            ("true" | "t" | "1", false) => "1".to_string(),
+            ("false" | "f" | "0", false) => "0".to_string(),
+            _ => quote(value),
+        },
+        ValueKind::Binary
+            if !is_postgres
+                && value.len() > 2
+                && value.starts_with("0x")
+                && value[2..].chars().all(|c| c.is_ascii_hexdigit()) =>
+        {
+            value.to_string()
+        }
+        ValueKind::Spatial => format!("ST_GeomFromText({})", quote(value)),
+        _ => quote(value),
+    }
+}
+
+/// Arrow type used for a column in Parquet output
+fn arrow_type(kind: ValueKind, decimal: Option<DecimalSpec>) -> arrow_schema::DataType {
+    use arrow_schema::{DataType, TimeUnit};
+
+    match kind {
+        ValueKind::Integer => DataType::Int64,
+        ValueKind::Float => DataType::Float64,
+        ValueKind::Decimal => match decimal {
+            Some(spec) => DataType::Decimal128(spec.precision, spec.scale),
+            // Unbounded precision has no lossless Arrow mapping; keep the exact text
+            None => DataType::Utf8,
+        },
+        ValueKind::Bool => DataType::Boolean,
+        ValueKind::Date => DataType::Date32,
+        ValueKind::DateTime => DataType::Timestamp(TimeUnit::Microsecond, None),
+        ValueKind::Time => DataType::Time64(TimeUnit::Microsecond),
+        ValueKind::Json | ValueKind::Binary | ValueKind::Spatial | ValueKind::Text => DataType::Utf8,
+    }
+}
+
+/// Build one Arrow column from formatted values
+fn build_arrow_column(
+    records: &[Vec<String>],
+    col: usize,
+    kind: ValueKind,
+    decimal: Option<DecimalSpec>,
+    column_name: &str,
+) -> AppResult<arrow_array::ArrayRef> {
+    use arrow_array::builder::*;
+
+    let invalid = |value: &str| {
+        AppError::ImportExportError(format!(
+            "Column '{}': cannot convert '{}' for Parquet export",
+            column_name, value
+        ))
+    };
+    let values = records.iter().map(|record| {
+        let value = record[col].as_str();
+        (value != CSV_NULL_MARKER).then_some(value)
+    });
+
+    let array: arrow_array::ArrayRef = match (kind, decimal) {
+        (ValueKind::Integer, _) => {
+            let mut builder = Int64Builder::with_capacity(records.len());
+            for value in values {
+                match value {
+                    Some(v) => builder.append_value(v.parse::<i64>().map_err(|_| invalid(v))?),
+                    None => builder.append_null(),
+                }
+            }
+            Arc::new(builder.finish())
+        }
+        (ValueKind::Float, _) => {
+            let mut builder = Float64Builder::with_capacity(records.len());
+            for value in values {
+                match value {
+                    Some(v) => builder.append_value(v.parse::<f64>().map_err(|_| invalid(v))?),
+                    None => builder.append_null(),
+                }
+            }
+            Arc::new(builder.finish())
+        }
+        (ValueKind::Decimal, Some(spec)) => {
+            let mut builder = Decimal128Builder::with_capacity(records.len());
+            for value in values {
+                match value {
+                    Some(v) => builder.append_value(decimal_to_i128(v, spec.scale).ok_or_else(|| invalid(v))?),
+                    None => builder.append_null(),
+                }
+            }
+            Arc::new(
+                builder
+                    .finish()
+                    .with_precision_and_scale(spec.precision, spec.scale)
+                    .map_err(|e| AppError::ImportExportError(e.to_string()))?,
+            )
+        }
+        (ValueKind::Bool, _) => {
+            let mut builder = BooleanBuilder::with_capacity(records.len());
+            for value in values {
+                match value {
+                    Some("true" | "t" | "1") => builder.append_value(true),
+                    Some("false" | "f" | "0") => builder.append_value(false),
+                    Some(v) => return Err(invalid(v)),
+                    None => builder.append_null(),
+                }
+            }
+            Arc::new(builder.finish())
+        }
+        (ValueKind::Date, _) => {
+            let epoch = chrono::NaiveDate::from_ymd_opt(1970, 1, 1).unwrap();
+            let mut builder = Date32Builder::with_capacity(records.len());
+            for value in values {
+                match value {
+                    Some(v) => {
+                        let date = chrono::NaiveDate::parse_from_str(v, "%Y-%m-%d").map_err(|_| invalid(v))?;
+                        builder.append_value((date - epoch).num_days() as i32);
+                    }
+                    None => builder.append_null(),
+                }
+            }
+            Arc::new(builder.finish())
+        }
+        (ValueKind::DateTime, _) => {
+            let mut builder = TimestampMicrosecondBuilder::with_capacity(records.len());
+            for value in values {
+                match value {
+                    Some(v) => {
+                        let datetime = parse_export_datetime(v).ok_or_else(|| invalid(v))?;
+                        builder.append_value(datetime.and_utc().timestamp_micros());
+                    }
+                    None => builder.append_null(),
+                }
+            }
+            Arc::new(builder.finish())
+        }
+        (ValueKind::Time, _) => {
+            use chrono::Timelike;
+
+            let mut builder = Time64MicrosecondBuilder::with_capacity(records.len());
+            for value in values {
+                match value {
+                    Some(v) => {
+                        let time = chrono::NaiveTime::parse_from_str(v, "%H:%M:%S%.f").map_err(|_| invalid(v))?;
+                        let micros = time.num_seconds_from_midnight() as i64 * 1_000_000
+                            + (time.nanosecond() / 1_000) as i64;
+                        builder.append_value(micros);
+                    }
+                    None => builder.append_null(),
+                }
+            }
+            Arc::new(builder.finish())
+        }
+        (ValueKind::Decimal, None)
+        | (ValueKind::Json | ValueKind::Binary | ValueKind::Spatial | ValueKind::Text, _) => {
+            let mut builder = StringBuilder::with_capacity(records.len(), records.len() * 16);
+            for value in values {
+                builder.append_option(value);
+            }
+            Arc::new(builder.finish())
+        }
+    };
+
+    Ok(array)
+}
+
+/// Scale a decimal string to an integer mantissa with exactly `scale` fractional digits
+fn decimal_to_i128(value: &str, scale: i8) -> Option<i128> {
+    use std::str::FromStr;
+
+    let mut decimal = rust_decimal::Decimal::from_str(value).ok()?;
+    if decimal.scale() > scale as u32 {
+        // More digits than the column allows would not round-trip; refuse rather than round
+        return None;
+    }
+    decimal.rescale(scale as u32);
+    Some(decimal.mantissa())
+}
+
+/// Convert a value produced by `format_postgres_value`/`format_mysql_value` into JSON
+fn formatted_to_json(value: String, kind: ValueKind) -> serde_json::Value {
+    if value == CSV_NULL_MARKER {
+        return serde_json::Value::Null;
+    }
+
+    match kind {
+        ValueKind::Integer => value
+            .parse::<i64>()
+            .map(serde_json::Value::from)
+            .or_else(|_| value.parse::<u64>().map(serde_json::Value::from))
+            .unwrap_or(serde_json::Value::String(value)),
+        ValueKind::Float => value
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+            .map(serde_json::Value::Number)
+            // NaN and Infinity have no JSON representation
+            .unwrap_or(serde_json::Value::String(value)),
+        ValueKind::Bool => match value.as_str() {
+            "true" | "t" | "1" => serde_json::Value::Bool(true),
+            "false" | "f" | "0" => serde_json::Value::Bool(false),
+            _ => serde_json::Value::String(value),
+        },
+        ValueKind::Json => serde_json::from_str(&value).unwrap_or(serde_json::Value::String(value)),
+        ValueKind::Decimal
+        | ValueKind::Date
+        | ValueKind::DateTime
+        | ValueKind::Time
+        | ValueKind::Binary
+        | ValueKind::Spatial
+        | ValueKind::Text => serde_json::Value::String(value),
+    }
+}
+
+fn postgres_value_kind(udt_name: &str) -> ValueKind {
+    match udt_name {
+        "int2" | "int4" | "int8" | "oid" => ValueKind::Integer,
+        "float4" | "float8" => ValueKind::Float,
+        "numeric" => ValueKind::Decimal,
+        "bool" => ValueKind::Bool,
+        "json" | "jsonb" => ValueKind::Json,
+        "date" => ValueKind::Date,
+        "timestamp" | "timestamptz" => ValueKind::DateTime,
+        "time" => ValueKind::Time,
+        "bytea" => ValueKind::Binary,
+        _ => ValueKind::Text,
+    }
+}
+
+fn mysql_value_kind(data_type: &str) -> ValueKind {
+    match data_type.to_lowercase().as_str() {
+        "tinyint" | "smallint" | "mediumint" | "int" | "integer" | "bigint" | "year" | "bit" => {
+            ValueKind::Integer
+        }
+        "float" | "double" | "real" => ValueKind::Float,
+        "decimal" | "numeric" => ValueKind::Decimal,
+        "json" => ValueKind::Json,
+        "date" => ValueKind::Date,
+        "datetime" | "timestamp" => ValueKind::DateTime,
+        "time" => ValueKind::Time,
+        "binary" | "varbinary" | "blob" | "tinyblob" | "mediumblob" | "longblob" => ValueKind::Binary,
+        "geometry" | "point" | "linestring" | "polygon" | "multipoint" | "multilinestring"
+        | "multipolygon" | "geometrycollection" => ValueKind::Spatial,
+        _ => ValueKind::Text,
+    }
+}
+
+/// Column metadata as (name, type name, full type, numeric precision, numeric scale)
+type ColumnMetadata = (String, String, String, Option<i32>, Option<i32>);
+
+/// Keep only the selected columns, in selection order; every column when nothing is selected
+fn select_columns(
+    metadata: Vec<ColumnMetadata>,
+    selection: Option<&[String]>,
+    table_name: &str,
+) -> AppResult<Vec<ColumnMetadata>> {
+    let Some(selection) = selection else {
+        return Ok(metadata);
+    };
+    selection
+        .iter()
+        .map(|column| {
+            metadata.iter().find(|(name, ..)| name == column).cloned().ok_or_else(|| {
+                AppError::ValidationError(format!(
+                    "Column '{}' does not exist in table '{}'",
+                    column, table_name
+                ))
+            })
+        })
+        .collect()
+}
+
+/// `filter` is a validated ` WHERE ... LIMIT ...` suffix (see `TableFilter::sql_suffix`)
+async fn fetch_postgres_table(
+    manager: &ConnectionManager,
+    connection_id: &str,
+    table_name: &str,
+    filter: &str,
+    columns: Option<&[String]>,
+) -> AppResult<TableData> {
     let pool = manager.get_pool_postgres(connection_id).await?;
+    let search_path = manager.get_connection(connection_id)?.search_path();
+    let (table_schema, bare_table) =
+        crate::db::query::resolve_postgres_table(&pool, table_name, &search_path).await;
 
     // First, query column metadata to get types (using parameterized query)
-    let column_metadata: Vec<(String, String, String)> = sqlx::query_as(
-        "SELECT column_name, udt_name, data_type
+    let column_metadata: Vec<ColumnMetadata> = sqlx::query_as(
+        "SELECT column_name, udt_name, data_type, numeric_precision::INT4, numeric_scale::INT4
          FROM information_schema.columns
-         WHERE table_name = $1 AND table_schema = 'public'
+         WHERE table_name = $1 AND table_schema = $2
          ORDER BY ordinal_position"
     )
-    .bind(table_name)
+    .bind(bare_table)
+    .bind(&table_schema)
     .fetch_all(&pool)
     .await?;
 
     if column_metadata.is_empty() {
         return Err(AppError::DatabaseError(format!("Table '{}' not found or has no columns", table_name)));
     }
+    let column_metadata = select_columns(column_metadata, columns, table_name)?;
 
     // Build SELECT query with special handling for geometry/geography types
     let select_parts: Vec<String> = column_metadata
         .iter()
-        .map(|(column_name, udt_name, _)| {
+        .map(|(column_name, udt_name, ..)| {
             let quoted_col = quote_identifier_postgres(column_name);
             match udt_name.as_str() {
                 "geometry" | "geography" => {
                     // Export geometry as EWKT (includes SRID)
                     format!("ST_AsEWKT({}) as {}", quoted_col, quoted_col)
                 }
+                // pgvector has no client-side decoder; its text form is the full `[...]` literal
+                "vector" => format!("{}::TEXT as {}", quoted_col, quoted_col),
+                // Ranges keep their canonical text, including `empty` and open bounds like `[1,)`
+                "int4range" | "int8range" | "numrange" | "tsrange" | "tstzrange" | "daterange" => {
+                    format!("{}::TEXT as {}", quoted_col, quoted_col)
+                }
                 _ => quoted_col
             }
         })
         .collect();
 
-    let query = format!("SELECT {} FROM {}", select_parts.join(", "), quote_identifier_postgres(table_name));
+    let query = format!(
+        "SELECT {} FROM {}.{}{}",
+        select_parts.join(", "),
+        quote_identifier_postgres(&table_schema),
+        quote_identifier_postgres(bare_table),
+        filter
+    );
     let rows = sqlx::query(&query).fetch_all(&pool).await?;
 
-    let csv_path = output_path.join(format!("{}.csv", table_name));
-    let file = File::create(&csv_path).map_err(|e| {
-        AppError::IoError(format!("Failed to create CSV file: {}", e))
-    })?;
-
-    let mut writer = Writer::from_writer(file);
-
-    // Write header
-    let column_names: Vec<String> = column_metadata.iter().map(|(name, _, _)| name.clone()).collect();
-    writer.write_record(&column_names).map_err(|e| {
-        AppError::IoError(format!("Failed to write CSV header: {}", e))
-    })?;
-
-    if rows.is_empty() {
-        writer.flush().map_err(|e| {
-            AppError::IoError(format!("Failed to flush CSV: {}", e))
-        })?;
-        return Ok(());
-    }
+    let column_names: Vec<String> = column_metadata.iter().map(|(name, ..)| name.clone()).collect();
+    let kinds: Vec<ValueKind> = column_metadata
+        .iter()
+        .map(|(_, udt_name, ..)| postgres_value_kind(udt_name))
+        .collect();
+    // Unconstrained NUMERIC has no precision and is exported as text
+    let decimal_specs: Vec<Option<DecimalSpec>> = column_metadata
+        .iter()
+        .map(|(_, udt_name, _, precision, scale)| {
+            if udt_name == "numeric" {
+                DecimalSpec::new(*precision, *scale)
+            } else {
+                None
+            }
+        })
+        .collect();
 
     // Convert rows to records using rayon for parallel processing
     // Use column metadata to determine how to format each value
-    let csv_records: Vec<Vec<String>> = rows
+    let records: Vec<Vec<String>> = rows
         .par_iter()
         .map(|row| {
             column_metadata
                 .iter()
                 .enumerate()
-                .map(|(idx, (_, udt_name, data_type))| {
+                .map(|(idx, (_, udt_name, data_type, ..))| {
                     format_postgres_value(row, idx, udt_name, data_type)
                 })
                 .collect()
         })
         .collect();
 
-    // Write all records (csv crate handles escaping automatically)
-    for record in csv_records {
-        writer.write_record(&record).map_err(|e| {
-            AppError::IoError(format!("Failed to write CSV row: {}", e))
-        })?;
-    }
-
-    writer.flush().map_err(|e| {
-        AppError::IoError(format!("Failed to flush CSV: {}", e))
-    })?;
-
-    Ok(())
+    Ok(TableData {
+        column_names,
+        kinds,
+        decimal_specs,
+        records,
+    })
 }
 
 /// Format a PostgreSQL value based on its type
@@ -500,8 +1870,10 @@ fn format_postgres_value(
             }
         }
 
-        // Geometry/Geography types (already converted to EWKT in SELECT)
-        "geometry" | "geography" => {
+        // Geometry/Geography types (already converted to EWKT in SELECT),
+        // pgvector and range types (already cast to text)
+        "geometry" | "geography" | "vector" | "int4range" | "int8range" | "numrange" | "tsrange"
+        | "tstzrange" | "daterange" => {
             if let Ok(val) = row.try_get::<Option<String>, _>(idx) {
                 return val.unwrap_or_else(|| CSV_NULL_MARKER.to_string());
             }
@@ -581,17 +1953,20 @@ fn format_postgres_value(
     String::new()
 }
 
-async fn export_mysql_table(
+/// `filter` is a validated ` WHERE ... LIMIT ...` suffix (see `TableFilter::sql_suffix`)
+async fn fetch_mysql_table(
     manager: &ConnectionManager,
     connection_id: &str,
     table_name: &str,
-    output_path: &PathBuf,
-) -> AppResult<()> {
+    filter: &str,
+    columns: Option<&[String]>,
+) -> AppResult<TableData> {
     let pool = manager.get_pool_mysql(connection_id).await?;
 
     // First, query column metadata to get types (using parameterized query)
-    let column_metadata: Vec<(String, String, String)> = sqlx::query_as(
-        "SELECT COLUMN_NAME, DATA_TYPE, COLUMN_TYPE
+    let column_metadata: Vec<ColumnMetadata> = sqlx::query_as(
+        "SELECT COLUMN_NAME, DATA_TYPE, COLUMN_TYPE,
+                CAST(NUMERIC_PRECISION AS SIGNED), CAST(NUMERIC_SCALE AS SIGNED)
          FROM INFORMATION_SCHEMA.COLUMNS
          WHERE TABLE_NAME = ? AND TABLE_SCHEMA = DATABASE()
          ORDER BY ORDINAL_POSITION"
@@ -603,11 +1978,12 @@ async fn export_mysql_table(
     if column_metadata.is_empty() {
         return Err(AppError::DatabaseError(format!("Table '{}' not found or has no columns", table_name)));
     }
+    let column_metadata = select_columns(column_metadata, columns, table_name)?;
 
     // Build SELECT with ST_AsText() for geometry columns to export as WKT
     let select_parts: Vec<String> = column_metadata
         .iter()
-        .map(|(col_name, data_type, _)| {
+        .map(|(col_name, data_type, ..)| {
             let quoted_col = quote_identifier_mysql(col_name);
             match data_type.to_lowercase().as_str() {
                 "geometry" | "point" | "linestring" | "polygon" |
@@ -620,56 +1996,51 @@ async fn export_mysql_table(
         })
         .collect();
 
-    let query = format!("SELECT {} FROM {}", select_parts.join(", "), quote_identifier_mysql(table_name));
+    let query = format!(
+        "SELECT {} FROM {}{}",
+        select_parts.join(", "),
+        quote_identifier_mysql(table_name),
+        filter
+    );
     let rows = sqlx::query(&query).fetch_all(&pool).await?;
 
-    let csv_path = output_path.join(format!("{}.csv", table_name));
-    let file = File::create(&csv_path).map_err(|e| {
-        AppError::IoError(format!("Failed to create CSV file: {}", e))
-    })?;
-
-    let mut writer = Writer::from_writer(file);
-
-    // Write header
-    let column_names: Vec<String> = column_metadata.iter().map(|(name, _, _)| name.clone()).collect();
-    writer.write_record(&column_names).map_err(|e| {
-        AppError::IoError(format!("Failed to write CSV header: {}", e))
-    })?;
-
-    if rows.is_empty() {
-        writer.flush().map_err(|e| {
-            AppError::IoError(format!("Failed to flush CSV: {}", e))
-        })?;
-        return Ok(());
-    }
+    let column_names: Vec<String> = column_metadata.iter().map(|(name, ..)| name.clone()).collect();
+    let kinds: Vec<ValueKind> = column_metadata
+        .iter()
+        .map(|(_, data_type, ..)| mysql_value_kind(data_type))
+        .collect();
+    let decimal_specs: Vec<Option<DecimalSpec>> = column_metadata
+        .iter()
+        .map(|(_, data_type, _, precision, scale)| {
+            if mysql_value_kind(data_type) == ValueKind::Decimal {
+                DecimalSpec::new(*precision, *scale)
+            } else {
+                None
+            }
+        })
+        .collect();
 
     // Convert rows to records using rayon for parallel processing
     // Use column metadata to determine how to format each value
-    let csv_records: Vec<Vec<String>> = rows
+    let records: Vec<Vec<String>> = rows
         .par_iter()
         .map(|row| {
             column_metadata
                 .iter()
                 .enumerate()
-                .map(|(idx, (_, data_type, column_type))| {
+                .map(|(idx, (_, data_type, column_type, ..))| {
                     format_mysql_value(row, idx, data_type, column_type)
                 })
                 .collect()
         })
         .collect();
 
-    // Write all records (csv crate handles escaping automatically)
-    for record in csv_records {
-        writer.write_record(&record).map_err(|e| {
-            AppError::IoError(format!("Failed to write CSV row: {}", e))
-        })?;
-    }
-
-    writer.flush().map_err(|e| {
-        AppError::IoError(format!("Failed to flush CSV: {}", e))
-    })?;
-
-    Ok(())
+    Ok(TableData {
+        column_names,
+        kinds,
+        decimal_specs,
+        records,
+    })
 }
 
 /// Format a MySQL/MariaDB value based on its type
@@ -827,20 +2198,23 @@ fn create_zip_archive(
         .compression_method(CompressionMethod::Stored)
         .unix_permissions(0o755);
 
-    // Collect all CSV and SQL files first to show progress
+    // Collect all data and SQL files first to show progress
     let entries: Vec<_> = fs::read_dir(source_dir)
         .map_err(|e| AppError::IoError(format!("Failed to read directory: {}", e)))?
         .filter_map(|entry| entry.ok())
         .filter(|entry| {
             let path = entry.path();
             let ext = path.extension().and_then(|s| s.to_str());
-            ext == Some("csv") || ext == Some("sql")
+            matches!(
+                ext,
+                Some("csv") | Some("json") | Some("ndjson") | Some("xlsx") | Some("parquet") | Some("sql")
+            )
         })
         .collect();
 
     let total_files = entries.len();
 
-    // Add all CSV and SQL files to the ZIP archive with streaming I/O
+    // Add all data and SQL files to the ZIP archive with streaming I/O
     for (idx, entry) in entries.iter().enumerate() {
         let path = entry.path();
         let file_name = path
@@ -850,10 +2224,14 @@ fn create_zip_archive(
             .to_string();
 
         // Emit progress for each file being compressed
-        let display_name = if file_name.ends_with(".sql") {
+        let display_name = if file_name == "schema.sql" {
             "schema".to_string()
+        } else if let Some(table_name) = file_name.strip_suffix(".data.sql") {
+            table_name.to_string()
         } else {
-            file_name.replace(".csv", "")
+            path.file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_else(|| file_name.clone())
         };
 
         app.emit(
@@ -941,6 +2319,7 @@ async fn export_postgres_schema(
     app: &AppHandle,
 ) -> AppResult<()> {
     let pool = manager.get_pool_postgres(connection_id).await?;
+    let search_path = manager.get_connection(connection_id)?.search_path();
 
     app.emit(
         "export-progress",
@@ -965,8 +2344,13 @@ async fn export_postgres_schema(
         AppError::IoError(format!("Failed to write to schema file: {}", e))
     })?;
 
-    // Fetch table definitions and constraints in parallel for maximum speed
-    let tables_future = sqlx::query_as::<_, (String,)>(
+    // Definitions are read in one transaction with search_path pinned to `public`, so
+    // pg_get_constraintdef/pg_get_expr schema-qualify references to every other schema
+    let mut tx = pool.begin().await?;
+    sqlx::query("SET LOCAL search_path = public").execute(&mut *tx).await?;
+
+    // Tables outside `public` are written as "schema"."table"
+    let tables = sqlx::query_as::<_, (String, String, String)>(
         r#"
         WITH table_columns AS MATERIALIZED (
             SELECT
@@ -981,7 +2365,12 @@ async fn export_postgres_schema(
             WHERE a.attnum > 0 AND NOT a.attisdropped
         )
         SELECT
-            'CREATE TABLE "' || c.relname || '" (' ||
+            n.nspname::TEXT as schema_name,
+            CASE WHEN n.nspname = 'public' THEN '' ELSE '"' || n.nspname || '".' END ||
+                '"' || c.relname || '"' as qualified_name,
+            'CREATE TABLE ' ||
+            CASE WHEN n.nspname = 'public' THEN '' ELSE '"' || n.nspname || '".' END ||
+            '"' || c.relname || '" (' ||
             string_agg(
                 '"' || tc.attname || '" ' || tc.data_type ||
                 CASE WHEN tc.attnotnull THEN ' NOT NULL' ELSE '' END ||
@@ -991,32 +2380,35 @@ async fn export_postgres_schema(
         FROM pg_class c
         JOIN pg_namespace n ON n.oid = c.relnamespace
         JOIN table_columns tc ON tc.attrelid = c.oid
-        WHERE c.relkind = 'r' AND n.nspname = 'public'
-        GROUP BY c.oid, c.relname
-        ORDER BY c.relname
+        WHERE c.relkind = 'r' AND n.nspname = ANY($1)
+        GROUP BY c.oid, n.nspname, c.relname
+        ORDER BY n.nspname, c.relname
         "#
     )
-    .fetch_all(&pool);
+    .bind(&search_path)
+    .fetch_all(&mut *tx)
+    .await?;
 
-    let constraints_future = sqlx::query_as::<_, (String,)>(
+    // Primary keys come before foreign keys so cross-schema references can resolve
+    let constraints = sqlx::query_as::<_, (String,)>(
         r#"
         SELECT
-            'ALTER TABLE "' || c.relname || '" ADD CONSTRAINT ' || con.conname || ' ' ||
+            'ALTER TABLE ' ||
+            CASE WHEN n.nspname = 'public' THEN '' ELSE '"' || n.nspname || '".' END ||
+            '"' || c.relname || '" ADD CONSTRAINT ' || con.conname || ' ' ||
             pg_get_constraintdef(con.oid) || ';' as constraint_stmt
         FROM pg_constraint con
         JOIN pg_class c ON c.oid = con.conrelid
         JOIN pg_namespace n ON n.oid = c.relnamespace
-        WHERE n.nspname = 'public'
+        WHERE n.nspname = ANY($1)
             AND con.contype IN ('p', 'f')
-        ORDER BY c.relname, con.contype DESC
+        ORDER BY con.contype DESC, n.nspname, c.relname
         "#
     )
-    .fetch_all(&pool);
-
-    // Execute both queries in parallel
-    let (schema_sql, constraints) = tokio::join!(tables_future, constraints_future);
-    let schema_sql = schema_sql?;
-    let constraints = constraints?;
+    .bind(&search_path)
+    .fetch_all(&mut *tx)
+    .await?;
+    tx.commit().await?;
 
     app.emit(
         "export-progress",
@@ -1030,19 +2422,25 @@ async fn export_postgres_schema(
     )
     .ok();
 
+    // Non-public schemas must exist before their tables are created
+    let mut schemas: Vec<&str> = tables
+        .iter()
+        .map(|(schema, ..)| schema.as_str())
+        .filter(|schema| *schema != "public")
+        .collect();
+    schemas.dedup();
+    for schema in schemas {
+        writeln!(file, "CREATE SCHEMA IF NOT EXISTS {};\n", quote_identifier_postgres(schema)).map_err(|e| {
+            AppError::IoError(format!("Failed to write to schema file: {}", e))
+        })?;
+    }
+
     // Write DROP TABLE IF EXISTS and CREATE TABLE statements
-    for (create_stmt,) in schema_sql {
-        // Extract table name from CREATE TABLE statement
-        // Format: CREATE TABLE "table_name" (...)
-        if let Some(table_name) = create_stmt
-            .strip_prefix("CREATE TABLE \"")
-            .and_then(|s| s.split('"').next())
-        {
-            // Write DROP TABLE IF EXISTS first for idempotent imports
-            writeln!(file, "DROP TABLE IF EXISTS \"{}\" CASCADE;\n", table_name).map_err(|e| {
-                AppError::IoError(format!("Failed to write to schema file: {}", e))
-            })?;
-        }
+    for (_, qualified_name, create_stmt) in tables {
+        // Write DROP TABLE IF EXISTS first for idempotent imports
+        writeln!(file, "DROP TABLE IF EXISTS {} CASCADE;\n", qualified_name).map_err(|e| {
+            AppError::IoError(format!("Failed to write to schema file: {}", e))
+        })?;
 
         // Write CREATE TABLE statement
         writeln!(file, "{}\n", create_stmt).map_err(|e| {
@@ -1196,3 +2594,175 @@ async fn export_mysql_schema(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn export_options(extra: serde_json::Value) -> ExportOptions {
+        let mut options = serde_json::json!({
+            "connection_id": "conn",
+            "tables": ["users", "orders"],
+            "output_dir": "/tmp/export",
+            "create_zip": false,
+        });
+        options.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+        serde_json::from_value(options).unwrap()
+    }
+
+    #[test]
+    fn schema_only_exports_no_table_files_and_one_progress_step() {
+        let full = export_options(serde_json::json!({}));
+        assert!(full.validate_scope().is_ok());
+        assert!(full.writes_table_files());
+        assert_eq!(full.progress_total(), 2);
+
+        let schema_only = export_options(serde_json::json!({ "schema_only": true }));
+        assert!(schema_only.validate_scope().is_ok());
+        assert!(!schema_only.writes_table_files());
+        assert_eq!(schema_only.progress_total(), 1);
+
+        let data_only = export_options(serde_json::json!({ "data_only": true }));
+        assert!(data_only.validate_scope().is_ok());
+        assert_eq!(data_only.progress_total(), 2);
+
+        let both = export_options(serde_json::json!({ "schema_only": true, "data_only": true }));
+        assert!(both.validate_scope().is_err());
+        let resumed_schema = export_options(serde_json::json!({ "schema_only": true, "resume": true }));
+        assert!(resumed_schema.validate_scope().is_err());
+    }
+
+    #[test]
+    fn column_selection_is_resolved_against_the_schema_and_keeps_its_order() {
+        use crate::db::schema::{ColumnInfo, Table};
+        let table = |name: &str, columns: &[&str]| {
+            Table::fixture(name, columns.iter().map(|c| ColumnInfo::fixture(c)).collect())
+        };
+        let schema = Schema {
+            database_name: "shop".to_string(),
+            tables: vec![table("users", &["id", "email", "name"]), table("orders", &["id", "total"])],
+            routines: vec![],
+        };
+
+        let options = export_options(serde_json::json!({
+            "column_selection": { "users": ["Name", "id"] },
+        }));
+        let selection = options.resolve_column_selection(&schema).unwrap();
+        assert_eq!(selection["users"], vec!["name", "id"]);
+        assert!(!selection.contains_key("orders"));
+
+        let invalid = |selection: serde_json::Value| {
+            export_options(serde_json::json!({ "column_selection": selection }))
+                .resolve_column_selection(&schema)
+                .unwrap_err()
+                .to_string()
+        };
+        assert!(invalid(serde_json::json!({ "users": ["emial"] })).contains("'emial' does not exist"));
+        assert!(invalid(serde_json::json!({ "users": [] })).contains("at least one column"));
+        assert!(invalid(serde_json::json!({ "users": ["id", "ID"] })).contains("more than once"));
+        assert!(invalid(serde_json::json!({ "products": ["id"] })).contains("not being exported"));
+
+        let metadata = |name: &str| -> ColumnMetadata {
+            (name.to_string(), "text".to_string(), "text".to_string(), None, None)
+        };
+        let all = vec![metadata("id"), metadata("email"), metadata("name")];
+        let names = |columns: Vec<ColumnMetadata>| columns.into_iter().map(|c| c.0).collect::<Vec<_>>();
+        assert_eq!(names(select_columns(all.clone(), None, "users").unwrap()), vec!["id", "email", "name"]);
+        let selected = ["name".to_string(), "id".to_string()];
+        assert_eq!(names(select_columns(all, Some(&selected), "users").unwrap()), vec!["name", "id"]);
+    }
+
+    #[test]
+    fn test_unique_sheet_name_truncates_and_sanitizes() {
+        let name = unique_sheet_name("a_very_long_table_name_that_exceeds_limits", &[]);
+        assert_eq!(name.chars().count(), XLSX_MAX_SHEET_NAME_LEN);
+        assert_eq!(unique_sheet_name("sales/2024:q1", &[]), "sales_2024_q1");
+        assert_eq!(unique_sheet_name("''", &[]), "Sheet");
+    }
+
+    #[test]
+    fn test_unique_sheet_name_deduplicates() {
+        let existing = vec!["Orders".to_string()];
+        assert_eq!(unique_sheet_name("orders", &existing), "orders~2");
+
+        let long = "x".repeat(40);
+        let first = unique_sheet_name(&long, &[]);
+        let second = unique_sheet_name(&long, &[first.clone()]);
+        assert_ne!(first, second);
+        assert!(second.ends_with("~2"));
+        assert_eq!(second.chars().count(), XLSX_MAX_SHEET_NAME_LEN);
+    }
+
+    #[test]
+    fn decimal_values_scale_to_column_mantissa() {
+        assert_eq!(decimal_to_i128("12.5", 2), Some(1250));
+        assert_eq!(decimal_to_i128("-0.01", 2), Some(-1));
+        assert_eq!(decimal_to_i128("7", 0), Some(7));
+        // Extra fractional digits would be lost, so they are rejected
+        assert_eq!(decimal_to_i128("1.234", 2), None);
+
+        assert!(DecimalSpec::new(Some(10), Some(2)).is_some());
+        assert!(DecimalSpec::new(Some(65), Some(2)).is_none());
+        assert!(DecimalSpec::new(None, None).is_none());
+    }
+
+    #[test]
+    fn sql_literals_are_typed_and_escaped_per_dialect() {
+        let pg = DatabaseType::PostgreSQL;
+        let mysql = DatabaseType::MySQL;
+
+        assert_eq!(sql_literal(CSV_NULL_MARKER, ValueKind::Text, &pg), "NULL");
+        assert_eq!(sql_literal("42", ValueKind::Integer, &pg), "42");
+        assert_eq!(sql_literal("NaN", ValueKind::Float, &pg), "'NaN'");
+        assert_eq!(sql_literal("t", ValueKind::Bool, &pg), "TRUE");
+        assert_eq!(sql_literal("true", ValueKind::Bool, &mysql), "1");
+        assert_eq!(sql_literal("it's a\\path", ValueKind::Text, &pg), "'it''s a\\path'");
+        assert_eq!(sql_literal("it's a\\path", ValueKind::Text, &mysql), "'it''s a\\\\path'");
+        assert_eq!(sql_literal("0xdeadbeef", ValueKind::Binary, &mysql), "0xdeadbeef");
+        assert_eq!(sql_literal("\\xdeadbeef", ValueKind::Binary, &pg), "'\\xdeadbeef'");
+        assert_eq!(sql_literal("POINT(1 2)", ValueKind::Spatial, &mysql), "ST_GeomFromText('POINT(1 2)')");
+    }
+
+    #[test]
+    fn combined_csv_tags_tables_and_fills_missing_columns() {
+        let table = |columns: &[&str], record: &[&str]| TableData {
+            column_names: columns.iter().map(|c| c.to_string()).collect(),
+            kinds: vec![ValueKind::Text; columns.len()],
+            decimal_specs: vec![None; columns.len()],
+            records: vec![record.iter().map(|v| v.to_string()).collect()],
+        };
+        let path = std::env::temp_dir().join(format!("dataspeak_combined_{}.csv", std::process::id()));
+        let columns = vec!["id".to_string(), "name".to_string(), "total".to_string()];
+
+        let mut writer = CombinedWriter::create(&path, ExportFormat::Csv, columns, &CsvDialect::default()).unwrap();
+        writer.write_table("users", &table(&["id", "name"], &["1", "ann"])).unwrap();
+        writer.write_table("orders", &table(&["total", "id"], &["9.5", "7"])).unwrap();
+        assert!(writer.write_table("bad", &table(&["email"], &["x"])).is_err());
+        writer.finish().unwrap();
+
+        let contents = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).ok();
+        assert_eq!(
+            contents,
+            "\"__table\",\"id\",\"name\",\"total\"\n\"users\",1,\"ann\",\"\\N\"\n\"orders\",7,\"\\N\",9.5\n"
+        );
+    }
+
+    #[test]
+    fn sql_dump_quotes_schema_qualified_tables_per_part() {
+        let data = TableData {
+            column_names: vec!["id".to_string(), "note".to_string()],
+            kinds: vec![ValueKind::Integer, ValueKind::Text],
+            decimal_specs: vec![None, None],
+            records: vec![vec!["1".to_string(), "first".to_string()]],
+        };
+        let path = std::env::temp_dir().join(format!("dataspeak_dump_{}.sql", std::process::id()));
+
+        write_sql_dump_file(&path, "sales.orders", &data, &DatabaseType::PostgreSQL).unwrap();
+        let contents = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).ok();
+
+        assert!(contents.contains("INSERT INTO \"sales\".\"orders\" (\"id\", \"note\") VALUES"));
+        assert!(!contents.contains("\"sales.orders\""));
+    }
+}
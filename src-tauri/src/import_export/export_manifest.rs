@@ -0,0 +1,127 @@
+use crate::error::{AppError, AppResult};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Name of the progress file kept next to the exported table files
+pub const EXPORT_MANIFEST_FILE: &str = ".export_manifest.json";
+
+/// A table whose file was fully written and closed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompletedTable {
+    /// File name inside the export directory
+    pub file: String,
+    /// Size when the table finished; any other size means the file was cut short or changed
+    pub bytes: u64,
+}
+
+/// Tracks which tables of a per-table export are done, so an interrupted export can resume.
+/// A table is only recorded after its file is complete, which makes the entry its completion
+/// marker: a file without an entry, or with a different size, is exported again.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportManifest {
+    /// Hash of the export options the files were written with
+    pub fingerprint: String,
+    pub completed: HashMap<String, CompletedTable>,
+}
+
+impl ExportManifest {
+    pub fn new(fingerprint: String) -> Self {
+        Self {
+            fingerprint,
+            completed: HashMap::new(),
+        }
+    }
+
+    pub fn path(dir: &Path) -> PathBuf {
+        dir.join(EXPORT_MANIFEST_FILE)
+    }
+
+    /// Manifest left by an earlier run with the same options; unreadable or stale manifests are ignored
+    pub fn load(dir: &Path, fingerprint: &str) -> Option<Self> {
+        let text = fs::read_to_string(Self::path(dir)).ok()?;
+        let manifest: Self = serde_json::from_str(&text).ok()?;
+        (manifest.fingerprint == fingerprint).then_some(manifest)
+    }
+
+    /// Write to a temporary file first so a crash never leaves a half-written manifest
+    pub fn save(&self, dir: &Path) -> AppResult<()> {
+        let path = Self::path(dir);
+        let temp_path = path.with_extension("json.tmp");
+        let text = serde_json::to_string_pretty(self)?;
+        fs::write(&temp_path, text)
+            .and_then(|_| fs::rename(&temp_path, &path))
+            .map_err(|e| AppError::IoError(format!("Failed to write export manifest: {}", e)))
+    }
+
+    pub fn remove(dir: &Path) {
+        fs::remove_file(Self::path(dir)).ok();
+    }
+
+    /// Record a table once its file at `file_path` has been written and closed
+    pub fn mark_completed(&mut self, table_name: &str, file_path: &Path) -> AppResult<()> {
+        let bytes = fs::metadata(file_path)
+            .map_err(|e| AppError::IoError(format!("Failed to read exported file size: {}", e)))?
+            .len();
+        let file = file_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        self.completed.insert(table_name.to_string(), CompletedTable { file, bytes });
+        Ok(())
+    }
+
+    /// Whether a table can be skipped: it was recorded and its file is still there at full size
+    pub fn is_completed(&self, table_name: &str, dir: &Path) -> bool {
+        self.completed.get(table_name).is_some_and(|entry| {
+            fs::metadata(dir.join(&entry.file)).is_ok_and(|meta| meta.is_file() && meta.len() == entry.bytes)
+        })
+    }
+}
+
+/// Stable hash of a serializable value; object keys are sorted, so map order does not matter
+pub fn options_fingerprint<T: Serialize>(options: &T) -> AppResult<String> {
+    let value = serde_json::to_value(options)?;
+    Ok(hex::encode(Sha256::digest(value.to_string().as_bytes())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_complete_recorded_files_count_as_done() {
+        let dir = std::env::temp_dir().join(format!("dataspeak_manifest_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let users = dir.join("users.csv");
+        let orders = dir.join("orders.csv");
+        fs::write(&users, "id\n1\n2\n").unwrap();
+        fs::write(&orders, "id\n1\n").unwrap();
+
+        let mut manifest = ExportManifest::new("abc".to_string());
+        manifest.mark_completed("users", &users).unwrap();
+        manifest.mark_completed("orders", &orders).unwrap();
+        manifest.save(&dir).unwrap();
+
+        assert!(ExportManifest::load(&dir, "other").is_none());
+        let loaded = ExportManifest::load(&dir, "abc").unwrap();
+        // A file truncated after it was recorded is not trusted
+        fs::write(&orders, "id\n").unwrap();
+        assert!(loaded.is_completed("users", &dir));
+        assert!(!loaded.is_completed("orders", &dir));
+        assert!(!loaded.is_completed("products", &dir));
+
+        ExportManifest::remove(&dir);
+        assert!(ExportManifest::load(&dir, "abc").is_none());
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn fingerprint_ignores_map_order() {
+        let a: HashMap<&str, u32> = [("x", 1), ("y", 2), ("z", 3)].into_iter().collect();
+        let b: HashMap<&str, u32> = [("z", 3), ("x", 1), ("y", 2)].into_iter().collect();
+        assert_eq!(options_fingerprint(&a).unwrap(), options_fingerprint(&b).unwrap());
+    }
+}
@@ -1,21 +1,144 @@
 use crate::db::connection::{ConnectionManager, DatabaseType};
+use crate::db::query::{quote_table, resolve_postgres_table, split_qualified_table};
 use crate::error::{AppError, AppResult};
+use crate::import_export::csv_dialect::{
+    default_csv_delimiter, default_csv_quote, default_has_headers, deserialize_csv_byte, CsvDialect,
+    CsvEncoding, DecodingReader,
+};
 use crate::import_export::export::CSV_NULL_MARKER;
-use csv::ReaderBuilder;
 use futures::stream::{self, StreamExt};
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
 use sqlparser::dialect::{MySqlDialect, PostgreSqlDialect};
 use sqlparser::parser::Parser;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs::{self, File};
-use std::io::{BufReader, Read, Write};
-use std::path::PathBuf;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tauri::{AppHandle, Emitter};
 use tokio::sync::{Mutex, RwLock};
 use tokio_util::sync::CancellationToken;
 
+/// Rows inserted per INSERT statement
+const IMPORT_BATCH_SIZE: usize = 1000;
+
+/// Upper bound on files imported concurrently (further limited by the pool size)
+const IMPORT_MAX_CONCURRENT_FILES: usize = 8;
+
+/// Source file formats accepted by import, detected from the file extension
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ImportFileFormat {
+    Csv,
+    /// A single JSON array of objects
+    Json,
+    /// One JSON object per line (.ndjson / .jsonl)
+    Ndjson,
+}
+
+impl ImportFileFormat {
+    fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "csv" => Some(Self::Csv),
+            "json" => Some(Self::Json),
+            "ndjson" | "jsonl" => Some(Self::Ndjson),
+            _ => None,
+        }
+    }
+}
+
+/// Rows sampled to infer column types when creating a missing table
+const TYPE_INFERENCE_SAMPLE_ROWS: usize = IMPORT_BATCH_SIZE;
+
+/// Column type inferred from sampled import values
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum InferredType {
+    Integer,
+    Float,
+    Boolean,
+    Timestamp,
+    Text,
+}
+
+impl InferredType {
+    fn ddl(&self, db_type: &DatabaseType) -> &'static str {
+        match (self, db_type) {
+            (Self::Integer, _) => "BIGINT",
+            (Self::Float, DatabaseType::PostgreSQL) => "DOUBLE PRECISION",
+            (Self::Float, _) => "DOUBLE",
+            (Self::Boolean, DatabaseType::PostgreSQL) => "BOOLEAN",
+            (Self::Boolean, _) => "TINYINT(1)",
+            (Self::Timestamp, DatabaseType::PostgreSQL) => "TIMESTAMP",
+            (Self::Timestamp, _) => "DATETIME",
+            (Self::Text, _) => "TEXT",
+        }
+    }
+
+    /// Classify a single non-null value
+    fn of_value(value: &str) -> Self {
+        let trimmed = value.trim();
+
+        // Leading zeros (zip codes, ids) and surrounding whitespace must survive as text
+        let has_leading_zero = trimmed.len() > 1
+            && trimmed.trim_start_matches(['-', '+']).starts_with('0')
+            && !trimmed.trim_start_matches(['-', '+']).starts_with("0.");
+        if trimmed != value || trimmed.is_empty() || has_leading_zero {
+            return Self::Text;
+        }
+
+        if trimmed.parse::<i64>().is_ok() {
+            return Self::Integer;
+        }
+        if trimmed.parse::<f64>().is_ok_and(|f| f.is_finite())
+            && trimmed.chars().all(|c| c.is_ascii_digit() || matches!(c, '.' | '-' | '+' | 'e' | 'E'))
+        {
+            return Self::Float;
+        }
+        if trimmed.eq_ignore_ascii_case("true") || trimmed.eq_ignore_ascii_case("false") {
+            return Self::Boolean;
+        }
+        if is_timestamp_value(trimmed) {
+            return Self::Timestamp;
+        }
+        Self::Text
+    }
+
+    /// Combine two observations; anything other than int/float widening is ambiguous
+    fn merge(self, other: Self) -> Self {
+        match (self, other) {
+            (a, b) if a == b => a,
+            (Self::Integer, Self::Float) | (Self::Float, Self::Integer) => Self::Float,
+            _ => Self::Text,
+        }
+    }
+}
+
+fn is_timestamp_value(value: &str) -> bool {
+    const DATETIME_FORMATS: [&str; 2] = ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"];
+
+    DATETIME_FORMATS
+        .iter()
+        .any(|format| chrono::NaiveDateTime::parse_from_str(value, format).is_ok())
+        || chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok()
+}
+
+/// Infer one type per column from sampled rows. NULL markers are ignored;
+/// columns with no non-null samples fall back to text.
+fn infer_column_types(column_count: usize, sample: &[Vec<String>]) -> Vec<InferredType> {
+    (0..column_count)
+        .map(|index| {
+            sample
+                .iter()
+                .filter_map(|row| row.get(index))
+                .filter(|value| value.as_str() != CSV_NULL_MARKER)
+                .map(|value| InferredType::of_value(value))
+                .reduce(InferredType::merge)
+                .unwrap_or(InferredType::Text)
+        })
+        .collect()
+}
+
 /// Safely quote a PostgreSQL identifier (table/column name)
 fn quote_identifier_postgres(identifier: &str) -> String {
     format!("\"{}\"", identifier.replace('"', "\"\""))
@@ -82,6 +205,64 @@ pub struct ImportProgress {
     pub total: usize,
     pub status: String,
     pub cancelled: bool,
+    /// Set when a failed file (or the whole all-or-nothing import) was rolled back
+    #[serde(default)]
+    pub rolled_back: bool,
+    /// Rows inserted so far from `file_name`; only set on per-batch events
+    #[serde(default)]
+    pub rows_inserted: Option<usize>,
+}
+
+/// Per-file context for the readers: reports each inserted batch and stops the file
+/// between batches once the import is cancelled
+struct BatchProgress<'a> {
+    app: &'a AppHandle,
+    file_name: &'a str,
+    table_name: &'a str,
+    current: usize,
+    total: usize,
+    cancel_token: &'a CancellationToken,
+}
+
+impl BatchProgress<'_> {
+    fn check_cancelled(&self) -> AppResult<()> {
+        if self.cancel_token.is_cancelled() {
+            return Err(AppError::OperationCancelled("Import cancelled by user".to_string()));
+        }
+        Ok(())
+    }
+
+    fn batch_inserted(&self, rows_inserted: usize) {
+        self.app
+            .emit(
+                "import-progress",
+                ImportProgress {
+                    file_name: self.file_name.to_string(),
+                    current: self.current,
+                    total: self.total,
+                    status: format!("Imported {} rows into {}", rows_inserted, self.table_name),
+                    cancelled: false,
+                    rolled_back: false,
+                    rows_inserted: Some(rows_inserted),
+                },
+            )
+            .ok();
+    }
+}
+
+/// How imported rows interact with rows already in the target table
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportMode {
+    /// Insert every row; duplicates of existing keys fail
+    #[default]
+    Append,
+    /// Insert new rows and update existing ones matched by primary key
+    Upsert,
+    /// Remove all existing rows before importing. The clear and every file share
+    /// one transaction, as with `TransactionScope::AllOrNothing`, so a failed
+    /// import never leaves a table emptied.
+    Replace,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -89,7 +270,125 @@ pub struct ImportOptions {
     pub connection_id: String,
     pub source_path: String,
     pub is_zip: bool,
-    pub table_mappings: HashMap<String, String>, // CSV filename -> table name
+    pub table_mappings: HashMap<String, String>, // Data file name (without extension) -> table name
+    /// Create missing target tables with column types inferred from the data
+    #[serde(default)]
+    pub auto_create: bool,
+    #[serde(default)]
+    pub mode: ImportMode,
+    #[serde(default)]
+    pub transaction_scope: TransactionScope,
+    /// Unquoted CSV fields equal to this are imported as NULL; a quoted field never is,
+    /// so `""` still imports an empty string. `\N` is always treated as NULL.
+    #[serde(default)]
+    pub null_token: String,
+    /// How CSV header columns are matched to the target table's columns
+    #[serde(default)]
+    pub csv_column_matching: CsvColumnMatching,
+    /// With by-name matching, skip CSV columns the table doesn't have instead of failing
+    #[serde(default)]
+    pub ignore_extra_columns: bool,
+    /// CSV field separator, e.g. `;` or a tab; defaults to a comma
+    #[serde(default = "default_csv_delimiter", deserialize_with = "deserialize_csv_byte")]
+    pub delimiter: u8,
+    /// CSV quote character; defaults to a double quote
+    #[serde(default = "default_csv_quote", deserialize_with = "deserialize_csv_byte")]
+    pub quote: u8,
+    /// Whether the first CSV record holds column names. Without one, fields are
+    /// inserted into the table's columns in order.
+    #[serde(default = "default_has_headers")]
+    pub has_headers: bool,
+    /// Encoding of CSV files; a UTF-8 byte order mark is stripped regardless
+    #[serde(default)]
+    pub encoding: CsvEncoding,
+}
+
+impl ImportOptions {
+    fn csv_dialect(&self) -> CsvDialect {
+        CsvDialect {
+            delimiter: self.delimiter,
+            quote: self.quote,
+            has_headers: self.has_headers,
+            encoding: self.encoding,
+            ..CsvDialect::default()
+        }
+    }
+}
+
+/// How CSV columns are mapped onto the target table
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CsvColumnMatching {
+    /// Insert the fields in file order under the header names exactly as written
+    #[default]
+    Positional,
+    /// Match each header to a table column by name, ignoring case, and reorder the
+    /// fields to fit. Fails when a required column has no matching header.
+    ByName,
+}
+
+/// What a failed import rolls back
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionScope {
+    /// Each file commits on its own; a failure only rolls back that file
+    #[default]
+    PerFile,
+    /// All files share one transaction that commits only if every file succeeds.
+    /// On MySQL, tables created by `auto_create` are not removed on rollback.
+    AllOrNothing,
+}
+
+/// Transaction an import writes through; its rows only become visible on commit
+enum ImportTx {
+    Postgres(sqlx::Transaction<'static, sqlx::Postgres>),
+    MySql(sqlx::Transaction<'static, sqlx::MySql>),
+}
+
+impl ImportTx {
+    /// Begin a transaction with foreign key checks relaxed. MySQL skips the checks
+    /// entirely, so files load in any order. PostgreSQL only defers constraints
+    /// declared `DEFERRABLE`; other foreign keys are still checked per statement,
+    /// so their parent tables must be imported first.
+    async fn begin(manager: &ConnectionManager, connection_id: &str, db_type: &DatabaseType) -> AppResult<Self> {
+        match db_type {
+            DatabaseType::PostgreSQL => {
+                let pool = manager.get_pool_postgres(connection_id).await?;
+                let mut tx = pool.begin().await?;
+                sqlx::query("SET CONSTRAINTS ALL DEFERRED").execute(&mut *tx).await?;
+                Ok(Self::Postgres(tx))
+            }
+            DatabaseType::MariaDB | DatabaseType::MySQL => {
+                let pool = manager.get_pool_mysql(connection_id).await?;
+                let mut tx = pool.begin().await?;
+                sqlx::query("SET FOREIGN_KEY_CHECKS = 0").execute(&mut *tx).await?;
+                Ok(Self::MySql(tx))
+            }
+        }
+    }
+
+    async fn commit(self) -> AppResult<()> {
+        match self {
+            Self::Postgres(tx) => tx.commit().await?,
+            Self::MySql(mut tx) => {
+                // Session setting; restore it before the connection goes back to the pool
+                sqlx::query("SET FOREIGN_KEY_CHECKS = 1").execute(&mut *tx).await?;
+                tx.commit().await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn rollback(self) -> AppResult<()> {
+        match self {
+            Self::Postgres(tx) => tx.rollback().await?,
+            Self::MySql(mut tx) => {
+                sqlx::query("SET FOREIGN_KEY_CHECKS = 1").execute(&mut *tx).await?;
+                tx.rollback().await?;
+            }
+        }
+        Ok(())
+    }
 }
 
 // Global import cancellation tokens
@@ -103,6 +402,17 @@ pub async fn import_tables(
     manager: &ConnectionManager,
     options: ImportOptions,
 ) -> AppResult<()> {
+    let conn = manager.get_connection(&options.connection_id)?;
+    conn.ensure_writable("Import")?;
+
+    let csv_dialect = options.csv_dialect();
+    csv_dialect.validate()?;
+    if !csv_dialect.has_headers && options.csv_column_matching == CsvColumnMatching::ByName {
+        return Err(AppError::ValidationError(
+            "CSV columns can only be matched by name when the files have a header row".to_string(),
+        ));
+    }
+
     // Create and register cancellation token
     let cancel_token = CancellationToken::new();
     let import_id = options.connection_id.clone();
@@ -111,7 +421,6 @@ pub async fn import_tables(
         tokens.insert(import_id.clone(), cancel_token.clone());
     }
 
-    let conn = manager.get_connection(&options.connection_id)?;
     let db_type = conn.database_type.clone();
 
     // Extract files if ZIP
@@ -124,6 +433,8 @@ pub async fn import_tables(
                 total: 1,
                 status: "Extracting ZIP archive...".to_string(),
                 cancelled: false,
+                rolled_back: false,
+                rows_inserted: None,
             },
         )
         .ok();
@@ -148,6 +459,8 @@ pub async fn import_tables(
                     total: total_files,
                     status: "Importing database schema...".to_string(),
                     cancelled: false,
+                    rolled_back: false,
+                    rows_inserted: None,
                 },
             )
             .ok();
@@ -163,27 +476,103 @@ pub async fn import_tables(
                     total: total_files,
                     status: "Warning: No schema.sql found in ZIP. Tables must already exist.".to_string(),
                     cancelled: false,
+                    rolled_back: false,
+                    rows_inserted: None,
                 },
             )
             .ok();
         }
     }
 
+    let target_table = |path: &Path| {
+        let file_name = path.file_stem().and_then(|s| s.to_str()).unwrap_or("unknown");
+        options
+            .table_mappings
+            .get(file_name)
+            .cloned()
+            .unwrap_or_else(|| file_name.to_string())
+    };
+
+    let mut targets = Vec::with_capacity(csv_files.len());
+    for path in &csv_files {
+        match qualify_target_table(manager, &options.connection_id, &db_type, target_table(path)).await {
+            Ok(table) => targets.push(table),
+            Err(e) => {
+                if let Some(dir) = temp_dir {
+                    fs::remove_dir_all(&dir).ok();
+                }
+                return Err(e);
+            }
+        }
+    }
+
+    // All-or-nothing and replace imports share one transaction, so files are loaded
+    // one at a time. Replace needs it because several files may feed one table, and
+    // clearing in a transaction of its own would commit before the data is loaded.
+    let shared_tx = if options.transaction_scope == TransactionScope::AllOrNothing
+        || options.mode == ImportMode::Replace
+    {
+        match ImportTx::begin(manager, &options.connection_id, &db_type).await {
+            Ok(tx) => Some(Arc::new(Mutex::new(tx))),
+            Err(e) => {
+                if let Some(dir) = temp_dir {
+                    fs::remove_dir_all(&dir).ok();
+                }
+                return Err(e);
+            }
+        }
+    } else {
+        None
+    };
+
+    if let (ImportMode::Replace, Some(shared)) = (options.mode, &shared_tx) {
+        let mut tables = targets.clone();
+        tables.sort();
+        tables.dedup();
+
+        // Clear every table up front, since several files may feed one table
+        let cleared = clear_target_tables(&mut *shared.lock().await, &tables).await;
+
+        if let Err(e) = cleared {
+            if let Some(shared) = shared_tx {
+                if let Ok(tx) = Arc::try_unwrap(shared) {
+                    tx.into_inner().rollback().await.ok();
+                }
+            }
+            if let Some(dir) = temp_dir {
+                fs::remove_dir_all(&dir).ok();
+            }
+            return Err(e);
+        }
+    }
+
+    let rolls_back_all = shared_tx.is_some();
+
     // Use Arc to share progress counter across tasks
     let completed = Arc::new(Mutex::new(0_usize));
     let app_handle = app.clone();
     let connection_id = options.connection_id.clone();
 
-    // Import CSV files in parallel (up to 8 concurrent)
-    let results: Vec<AppResult<()>> = stream::iter(csv_files.into_iter())
-        .map(|csv_path| {
+    // Each file holds a pooled connection for its transaction; leave one free for MySQL DDL
+    let concurrency = (conn.max_connections as usize)
+        .saturating_sub(1)
+        .clamp(1, IMPORT_MAX_CONCURRENT_FILES);
+
+    // Import data files in parallel
+    let results: Vec<AppResult<()>> = stream::iter(csv_files.into_iter().zip(targets))
+        .map(|(csv_path, table_name)| {
             let connection_id = connection_id.clone();
-            let table_mappings = options.table_mappings.clone();
             let db_type = db_type.clone();
             let completed = completed.clone();
             let app = app_handle.clone();
             let total = total_files;
             let cancel_token = cancel_token.clone();
+            let auto_create = options.auto_create;
+            let mode = options.mode;
+            let column_matching = options.csv_column_matching;
+            let ignore_extra_columns = options.ignore_extra_columns;
+            let csv_dialect = csv_dialect;
+            let shared_tx = shared_tx.clone();
 
             async move {
                 // Check for cancellation
@@ -198,10 +587,11 @@ pub async fn import_tables(
                     .and_then(|s| s.to_str())
                     .unwrap_or("unknown");
 
-                let table_name = table_mappings
-                    .get(file_name)
-                    .cloned()
-                    .unwrap_or_else(|| file_name.to_string());
+                // Wait for the shared transaction before reporting this file as started
+                let mut shared_guard = match &shared_tx {
+                    Some(shared) => Some(shared.lock().await),
+                    None => None,
+                };
 
                 // Update progress
                 let mut count = completed.lock().await;
@@ -217,27 +607,96 @@ pub async fn import_tables(
                         total,
                         status: format!("Importing into table: {}", table_name),
                         cancelled: false,
+                        rolled_back: false,
+                        rows_inserted: None,
                     },
                 )
                 .ok();
 
-                // Import CSV with streaming
-                import_csv_to_table_streaming(
-                    manager,
-                    &connection_id,
-                    &csv_path,
-                    &table_name,
-                    &db_type,
-                )
-                .await
+                let progress = BatchProgress {
+                    app: &app,
+                    file_name,
+                    table_name: &table_name,
+                    current,
+                    total,
+                    cancel_token: &cancel_token,
+                };
+
+                let result = match shared_guard.as_deref_mut() {
+                    Some(tx) => {
+                        import_file(
+                            manager,
+                            &connection_id,
+                            tx,
+                            &csv_path,
+                            &table_name,
+                            &db_type,
+                            auto_create,
+                            mode,
+                            &options.null_token,
+                            column_matching,
+                            ignore_extra_columns,
+                            &csv_dialect,
+                            &progress,
+                        )
+                        .await
+                    }
+                    None => {
+                        let mut tx = ImportTx::begin(manager, &connection_id, &db_type).await?;
+                        match import_file(
+                            manager,
+                            &connection_id,
+                            &mut tx,
+                            &csv_path,
+                            &table_name,
+                            &db_type,
+                            auto_create,
+                            mode,
+                            &options.null_token,
+                            column_matching,
+                            ignore_extra_columns,
+                            &csv_dialect,
+                            &progress,
+                        )
+                        .await
+                        {
+                            Ok(()) => tx.commit().await,
+                            Err(e) => {
+                                tx.rollback().await.ok();
+                                Err(e)
+                            }
+                        }
+                    }
+                };
+
+                if let Err(e) = &result {
+                    if !matches!(e, AppError::OperationCancelled(_)) {
+                        app.emit(
+                            "import-progress",
+                            ImportProgress {
+                                file_name: file_name.to_string(),
+                                current,
+                                total,
+                                status: format!("Import into {} failed and was rolled back: {}", table_name, e),
+                                cancelled: false,
+                                rolled_back: true,
+                                rows_inserted: None,
+                            },
+                        )
+                        .ok();
+                    }
+                }
+
+                result
             }
         })
-        .buffer_unordered(8) // Process up to 8 files concurrently
+        .buffer_unordered(concurrency)
         .collect()
         .await;
 
     // Check for cancellation or errors
     let mut was_cancelled = false;
+    let mut failure = None;
     for result in results {
         match result {
             Err(AppError::OperationCancelled(_)) => {
@@ -245,16 +704,40 @@ pub async fn import_tables(
                 break;
             }
             Err(e) => {
-                // Clean up before returning error
-                if let Some(dir) = temp_dir {
-                    fs::remove_dir_all(&dir).ok();
-                }
-                return Err(e);
+                failure = Some(e);
+                break;
             }
             Ok(_) => {}
         }
     }
 
+    // The shared transaction commits only if every file imported
+    if let Some(shared) = shared_tx {
+        let tx = Arc::try_unwrap(shared)
+            .map_err(|_| AppError::Other("Import transaction is still in use".to_string()))?
+            .into_inner();
+        if was_cancelled || failure.is_some() {
+            tx.rollback().await.ok();
+            if let Some(e) = &failure {
+                app.emit(
+                    "import-progress",
+                    ImportProgress {
+                        file_name: String::new(),
+                        current: total_files,
+                        total: total_files,
+                        status: format!("Import failed and all files were rolled back: {}", e),
+                        cancelled: false,
+                        rolled_back: true,
+                        rows_inserted: None,
+                    },
+                )
+                .ok();
+            }
+        } else if let Err(e) = tx.commit().await {
+            failure = Some(e);
+        }
+    }
+
     // Clean up temporary directory
     if let Some(dir) = temp_dir {
         fs::remove_dir_all(&dir).ok();
@@ -266,6 +749,10 @@ pub async fn import_tables(
         tokens.remove(&import_id);
     }
 
+    if let Some(e) = failure {
+        return Err(e);
+    }
+
     if was_cancelled {
         app.emit(
             "import-progress",
@@ -275,6 +762,8 @@ pub async fn import_tables(
                 total: total_files,
                 status: "Import cancelled".to_string(),
                 cancelled: true,
+                rolled_back: rolls_back_all,
+                rows_inserted: None,
             },
         )
         .ok();
@@ -292,6 +781,8 @@ pub async fn import_tables(
             total: total_files,
             status: "Import completed!".to_string(),
             cancelled: false,
+            rolled_back: false,
+            rows_inserted: None,
         },
     )
     .ok();
@@ -299,6 +790,67 @@ pub async fn import_tables(
     Ok(())
 }
 
+/// Import one data file into `table_name` inside `tx`, picking the reader from the file extension
+async fn import_file(
+    manager: &ConnectionManager,
+    connection_id: &str,
+    tx: &mut ImportTx,
+    path: &PathBuf,
+    table_name: &str,
+    db_type: &DatabaseType,
+    auto_create: bool,
+    mode: ImportMode,
+    null_token: &str,
+    column_matching: CsvColumnMatching,
+    ignore_extra_columns: bool,
+    csv_dialect: &CsvDialect,
+    progress: &BatchProgress<'_>,
+) -> AppResult<()> {
+    match ImportFileFormat::from_path(path) {
+        Some(ImportFileFormat::Csv) => {
+            // Import CSV with streaming
+            import_csv_to_table_streaming(
+                manager,
+                connection_id,
+                tx,
+                path,
+                table_name,
+                db_type,
+                auto_create,
+                mode,
+                null_token,
+                column_matching,
+                ignore_extra_columns,
+                csv_dialect,
+                progress,
+            )
+            .await
+        }
+        Some(ImportFileFormat::Json) => {
+            import_json_to_table(manager, connection_id, tx, path, table_name, db_type, auto_create, mode, progress)
+                .await
+        }
+        Some(ImportFileFormat::Ndjson) => {
+            import_ndjson_to_table_streaming(
+                manager,
+                connection_id,
+                tx,
+                path,
+                table_name,
+                db_type,
+                auto_create,
+                mode,
+                progress,
+            )
+            .await
+        }
+        None => Err(AppError::ValidationError(format!(
+            "Unsupported import file type: {}",
+            path.display()
+        ))),
+    }
+}
+
 /// Cancel an ongoing import operation
 pub async fn cancel_import(connection_id: String) -> AppResult<()> {
     let tokens = IMPORT_TOKENS.read().await;
@@ -371,114 +923,931 @@ async fn import_schema(
     Ok(())
 }
 
-/// Streaming CSV import - reads and processes in chunks, no full file load
+/// Streaming CSV import - reads and processes in chunks, no full file load. At most one
+/// batch of records is held in memory, whatever the file size.
 async fn import_csv_to_table_streaming(
     manager: &ConnectionManager,
     connection_id: &str,
+    tx: &mut ImportTx,
     csv_path: &PathBuf,
     table_name: &str,
     db_type: &DatabaseType,
+    auto_create: bool,
+    mode: ImportMode,
+    null_token: &str,
+    column_matching: CsvColumnMatching,
+    ignore_extra_columns: bool,
+    csv_dialect: &CsvDialect,
+    progress: &BatchProgress<'_>,
 ) -> AppResult<()> {
     // Open file with buffered reader
     let file = File::open(csv_path).map_err(|e| {
         AppError::IoError(format!("Failed to open CSV file: {}", e))
     })?;
 
-    let mut reader = ReaderBuilder::new()
-        .has_headers(true)
-        .from_reader(BufReader::with_capacity(256 * 1024, file)); // 256KB buffer
+    // The parser doesn't say whether a field was quoted, so keep the raw bytes around
+    let captured = Arc::new(std::sync::Mutex::new(Vec::new()));
+    // Decoding happens before capture, so captured bytes line up with parser positions
+    let mut reader = csv_dialect.reader_builder().from_reader(CapturingReader {
+        inner: DecodingReader::new(
+            BufReader::with_capacity(256 * 1024, file), // 256KB buffer
+            csv_dialect.encoding,
+        ),
+        captured: captured.clone(),
+    });
 
     // Get headers
     let headers = reader
         .headers()
-        .map_err(|e| AppError::IoError(format!("Failed to read CSV headers: {}", e)))?
+        .map_err(|e| csv_read_error("Failed to read CSV headers", e))?
         .clone();
 
-    let column_names: Vec<String> = headers.iter().map(|h| h.to_string()).collect();
+    // Without a header row `headers()` is the first record, which is still read as data
+    let header_names: Vec<String> = if csv_dialect.has_headers {
+        headers.iter().map(|h| h.to_string()).collect()
+    } else {
+        headerless_column_names(&get_target_columns(tx, table_name).await?, headers.len(), table_name)?
+    };
 
-    if column_names.is_empty() {
+    if header_names.is_empty() {
         return Ok(());
     }
 
-    // Process in batches of 1000 rows without loading entire file
-    let batch_size = 1000;
-    let mut batch: Vec<Vec<String>> = Vec::with_capacity(batch_size);
+    // Which CSV field feeds each inserted column; None keeps the file's order
+    let (column_names, field_indexes) = match column_matching {
+        CsvColumnMatching::Positional => (header_names, None),
+        CsvColumnMatching::ByName => {
+            let table_columns = get_target_columns(tx, table_name).await?;
+            if table_columns.is_empty() {
+                // Table doesn't exist yet; auto_create builds it from the headers
+                (header_names, None)
+            } else {
+                let (names, indexes) = map_csv_columns(&header_names, &table_columns, ignore_extra_columns)?;
+                (names, Some(indexes))
+            }
+        }
+    };
 
-    for result in reader.records() {
-        let record = result.map_err(|e| {
-            AppError::IoError(format!("Failed to read CSV record: {}", e))
-        })?;
+    // Process in batches without loading entire file
+    let batch_size = IMPORT_BATCH_SIZE;
+    let mut batch: Vec<Vec<String>> = Vec::with_capacity(batch_size);
+    // Resolved from the first batch, which doubles as the type inference sample
+    let mut target: Option<TargetTable> = None;
+    let mut rows_inserted = 0;
+
+    // File offset of the first byte still held in `captured`
+    let mut captured_offset = 0u64;
+    let mut record = csv::StringRecord::new();
+
+    loop {
+        let has_record = reader
+            .read_record(&mut record)
+            .map_err(|e| csv_read_error("Failed to read CSV record", e))?;
+        if !has_record {
+            break;
+        }
 
-        let values: Vec<String> = record.iter().map(|field| field.to_string()).collect();
+        let end = reader.position().byte();
+        let start = record.position().map_or(captured_offset, |p| p.byte());
+        let quoted = {
+            let mut raw = captured
+                .lock()
+                .map_err(|e| AppError::ImportExportError(format!("Failed to lock CSV buffer: {}", e)))?;
+            let consumed = (end.saturating_sub(captured_offset) as usize).min(raw.len());
+            let record_start = (start.saturating_sub(captured_offset) as usize).min(consumed);
+            let flags = quoted_field_flags(&raw[record_start..consumed], csv_dialect.delimiter, csv_dialect.quote);
+            raw.drain(..consumed);
+            captured_offset = end;
+            flags
+        };
+
+        let to_value = |i: usize| {
+            let field = record.get(i).unwrap_or_default();
+            if field == null_token && !quoted.get(i).copied().unwrap_or(false) {
+                CSV_NULL_MARKER.to_string()
+            } else {
+                field.to_string()
+            }
+        };
+        let values: Vec<String> = match &field_indexes {
+            Some(indexes) => indexes.iter().map(|&i| to_value(i)).collect(),
+            None => (0..record.len()).map(to_value).collect(),
+        };
         batch.push(values);
 
         // When batch is full, insert it
         if batch.len() >= batch_size {
+            progress.check_cancelled()?;
+            if target.is_none() {
+                target = Some(
+                    prepare_target_table(manager, connection_id, tx, table_name, &column_names, &batch, db_type, auto_create, mode)
+                        .await?,
+                );
+            }
+            if let Some(target) = &target {
+                insert_batch(
+                    tx,
+                    table_name,
+                    &column_names,
+                    target,
+                    &batch,
+                    rows_inserted + 1,
+                )
+                .await?;
+                rows_inserted += batch.len();
+                progress.batch_inserted(rows_inserted);
+            }
+            batch.clear();
+        }
+    }
+
+    progress.check_cancelled()?;
+    if target.is_none() {
+        target = Some(
+            prepare_target_table(manager, connection_id, tx, table_name, &column_names, &batch, db_type, auto_create, mode)
+                .await?,
+        );
+    }
+
+    // Insert remaining records
+    if !batch.is_empty() {
+        if let Some(target) = &target {
             insert_batch(
-                manager,
-                connection_id,
+                tx,
                 table_name,
                 &column_names,
+                target,
                 &batch,
-                db_type,
+                rows_inserted + 1,
             )
             .await?;
-            batch.clear();
+            progress.batch_inserted(rows_inserted + batch.len());
         }
     }
 
-    // Insert remaining records
-    if !batch.is_empty() {
-        insert_batch(
-            manager,
-            connection_id,
+    Ok(())
+}
+
+/// Read errors from invalid UTF-8 point at the encoding option
+fn csv_read_error(context: &str, e: csv::Error) -> AppError {
+    if matches!(e.kind(), csv::ErrorKind::Utf8 { .. }) {
+        AppError::ImportExportError(format!(
+            "{}: {}. The file is not UTF-8; choose Windows-1252 or Latin-1 as the import encoding",
+            context, e
+        ))
+    } else {
+        AppError::IoError(format!("{}: {}", context, e))
+    }
+}
+
+/// Column names for a CSV without a header row: the table's columns in order, or
+/// `column_1`, `column_2`, ... when the table will be created from the file
+fn headerless_column_names(
+    table_columns: &[TargetColumn],
+    field_count: usize,
+    table_name: &str,
+) -> AppResult<Vec<String>> {
+    if table_columns.is_empty() {
+        return Ok((1..=field_count).map(|i| format!("column_{}", i)).collect());
+    }
+    if field_count > table_columns.len() {
+        return Err(AppError::ImportExportError(format!(
+            "CSV rows have {} fields but table '{}' only has {} columns",
+            field_count,
             table_name,
-            &column_names,
-            &batch,
-            db_type,
-        )
-        .await?;
+            table_columns.len()
+        )));
+    }
+    Ok(table_columns.iter().take(field_count).map(|c| c.name.clone()).collect())
+}
+
+/// Passes reads through while keeping a copy of every byte read, so the CSV
+/// importer can look at the raw text of the record it just parsed
+struct CapturingReader<R> {
+    inner: R,
+    captured: Arc<std::sync::Mutex<Vec<u8>>>,
+}
+
+impl<R: Read> Read for CapturingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        if let Ok(mut captured) = self.captured.lock() {
+            captured.extend_from_slice(&buf[..n]);
+        }
+        Ok(n)
+    }
+}
+
+/// Whether each field of a raw CSV record started with a quote
+fn quoted_field_flags(raw: &[u8], delimiter: u8, quote: u8) -> Vec<bool> {
+    let mut flags = Vec::new();
+    let mut in_quotes = false;
+    let mut at_field_start = true;
+
+    for &byte in raw {
+        if at_field_start {
+            flags.push(byte == quote);
+            at_field_start = false;
+        }
+        if byte == quote {
+            // A doubled quote toggles twice, so escaped quotes don't change the state
+            in_quotes = !in_quotes;
+        } else if byte == delimiter && !in_quotes {
+            at_field_start = true;
+        }
+    }
+    if at_field_start {
+        flags.push(false);
+    }
+
+    flags
+}
+
+/// Declared types of the target table's columns (empty if the table does not exist).
+/// PostgreSQL types come without their modifier, since inserts cast to them and a cast
+/// to `varchar(n)` or `numeric(p,s)` would silently truncate or round the value.
+async fn get_target_column_types(tx: &mut ImportTx, table_name: &str) -> AppResult<HashMap<String, String>> {
+    let rows: Vec<(String, String)> = match tx {
+        ImportTx::Postgres(tx) => {
+            let (schema, table) = split_qualified_table(table_name);
+            sqlx::query_as(
+                "SELECT a.attname::TEXT, pg_catalog.format_type(a.atttypid, NULL)
+                 FROM pg_attribute a
+                 JOIN pg_class c ON c.oid = a.attrelid
+                 JOIN pg_namespace n ON n.oid = c.relnamespace
+                 WHERE n.nspname = COALESCE($2::TEXT, current_schema()) AND c.relname = $1
+                   AND a.attnum > 0 AND NOT a.attisdropped"
+            )
+            .bind(table)
+            .bind(schema)
+            .fetch_all(&mut **tx)
+            .await?
+        }
+        ImportTx::MySql(tx) => {
+            sqlx::query_as(
+                "SELECT COLUMN_NAME, DATA_TYPE FROM INFORMATION_SCHEMA.COLUMNS
+                 WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = ?"
+            )
+            .bind(table_name)
+            .fetch_all(&mut **tx)
+            .await?
+        }
+    };
+
+    Ok(rows
+        .into_iter()
+        .map(|(column, data_type)| (column, data_type.to_lowercase()))
+        .collect())
+}
+
+/// A target table column and whether an insert must supply it
+#[derive(Debug, Clone)]
+struct TargetColumn {
+    name: String,
+    /// NOT NULL with no default, identity, auto-increment or generated value
+    required: bool,
+}
+
+/// Columns of the target table in table order (empty if the table does not exist)
+async fn get_target_columns(tx: &mut ImportTx, table_name: &str) -> AppResult<Vec<TargetColumn>> {
+    let rows: Vec<(String, bool)> = match tx {
+        ImportTx::Postgres(tx) => {
+            let (schema, table) = split_qualified_table(table_name);
+            sqlx::query_as(
+                "SELECT a.attname::TEXT,
+                        a.attnotnull AND NOT a.atthasdef AND a.attidentity = '' AND a.attgenerated = ''
+                 FROM pg_attribute a
+                 JOIN pg_class c ON c.oid = a.attrelid
+                 JOIN pg_namespace n ON n.oid = c.relnamespace
+                 WHERE n.nspname = COALESCE($2::TEXT, current_schema()) AND c.relname = $1
+                   AND a.attnum > 0 AND NOT a.attisdropped
+                 ORDER BY a.attnum"
+            )
+            .bind(table)
+            .bind(schema)
+            .fetch_all(&mut **tx)
+            .await?
+        }
+        ImportTx::MySql(tx) => {
+            let rows: Vec<(String, i64)> = sqlx::query_as(
+                "SELECT COLUMN_NAME,
+                        CAST(IS_NULLABLE = 'NO' AND COLUMN_DEFAULT IS NULL
+                          AND EXTRA NOT LIKE '%auto_increment%' AND EXTRA NOT LIKE '%GENERATED%' AS SIGNED)
+                 FROM INFORMATION_SCHEMA.COLUMNS
+                 WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = ?
+                 ORDER BY ORDINAL_POSITION"
+            )
+            .bind(table_name)
+            .fetch_all(&mut **tx)
+            .await?;
+            rows.into_iter().map(|(name, required)| (name, required != 0)).collect()
+        }
+    };
+
+    Ok(rows
+        .into_iter()
+        .map(|(name, required)| TargetColumn { name, required })
+        .collect())
+}
+
+/// Match CSV headers to table columns by name, ignoring case. Returns the table's
+/// spelling of each matched column and the CSV field index that feeds it.
+fn map_csv_columns(
+    headers: &[String],
+    table_columns: &[TargetColumn],
+    ignore_extra_columns: bool,
+) -> AppResult<(Vec<String>, Vec<usize>)> {
+    let mut names = Vec::new();
+    let mut indexes = Vec::new();
+    let mut extra = Vec::new();
+
+    for (index, header) in headers.iter().enumerate() {
+        let header = header.trim();
+        match table_columns.iter().find(|c| c.name.eq_ignore_ascii_case(header)) {
+            Some(column) if names.contains(&column.name) => {
+                return Err(AppError::ValidationError(format!(
+                    "CSV columns map to table column \"{}\" more than once",
+                    column.name
+                )));
+            }
+            Some(column) => {
+                names.push(column.name.clone());
+                indexes.push(index);
+            }
+            None => extra.push(header.to_string()),
+        }
+    }
+
+    if !extra.is_empty() && !ignore_extra_columns {
+        return Err(AppError::ValidationError(format!(
+            "CSV columns not found in the table: {}",
+            extra.join(", ")
+        )));
+    }
+
+    let missing: Vec<&str> = table_columns
+        .iter()
+        .filter(|c| c.required && !names.contains(&c.name))
+        .map(|c| c.name.as_str())
+        .collect();
+    if !missing.is_empty() {
+        return Err(AppError::ValidationError(format!(
+            "CSV has no column for required table columns: {}",
+            missing.join(", ")
+        )));
+    }
+    if names.is_empty() {
+        return Err(AppError::ValidationError("No CSV columns match the table".to_string()));
+    }
+
+    Ok((names, indexes))
+}
+
+/// Primary key columns of the target table, in key order (empty if it has none)
+async fn get_primary_key_columns(tx: &mut ImportTx, table_name: &str) -> AppResult<Vec<String>> {
+    let rows: Vec<(String,)> = match tx {
+        ImportTx::Postgres(tx) => {
+            let (schema, table) = split_qualified_table(table_name);
+            sqlx::query_as(
+                "SELECT a.attname::TEXT
+                 FROM pg_index i
+                 JOIN pg_class c ON c.oid = i.indrelid
+                 JOIN pg_namespace n ON n.oid = c.relnamespace
+                 JOIN pg_attribute a ON a.attrelid = c.oid AND a.attnum = ANY(i.indkey)
+                 WHERE i.indisprimary AND n.nspname = COALESCE($2::TEXT, current_schema()) AND c.relname = $1
+                 ORDER BY array_position(i.indkey::INT2[], a.attnum)"
+            )
+            .bind(table)
+            .bind(schema)
+            .fetch_all(&mut **tx)
+            .await?
+        }
+        ImportTx::MySql(tx) => {
+            sqlx::query_as(
+                "SELECT COLUMN_NAME FROM INFORMATION_SCHEMA.KEY_COLUMN_USAGE
+                 WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = ? AND CONSTRAINT_NAME = 'PRIMARY'
+                 ORDER BY ORDINAL_POSITION"
+            )
+            .bind(table_name)
+            .fetch_all(&mut **tx)
+            .await?
+        }
+    };
+
+    Ok(rows.into_iter().map(|(column,)| column).collect())
+}
+
+/// Build the ON CONFLICT / ON DUPLICATE KEY clause that turns the batch INSERT into an
+/// upsert on `primary_key`. Every key column must be part of the import.
+fn upsert_clause(
+    table_name: &str,
+    column_names: &[String],
+    primary_key: &[String],
+    db_type: &DatabaseType,
+) -> AppResult<String> {
+    if primary_key.is_empty() {
+        return Err(AppError::ValidationError(format!(
+            "Cannot upsert into '{}': the table has no primary key",
+            table_name
+        )));
+    }
+    if let Some(missing) = primary_key.iter().find(|key| !column_names.contains(key)) {
+        return Err(AppError::ValidationError(format!(
+            "Cannot upsert into '{}': primary key column '{}' is missing from the import",
+            table_name, missing
+        )));
+    }
+
+    let updated: Vec<&String> = column_names.iter().filter(|c| !primary_key.contains(c)).collect();
+
+    Ok(match db_type {
+        DatabaseType::PostgreSQL => {
+            let target = primary_key
+                .iter()
+                .map(|c| quote_identifier_postgres(c))
+                .collect::<Vec<_>>()
+                .join(", ");
+            if updated.is_empty() {
+                format!(" ON CONFLICT ({}) DO NOTHING", target)
+            } else {
+                let assignments = updated
+                    .iter()
+                    .map(|c| {
+                        let quoted = quote_identifier_postgres(c);
+                        format!("{} = EXCLUDED.{}", quoted, quoted)
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!(" ON CONFLICT ({}) DO UPDATE SET {}", target, assignments)
+            }
+        }
+        DatabaseType::MariaDB | DatabaseType::MySQL => {
+            // VALUES() rather than the row alias syntax, which MariaDB doesn't support.
+            // A key-only import still needs one assignment; the no-op keeps the existing row.
+            let assignments = if updated.is_empty() {
+                let quoted = quote_identifier_mysql(&primary_key[0]);
+                format!("{} = {}", quoted, quoted)
+            } else {
+                updated
+                    .iter()
+                    .map(|c| {
+                        let quoted = quote_identifier_mysql(c);
+                        format!("{} = VALUES({})", quoted, quoted)
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            format!(" ON DUPLICATE KEY UPDATE {}", assignments)
+        }
+    })
+}
+
+/// Qualify a PostgreSQL target with the schema it resolves to on the connection's
+/// search path, so every lookup and statement addresses the same table. MySQL
+/// names are returned unchanged.
+async fn qualify_target_table(
+    manager: &ConnectionManager,
+    connection_id: &str,
+    db_type: &DatabaseType,
+    table_name: String,
+) -> AppResult<String> {
+    match db_type {
+        DatabaseType::PostgreSQL => {
+            let pool = manager.get_pool_postgres(connection_id).await?;
+            let search_path = manager.get_connection(connection_id)?.search_path();
+            let (schema, table) = resolve_postgres_table(&pool, &table_name, &search_path).await;
+            Ok(format!("{}.{}", schema, table))
+        }
+        DatabaseType::MariaDB | DatabaseType::MySQL => Ok(table_name),
+    }
+}
+
+/// Remove every row from the target tables inside `tx`, for `ImportMode::Replace`.
+/// Tables that don't exist yet (to be auto-created) are skipped. MySQL uses DELETE
+/// because TRUNCATE commits implicitly and can't be rolled back.
+async fn clear_target_tables(tx: &mut ImportTx, tables: &[String]) -> AppResult<()> {
+    let mut existing = Vec::new();
+    for table in tables {
+        if !get_target_column_types(tx, table).await?.is_empty() {
+            existing.push(table);
+        }
+    }
+    if existing.is_empty() {
+        return Ok(());
+    }
+
+    match tx {
+        ImportTx::Postgres(tx) => {
+            let quoted: Vec<String> = existing
+                .iter()
+                .map(|t| quote_table(t, &DatabaseType::PostgreSQL))
+                .collect();
+            // A single TRUNCATE may include tables that reference each other
+            sqlx::query(&format!("TRUNCATE TABLE {}", quoted.join(", ")))
+                .execute(&mut **tx)
+                .await?;
+        }
+        ImportTx::MySql(tx) => {
+            for table in existing {
+                sqlx::query(&format!("DELETE FROM {}", quote_identifier_mysql(table)))
+                    .execute(&mut **tx)
+                    .await?;
+            }
+        }
     }
 
     Ok(())
 }
 
-/// Insert a single batch
-async fn insert_batch(
+/// Target table details resolved once per imported file
+#[derive(Debug, Clone, Default)]
+struct TargetTable {
+    /// Declared type of each import column (aligned with the column names), if known
+    column_types: Vec<Option<String>>,
+    /// Appended to every INSERT; empty unless upserting
+    conflict_clause: String,
+    /// Positions of the primary key columns in each row, when upserting
+    key_indexes: Vec<usize>,
+}
+
+/// Resolve the declared type of each import column, creating the table from
+/// types inferred on `sample` when it is missing and `auto_create` is set.
+/// For `ImportMode::Upsert` the conflict target comes from the table's primary key.
+async fn prepare_target_table(
     manager: &ConnectionManager,
     connection_id: &str,
+    tx: &mut ImportTx,
     table_name: &str,
     column_names: &[String],
-    batch: &[Vec<String>],
+    sample: &[Vec<String>],
+    db_type: &DatabaseType,
+    auto_create: bool,
+    mode: ImportMode,
+) -> AppResult<TargetTable> {
+    let mut declared = get_target_column_types(tx, table_name).await?;
+
+    if declared.is_empty() && auto_create {
+        let sample = &sample[..sample.len().min(TYPE_INFERENCE_SAMPLE_ROWS)];
+        let inferred = infer_column_types(column_names.len(), sample);
+        let quote = match db_type {
+            DatabaseType::PostgreSQL => quote_identifier_postgres,
+            DatabaseType::MariaDB | DatabaseType::MySQL => quote_identifier_mysql,
+        };
+
+        let column_defs: Vec<String> = column_names
+            .iter()
+            .zip(&inferred)
+            .map(|(name, ty)| format!("{} {} NULL", quote(name), ty.ddl(db_type)))
+            .collect();
+
+        // IF NOT EXISTS: several files may target the same table concurrently
+        let ddl = format!(
+            "CREATE TABLE IF NOT EXISTS {} ({})",
+            quote_table(table_name, db_type),
+            column_defs.join(", ")
+        );
+
+        match tx {
+            ImportTx::Postgres(tx) => {
+                sqlx::query(&ddl).execute(&mut **tx).await?;
+            }
+            // DDL commits implicitly in MySQL, so it runs outside the import transaction
+            ImportTx::MySql(_) => {
+                let pool = manager.get_pool_mysql(connection_id).await?;
+                sqlx::query(&ddl).execute(&pool).await?;
+            }
+        }
+
+        declared = get_target_column_types(tx, table_name).await?;
+    }
+
+    let column_types = column_names.iter().map(|name| declared.remove(name)).collect();
+
+    let (conflict_clause, key_indexes) = if mode == ImportMode::Upsert {
+        let primary_key = get_primary_key_columns(tx, table_name).await?;
+        let clause = upsert_clause(table_name, column_names, &primary_key, db_type)?;
+        let key_indexes = primary_key
+            .iter()
+            .filter_map(|key| column_names.iter().position(|c| c == key))
+            .collect();
+        (clause, key_indexes)
+    } else {
+        (String::new(), Vec::new())
+    };
+
+    Ok(TargetTable {
+        column_types,
+        conflict_clause,
+        key_indexes,
+    })
+}
+
+/// Convert a JSON field into the string form the batch inserters bind.
+/// Nested objects/arrays become JSON text so they land intact in JSON/JSONB columns.
+fn json_value_to_import_field(value: Option<&serde_json::Value>, db_type: &DatabaseType) -> String {
+    match value {
+        None | Some(serde_json::Value::Null) => CSV_NULL_MARKER.to_string(),
+        Some(serde_json::Value::String(s)) => s.clone(),
+        Some(serde_json::Value::Bool(b)) => match db_type {
+            DatabaseType::PostgreSQL => b.to_string(),
+            // MySQL booleans are TINYINT(1)
+            DatabaseType::MariaDB | DatabaseType::MySQL => if *b { "1" } else { "0" }.to_string(),
+        },
+        Some(other) => other.to_string(),
+    }
+}
+
+/// Build a row in column order, with NULL for keys the object does not have
+fn json_object_to_row(
+    object: &serde_json::Map<String, serde_json::Value>,
+    column_names: &[String],
     db_type: &DatabaseType,
+) -> Vec<String> {
+    column_names
+        .iter()
+        .map(|column| json_value_to_import_field(object.get(column), db_type))
+        .collect()
+}
+
+/// Add keys not seen before to the column list, keeping first-seen order
+fn collect_json_keys(
+    object: &serde_json::Map<String, serde_json::Value>,
+    column_names: &mut Vec<String>,
+    seen: &mut HashSet<String>,
+) {
+    for key in object.keys() {
+        if seen.insert(key.clone()) {
+            column_names.push(key.clone());
+        }
+    }
+}
+
+fn parse_ndjson_line(line: &str, line_number: usize) -> AppResult<serde_json::Map<String, serde_json::Value>> {
+    match serde_json::from_str::<serde_json::Value>(line) {
+        Ok(serde_json::Value::Object(object)) => Ok(object),
+        Ok(_) => Err(AppError::ImportExportError(format!(
+            "NDJSON line {} is not a JSON object",
+            line_number
+        ))),
+        Err(e) => Err(AppError::ImportExportError(format!(
+            "Failed to parse NDJSON line {}: {}",
+            line_number, e
+        ))),
+    }
+}
+
+fn open_ndjson_reader(path: &Path) -> AppResult<BufReader<File>> {
+    let file = File::open(path).map_err(|e| {
+        AppError::IoError(format!("Failed to open NDJSON file: {}", e))
+    })?;
+    Ok(BufReader::with_capacity(256 * 1024, file))
+}
+
+/// Import a JSON array of objects. Columns are the union of all object keys.
+async fn import_json_to_table(
+    manager: &ConnectionManager,
+    connection_id: &str,
+    tx: &mut ImportTx,
+    json_path: &Path,
+    table_name: &str,
+    db_type: &DatabaseType,
+    auto_create: bool,
+    mode: ImportMode,
+    progress: &BatchProgress<'_>,
 ) -> AppResult<()> {
-    match db_type {
-        DatabaseType::PostgreSQL => {
-            insert_postgres_batch(manager, connection_id, table_name, column_names, batch).await
+    let file = File::open(json_path).map_err(|e| {
+        AppError::IoError(format!("Failed to open JSON file: {}", e))
+    })?;
+
+    let value: serde_json::Value = serde_json::from_reader(BufReader::with_capacity(256 * 1024, file))
+        .map_err(|e| AppError::ImportExportError(format!("Failed to parse JSON file: {}", e)))?;
+
+    let serde_json::Value::Array(items) = value else {
+        return Err(AppError::ImportExportError(
+            "JSON import expects an array of objects".to_string(),
+        ));
+    };
+
+    let mut objects = Vec::with_capacity(items.len());
+    for (index, item) in items.into_iter().enumerate() {
+        match item {
+            serde_json::Value::Object(object) => objects.push(object),
+            _ => {
+                return Err(AppError::ImportExportError(format!(
+                    "JSON array element {} is not an object",
+                    index
+                )))
+            }
         }
-        DatabaseType::MariaDB | DatabaseType::MySQL => {
-            insert_mysql_batch(manager, connection_id, table_name, column_names, batch).await
+    }
+
+    let mut column_names = Vec::new();
+    let mut seen = HashSet::new();
+    for object in &objects {
+        collect_json_keys(object, &mut column_names, &mut seen);
+    }
+
+    if column_names.is_empty() {
+        return Ok(());
+    }
+
+    let mut target: Option<TargetTable> = None;
+    let mut rows_inserted = 0;
+
+    for chunk in objects.chunks(IMPORT_BATCH_SIZE) {
+        progress.check_cancelled()?;
+        let batch: Vec<Vec<String>> = chunk
+            .iter()
+            .map(|object| json_object_to_row(object, &column_names, db_type))
+            .collect();
+        if target.is_none() {
+            target = Some(
+                prepare_target_table(manager, connection_id, tx, table_name, &column_names, &batch, db_type, auto_create, mode)
+                    .await?,
+            );
+        }
+        if let Some(target) = &target {
+            insert_batch(
+                tx,
+                table_name,
+                &column_names,
+                target,
+                &batch,
+                rows_inserted + 1,
+            )
+            .await?;
+            rows_inserted += batch.len();
+            progress.batch_inserted(rows_inserted);
         }
     }
+
+    Ok(())
 }
 
-async fn insert_postgres_batch(
+/// Streaming NDJSON import. A first pass reads line by line to collect the union of keys,
+/// a second pass inserts in batches, so only one batch is held in memory.
+async fn import_ndjson_to_table_streaming(
     manager: &ConnectionManager,
     connection_id: &str,
+    tx: &mut ImportTx,
+    ndjson_path: &Path,
+    table_name: &str,
+    db_type: &DatabaseType,
+    auto_create: bool,
+    mode: ImportMode,
+    progress: &BatchProgress<'_>,
+) -> AppResult<()> {
+    let mut column_names = Vec::new();
+    let mut seen = HashSet::new();
+
+    for (index, line) in open_ndjson_reader(ndjson_path)?.lines().enumerate() {
+        if index % IMPORT_BATCH_SIZE == 0 {
+            progress.check_cancelled()?;
+        }
+        let line = line.map_err(|e| AppError::IoError(format!("Failed to read NDJSON file: {}", e)))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let object = parse_ndjson_line(&line, index + 1)?;
+        collect_json_keys(&object, &mut column_names, &mut seen);
+    }
+
+    if column_names.is_empty() {
+        return Ok(());
+    }
+
+    let mut batch: Vec<Vec<String>> = Vec::with_capacity(IMPORT_BATCH_SIZE);
+    // Resolved from the first batch, which doubles as the type inference sample
+    let mut target: Option<TargetTable> = None;
+    let mut rows_inserted = 0;
+
+    for (index, line) in open_ndjson_reader(ndjson_path)?.lines().enumerate() {
+        let line = line.map_err(|e| AppError::IoError(format!("Failed to read NDJSON file: {}", e)))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let object = parse_ndjson_line(&line, index + 1)?;
+        batch.push(json_object_to_row(&object, &column_names, db_type));
+
+        if batch.len() >= IMPORT_BATCH_SIZE {
+            progress.check_cancelled()?;
+            if target.is_none() {
+                target = Some(
+                    prepare_target_table(manager, connection_id, tx, table_name, &column_names, &batch, db_type, auto_create, mode)
+                        .await?,
+                );
+            }
+            if let Some(target) = &target {
+                insert_batch(
+                    tx,
+                    table_name,
+                    &column_names,
+                    target,
+                    &batch,
+                    rows_inserted + 1,
+                )
+                .await?;
+                rows_inserted += batch.len();
+                progress.batch_inserted(rows_inserted);
+            }
+            batch.clear();
+        }
+    }
+
+    progress.check_cancelled()?;
+    if target.is_none() {
+        target = Some(
+            prepare_target_table(manager, connection_id, tx, table_name, &column_names, &batch, db_type, auto_create, mode)
+                .await?,
+        );
+    }
+
+    if !batch.is_empty() {
+        if let Some(target) = &target {
+            insert_batch(
+                tx,
+                table_name,
+                &column_names,
+                target,
+                &batch,
+                rows_inserted + 1,
+            )
+            .await?;
+            progress.batch_inserted(rows_inserted + batch.len());
+        }
+    }
+
+    Ok(())
+}
+
+/// Insert a single batch within the import transaction. `target.column_types` holds the
+/// declared type of each column (aligned with `column_names`); values are cast to it, or
+/// bound as-is when unknown. `first_row` is the 1-based number of the batch's first data
+/// row, reported if the batch fails.
+async fn insert_batch(
+    tx: &mut ImportTx,
     table_name: &str,
     column_names: &[String],
+    target: &TargetTable,
     batch: &[Vec<String>],
+    first_row: usize,
 ) -> AppResult<()> {
-    let pool = manager.get_pool_postgres(connection_id).await?;
+    let result = match tx {
+        ImportTx::Postgres(tx) => {
+            // ON CONFLICT DO UPDATE rejects a statement that touches the same row twice
+            let deduplicated;
+            let batch = if target.key_indexes.is_empty() {
+                batch
+            } else {
+                deduplicated = last_row_per_key(batch, &target.key_indexes);
+                &deduplicated
+            };
+            insert_postgres_batch(&mut **tx, table_name, column_names, target, batch).await
+        }
+        ImportTx::MySql(tx) => insert_mysql_batch(&mut **tx, table_name, column_names, target, batch).await,
+    };
 
-    // Get a dedicated connection for this batch
-    let mut conn = pool.acquire().await?;
+    result.map_err(|e| {
+        AppError::ImportExportError(format!(
+            "Failed to import rows {}-{} into '{}': {}",
+            first_row,
+            first_row + batch.len().saturating_sub(1),
+            table_name,
+            e
+        ))
+    })
+}
 
-    // For PostgreSQL, defer constraints within this transaction
-    // This allows inserting data regardless of FK order
-    sqlx::query("SET CONSTRAINTS ALL DEFERRED")
-        .execute(&mut *conn)
-        .await?;
+/// Keep only the last row for each key, preserving the order in which keys first appear
+fn last_row_per_key(batch: &[Vec<String>], key_indexes: &[usize]) -> Vec<Vec<String>> {
+    let mut positions: HashMap<Vec<&str>, usize> = HashMap::new();
+    let mut rows: Vec<Vec<String>> = Vec::with_capacity(batch.len());
+
+    for record in batch {
+        let key: Vec<&str> = key_indexes
+            .iter()
+            .map(|&i| record.get(i).map(String::as_str).unwrap_or_default())
+            .collect();
+        match positions.get(&key) {
+            Some(&position) => rows[position] = record.clone(),
+            None => {
+                positions.insert(key, rows.len());
+                rows.push(record.clone());
+            }
+        }
+    }
+
+    rows
+}
+
+async fn insert_postgres_batch(
+    conn: &mut sqlx::PgConnection,
+    table_name: &str,
+    column_names: &[String],
+    target: &TargetTable,
+    batch: &[Vec<String>],
+) -> AppResult<()> {
+    let column_types = &target.column_types;
 
     let columns = column_names
         .iter()
@@ -487,13 +1856,17 @@ async fn insert_postgres_batch(
         .join(", ");
 
     let mut placeholders = Vec::new();
-    let mut values: Vec<&str> = Vec::new();
+    let mut values: Vec<(&str, Option<&str>)> = Vec::new();
     let mut param_index = 1;
 
     for record in batch {
         let row_placeholders: Vec<String> = (0..column_names.len())
-            .map(|_| {
-                let placeholder = format!("${}", param_index);
+            .map(|column_index| {
+                // Text parameters must be cast explicitly to non-text column types
+                let placeholder = match column_types.get(column_index).and_then(|t| t.as_deref()) {
+                    Some(column_type) => format!("${}::{}", param_index, column_type),
+                    None => format!("${}", param_index),
+                };
                 param_index += 1;
                 placeholder
             })
@@ -501,24 +1874,31 @@ async fn insert_postgres_batch(
 
         placeholders.push(format!("({})", row_placeholders.join(", ")));
 
-        for value in record {
-            values.push(value.as_str());
+        for (column_index, value) in record.iter().enumerate() {
+            let column_type = column_types.get(column_index).and_then(|t| t.as_deref());
+            values.push((value.as_str(), column_type));
         }
     }
 
     let query = format!(
-        "INSERT INTO {} ({}) VALUES {}",
-        quote_identifier_postgres(table_name),
+        "INSERT INTO {} ({}) VALUES {}{}",
+        quote_table(table_name, &DatabaseType::PostgreSQL),
         columns,
-        placeholders.join(", ")
+        placeholders.join(", "),
+        target.conflict_clause
     );
 
     let mut query_builder = sqlx::query(&query);
-    for value in values {
+    for (value, column_type) in values {
+        // Hex strings are only decoded for bytea (or unknown) columns; text columns keep them verbatim
+        let binary_target = column_type.map_or(true, |t| t == "bytea");
+
         // Handle NULL marker from CSV export (PostgreSQL COPY convention)
         // Empty strings are now preserved as empty strings for VARCHAR/TEXT columns
         if value == CSV_NULL_MARKER {
             query_builder = query_builder.bind(None::<String>);
+        } else if !binary_target {
+            query_builder = query_builder.bind(value);
         } else if value.starts_with("\\x") && value.len() > 2 && value != CSV_NULL_MARKER {
             // PostgreSQL hex format for BYTEA columns
             match hex::decode(&value[2..]) {
@@ -552,22 +1932,13 @@ async fn insert_postgres_batch(
 }
 
 async fn insert_mysql_batch(
-    manager: &ConnectionManager,
-    connection_id: &str,
+    conn: &mut sqlx::MySqlConnection,
     table_name: &str,
     column_names: &[String],
+    target: &TargetTable,
     batch: &[Vec<String>],
 ) -> AppResult<()> {
-    let pool = manager.get_pool_mysql(connection_id).await?;
-
-    // Get a dedicated connection for this batch
-    let mut conn = pool.acquire().await?;
-
-    // For MySQL/MariaDB, disable FK checks for this connection's session
-    // This allows inserting data regardless of FK order during parallel imports
-    sqlx::query("SET FOREIGN_KEY_CHECKS = 0")
-        .execute(&mut *conn)
-        .await?;
+    let column_types = &target.column_types;
 
     let columns = column_names
         .iter()
@@ -584,19 +1955,43 @@ async fn insert_mysql_batch(
         .collect();
 
     let query = format!(
-        "INSERT INTO {} ({}) VALUES {}",
+        "INSERT INTO {} ({}) VALUES {}{}",
         quote_identifier_mysql(table_name),
         columns,
-        placeholders.join(", ")
+        placeholders.join(", "),
+        target.conflict_clause
     );
 
     let mut query_builder = sqlx::query(&query);
     for record in batch {
-        for value in record {
+        for (column_index, value) in record.iter().enumerate() {
+            let column_type = column_types.get(column_index).and_then(|t| t.as_deref());
+
             // Handle NULL marker from CSV export (PostgreSQL COPY convention)
             // Empty strings are now preserved as empty strings for VARCHAR/TEXT columns
             if value == CSV_NULL_MARKER {
                 query_builder = query_builder.bind(None::<String>);
+            } else if let Some(column_type) = column_type.filter(|t| !is_mysql_binary_type(t)) {
+                // Known non-binary column: convert to the declared type before binding
+                query_builder = match column_type {
+                    "tinyint" | "smallint" | "mediumint" | "int" | "integer" | "bigint" => {
+                        if value.eq_ignore_ascii_case("true") {
+                            query_builder.bind(1i64)
+                        } else if value.eq_ignore_ascii_case("false") {
+                            query_builder.bind(0i64)
+                        } else if let Ok(i) = value.trim().parse::<i64>() {
+                            query_builder.bind(i)
+                        } else {
+                            query_builder.bind(value)
+                        }
+                    }
+                    "float" | "double" | "real" => match value.trim().parse::<f64>() {
+                        Ok(f) => query_builder.bind(f),
+                        Err(_) => query_builder.bind(value),
+                    },
+                    // DECIMAL, dates and text types parse exactly from strings
+                    _ => query_builder.bind(value),
+                };
             } else if (value.starts_with("\\x") && value.len() > 2) ||
                       value.starts_with("0x") || value.starts_with("0X") {
                 // Decode hex strings back to binary (for BLOB/VARBINARY columns)
@@ -613,15 +2008,16 @@ async fn insert_mysql_batch(
     }
 
     query_builder.execute(&mut *conn).await?;
-
-    // Re-enable FK checks for this connection
-    sqlx::query("SET FOREIGN_KEY_CHECKS = 1")
-        .execute(&mut *conn)
-        .await?;
-
     Ok(())
 }
 
+fn is_mysql_binary_type(data_type: &str) -> bool {
+    matches!(
+        data_type,
+        "binary" | "varbinary" | "tinyblob" | "blob" | "mediumblob" | "longblob"
+    )
+}
+
 /// Streaming ZIP extraction - doesn't load entire files into memory
 fn extract_zip_archive_streaming(zip_path: &str) -> AppResult<(Vec<PathBuf>, PathBuf)> {
     use zip::ZipArchive;
@@ -658,7 +2054,9 @@ fn extract_zip_archive_streaming(zip_path: &str) -> AppResult<(Vec<PathBuf>, Pat
 
         let file_name = file.name().to_string();
 
-        if file_name.ends_with(".csv") || file_name.ends_with(".sql") {
+        let is_data_file = ImportFileFormat::from_path(Path::new(&file_name)).is_some();
+
+        if is_data_file || file_name.ends_with(".sql") {
             let output_path = extract_dir.join(&file_name);
 
             // Create parent directories if needed
@@ -688,7 +2086,7 @@ fn extract_zip_archive_streaming(zip_path: &str) -> AppResult<(Vec<PathBuf>, Pat
                 })?;
             }
 
-            if file_name.ends_with(".csv") {
+            if is_data_file {
                 csv_files.push(output_path);
             }
         }
@@ -696,3 +2094,106 @@ fn extract_zip_archive_streaming(zip_path: &str) -> AppResult<(Vec<PathBuf>, Pat
 
     Ok((csv_files, extract_dir))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(values: &[&str]) -> Vec<Vec<String>> {
+        values.iter().map(|v| vec![v.to_string()]).collect()
+    }
+
+    #[test]
+    fn test_infer_column_types_widens_integer_to_float() {
+        assert_eq!(infer_column_types(1, &rows(&["1", "2.5", CSV_NULL_MARKER])), vec![InferredType::Float]);
+        assert_eq!(infer_column_types(1, &rows(&["-3", "42"])), vec![InferredType::Integer]);
+    }
+
+    #[test]
+    fn test_infer_column_types_is_conservative() {
+        // Leading zeros, mixed kinds and empty columns all fall back to text
+        assert_eq!(infer_column_types(1, &rows(&["00123", "45"])), vec![InferredType::Text]);
+        assert_eq!(infer_column_types(1, &rows(&["true", "1"])), vec![InferredType::Text]);
+        assert_eq!(infer_column_types(1, &rows(&[CSV_NULL_MARKER])), vec![InferredType::Text]);
+        assert_eq!(infer_column_types(1, &rows(&["NaN"])), vec![InferredType::Text]);
+    }
+
+    #[test]
+    fn test_map_csv_columns_by_name() {
+        let table = vec![
+            TargetColumn { name: "id".to_string(), required: false },
+            TargetColumn { name: "Email".to_string(), required: true },
+            TargetColumn { name: "name".to_string(), required: false },
+        ];
+        let headers = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+
+        let (names, indexes) = map_csv_columns(&headers(&["NAME", "email", "id"]), &table, false).unwrap();
+        assert_eq!(names, vec!["name", "Email", "id"]);
+        assert_eq!(indexes, vec![0, 1, 2]);
+
+        // Extra columns are skipped only when asked
+        assert!(map_csv_columns(&headers(&["email", "notes"]), &table, false).is_err());
+        let (names, indexes) = map_csv_columns(&headers(&["notes", "email"]), &table, true).unwrap();
+        assert_eq!(names, vec!["Email"]);
+        assert_eq!(indexes, vec![1]);
+
+        // A required column with no header fails
+        assert!(map_csv_columns(&headers(&["id", "name"]), &table, true).is_err());
+    }
+
+    #[test]
+    fn test_quoted_field_flags() {
+        assert_eq!(quoted_field_flags(b"1,\"\",\n", b',', b'"'), vec![false, true, false]);
+        assert_eq!(quoted_field_flags(b"\"a,\"\"b\"\"\",x\r\n", b',', b'"'), vec![true, false]);
+        assert_eq!(quoted_field_flags(b"\"multi\nline\",", b',', b'"'), vec![true, false]);
+        assert_eq!(quoted_field_flags(b"'a;b';''\n", b';', b'\''), vec![true, true]);
+    }
+
+    #[test]
+    fn test_headerless_column_names() {
+        let columns: Vec<TargetColumn> = ["id", "name", "email"]
+            .iter()
+            .map(|name| TargetColumn { name: name.to_string(), required: false })
+            .collect();
+
+        assert_eq!(headerless_column_names(&columns, 2, "users").unwrap(), vec!["id", "name"]);
+        assert_eq!(headerless_column_names(&[], 2, "new").unwrap(), vec!["column_1", "column_2"]);
+        assert!(headerless_column_names(&columns, 4, "users").is_err());
+    }
+
+    #[test]
+    fn test_upsert_clause_uses_primary_key() {
+        let columns = vec!["tenant".to_string(), "id".to_string(), "name".to_string()];
+        let key = vec!["tenant".to_string(), "id".to_string()];
+
+        assert_eq!(
+            upsert_clause("t", &columns, &key, &DatabaseType::PostgreSQL).unwrap(),
+            " ON CONFLICT (\"tenant\", \"id\") DO UPDATE SET \"name\" = EXCLUDED.\"name\""
+        );
+        assert_eq!(
+            upsert_clause("t", &columns, &key, &DatabaseType::MySQL).unwrap(),
+            " ON DUPLICATE KEY UPDATE `name` = VALUES(`name`)"
+        );
+        assert!(upsert_clause("t", &columns, &[], &DatabaseType::PostgreSQL).is_err());
+        assert!(upsert_clause("t", &columns[1..], &key, &DatabaseType::MySQL).is_err());
+
+        let batch = vec![
+            vec!["1".to_string(), "a".to_string()],
+            vec!["2".to_string(), "b".to_string()],
+            vec!["1".to_string(), "c".to_string()],
+        ];
+        assert_eq!(
+            last_row_per_key(&batch, &[0]),
+            vec![vec!["1".to_string(), "c".to_string()], vec!["2".to_string(), "b".to_string()]]
+        );
+    }
+
+    #[test]
+    fn test_infer_column_types_detects_booleans_and_timestamps() {
+        assert_eq!(infer_column_types(1, &rows(&["TRUE", "false"])), vec![InferredType::Boolean]);
+        assert_eq!(
+            infer_column_types(1, &rows(&["2024-01-05 10:00:00", "2024-02-01T08:30:15.250", "2024-03-01"])),
+            vec![InferredType::Timestamp]
+        );
+    }
+}
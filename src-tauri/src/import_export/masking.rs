@@ -0,0 +1,81 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// How a column's values are redacted during export
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MaskStrategy {
+    /// Export every value as NULL
+    Null,
+    /// Replace values with a deterministic SHA-256 digest, so equal values
+    /// (e.g. a key and the foreign keys referencing it) still match after masking
+    Hash,
+    /// Replace every value with the given text
+    FixedString(String),
+    /// Keep the first character and the domain: `jane@example.com` -> `j***@example.com`
+    PartialEmail,
+}
+
+impl MaskStrategy {
+    /// Masked form of a non-NULL value; None means export NULL.
+    /// Integer columns hash to a non-negative BIGINT so masked keys keep their type.
+    pub fn mask(&self, value: &str, integer: bool) -> Option<String> {
+        match self {
+            MaskStrategy::Null => None,
+            MaskStrategy::Hash => {
+                let digest = Sha256::digest(value.as_bytes());
+                if integer {
+                    let mut bytes = [0u8; 8];
+                    bytes.copy_from_slice(&digest[..8]);
+                    Some((u64::from_be_bytes(bytes) >> 1).to_string())
+                } else {
+                    Some(hex::encode(&digest[..16]))
+                }
+            }
+            MaskStrategy::FixedString(text) => Some(text.clone()),
+            MaskStrategy::PartialEmail => {
+                let (local, domain) = match value.rsplit_once('@') {
+                    Some((local, domain)) => (local, Some(domain)),
+                    None => (value, None),
+                };
+                let mut masked: String = local.chars().take(1).collect();
+                masked.push_str("***");
+                if let Some(domain) = domain {
+                    masked.push('@');
+                    masked.push_str(domain);
+                }
+                Some(masked)
+            }
+        }
+    }
+
+    /// Whether masked values of an integer column are still integers
+    pub fn keeps_integer_type(&self) -> bool {
+        matches!(self, MaskStrategy::Null | MaskStrategy::Hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash_is_deterministic_and_type_preserving() {
+        let first = MaskStrategy::Hash.mask("42", true).unwrap();
+        assert_eq!(MaskStrategy::Hash.mask("42", true).unwrap(), first);
+        assert!(first.parse::<i64>().is_ok_and(|n| n >= 0));
+        assert_ne!(MaskStrategy::Hash.mask("43", true).unwrap(), first);
+
+        let text = MaskStrategy::Hash.mask("jane@example.com", false).unwrap();
+        assert_eq!(text.len(), 32);
+        assert_eq!(MaskStrategy::Hash.mask("jane@example.com", false).unwrap(), text);
+    }
+
+    #[test]
+    fn partial_email_keeps_domain() {
+        let mask = MaskStrategy::PartialEmail;
+        assert_eq!(mask.mask("jane.doe@example.com", false).unwrap(), "j***@example.com");
+        assert_eq!(mask.mask("not-an-email", false).unwrap(), "n***");
+        assert_eq!(MaskStrategy::Null.mask("x", false), None);
+    }
+}
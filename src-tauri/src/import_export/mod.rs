@@ -1,2 +1,5 @@
+pub mod csv_dialect;
 pub mod export;
+pub mod export_manifest;
 pub mod import;
+pub mod masking;
@@ -6,7 +6,7 @@ mod import_export;
 
 use error::AppResult;
 use storage::{StorageManager, StrongholdStorage, AppSettings};
-use db::connection::{Connection, ConnectionManager};
+use db::connection::{Connection, ConnectionFilter, ConnectionManager};
 use std::sync::{Arc, Mutex};
 use tauri::{Emitter, Manager, State};
 use chrono::Utc;
@@ -18,6 +18,19 @@ pub struct AppState {
     connections: Arc<ConnectionManager>,
 }
 
+impl AppState {
+    /// Query timeout from the saved settings, or the default when none are saved
+    fn query_timeout_ms(&self) -> AppResult<u64> {
+        let storage = self.storage.lock().map_err(|e| {
+            error::AppError::StorageError(format!("Failed to lock storage: {}", e))
+        })?;
+        Ok(storage
+            .get_settings()?
+            .map(|s| s.query_timeout_ms)
+            .unwrap_or_else(storage::default_query_timeout_ms))
+    }
+}
+
 // Settings Commands
 #[tauri::command]
 async fn save_settings(
@@ -38,6 +51,32 @@ async fn get_settings(state: State<'_, AppState>) -> AppResult<Option<AppSetting
     storage.get_settings()
 }
 
+/// Probe OpenRouter with the key and model entered on the settings screen
+#[tauri::command]
+async fn validate_settings(
+    api_key: String,
+    model: String,
+) -> AppResult<ai::openrouter::types::SettingsValidation> {
+    let client = ai::openrouter::OpenRouterClient::new(api_key);
+    Ok(client.validate(&model).await)
+}
+
+#[tauri::command]
+async fn list_openrouter_models(
+    state: State<'_, AppState>,
+) -> AppResult<Vec<ai::openrouter::types::OpenRouterModel>> {
+    let api_key = {
+        let storage = state.storage.lock().map_err(|e| {
+            error::AppError::StorageError(format!("Failed to lock storage: {}", e))
+        })?;
+        storage
+            .get_settings()?
+            .map(|s| s.openrouter_api_key)
+            .unwrap_or_default()
+    };
+    ai::openrouter::OpenRouterClient::new(api_key).list_models().await
+}
+
 // Connection Commands
 #[tauri::command]
 async fn test_connection(
@@ -52,6 +91,42 @@ async fn test_connection(
     }))
 }
 
+#[tauri::command]
+async fn list_databases(
+    database_type: db::connection::DatabaseType,
+    host: String,
+    port: u16,
+    username: String,
+    password: String,
+    ssl_mode: Option<db::connection::SslMode>,
+) -> AppResult<Vec<String>> {
+    db::connection::list_databases(
+        &database_type,
+        &host,
+        port,
+        &username,
+        &password,
+        ssl_mode.unwrap_or_default(),
+    )
+    .await
+}
+
+#[tauri::command]
+async fn test_all_connections(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> AppResult<Vec<db::connection::ConnectionTestResult>> {
+    state.connections.test_all_connections(&app).await
+}
+
+#[tauri::command]
+async fn connection_diagnostics(
+    state: State<'_, AppState>,
+    connection_id: String,
+) -> AppResult<db::diagnostics::ConnectionDiagnostics> {
+    db::diagnostics::connection_diagnostics(&state.connections, &connection_id).await
+}
+
 #[tauri::command]
 async fn save_connection(
     state: State<'_, AppState>,
@@ -60,7 +135,13 @@ async fn save_connection(
     // Generate ID and timestamps if new
     if connection.id.is_empty() {
         connection.id = uuid::Uuid::new_v4().to_string();
+        // Production databases start out read-only; writes can be enabled afterwards
+        if connection.is_production() {
+            connection.read_only = true;
+        }
     }
+    connection.normalize_labels();
+    connection.extract_uri_credentials()?;
 
     let now = Utc::now().to_rfc3339();
     if connection.created_at.is_empty() {
@@ -81,14 +162,22 @@ async fn save_connection(
 }
 
 #[tauri::command]
-async fn get_connections(state: State<'_, AppState>) -> AppResult<Vec<Connection>> {
-    state.connections.get_connections()
+async fn get_connections(
+    state: State<'_, AppState>,
+    filter: Option<ConnectionFilter>,
+) -> AppResult<Vec<Connection>> {
+    let connections = state.connections.get_connections()?;
+    Ok(match filter {
+        Some(filter) => connections.into_iter().filter(|c| filter.matches(c)).collect(),
+        None => connections,
+    })
 }
 
 #[tauri::command]
 async fn delete_connection(state: State<'_, AppState>, id: String) -> AppResult<()> {
     // Delete from in-memory storage
     state.connections.delete_connection(&id)?;
+    db::commit::clear_undo_log(&id);
 
     // Delete persisted connection data from Stronghold
     let stronghold = state.stronghold.lock().map_err(|e| {
@@ -105,9 +194,14 @@ async fn update_connection(
     mut connection: Connection,
 ) -> AppResult<Connection> {
     connection.updated_at = Utc::now().to_rfc3339();
+    connection.normalize_labels();
+    connection.extract_uri_credentials()?;
 
     // Save to in-memory storage
     state.connections.save_connection(connection.clone())?;
+    db::query::invalidate_query_cache(&connection.id);
+    // Recorded commits may not apply to the database the connection now points at
+    db::commit::clear_undo_log(&connection.id);
 
     // Persist full connection data to Stronghold
     let stronghold = state.stronghold.lock().map_err(|e| {
@@ -128,6 +222,26 @@ async fn get_schema(
     db::schema::get_schema(&state.connections, &connection_id, &app).await
 }
 
+#[tauri::command]
+async fn refresh_schema(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    connection_id: String,
+) -> AppResult<db::schema::Schema> {
+    db::schema::refresh_schema(&state.connections, &connection_id, &app).await
+}
+
+/// Graphviz DOT or Mermaid text describing the tables and their foreign keys
+#[tauri::command]
+async fn generate_er_diagram(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    connection_id: String,
+    format: db::schema::ErDiagramFormat,
+) -> AppResult<String> {
+    db::schema::generate_er_diagram(&state.connections, &connection_id, &app, format).await
+}
+
 #[tauri::command]
 async fn get_sql_keywords(
     state: State<'_, AppState>,
@@ -144,18 +258,51 @@ async fn highlight_sql(
     Ok(db::syntax_highlight::highlight_sql(&sql, &config))
 }
 
+#[tauri::command]
+async fn tokenize_sql_json(
+    sql: String,
+    config: db::syntax_highlight::HighlightConfig,
+) -> AppResult<Vec<db::syntax_highlight::Token>> {
+    Ok(db::syntax_highlight::tokenize_sql(&sql, &config))
+}
+
 #[tauri::command]
 async fn run_query(
     state: State<'_, AppState>,
     connection_id: String,
     query: String,
-    limit: i32,
+    limit: Option<i32>,
     offset: i32,
+    params: Option<Vec<serde_json::Value>>,
+    query_token: Option<String>,
+    compute_summary: Option<bool>,
 ) -> AppResult<db::query::QueryResult> {
+    let timeout_ms = state.query_timeout_ms()?;
+
     let start = std::time::Instant::now();
-    let result = db::query::execute_query(&state.connections, &connection_id, &query, limit, offset).await;
+    let params = params.unwrap_or_default();
+    let result = db::query::execute_query_with_token(
+        &state.connections,
+        &connection_id,
+        &query,
+        &params,
+        limit,
+        offset,
+        timeout_ms,
+        query_token.as_deref(),
+    ).await
+    .map(|result| if compute_summary.unwrap_or(false) { result.with_summary() } else { result });
     let execution_time_ms = start.elapsed().as_secs_f64() * 1000.0;
 
+    // DDL and DML from the editor can change tables or row counts
+    let is_read = state.connections.get_connection(&connection_id).is_ok_and(|conn| {
+        crate::ai::sanitizer::validate_sql(&query, &conn.database_type).is_ok()
+    });
+    if !is_read {
+        state.connections.invalidate_schema(&connection_id);
+        db::query::invalidate_query_cache(&connection_id);
+    }
+
     // Save to history
     let success = result.is_ok();
     let _ = storage::query_history::add_query_to_history(
@@ -168,8 +315,182 @@ async fn run_query(
     result
 }
 
+#[tauri::command]
+async fn get_connection_status(
+    state: State<'_, AppState>,
+) -> AppResult<Vec<db::connection::ConnectionStatus>> {
+    state.connections.connection_statuses()
+}
+
+/// Periodically ping open pools so stale connections are replaced before the
+/// user's next query. The interval is re-read from settings on every tick.
+fn spawn_pool_keepalive(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let interval_secs = {
+                let state = app.state::<AppState>();
+                let storage = state.storage.lock();
+                storage
+                    .ok()
+                    .and_then(|storage| storage.get_settings().ok().flatten())
+                    .map(|s| s.keepalive_interval_secs)
+                    .unwrap_or_else(storage::default_keepalive_interval_secs)
+            };
+
+            // 0 disables the keepalive; check again later in case it is re-enabled
+            if interval_secs == 0 {
+                tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+                continue;
+            }
+
+            tokio::time::sleep(std::time::Duration::from_secs(interval_secs)).await;
+            let connections = Arc::clone(&app.state::<AppState>().connections);
+            connections.check_pool_health().await;
+        }
+    });
+}
+
+#[tauri::command]
+async fn diff_query_results(
+    before: db::query::QueryResult,
+    after: db::query::QueryResult,
+    key_columns: Vec<String>,
+) -> AppResult<db::query::QueryDiff> {
+    db::query::diff_query_results(&before, &after, &key_columns)
+}
+
+/// Render a loaded result as TSV, CSV, Markdown or JSON for the clipboard
+#[tauri::command]
+fn serialize_query_result(
+    result: db::query::QueryResult,
+    format: db::query::ResultTextFormat,
+) -> AppResult<String> {
+    db::query::serialize_query_result(&result, format)
+}
+
+/// Pivot a loaded long-format result into one column per distinct pivot value
+#[tauri::command]
+fn pivot_query_result(
+    result: db::query::QueryResult,
+    index_column: String,
+    pivot_column: String,
+    value_column: String,
+    aggregate: Option<db::query::PivotAggregate>,
+) -> AppResult<db::query::QueryResult> {
+    db::query::pivot_query_result(
+        &result,
+        &index_column,
+        &pivot_column,
+        &value_column,
+        aggregate.unwrap_or_default(),
+    )
+}
+
+/// Like `run_query`, but reports progress through "query-started", "query-rows",
+/// "query-finished" and "query-error" events tagged with `query_id`
+#[tauri::command]
+async fn run_query_with_events(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    connection_id: String,
+    query_id: String,
+    query: String,
+    limit: Option<i32>,
+    offset: i32,
+    params: Option<Vec<serde_json::Value>>,
+) -> AppResult<db::query::QueryResult> {
+    let timeout_ms = state.query_timeout_ms()?;
+
+    let start = std::time::Instant::now();
+    let params = params.unwrap_or_default();
+    let result = db::query::execute_query_with_events(
+        &app,
+        &state.connections,
+        &connection_id,
+        &query_id,
+        &query,
+        &params,
+        limit,
+        offset,
+        timeout_ms,
+    ).await;
+    let execution_time_ms = start.elapsed().as_secs_f64() * 1000.0;
+
+    let is_read = state.connections.get_connection(&connection_id).is_ok_and(|conn| {
+        crate::ai::sanitizer::validate_sql(&query, &conn.database_type).is_ok()
+    });
+    if !is_read {
+        state.connections.invalidate_schema(&connection_id);
+        db::query::invalidate_query_cache(&connection_id);
+    }
+
+    let success = result.is_ok();
+    let _ = storage::query_history::add_query_to_history(
+        query,
+        connection_id,
+        execution_time_ms,
+        success,
+    ).await;
+
+    result
+}
+
+#[tauri::command]
+async fn run_query_batch(
+    state: State<'_, AppState>,
+    connection_id: String,
+    script: String,
+) -> AppResult<Vec<db::query::QueryResult>> {
+    let timeout_ms = state.query_timeout_ms()?;
+
+    let start = std::time::Instant::now();
+    let result = db::query::execute_query_batch(
+        &state.connections,
+        &connection_id,
+        &script,
+        timeout_ms,
+    ).await;
+    let execution_time_ms = start.elapsed().as_secs_f64() * 1000.0;
+
+    // Scripts usually contain DDL/DML
+    state.connections.invalidate_schema(&connection_id);
+    db::query::invalidate_query_cache(&connection_id);
+
+    let success = result.is_ok();
+    let _ = storage::query_history::add_query_to_history(
+        script,
+        connection_id,
+        execution_time_ms,
+        success,
+    ).await;
+
+    result
+}
+
+#[tauri::command]
+async fn profile_column(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    connection_id: String,
+    table_name: String,
+    column_name: String,
+) -> AppResult<db::query::ColumnProfile> {
+    let timeout_ms = state.query_timeout_ms()?;
+
+    let schema = db::schema::get_schema(&state.connections, &connection_id, &app).await?;
+    db::query::profile_column(
+        &state.connections,
+        &connection_id,
+        &schema,
+        &table_name,
+        &column_name,
+        timeout_ms,
+    ).await
+}
+
 #[tauri::command]
 async fn run_table_query(
+    app: tauri::AppHandle,
     state: State<'_, AppState>,
     connection_id: String,
     table_name: String,
@@ -178,9 +499,11 @@ async fn run_table_query(
     limit: i32,
     offset: i32,
 ) -> AppResult<db::query::QueryResult> {
+    let schema = db::schema::get_schema(&state.connections, &connection_id, &app).await?;
     db::query::execute_table_query(
         &state.connections,
         &connection_id,
+        &schema,
         &table_name,
         filter_column,
         filter_value,
@@ -189,6 +512,116 @@ async fn run_table_query(
     ).await
 }
 
+/// Sidebar table preview; deliberately not recorded in query history
+#[tauri::command]
+async fn preview_table(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    connection_id: String,
+    table_name: String,
+    filter_column: Option<String>,
+    filter_value: Option<serde_json::Value>,
+    limit: Option<i32>,
+) -> AppResult<db::query::QueryResult> {
+    let schema = db::schema::get_schema(&state.connections, &connection_id, &app).await?;
+    db::query::preview_table(
+        &state.connections,
+        &connection_id,
+        &schema,
+        &table_name,
+        filter_column,
+        filter_value,
+        limit.unwrap_or(db::query::PREVIEW_ROW_LIMIT),
+    ).await
+}
+
+#[tauri::command]
+async fn run_table_query_keyset(
+    state: State<'_, AppState>,
+    connection_id: String,
+    table_name: String,
+    order_column: String,
+    descending: bool,
+    last_value: Option<serde_json::Value>,
+    limit: i32,
+) -> AppResult<db::query::KeysetQueryResult> {
+    db::query::execute_query_keyset(
+        &state.connections,
+        &connection_id,
+        &table_name,
+        &order_column,
+        descending,
+        last_value,
+        limit,
+    ).await
+}
+
+#[tauri::command]
+async fn explain_query(
+    state: State<'_, AppState>,
+    connection_id: String,
+    query: String,
+) -> AppResult<serde_json::Value> {
+    db::query::explain_query(&state.connections, &connection_id, &query).await
+}
+
+#[tauri::command]
+async fn run_query_streaming(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    connection_id: String,
+    stream_id: String,
+    query: String,
+    batch_size: Option<usize>,
+) -> AppResult<db::query::QueryStreamComplete> {
+    db::query::execute_query_streaming(
+        app,
+        &state.connections,
+        &connection_id,
+        &stream_id,
+        &query,
+        batch_size.unwrap_or(500),
+    ).await
+}
+
+#[tauri::command]
+async fn cancel_query_stream(stream_id: String) -> AppResult<()> {
+    db::query::cancel_query_stream(&stream_id).await
+}
+
+#[tauri::command]
+async fn cancel_query(state: State<'_, AppState>, query_token: String) -> AppResult<()> {
+    db::query::cancel_query(&state.connections, &query_token).await
+}
+
+#[tauri::command]
+async fn open_cursor(
+    state: State<'_, AppState>,
+    connection_id: String,
+    query: String,
+) -> AppResult<String> {
+    db::cursor::open_cursor(&state.connections, &connection_id, &query).await
+}
+
+#[tauri::command]
+async fn fetch_cursor(cursor_id: String, count: usize) -> AppResult<db::cursor::CursorPage> {
+    db::cursor::fetch_cursor(&cursor_id, count).await
+}
+
+#[tauri::command]
+async fn close_cursor(cursor_id: String) -> AppResult<()> {
+    db::cursor::close_cursor(&cursor_id).await
+}
+
+#[tauri::command]
+async fn analyze_query_risk(
+    state: State<'_, AppState>,
+    connection_id: String,
+    query: String,
+) -> AppResult<db::risk::QueryRiskReport> {
+    db::risk::analyze_query_risk(&state.connections, &connection_id, &query).await
+}
+
 #[tauri::command]
 async fn get_query_history(connection_id: Option<String>) -> AppResult<Vec<storage::query_history::QueryHistoryEntry>> {
     storage::query_history::get_query_history(connection_id).await
@@ -204,28 +637,103 @@ async fn delete_query_from_history(query_id: String) -> AppResult<()> {
     storage::query_history::delete_query_from_history(query_id).await
 }
 
+#[tauri::command]
+async fn save_query(
+    request: storage::saved_queries::SaveQueryRequest,
+) -> AppResult<storage::saved_queries::SavedQuery> {
+    storage::saved_queries::save_query(request).await
+}
+
+#[tauri::command]
+async fn get_saved_queries(
+    connection_id: Option<String>,
+) -> AppResult<Vec<storage::saved_queries::SavedQuery>> {
+    storage::saved_queries::get_saved_queries(connection_id).await
+}
+
+#[tauri::command]
+async fn delete_saved_query(query_id: String) -> AppResult<()> {
+    storage::saved_queries::delete_saved_query(query_id).await
+}
+
 #[tauri::command]
 async fn commit_data_changes(
     state: State<'_, AppState>,
     request: db::commit::CommitRequest,
 ) -> AppResult<db::commit::CommitResult> {
-    db::commit::commit_data_changes(&state.connections, request).await
+    let connection_id = request.connection_id.clone();
+    let dry_run = request.dry_run;
+    let result = db::commit::commit_data_changes(&state.connections, request).await;
+    if !dry_run {
+        state.connections.invalidate_schema(&connection_id);
+        db::query::invalidate_query_cache(&connection_id);
+    }
+    result
+}
+
+#[tauri::command]
+async fn revert_last_commit(
+    state: State<'_, AppState>,
+    connection_id: String,
+    dry_run: Option<bool>,
+) -> AppResult<db::commit::CommitResult> {
+    let dry_run = dry_run.unwrap_or(false);
+    let result = db::commit::revert_last_commit(&state.connections, &connection_id, dry_run).await;
+    if !dry_run {
+        state.connections.invalidate_schema(&connection_id);
+        db::query::invalidate_query_cache(&connection_id);
+    }
+    result
 }
 
 #[tauri::command]
 async fn clear_data_only(
     state: State<'_, AppState>,
     connection_id: String,
+    confirmation: Option<String>,
+) -> AppResult<db::clear::ClearPlan> {
+    let result = db::clear::clear_data_only(&state.connections, &connection_id, confirmation.as_deref()).await;
+    // A plan awaiting confirmation changed nothing
+    if !result.as_ref().is_ok_and(|plan| !plan.executed) {
+        state.connections.invalidate_schema(&connection_id);
+        db::query::invalidate_query_cache(&connection_id);
+    }
+    result
+}
+
+#[tauri::command]
+async fn clear_table(
+    state: State<'_, AppState>,
+    connection_id: String,
+    table_name: String,
+    cascade: Option<bool>,
+    restart_identity: Option<bool>,
 ) -> AppResult<()> {
-    db::clear::clear_data_only(&state.connections, &connection_id).await
+    let result = db::clear::clear_table(
+        &state.connections,
+        &connection_id,
+        &table_name,
+        cascade.unwrap_or(false),
+        restart_identity.unwrap_or(false),
+    )
+    .await;
+    state.connections.invalidate_schema(&connection_id);
+    db::query::invalidate_query_cache(&connection_id);
+    result
 }
 
 #[tauri::command]
 async fn clear_database(
     state: State<'_, AppState>,
     connection_id: String,
-) -> AppResult<()> {
-    db::clear::clear_database(&state.connections, &connection_id).await
+    confirmation: Option<String>,
+) -> AppResult<db::clear::ClearPlan> {
+    let result = db::clear::clear_database(&state.connections, &connection_id, confirmation.as_deref()).await;
+    if !result.as_ref().is_ok_and(|plan| !plan.executed) {
+        state.connections.invalidate_schema(&connection_id);
+        db::query::invalidate_query_cache(&connection_id);
+    }
+    result
 }
 
 // Import/Export Commands
@@ -249,7 +757,12 @@ async fn import_tables(
     state: State<'_, AppState>,
     options: import_export::import::ImportOptions,
 ) -> AppResult<()> {
-    import_export::import::import_tables(app, &state.connections, options).await
+    let connection_id = options.connection_id.clone();
+    let result = import_export::import::import_tables(app, &state.connections, options).await;
+    // Even a failed or cancelled import may have created tables or inserted rows
+    state.connections.invalidate_schema(&connection_id);
+    db::query::invalidate_query_cache(&connection_id);
+    result
 }
 
 #[tauri::command]
@@ -265,6 +778,7 @@ async fn stream_ai_chat(
     session_id: String,
     message: String,
     connection_id: String,
+    allow_writes: Option<bool>,
 ) -> AppResult<()> {
     // Get settings
     let storage = state.storage.lock().map_err(|e| {
@@ -285,12 +799,23 @@ async fn stream_ai_chat(
     // Run agent in background (non-blocking)
     let connections = Arc::clone(&state.connections);
     let history_limit = settings.conversation_history_limit;
+    let history_token_budget = settings.conversation_token_budget;
+    let cancel_token = ai::register_chat_session(&session_id).await;
     tokio::spawn(async move {
+        if settings.summarize_conversation_history {
+            if let Err(e) =
+                ai::memory::update_conversation_summary(&app, &session_id, history_limit, &settings).await
+            {
+                eprintln!("Failed to summarize conversation history: {}", e);
+            }
+        }
+
         // Load conversation history with limit
         let previous_messages = ai::load_conversation_with_limit(
             &app,
             &session_id,
             history_limit,
+            history_token_budget,
         )
         .unwrap_or_else(|e| {
             eprintln!("Failed to load conversation history: {}", e);
@@ -306,9 +831,14 @@ async fn stream_ai_chat(
             &app,
             &connections,
             &settings,
+            &cancel_token,
+            allow_writes.unwrap_or(false),
         ).await;
 
-        // Save conversation after agent completes
+        ai::unregister_chat_session(&session_id).await;
+
+        // Save conversation after agent completes (including cancelled runs,
+        // so the partial exchange stays in context)
         if let Ok(response) = &result {
             // Load all existing messages (not limited)
             let mut all_messages = ai::load_conversation(&app, &session_id)
@@ -317,8 +847,11 @@ async fn stream_ai_chat(
             // Append user message
             all_messages.push(ai::agent::Message::user(&message));
 
-            // Append assistant response
-            all_messages.push(ai::agent::Message::assistant(&response.answer));
+            // Append assistant response with the SQL and data behind it
+            all_messages.push(
+                ai::agent::Message::assistant(&response.answer)
+                    .with_query_output(response.sql_queries.clone(), response.results.clone()),
+            );
 
             // Save complete conversation
             let _ = ai::save_conversation(&app, &session_id, &connection_id, &all_messages);
@@ -337,6 +870,24 @@ async fn stream_ai_chat(
     Ok(())
 }
 
+#[tauri::command]
+async fn cancel_ai_chat(session_id: String) -> AppResult<()> {
+    ai::cancel_chat_session(&session_id).await
+}
+
+/// Approve (run) or reject the data change the agent proposed in write mode
+#[tauri::command]
+async fn confirm_ai_write(
+    state: State<'_, AppState>,
+    session_id: String,
+    proposal_id: String,
+    approved: bool,
+) -> AppResult<Option<db::query::QueryResult>> {
+    let timeout_ms = state.query_timeout_ms()?;
+
+    ai::confirm_write(&state.connections, &session_id, &proposal_id, approved, timeout_ms).await
+}
+
 #[tauri::command]
 async fn get_conversation_history(
     app: tauri::AppHandle,
@@ -361,6 +912,28 @@ async fn list_conversations(
     ai::list_conversations(&app, &connection_id)
 }
 
+#[tauri::command]
+async fn export_conversation(
+    app: tauri::AppHandle,
+    session_id: String,
+    format: ai::ConversationExportFormat,
+    output_path: String,
+    include_internal: Option<bool>,
+) -> AppResult<String> {
+    ai::export_conversation(
+        &app,
+        &session_id,
+        format,
+        std::path::Path::new(&output_path),
+        include_internal.unwrap_or(false),
+    )
+}
+
+#[tauri::command]
+async fn get_session_usage(session_id: String) -> AppResult<ai::openrouter::types::TokenUsage> {
+    Ok(ai::get_session_usage(&session_id))
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
@@ -409,6 +982,7 @@ pub fn run() {
                 .path()
                 .app_data_dir()
                 .expect("Failed to get app data dir");
+            storage::saved_queries::init_saved_queries_path(app_data_dir.clone());
             storage::query_history::init_history_path(app_data_dir);
 
             // Initialize storage
@@ -442,35 +1016,71 @@ pub fn run() {
                 connections: connection_manager,
             });
 
+            spawn_pool_keepalive(app_handle.clone());
+
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             save_settings,
             get_settings,
+            validate_settings,
+            list_openrouter_models,
             test_connection,
+            test_all_connections,
+            connection_diagnostics,
+            list_databases,
             save_connection,
             get_connections,
             delete_connection,
             update_connection,
+            get_connection_status,
             get_schema,
+            refresh_schema,
+            generate_er_diagram,
             get_sql_keywords,
             highlight_sql,
+            tokenize_sql_json,
             run_query,
+            run_query_with_events,
+            run_query_batch,
+            diff_query_results,
+            serialize_query_result,
+            pivot_query_result,
+            profile_column,
             run_table_query,
+            preview_table,
+            run_table_query_keyset,
+            explain_query,
+            run_query_streaming,
+            cancel_query_stream,
+            cancel_query,
+            open_cursor,
+            fetch_cursor,
+            close_cursor,
+            analyze_query_risk,
             get_query_history,
             clear_query_history,
             delete_query_from_history,
+            save_query,
+            get_saved_queries,
+            delete_saved_query,
             commit_data_changes,
+            revert_last_commit,
             clear_data_only,
+            clear_table,
             clear_database,
             export_tables,
             cancel_export,
             import_tables,
             cancel_import,
             stream_ai_chat,
+            cancel_ai_chat,
+            confirm_ai_write,
             get_conversation_history,
             clear_conversation,
             list_conversations,
+            export_conversation,
+            get_session_usage,
             storage::stronghold::stronghold_save_connection,
             storage::stronghold::stronghold_delete_connection,
             storage::stronghold::stronghold_get_connection_ids,
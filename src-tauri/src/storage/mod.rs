@@ -1,6 +1,8 @@
 pub mod stronghold;
 pub mod query_history;
+pub mod saved_queries;
 
+use crate::ai::openrouter::types::{ModelParams, ProviderPreferences};
 use crate::error::{AppError, AppResult};
 use serde::{Deserialize, Serialize};
 use std::fs;
@@ -22,12 +24,136 @@ pub struct AppSettings {
     pub visualization_model: String,
     #[serde(default = "default_conversation_history_limit")]
     pub conversation_history_limit: usize,
+    /// Summarize messages beyond `conversation_history_limit` instead of dropping them
+    #[serde(default)]
+    pub summarize_conversation_history: bool,
+    /// Estimated tokens the conversation history may take up; the oldest messages
+    /// are dropped beyond it (0 disables)
+    #[serde(default)]
+    pub conversation_token_budget: usize,
+    /// Maximum time a single query may run before it is aborted (0 disables the limit)
+    #[serde(default = "default_query_timeout_ms")]
+    pub query_timeout_ms: u64,
+    /// Retries for transient OpenRouter failures (rate limits, 5xx)
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    /// OpenRouter requests allowed in flight at once, shared by every chat session
+    #[serde(default = "default_max_concurrent_ai_requests")]
+    pub max_concurrent_ai_requests: usize,
+    /// Include stored functions/procedures in the AI schema context
+    #[serde(default)]
+    pub include_routines: bool,
+    /// How long classification and schema selection responses are reused (0 disables)
+    #[serde(default = "default_llm_cache_ttl_secs")]
+    pub llm_cache_ttl_secs: u64,
+    /// How long results of identical AI read queries are reused (0 always queries)
+    #[serde(default = "default_query_result_cache_ttl_secs")]
+    pub query_result_cache_ttl_secs: u64,
+    /// Seconds between keepalive pings of open connection pools (0 disables)
+    #[serde(default = "default_keepalive_interval_secs")]
+    pub keepalive_interval_secs: u64,
+    /// Maximum rows an AI-generated query may return; enforced by the sanitizer
+    #[serde(default = "default_ai_max_rows")]
+    pub ai_max_rows: u64,
+    /// Models tried in order when the configured model is unavailable on OpenRouter
+    #[serde(default)]
+    pub fallback_models: Vec<String>,
+    /// Output token cap for classification, schema selection and SQL generation
+    #[serde(default = "default_max_tokens")]
+    pub max_tokens: u32,
+    /// Output token cap for the final answer and replies to general questions
+    #[serde(default = "default_max_tokens")]
+    pub final_answer_max_tokens: u32,
+    #[serde(default = "default_classification_temperature")]
+    pub classification_temperature: f32,
+    #[serde(default = "default_selector_temperature")]
+    pub selector_temperature: f32,
+    #[serde(default = "default_decomposer_temperature")]
+    pub decomposer_temperature: f32,
+    #[serde(default = "default_refiner_temperature")]
+    pub refiner_temperature: f32,
+    #[serde(default = "default_final_answer_temperature")]
+    pub final_answer_temperature: f32,
+    /// Nucleus sampling and repetition penalties; unset leaves the model's defaults
+    #[serde(default)]
+    pub top_p: Option<f32>,
+    #[serde(default)]
+    pub frequency_penalty: Option<f32>,
+    #[serde(default)]
+    pub presence_penalty: Option<f32>,
+    /// OpenRouter provider routing, e.g. to pin requests to one provider
+    #[serde(default)]
+    pub provider: Option<ProviderPreferences>,
+}
+
+impl AppSettings {
+    /// Request parameters for every OpenRouter completion
+    pub fn model_params(&self) -> ModelParams {
+        ModelParams {
+            top_p: self.top_p,
+            frequency_penalty: self.frequency_penalty,
+            presence_penalty: self.presence_penalty,
+            provider: self.provider.clone(),
+        }
+    }
 }
 
 fn default_conversation_history_limit() -> usize {
     10
 }
 
+pub fn default_query_timeout_ms() -> u64 {
+    30000
+}
+
+fn default_llm_cache_ttl_secs() -> u64 {
+    600
+}
+
+fn default_query_result_cache_ttl_secs() -> u64 {
+    60
+}
+
+pub fn default_keepalive_interval_secs() -> u64 {
+    60
+}
+
+fn default_ai_max_rows() -> u64 {
+    crate::ai::sanitizer::DEFAULT_MAX_ROWS
+}
+
+fn default_max_retries() -> u32 {
+    crate::ai::openrouter::client::DEFAULT_MAX_RETRIES
+}
+
+fn default_max_concurrent_ai_requests() -> usize {
+    crate::ai::openrouter::client::DEFAULT_MAX_CONCURRENT_REQUESTS
+}
+
+fn default_max_tokens() -> u32 {
+    crate::ai::openrouter::client::DEFAULT_MAX_TOKENS
+}
+
+fn default_classification_temperature() -> f32 {
+    0.0
+}
+
+fn default_selector_temperature() -> f32 {
+    0.1
+}
+
+fn default_decomposer_temperature() -> f32 {
+    0.2
+}
+
+fn default_refiner_temperature() -> f32 {
+    0.1
+}
+
+fn default_final_answer_temperature() -> f32 {
+    0.3
+}
+
 impl StorageManager {
     pub fn new(app_handle: &tauri::AppHandle) -> AppResult<Self> {
         let app_data_dir = app_handle
@@ -1,13 +1,17 @@
 use crate::error::{AppError, AppResult};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use crate::storage::stronghold::{stronghold_load_history, stronghold_save_history};
 use std::fs;
-use std::path::PathBuf;
-use std::sync::{Mutex, OnceLock};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, OnceLock};
 
 const MAX_HISTORY_SIZE: usize = 200;
 
-static HISTORY_PATH: OnceLock<Mutex<PathBuf>> = OnceLock::new();
+static HISTORY_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Serializes read-modify-write cycles on the history vault
+static HISTORY_LOCK: Mutex<()> = Mutex::new(());
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QueryHistoryEntry {
@@ -19,65 +23,68 @@ pub struct QueryHistoryEntry {
     pub success: bool,
 }
 
+/// Shape of the legacy plaintext history file
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct QueryHistory {
     entries: Vec<QueryHistoryEntry>,
 }
 
-impl Default for QueryHistory {
-    fn default() -> Self {
-        Self {
-            entries: Vec::new(),
-        }
-    }
-}
-
 pub fn init_history_path(app_data_dir: PathBuf) {
-    let path = app_data_dir.join("query_history.json");
-    HISTORY_PATH.set(Mutex::new(path)).ok();
-}
-
-fn get_history_path() -> AppResult<PathBuf> {
-    HISTORY_PATH
-        .get()
-        .ok_or_else(|| AppError::StorageError("History path not initialized".to_string()))?
-        .lock()
-        .map(|p| p.clone())
-        .map_err(|e| AppError::StorageError(format!("Failed to lock history path: {}", e)))
+    if let Err(e) = migrate_plaintext_history(&app_data_dir) {
+        eprintln!("Failed to migrate plaintext query history: {}", e);
+    }
+    HISTORY_DIR.set(app_data_dir).ok();
 }
 
-fn load_history() -> AppResult<QueryHistory> {
-    let path = get_history_path()?;
+/// Import history from the old plaintext `query_history.json` into the vault, then delete the file
+fn migrate_plaintext_history(app_data_dir: &Path) -> AppResult<()> {
+    let legacy_path = app_data_dir.join("query_history.json");
 
-    if !path.exists() {
-        return Ok(QueryHistory::default());
+    if !legacy_path.exists() {
+        return Ok(());
     }
 
-    let json = fs::read_to_string(&path)
+    let json = fs::read_to_string(&legacy_path)
         .map_err(|e| AppError::StorageError(format!("Failed to read query history: {}", e)))?;
 
-    // Try to parse the history, if it fails (corrupted file), reset to empty
-    match serde_json::from_str::<QueryHistory>(&json) {
-        Ok(history) => Ok(history),
-        Err(e) => {
-            eprintln!("Query history file corrupted, resetting: {}", e);
-            // Delete the corrupted file and return empty history
-            let _ = fs::remove_file(&path);
-            Ok(QueryHistory::default())
-        }
+    // A corrupted legacy file has nothing worth importing
+    if let Ok(legacy) = serde_json::from_str::<QueryHistory>(&json) {
+        let mut entries = stronghold_load_history(app_data_dir)?;
+        entries.extend(legacy.entries);
+        entries.sort_by(|a, b| b.executed_at.cmp(&a.executed_at));
+        entries.truncate(MAX_HISTORY_SIZE);
+        stronghold_save_history(app_data_dir, &entries)?;
+    } else {
+        eprintln!("Query history file corrupted, discarding during migration");
     }
-}
 
-fn save_history(history: &QueryHistory) -> AppResult<()> {
-    let path = get_history_path()?;
-    let json = serde_json::to_string_pretty(history)
-        .map_err(|e| AppError::StorageError(format!("Failed to serialize query history: {}", e)))?;
-    fs::write(&path, json)
-        .map_err(|e| AppError::StorageError(format!("Failed to write query history: {}", e)))?;
+    fs::remove_file(&legacy_path)
+        .map_err(|e| AppError::StorageError(format!("Failed to delete plaintext query history: {}", e)))?;
 
     Ok(())
 }
 
+fn get_history_dir() -> AppResult<&'static Path> {
+    HISTORY_DIR
+        .get()
+        .map(|p| p.as_path())
+        .ok_or_else(|| AppError::StorageError("History path not initialized".to_string()))
+}
+
+fn lock_history() -> AppResult<MutexGuard<'static, ()>> {
+    HISTORY_LOCK
+        .lock()
+        .map_err(|e| AppError::StorageError(format!("Failed to lock query history: {}", e)))
+}
+
+fn load_history() -> AppResult<Vec<QueryHistoryEntry>> {
+    stronghold_load_history(get_history_dir()?)
+}
+
+fn save_history(entries: &[QueryHistoryEntry]) -> AppResult<()> {
+    stronghold_save_history(get_history_dir()?, entries)
+}
+
 /// Add a query to history
 pub async fn add_query_to_history(
     query: String,
@@ -85,7 +92,8 @@ pub async fn add_query_to_history(
     execution_time_ms: f64,
     success: bool,
 ) -> AppResult<()> {
-    let mut history = load_history()?;
+    let _guard = lock_history()?;
+    let mut entries = load_history()?;
 
     // Create new entry
     let entry = QueryHistoryEntry {
@@ -98,53 +106,51 @@ pub async fn add_query_to_history(
     };
 
     // Add to front of list
-    history.entries.insert(0, entry);
+    entries.insert(0, entry);
 
     // Keep only last 200 entries
-    if history.entries.len() > MAX_HISTORY_SIZE {
-        history.entries.truncate(MAX_HISTORY_SIZE);
+    if entries.len() > MAX_HISTORY_SIZE {
+        entries.truncate(MAX_HISTORY_SIZE);
     }
 
-    save_history(&history)?;
+    save_history(&entries)?;
 
     Ok(())
 }
 
 /// Get query history for a specific connection
 pub async fn get_query_history(connection_id: Option<String>) -> AppResult<Vec<QueryHistoryEntry>> {
-    let history = load_history()?;
+    let entries = {
+        let _guard = lock_history()?;
+        load_history()?
+    };
 
     if let Some(conn_id) = connection_id {
         // Filter by connection ID
-        Ok(history.entries.into_iter()
+        Ok(entries.into_iter()
             .filter(|entry| entry.connection_id == conn_id)
             .collect())
     } else {
         // Return all entries
-        Ok(history.entries)
+        Ok(entries)
     }
 }
 
 /// Delete a specific query from history by ID
 pub async fn delete_query_from_history(query_id: String) -> AppResult<()> {
-    let mut history = load_history()?;
+    let _guard = lock_history()?;
+    let mut entries = load_history()?;
 
     // Remove the entry with the matching ID
-    history.entries.retain(|entry| entry.id != query_id);
+    entries.retain(|entry| entry.id != query_id);
 
-    save_history(&history)?;
+    save_history(&entries)?;
 
     Ok(())
 }
 
 /// Clear query history
 pub async fn clear_query_history() -> AppResult<()> {
-    let path = get_history_path()?;
-
-    if path.exists() {
-        fs::remove_file(&path)
-            .map_err(|e| AppError::StorageError(format!("Failed to delete query history: {}", e)))?;
-    }
-
-    Ok(())
+    let _guard = lock_history()?;
+    save_history(&[])
 }
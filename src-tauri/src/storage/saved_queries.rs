@@ -0,0 +1,214 @@
+use crate::error::{AppError, AppResult};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+const SAVED_QUERIES_FILE: &str = "saved_queries.json";
+
+static SAVED_QUERIES_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Serializes read-modify-write cycles on the saved queries file
+static SAVED_QUERIES_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedQuery {
+    pub id: String,
+    pub name: String,
+    pub sql: String,
+    /// Connection the query is pinned to; None makes it available for every connection
+    pub connection_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveQueryRequest {
+    /// Existing query to update; a new query is created when absent
+    pub id: Option<String>,
+    pub name: String,
+    pub sql: String,
+    pub connection_id: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SavedQueries {
+    queries: Vec<SavedQuery>,
+}
+
+pub fn init_saved_queries_path(app_data_dir: PathBuf) {
+    SAVED_QUERIES_DIR.set(app_data_dir).ok();
+}
+
+fn get_saved_queries_path() -> AppResult<PathBuf> {
+    SAVED_QUERIES_DIR
+        .get()
+        .map(|dir| dir.join(SAVED_QUERIES_FILE))
+        .ok_or_else(|| AppError::StorageError("Saved queries path not initialized".to_string()))
+}
+
+fn lock_saved_queries() -> AppResult<MutexGuard<'static, ()>> {
+    SAVED_QUERIES_LOCK
+        .lock()
+        .map_err(|e| AppError::StorageError(format!("Failed to lock saved queries: {}", e)))
+}
+
+fn load_saved_queries(path: &Path) -> AppResult<Vec<SavedQuery>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let json = fs::read_to_string(path)
+        .map_err(|e| AppError::StorageError(format!("Failed to read saved queries: {}", e)))?;
+    let saved: SavedQueries = serde_json::from_str(&json)
+        .map_err(|e| AppError::StorageError(format!("Failed to parse saved queries: {}", e)))?;
+
+    Ok(saved.queries)
+}
+
+/// Write via a temporary file so a crash mid-write can't corrupt the existing file
+fn write_saved_queries(path: &Path, queries: Vec<SavedQuery>) -> AppResult<()> {
+    let json = serde_json::to_string_pretty(&SavedQueries { queries })
+        .map_err(|e| AppError::StorageError(format!("Failed to serialize saved queries: {}", e)))?;
+
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, json)
+        .map_err(|e| AppError::StorageError(format!("Failed to write saved queries: {}", e)))?;
+    fs::rename(&tmp_path, path)
+        .map_err(|e| AppError::StorageError(format!("Failed to replace saved queries: {}", e)))?;
+
+    Ok(())
+}
+
+/// Reject a name already used by another query in the same scope
+/// (the same connection, or the connection-agnostic group). Case-insensitive.
+fn ensure_unique_name(queries: &[SavedQuery], request: &SaveQueryRequest) -> AppResult<()> {
+    let name = request.name.trim();
+    let duplicate = queries.iter().any(|q| {
+        q.connection_id == request.connection_id
+            && q.name.trim().eq_ignore_ascii_case(name)
+            && Some(&q.id) != request.id.as_ref()
+    });
+
+    if duplicate {
+        return Err(AppError::ValidationError(format!(
+            "A saved query named '{}' already exists{}",
+            name,
+            if request.connection_id.is_some() { " for this connection" } else { "" }
+        )));
+    }
+
+    Ok(())
+}
+
+/// Create or update a saved query
+pub async fn save_query(request: SaveQueryRequest) -> AppResult<SavedQuery> {
+    if request.name.trim().is_empty() {
+        return Err(AppError::ValidationError("Saved query name cannot be empty".to_string()));
+    }
+    if request.sql.trim().is_empty() {
+        return Err(AppError::ValidationError("Saved query SQL cannot be empty".to_string()));
+    }
+
+    let path = get_saved_queries_path()?;
+    let _guard = lock_saved_queries()?;
+    let mut queries = load_saved_queries(&path)?;
+
+    ensure_unique_name(&queries, &request)?;
+
+    let now = Utc::now();
+    let saved = match request.id.as_deref().and_then(|id| queries.iter_mut().find(|q| q.id == id)) {
+        Some(existing) => {
+            existing.name = request.name.trim().to_string();
+            existing.sql = request.sql;
+            existing.connection_id = request.connection_id;
+            existing.updated_at = now;
+            existing.clone()
+        }
+        None => {
+            let query = SavedQuery {
+                id: uuid::Uuid::new_v4().to_string(),
+                name: request.name.trim().to_string(),
+                sql: request.sql,
+                connection_id: request.connection_id,
+                created_at: now,
+                updated_at: now,
+            };
+            queries.push(query.clone());
+            query
+        }
+    };
+
+    write_saved_queries(&path, queries)?;
+
+    Ok(saved)
+}
+
+/// Saved queries usable on a connection (pinned to it or connection-agnostic),
+/// or every saved query when no connection is given. Sorted by name.
+pub async fn get_saved_queries(connection_id: Option<String>) -> AppResult<Vec<SavedQuery>> {
+    let path = get_saved_queries_path()?;
+    let mut queries = {
+        let _guard = lock_saved_queries()?;
+        load_saved_queries(&path)?
+    };
+
+    if let Some(conn_id) = connection_id {
+        queries.retain(|q| match &q.connection_id {
+            Some(id) => *id == conn_id,
+            None => true,
+        });
+    }
+    queries.sort_by_key(|q| q.name.to_lowercase());
+
+    Ok(queries)
+}
+
+/// Delete a saved query by ID
+pub async fn delete_saved_query(query_id: String) -> AppResult<()> {
+    let path = get_saved_queries_path()?;
+    let _guard = lock_saved_queries()?;
+    let mut queries = load_saved_queries(&path)?;
+
+    queries.retain(|q| q.id != query_id);
+
+    write_saved_queries(&path, queries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn saved(id: &str, name: &str, connection_id: Option<&str>) -> SavedQuery {
+        SavedQuery {
+            id: id.to_string(),
+            name: name.to_string(),
+            sql: "SELECT 1".to_string(),
+            connection_id: connection_id.map(str::to_string),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn request(id: Option<&str>, name: &str, connection_id: Option<&str>) -> SaveQueryRequest {
+        SaveQueryRequest {
+            id: id.map(str::to_string),
+            name: name.to_string(),
+            sql: "SELECT 2".to_string(),
+            connection_id: connection_id.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn names_are_unique_per_connection_scope() {
+        let queries = vec![saved("1", "Daily revenue", Some("pg")), saved("2", "Top users", None)];
+
+        assert!(ensure_unique_name(&queries, &request(None, "daily revenue", Some("pg"))).is_err());
+        assert!(ensure_unique_name(&queries, &request(None, "Daily revenue", Some("mysql"))).is_ok());
+        assert!(ensure_unique_name(&queries, &request(None, "Daily revenue", None)).is_ok());
+        assert!(ensure_unique_name(&queries, &request(None, "Top users", None)).is_err());
+        // Renaming a query to its own name is fine
+        assert!(ensure_unique_name(&queries, &request(Some("1"), "Daily revenue", Some("pg"))).is_ok());
+    }
+}
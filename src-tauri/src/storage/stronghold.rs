@@ -1,9 +1,21 @@
 use crate::db::connection::Connection;
 use crate::error::{AppError, AppResult};
+use crate::storage::query_history::QueryHistoryEntry;
+use argon2::password_hash::rand_core::{OsRng, RngCore};
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_stronghold::stronghold::Stronghold;
+
+const HISTORY_SNAPSHOT_FILE: &str = "query_history.stronghold";
+/// Key file written by older versions; migrated into the OS keychain on first use
+const HISTORY_KEY_FILE: &str = "query_history.key";
+/// OS keychain entry holding the history vault key
+const KEYCHAIN_SERVICE: &str = "DataSpeak";
+const HISTORY_KEY_ENTRY: &str = "query-history-key";
+const HISTORY_CLIENT: &[u8] = b"query_history";
+const HISTORY_RECORD: &[u8] = b"entries";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct ConnectionIndex {
@@ -161,6 +173,111 @@ impl StrongholdStorage {
     }
 }
 
+/// Load the vault key for query history from the OS keychain, generating one on first use.
+/// A key file left by older versions is moved into the keychain so existing history stays
+/// readable without the key sitting next to the data it protects.
+fn load_or_create_history_key(app_data_dir: &Path) -> AppResult<Vec<u8>> {
+    let keychain_err = |e: keyring::Error| {
+        AppError::StorageError(format!("Failed to access history key in the OS keychain: {}", e))
+    };
+    let entry = keyring::Entry::new(KEYCHAIN_SERVICE, HISTORY_KEY_ENTRY).map_err(keychain_err)?;
+
+    match entry.get_password() {
+        Ok(encoded) => return decode_history_key(&encoded),
+        Err(keyring::Error::NoEntry) => {}
+        Err(e) => return Err(keychain_err(e)),
+    }
+
+    let legacy_path = app_data_dir.join(HISTORY_KEY_FILE);
+    let key = if legacy_path.exists() {
+        let encoded = fs::read_to_string(&legacy_path)
+            .map_err(|e| AppError::StorageError(format!("Failed to read history key: {}", e)))?;
+        decode_history_key(&encoded)?
+    } else {
+        let mut key = vec![0u8; 32];
+        OsRng.fill_bytes(&mut key);
+        key
+    };
+
+    entry.set_password(&hex::encode(&key)).map_err(keychain_err)?;
+    // Only dropped once the keychain holds the key
+    if legacy_path.exists() {
+        fs::remove_file(&legacy_path)
+            .map_err(|e| AppError::StorageError(format!("Failed to remove old history key file: {}", e)))?;
+    }
+
+    Ok(key)
+}
+
+fn decode_history_key(encoded: &str) -> AppResult<Vec<u8>> {
+    let key = hex::decode(encoded.trim())
+        .map_err(|e| AppError::StorageError(format!("Failed to decode history key: {}", e)))?;
+    if key.len() != 32 {
+        return Err(AppError::StorageError("History key has an invalid length".to_string()));
+    }
+    Ok(key)
+}
+
+fn open_history_vault(app_data_dir: &Path) -> AppResult<Stronghold> {
+    let key = load_or_create_history_key(app_data_dir)?;
+    Stronghold::new(app_data_dir.join(HISTORY_SNAPSHOT_FILE), key)
+        .map_err(|e| AppError::StorageError(format!("Failed to open history vault: {}", e)))
+}
+
+/// Write the full query history list into the encrypted history vault
+pub fn stronghold_save_history(app_data_dir: &Path, entries: &[QueryHistoryEntry]) -> AppResult<()> {
+    let stronghold = open_history_vault(app_data_dir)?;
+
+    let client = match stronghold.load_client(HISTORY_CLIENT) {
+        Ok(client) => client,
+        Err(_) => stronghold
+            .create_client(HISTORY_CLIENT)
+            .map_err(|e| AppError::StorageError(format!("Failed to create history client: {}", e)))?,
+    };
+
+    let json = serde_json::to_vec(entries)
+        .map_err(|e| AppError::StorageError(format!("Failed to serialize query history: {}", e)))?;
+    client
+        .store()
+        .insert(HISTORY_RECORD.to_vec(), json, None)
+        .map_err(|e| AppError::StorageError(format!("Failed to store query history: {}", e)))?;
+
+    stronghold
+        .write_client(HISTORY_CLIENT)
+        .map_err(|e| AppError::StorageError(format!("Failed to write history client: {}", e)))?;
+    stronghold
+        .save()
+        .map_err(|e| AppError::StorageError(format!("Failed to save history vault: {}", e)))?;
+
+    Ok(())
+}
+
+/// Read the query history list from the encrypted history vault
+pub fn stronghold_load_history(app_data_dir: &Path) -> AppResult<Vec<QueryHistoryEntry>> {
+    if !app_data_dir.join(HISTORY_SNAPSHOT_FILE).exists() {
+        return Ok(Vec::new());
+    }
+
+    let stronghold = open_history_vault(app_data_dir)?;
+
+    // A snapshot without the history client has never had history written to it
+    let client = match stronghold.load_client(HISTORY_CLIENT) {
+        Ok(client) => client,
+        Err(_) => return Ok(Vec::new()),
+    };
+
+    let json = client
+        .store()
+        .get(HISTORY_RECORD)
+        .map_err(|e| AppError::StorageError(format!("Failed to read query history: {}", e)))?;
+
+    match json {
+        Some(json) => serde_json::from_slice(&json)
+            .map_err(|e| AppError::StorageError(format!("Failed to parse query history: {}", e))),
+        None => Ok(Vec::new()),
+    }
+}
+
 // Stronghold commands that will be called from JavaScript
 #[tauri::command]
 pub async fn stronghold_save_connection<R: Runtime>(
@@ -1,6 +1,12 @@
 import { invoke } from "@tauri-apps/api/core";
 import type { DataGridChanges } from "@/types/datagrid.types";
 
+/**
+ * How updates and deletes find the loaded row. Tables without a primary key need
+ * "full_row", or "ctid" on PostgreSQL (the loaded rows must then include ctid).
+ */
+export type RowIdentifier = "primary_key" | "full_row" | "ctid";
+
 export interface CommitRequest {
   connection_id: string;
   table_name: string;
@@ -19,6 +25,10 @@ export interface CommitRequest {
     }>;
   };
   original_rows: Record<string, any>[];
+  dry_run?: boolean;
+  check_conflicts?: boolean;
+  row_identifier?: RowIdentifier;
+  record_undo?: boolean; // remember the inverse so revertLastCommit can undo it
 }
 
 export interface CommitResult {
@@ -27,6 +37,9 @@ export interface CommitResult {
   edits_count: number;
   deletes_count: number;
   inserts_count: number;
+  statements?: string[]; // Generated SQL with values inlined
+  attempts?: number; // transactions run; above 1 when a deadlock was retried
+  undoable?: boolean; // recorded and can be reverted with revertLastCommit
 }
 
 export async function commitDataChanges(
@@ -34,7 +47,11 @@ export async function commitDataChanges(
   tableName: string,
   primaryKeyColumns: string[],
   changes: DataGridChanges,
-  originalRows: Record<string, any>[]
+  originalRows: Record<string, any>[],
+  dryRun = false,
+  checkConflicts = false,
+  rowIdentifier: RowIdentifier = "primary_key",
+  recordUndo = false
 ): Promise<CommitResult> {
   // Convert Map to array of edits and transform to match backend format
   const edits = Array.from(changes.edits.values()).map(edit => ({
@@ -63,7 +80,19 @@ export async function commitDataChanges(
       inserts,
     },
     original_rows: originalRows,
+    dry_run: dryRun,
+    check_conflicts: checkConflicts,
+    row_identifier: rowIdentifier,
+    record_undo: recordUndo,
   };
 
   return invoke<CommitResult>("commit_data_changes", { request });
 }
+
+/**
+ * Undo the newest commit recorded with `recordUndo` on a connection, in one transaction.
+ * Fails without changing anything when an affected row was modified since.
+ */
+export async function revertLastCommit(connectionId: string, dryRun = false): Promise<CommitResult> {
+  return invoke<CommitResult>("revert_last_commit", { connectionId, dryRun });
+}
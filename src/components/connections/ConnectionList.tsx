@@ -1,5 +1,6 @@
 import { useEffect, useState } from "react";
 import { Trash2, Link2, Upload, Download } from "lucide-react";
+import { Badge } from "@/components/ui/badge";
 import { Button } from "@/components/ui/button";
 import { ScrollArea } from "@/components/ui/scroll-area";
 import {
@@ -32,6 +33,10 @@ import { ExportDialog } from "@/components/export_import/ExportDialog";
 import { ImportDialog } from "@/components/export_import/ImportDialog";
 import type { Connection } from "@/types/database.types";
 
+// Mirrors Connection::is_production on the backend
+const isProduction = (connection: Connection) =>
+  ["prod", "production"].includes(connection.environment?.trim().toLowerCase() ?? "");
+
 export function ConnectionList() {
   const {
     connections,
@@ -124,6 +129,16 @@ export function ConnectionList() {
                         <div className="flex items-center justify-between w-full pr-2">
                           <div className="flex items-center gap-3">
                             <span className="font-semibold text-sm">{connection.name}</span>
+                            {connection.environment && (
+                              <Badge variant={isProduction(connection) ? "destructive" : "secondary"}>
+                                {connection.environment}
+                              </Badge>
+                            )}
+                            {connection.tags?.map((tag) => (
+                              <Badge key={tag} variant="outline">
+                                {tag}
+                              </Badge>
+                            ))}
                           </div>
                           <div className="flex items-center gap-2 text-xs text-muted-foreground">
                             <span>{connection.host}:{connection.port}</span>
@@ -74,7 +74,7 @@ export function ImportDialog({
         filters: [
           {
             name: "Import Files",
-            extensions: ["zip", "csv"],
+            extensions: ["zip", "csv", "json", "ndjson", "jsonl"],
           },
         ],
       });
@@ -85,9 +85,9 @@ export function ImportDialog({
         const isZipFile = selected.toLowerCase().endsWith(".zip");
         setIsZip(isZipFile);
 
-        // If CSV, extract filename
+        // If a single data file, extract filename without extension
         if (!isZipFile) {
-          const fileName = selected.split("/").pop()?.replace(".csv", "") || "";
+          const fileName = selected.split("/").pop()?.replace(/\.(csv|json|ndjson|jsonl)$/i, "") || "";
           setDetectedFiles([fileName]);
         } else {
           // For ZIP, we'll need to extract to see files
@@ -214,7 +214,7 @@ export function ImportDialog({
                   {detectedFiles.map((fileName) => (
                     <div key={fileName} className="flex items-center gap-2">
                       <span className="text-sm font-medium w-32 truncate">
-                        {fileName}.csv
+                        {fileName}
                       </span>
                       <span className="text-muted-foreground">→</span>
                       <Select
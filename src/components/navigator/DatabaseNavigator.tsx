@@ -23,6 +23,7 @@ import { ErrorHandler } from "@/lib/ErrorHandler";
 import { ScrollArea } from "@/components/ui/scroll-area";
 import { Badge } from "@/components/ui/badge";
 import { Skeleton } from "@/components/ui/skeleton";
+import { Input } from "@/components/ui/input";
 import {
   Collapsible,
   CollapsibleContent,
@@ -324,6 +325,9 @@ interface ConnectionItemProps {
 function ConnectionItem({ connection, isActive, isOpen: controlledIsOpen, onToggle, onConnect, onEdit, onDelete }: ConnectionItemProps) {
   const [clearDataDialogOpen, setClearDataDialogOpen] = useState(false);
   const [clearDatabaseDialogOpen, setClearDatabaseDialogOpen] = useState(false);
+  // Typed database name; the backend only clears when it matches
+  const [clearConfirmation, setClearConfirmation] = useState("");
+  const databaseName = connection.default_database || connection.name;
   const { schema, isLoading: schemaLoading } = useSchemaStore();
   const { addTableTab } = useQueryStore();
   const { activeConnection } = useConnectionStore();
@@ -354,6 +358,7 @@ function ConnectionItem({ connection, isActive, isOpen: controlledIsOpen, onTogg
 
       await invoke("clear_data_only", {
         connectionId: activeConnection.id,
+        confirmation: clearConfirmation,
       });
 
       await loadSchema(activeConnection.id);
@@ -362,6 +367,7 @@ function ConnectionItem({ connection, isActive, isOpen: controlledIsOpen, onTogg
       ErrorHandler.handle(error, "Failed to clear data");
     } finally {
       setClearDataDialogOpen(false);
+      setClearConfirmation("");
     }
   };
 
@@ -373,6 +379,7 @@ function ConnectionItem({ connection, isActive, isOpen: controlledIsOpen, onTogg
 
       await invoke("clear_database", {
         connectionId: activeConnection.id,
+        confirmation: clearConfirmation,
       });
 
       await loadSchema(activeConnection.id);
@@ -381,6 +388,7 @@ function ConnectionItem({ connection, isActive, isOpen: controlledIsOpen, onTogg
       ErrorHandler.handle(error, "Failed to clear database");
     } finally {
       setClearDatabaseDialogOpen(false);
+      setClearConfirmation("");
     }
   };
 
@@ -389,7 +397,7 @@ function ConnectionItem({ connection, isActive, isOpen: controlledIsOpen, onTogg
 
     try {
       const { loadSchema } = useSchemaStore.getState();
-      await loadSchema(activeConnection.id);
+      await loadSchema(activeConnection.id, true);
       ErrorHandler.success("Schema refreshed", "Database schema has been reloaded");
     } catch (error) {
       ErrorHandler.handle(error, "Failed to refresh schema");
@@ -533,20 +541,32 @@ function ConnectionItem({ connection, isActive, isOpen: controlledIsOpen, onTogg
       )}
 
       {/* Clear Data Confirmation Dialog */}
-      <AlertDialog open={clearDataDialogOpen} onOpenChange={setClearDataDialogOpen}>
+      <AlertDialog
+        open={clearDataDialogOpen}
+        onOpenChange={(open) => {
+          setClearDataDialogOpen(open);
+          setClearConfirmation("");
+        }}
+      >
         <AlertDialogContent>
           <AlertDialogHeader>
             <AlertDialogTitle>Clear All Data</AlertDialogTitle>
             <AlertDialogDescription>
-              Are you sure you want to clear all data from "{connection.default_database || connection.name}"?
+              Are you sure you want to clear all data from "{databaseName}"?
               This will delete all data from all tables but keep the table structures intact.
               This action cannot be undone.
             </AlertDialogDescription>
           </AlertDialogHeader>
+          <Input
+            value={clearConfirmation}
+            onChange={(e) => setClearConfirmation(e.target.value)}
+            placeholder={`Type "${databaseName}" to confirm`}
+          />
           <AlertDialogFooter>
             <AlertDialogCancel>Cancel</AlertDialogCancel>
             <AlertDialogAction
               onClick={handleClearData}
+              disabled={clearConfirmation !== databaseName}
               className="bg-destructive text-destructive-foreground hover:bg-destructive/90"
             >
               Clear All Data
@@ -556,20 +576,32 @@ function ConnectionItem({ connection, isActive, isOpen: controlledIsOpen, onTogg
       </AlertDialog>
 
       {/* Drop All Tables Confirmation Dialog */}
-      <AlertDialog open={clearDatabaseDialogOpen} onOpenChange={setClearDatabaseDialogOpen}>
+      <AlertDialog
+        open={clearDatabaseDialogOpen}
+        onOpenChange={(open) => {
+          setClearDatabaseDialogOpen(open);
+          setClearConfirmation("");
+        }}
+      >
         <AlertDialogContent>
           <AlertDialogHeader>
             <AlertDialogTitle>Drop All Tables</AlertDialogTitle>
             <AlertDialogDescription>
-              Are you sure you want to drop all tables from "{connection.default_database || connection.name}"?
+              Are you sure you want to drop all tables from "{databaseName}"?
               This will permanently remove all tables and their data from the database.
               This action cannot be undone and will result in a completely empty database.
             </AlertDialogDescription>
           </AlertDialogHeader>
+          <Input
+            value={clearConfirmation}
+            onChange={(e) => setClearConfirmation(e.target.value)}
+            placeholder={`Type "${databaseName}" to confirm`}
+          />
           <AlertDialogFooter>
             <AlertDialogCancel>Cancel</AlertDialogCancel>
             <AlertDialogAction
               onClick={handleClearDatabase}
+              disabled={clearConfirmation !== databaseName}
               className="bg-destructive text-destructive-foreground hover:bg-destructive/90"
             >
               Drop All Tables
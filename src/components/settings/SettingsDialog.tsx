@@ -1,4 +1,4 @@
-import { useEffect } from "react";
+import { useEffect, useState } from "react";
 import { useForm } from "react-hook-form";
 import {
   Dialog,
@@ -30,7 +30,7 @@ import { ScrollArea } from "@/components/ui/scroll-area";
 import { useUIStore } from "@/stores/uiStore";
 import { useSettingsStore } from "@/stores/settingsStore";
 import { useTheme } from "@/components/theme-provider";
-import type { AppSettings } from "@/types/settings.types";
+import type { AppSettings, SettingsValidation } from "@/types/settings.types";
 
 const TEXT_TO_SQL_MODELS = [
   { id: "anthropic/claude-3-opus", name: "Claude 3 Opus" },
@@ -45,10 +45,21 @@ const VISUALIZATION_MODELS = [
   { id: "google/gemini-2.0-pro", name: "Gemini 2.0 Pro" },
 ];
 
+const VALIDATION_MESSAGES: Record<SettingsValidation["status"], string> = {
+  valid: "API key and model are working.",
+  invalid_key: "The API key was rejected. Check that it is copied correctly and not revoked.",
+  insufficient_credits: "The API key is valid, but the account has no credits left.",
+  model_not_found: "The API key is valid, but OpenRouter does not know this model.",
+  error: "Could not reach OpenRouter.",
+};
+
 export function SettingsDialog() {
   const { settingsDialogOpen, setSettingsDialogOpen } = useUIStore();
-  const { settings, saveSettings, isLoading } = useSettingsStore();
+  const { settings, saveSettings, validateSettings, models, loadModels, isLoading } =
+    useSettingsStore();
   const { theme, setTheme } = useTheme();
+  const [validation, setValidation] = useState<SettingsValidation | null>(null);
+  const [isValidating, setIsValidating] = useState(false);
 
   const form = useForm<AppSettings>({
     defaultValues: {
@@ -64,8 +75,27 @@ export function SettingsDialog() {
     if (settingsDialogOpen && settings) {
       form.reset(settings);
     }
+    setValidation(null);
   }, [settingsDialogOpen, settings, form]);
 
+  useEffect(() => {
+    if (settingsDialogOpen && models.length === 0) {
+      loadModels();
+    }
+  }, [settingsDialogOpen, models.length, loadModels]);
+
+  // Fall back to the built-in lists until the OpenRouter catalogue has loaded
+  const textToSqlModels = models.length > 0 ? models : TEXT_TO_SQL_MODELS;
+  const visualizationModels = models.length > 0 ? models : VISUALIZATION_MODELS;
+  const selectedSqlModel = models.find((m) => m.id === form.watch("text_to_sql_model"));
+
+  const onValidate = async () => {
+    const { openrouter_api_key, text_to_sql_model } = form.getValues();
+    setIsValidating(true);
+    setValidation(await validateSettings(openrouter_api_key, text_to_sql_model));
+    setIsValidating(false);
+  };
+
   const onSubmit = async (data: AppSettings) => {
     await saveSettings(data);
     setSettingsDialogOpen(false);
@@ -157,7 +187,7 @@ export function SettingsDialog() {
                           </SelectTrigger>
                         </FormControl>
                         <SelectContent>
-                          {TEXT_TO_SQL_MODELS.map((model) => (
+                          {textToSqlModels.map((model) => (
                             <SelectItem key={model.id} value={model.id}>
                               {model.name}
                             </SelectItem>
@@ -167,11 +197,42 @@ export function SettingsDialog() {
                       <FormDescription>
                         Model used for generating SQL from natural language.
                       </FormDescription>
+                      {selectedSqlModel && !selectedSqlModel.supports_tools && (
+                        <p className="text-sm text-destructive">
+                          This model does not support tool calling, which the SQL agent needs
+                          to look up tables in large schemas.
+                        </p>
+                      )}
                       <FormMessage />
                     </FormItem>
                   )}
                 />
 
+                <div className="space-y-2">
+                  <Button
+                    type="button"
+                    variant="outline"
+                    size="sm"
+                    onClick={onValidate}
+                    disabled={isValidating || isLoading}
+                  >
+                    {isValidating ? "Testing..." : "Test API Key & Model"}
+                  </Button>
+                  {validation && (
+                    <p
+                      className={
+                        validation.status === "valid"
+                          ? "text-sm text-muted-foreground"
+                          : "text-sm text-destructive"
+                      }
+                    >
+                      {VALIDATION_MESSAGES[validation.status]}
+                      {validation.context_length !== null &&
+                        ` Context length: ${validation.context_length.toLocaleString()} tokens.`}
+                    </p>
+                  )}
+                </div>
+
                 <FormField
                   control={form.control}
                   name="visualization_model"
@@ -185,7 +246,7 @@ export function SettingsDialog() {
                           </SelectTrigger>
                         </FormControl>
                         <SelectContent>
-                          {VISUALIZATION_MODELS.map((model) => (
+                          {visualizationModels.map((model) => (
                             <SelectItem key={model.id} value={model.id}>
                               {model.name}
                             </SelectItem>
@@ -223,6 +284,31 @@ export function SettingsDialog() {
                     </FormItem>
                   )}
                 />
+
+                <FormField
+                  control={form.control}
+                  name="conversation_token_budget"
+                  render={({ field }) => (
+                    <FormItem>
+                      <FormLabel>Conversation Token Budget</FormLabel>
+                      <FormControl>
+                        <Input
+                          type="number"
+                          min={0}
+                          step={1000}
+                          placeholder="0"
+                          {...field}
+                          value={field.value ?? 0}
+                          onChange={(e) => field.onChange(parseInt(e.target.value) || 0)}
+                        />
+                      </FormControl>
+                      <FormDescription>
+                        Approximate tokens of previous messages to include. The oldest messages are dropped first, so a few large results cannot overflow the model's context. 0 means no limit.
+                      </FormDescription>
+                      <FormMessage />
+                    </FormItem>
+                  )}
+                />
               </div>
             </ScrollArea>
 
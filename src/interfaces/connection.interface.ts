@@ -8,6 +8,7 @@ export interface IConnectionFormData {
   username: string;
   password: string;
   default_database: string;
+  connection_uri?: string;
 }
 
 export interface IConnectionTestResult {
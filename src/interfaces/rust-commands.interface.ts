@@ -1,9 +1,21 @@
 import type {
   Connection,
+  ConnectionFilter,
+  ConnectionHealthStatus,
+  ConnectionTestResult,
+  ConnectionDiagnostics,
   Schema,
-  QueryResult
+  ErDiagramFormat,
+  QueryResult,
+  QueryDiff,
+  ResultTextFormat,
+  PivotAggregate,
+  ColumnProfile,
+  ClearPlan,
+  DatabaseType,
+  SslMode
 } from "@/types/database.types";
-import type { AppSettings } from "@/types/settings.types";
+import type { AppSettings, OpenRouterModel, SettingsValidation } from "@/types/settings.types";
 import type { ERDData } from "@/types/erd.types";
 import type {
   ExportResult,
@@ -16,17 +28,62 @@ export interface IRustCommands {
   // Settings
   save_settings(settings: AppSettings): Promise<void>;
   get_settings(): Promise<AppSettings | null>;
+  validate_settings(api_key: string, model: string): Promise<SettingsValidation>;
+  list_openrouter_models(): Promise<OpenRouterModel[]>;
 
   // Connections
   test_connection(connection: Partial<Connection>): Promise<{ success: boolean; message: string }>;
+  test_all_connections(): Promise<ConnectionTestResult[]>;
+  connection_diagnostics(connection_id: string): Promise<ConnectionDiagnostics>;
+  list_databases(
+    database_type: DatabaseType,
+    host: string,
+    port: number,
+    username: string,
+    password: string,
+    ssl_mode?: SslMode
+  ): Promise<string[]>;
   save_connection(connection: Partial<Connection>): Promise<Connection>;
-  get_connections(): Promise<Connection[]>;
+  get_connections(filter?: ConnectionFilter): Promise<Connection[]>;
   delete_connection(id: string): Promise<void>;
   update_connection(connection: Connection): Promise<Connection>;
+  get_connection_status(): Promise<ConnectionHealthStatus[]>;
 
   // Schema & Query
   get_schema(connection_id: string): Promise<Schema>;
-  run_query(connection_id: string, query: string, limit: number, offset: number): Promise<QueryResult>;
+  refresh_schema(connection_id: string): Promise<Schema>;
+  generate_er_diagram(connection_id: string, format: ErDiagramFormat): Promise<string>;
+  run_query(
+    connection_id: string,
+    query: string,
+    limit: number | null,
+    offset: number,
+    params?: unknown[],
+    query_token?: string,
+    compute_summary?: boolean
+  ): Promise<QueryResult>;
+  cancel_query(query_token: string): Promise<void>;
+  run_query_with_events(connection_id: string, query_id: string, query: string, limit: number | null, offset: number, params?: unknown[]): Promise<QueryResult>;
+  run_query_batch(connection_id: string, script: string): Promise<QueryResult[]>;
+  preview_table(
+    connection_id: string,
+    table_name: string,
+    filter_column?: string,
+    filter_value?: unknown,
+    limit?: number
+  ): Promise<QueryResult>;
+  diff_query_results(before: QueryResult, after: QueryResult, key_columns: string[]): Promise<QueryDiff>;
+  serialize_query_result(result: QueryResult, format: ResultTextFormat): Promise<string>;
+  pivot_query_result(
+    result: QueryResult,
+    index_column: string,
+    pivot_column: string,
+    value_column: string,
+    aggregate?: PivotAggregate
+  ): Promise<QueryResult>;
+  profile_column(connection_id: string, table_name: string, column_name: string): Promise<ColumnProfile>;
+  clear_data_only(connection_id: string, confirmation?: string): Promise<ClearPlan>;
+  clear_database(connection_id: string, confirmation?: string): Promise<ClearPlan>;
 
   // Import/Export
   export_database(connection_id: string, database_name: string): Promise<ExportResult>;
@@ -1,13 +1,16 @@
 import type { Connection, Schema, ConnectionStatus, SqlKeyword } from "@/types/database.types";
-import type { AppSettings } from "@/types/settings.types";
+import type { AppSettings, OpenRouterModel, SettingsValidation } from "@/types/settings.types";
 import type { Tab } from "@/types/query.types";
 import type { GeographicCell } from "@/types/geography.types";
 
 export interface ISettingsStore {
   settings: AppSettings | null;
   isLoading: boolean;
+  models: OpenRouterModel[];
   loadSettings: () => Promise<void>;
+  loadModels: () => Promise<void>;
   saveSettings: (settings: AppSettings) => Promise<void>;
+  validateSettings: (apiKey: string, model: string) => Promise<SettingsValidation | null>;
   updateApiKey: (apiKey: string) => void;
   updateModels: (textToSql: string, visualization: string) => void;
 }
@@ -31,7 +34,8 @@ export interface ISchemaStore {
   isLoading: boolean;
   isLoadingKeywords: boolean;
   error: string | null;
-  loadSchema: (connectionId: string) => Promise<void>;
+  /** Pass `refresh` to bypass the backend schema cache */
+  loadSchema: (connectionId: string, refresh?: boolean) => Promise<void>;
   fetchKeywords: (connectionId: string) => Promise<void>;
   clearSchema: () => void;
 }
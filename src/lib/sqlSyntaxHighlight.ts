@@ -9,6 +9,34 @@ import type { Schema, SqlKeyword } from "@/types/database.types";
 export interface HighlightConfig {
   keywords: SqlKeyword[];
   schema: Schema | null;
+  /** CSS class prefix; classes default to `sql-*` */
+  class_prefix?: string | null;
+}
+
+export type SqlTokenType =
+  | "keyword_dml"
+  | "keyword_clause"
+  | "keyword_reserved"
+  | "keyword_unreserved"
+  | "keyword_type"
+  | "keyword_function"
+  | "keyword_common"
+  | "table"
+  | "column"
+  | "string"
+  | "number"
+  | "operator"
+  | "paren_open"
+  | "paren_close"
+  | "comment"
+  | "text";
+
+export interface SqlToken {
+  type: SqlTokenType;
+  value: string;
+  /** Byte offsets into the UTF-8 encoded SQL */
+  start: number;
+  end: number;
 }
 
 /**
@@ -29,3 +57,10 @@ export async function highlightSQL(sql: string, config: HighlightConfig): Promis
       .replace(/\n/g, '<br/>');
   }
 }
+
+/**
+ * Tokenize SQL using Rust backend, for callers that render their own markup
+ */
+export async function tokenizeSQL(sql: string, config: HighlightConfig): Promise<SqlToken[]> {
+  return invoke<SqlToken[]>("tokenize_sql_json", { sql, config });
+}
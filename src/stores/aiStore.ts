@@ -1,6 +1,7 @@
 import { create } from "zustand";
 import { invoke } from "@tauri-apps/api/core";
 import { listen, UnlistenFn } from "@tauri-apps/api/event";
+import { save } from "@tauri-apps/plugin-dialog";
 import type {
   AiSession,
   ChatMessage,
@@ -14,7 +15,11 @@ import type {
   AiStatisticPayload,
   AiCompletePayload,
   AiErrorPayload,
+  AiUsagePayload,
+  AiModelFallbackPayload,
   ConversationMetadata,
+  ConversationExportFormat,
+  TokenUsage,
 } from "@/types/ai.types";
 import { ErrorHandler } from "@/lib/ErrorHandler";
 
@@ -32,6 +37,7 @@ interface IAiStore {
   error: string | null;
   isPanelOpen: boolean;
   currentMode: AiMode;
+  sessionUsage: TokenUsage | null;
 
   // Conversation management state
   conversations: ConversationMetadata[];
@@ -55,6 +61,11 @@ interface IAiStore {
   switchConversation: (sessionId: string) => Promise<void>;
   startNewConversation: () => Promise<void>;
   deleteConversation: (sessionId: string) => Promise<void>;
+  exportConversation: (
+    sessionId: string,
+    format: ConversationExportFormat,
+    includeInternal?: boolean
+  ) => Promise<string | null>;
   setSidebarOpen: (open: boolean) => void;
   setDeleteConfirmationId: (id: string | null) => void;
 
@@ -77,6 +88,7 @@ export const useAiStore = create<IAiStore>((set, get) => ({
   error: null,
   isPanelOpen: true,
   currentMode: 'sql',
+  sessionUsage: null,
   unlistenFns: [],
 
   // Conversation management state
@@ -96,7 +108,7 @@ export const useAiStore = create<IAiStore>((set, get) => ({
         lastActivity: new Date(),
       };
 
-      set({ session, error: null });
+      set({ session, error: null, sessionUsage: null });
 
       // Set up event listeners for this session
       await get().setupEventListeners(sessionId);
@@ -172,6 +184,24 @@ export const useAiStore = create<IAiStore>((set, get) => ({
       });
       unlistenFns.push(unlistenComplete);
 
+      // Token usage and cost
+      const unlistenUsage = await listen<AiUsagePayload>('ai_usage', (event) => {
+        if (event.payload.session_id === sessionId) {
+          set({ sessionUsage: event.payload.session_total });
+        }
+      });
+      unlistenFns.push(unlistenUsage);
+
+      // A fallback model replaced an unavailable one
+      const unlistenFallback = await listen<AiModelFallbackPayload>('ai_model_fallback', (event) => {
+        if (event.payload.session_id === sessionId) {
+          get().appendThinkingToLastMessage(
+            `Model ${event.payload.from_model} is unavailable, using ${event.payload.to_model} instead.\n`
+          );
+        }
+      });
+      unlistenFns.push(unlistenFallback);
+
       // Error
       const unlistenError = await listen<AiErrorPayload>('ai_error', (event) => {
         if (event.payload.session_id === sessionId) {
@@ -228,6 +258,7 @@ export const useAiStore = create<IAiStore>((set, get) => ({
         sessionId: session.id,
         message: content,
         connectionId: session.connectionId,
+        allowWrites: false,
       });
 
       // Note: isGenerating will be set to false by the 'ai_complete' or 'ai_error' event
@@ -484,6 +515,30 @@ export const useAiStore = create<IAiStore>((set, get) => ({
     }
   },
 
+  exportConversation: async (sessionId, format, includeInternal = false) => {
+    try {
+      const extension = format === 'markdown' ? 'md' : 'json';
+      const outputPath = await save({
+        defaultPath: `conversation-${sessionId}.${extension}`,
+        filters: [{
+          name: format === 'markdown' ? 'Markdown' : 'JSON',
+          extensions: [extension],
+        }],
+      });
+      if (!outputPath) return null;
+
+      return await invoke<string>('export_conversation', {
+        sessionId,
+        format,
+        outputPath,
+        includeInternal,
+      });
+    } catch (error) {
+      ErrorHandler.handle(error, "Failed to export conversation");
+      return null;
+    }
+  },
+
   setSidebarOpen: (open: boolean) => {
     set({ sidebarOpen: open });
   },
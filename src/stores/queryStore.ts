@@ -171,7 +171,7 @@ export const useQueryStore = create<IQueryStore>((set, get) => ({
       const result = await invoke<QueryResult>("run_query", {
         connectionId,
         query: tab.query,
-        limit: 50,
+        limit: null, // connection's default_limit
         offset: 0,
       });
 
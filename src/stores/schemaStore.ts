@@ -18,7 +18,7 @@ export const useSchemaStore = create<ISchemaStore>((set, get) => ({
   isLoadingKeywords: false,
   error: null,
 
-  loadSchema: async (connectionId: string) => {
+  loadSchema: async (connectionId: string, refresh = false) => {
     set({ isLoading: true, error: null, schema: null });
 
     // Use a Map to track unique tables by name (prevents duplicates)
@@ -52,7 +52,7 @@ export const useSchemaStore = create<ISchemaStore>((set, get) => ({
     });
 
     try {
-      const schema = await invoke<Schema>("get_schema", { connectionId });
+      const schema = await invoke<Schema>(refresh ? "refresh_schema" : "get_schema", { connectionId });
 
       // Update with final schema (use the complete schema from backend)
       set({
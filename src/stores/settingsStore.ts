@@ -1,12 +1,13 @@
 import { create } from "zustand";
 import { invoke } from "@tauri-apps/api/core";
-import type { AppSettings } from "@/types/settings.types";
+import type { AppSettings, OpenRouterModel, SettingsValidation } from "@/types/settings.types";
 import type { ISettingsStore } from "@/interfaces/store.interface";
 import { ErrorHandler } from "@/lib/ErrorHandler";
 
 export const useSettingsStore = create<ISettingsStore>((set, get) => ({
   settings: null,
   isLoading: false,
+  models: [],
 
   loadSettings: async () => {
     set({ isLoading: true });
@@ -19,6 +20,15 @@ export const useSettingsStore = create<ISettingsStore>((set, get) => ({
     }
   },
 
+  loadModels: async () => {
+    try {
+      const models = await invoke<OpenRouterModel[]>("list_openrouter_models");
+      set({ models });
+    } catch (error) {
+      ErrorHandler.handle(error, "Failed to load OpenRouter models");
+    }
+  },
+
   saveSettings: async (settings: AppSettings) => {
     set({ isLoading: true });
     try {
@@ -32,6 +42,15 @@ export const useSettingsStore = create<ISettingsStore>((set, get) => ({
     }
   },
 
+  validateSettings: async (apiKey: string, model: string) => {
+    try {
+      return await invoke<SettingsValidation>("validate_settings", { apiKey, model });
+    } catch (error) {
+      ErrorHandler.handle(error, "Failed to validate settings");
+      return null;
+    }
+  },
+
   updateApiKey: (apiKey: string) => {
     const { settings } = get();
     if (settings) {
@@ -95,6 +95,8 @@ export type ConversationMetadata = {
   updated_at: number; // Unix timestamp
 };
 
+export type ConversationExportFormat = 'markdown' | 'json';
+
 export type AiGenerationResult = {
   content: string;
   isSafe: boolean;
@@ -156,6 +158,34 @@ export type AiCompletePayload = {
   answer: string;
 };
 
+export type TokenUsage = {
+  prompt_tokens: number;
+  completion_tokens: number;
+  total_tokens: number;
+  cost: number; // estimated USD
+  requests: number;
+};
+
+export type AiUsagePayload = {
+  session_id: string;
+  usage: TokenUsage; // this run
+  session_total: TokenUsage;
+};
+
+export type AiModelFallbackPayload = {
+  session_id: string;
+  from_model: string;
+  to_model: string;
+  reason: string;
+};
+
+export type AiConfirmWritePayload = {
+  session_id: string;
+  proposal_id: string; // pass back to confirm_ai_write
+  sql: string;
+  reasoning: string;
+};
+
 export type AiErrorPayload = {
   session_id: string;
   error: string;
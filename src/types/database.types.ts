@@ -1,5 +1,13 @@
 export type DatabaseType = "PostgreSQL" | "MariaDB" | "MySQL";
 
+export type SslMode = "Disable" | "Prefer" | "Require" | "VerifyCa" | "VerifyFull";
+
+// Narrows get_connections; unset fields match everything
+export type ConnectionFilter = {
+  tag?: string;
+  environment?: string;
+};
+
 export type Connection = {
   id: string;
   name: string;
@@ -9,6 +17,23 @@ export type Connection = {
   username: string;
   password: string;
   default_database: string;
+  connection_uri?: string | null;
+  ssl_mode?: SslMode;
+  ssl_root_cert?: string | null;
+  ssh_host?: string | null;
+  ssh_port?: number | null;
+  ssh_user?: string | null;
+  ssh_key_path?: string | null;
+  ssh_password?: string | null;
+  ssh_host_key_fingerprint?: string | null; // "SHA256:..."; otherwise ~/.ssh/known_hosts must list the bastion
+  read_only?: boolean;
+  max_connections?: number; // default 5
+  min_connections?: number; // default 0
+  acquire_timeout_secs?: number; // default 30
+  schema_search_path?: string[]; // PostgreSQL schemas to browse, default ["public"]
+  default_limit?: number | null; // page size for editor queries without a LIMIT, default 50
+  tags?: string[];
+  environment?: string | null; // e.g. "prod" or "staging"; new prod connections start read-only
   created_at: string;
   updated_at: string;
 };
@@ -21,6 +46,8 @@ export type Table = {
   indexes: Index[];
   triggers: Trigger[];
   constraints: Constraint[];
+  is_view?: boolean;
+  comment?: string | null;
 };
 
 export type Column = {
@@ -33,6 +60,8 @@ export type Column = {
   foreign_key_column?: string;
   default_value?: string;
   character_maximum_length?: number;
+  enum_values?: string[];
+  comment?: string | null;
 };
 
 export type Index = {
@@ -58,11 +87,22 @@ export type Constraint = {
   referenced_columns?: string[];
 };
 
+export type Routine = {
+  name: string;
+  schema?: string;
+  routine_type: string; // FUNCTION | PROCEDURE
+  return_type?: string;
+  parameters: string;
+};
+
 export type Schema = {
   database_name: string;
   tables: Table[];
+  routines?: Routine[];
 };
 
+export type ErDiagramFormat = 'dot' | 'mermaid';
+
 export type SqlKeyword = {
   word: string;
   category: string;
@@ -71,9 +111,81 @@ export type SqlKeyword = {
 
 export type ConnectionStatus = "connected" | "disconnected" | "connecting" | "error";
 
+// Pool health from the backend keepalive (get_connection_status)
+export type ConnectionHealth = "Connected" | "Degraded" | "Disconnected";
+
+export type ConnectionHealthStatus = {
+  connection_id: string;
+  health: ConnectionHealth;
+  last_checked: string | null;
+  latency_ms: number | null;
+  error: string | null;
+};
+
+export type ConnectionTestResult = {
+  connection_id: string;
+  name: string;
+  success: boolean;
+  latency_ms: number;
+  error: string | null;
+};
+
+/** Server snapshot for support; fields are null when their lookup failed (see `errors`) */
+export type ConnectionDiagnostics = {
+  connection_id: string;
+  database_type: DatabaseType;
+  server_version: string | null;
+  database_size_bytes: number | null;
+  table_count: number | null;
+  active_connections: number | null;
+  errors: string[];
+};
+
 export type QueryResult = {
   columns: string[];
   rows: Record<string, any>[];
   row_count: number;
   execution_time_ms: number;
 };
+
+export type RowChange = {
+  before: Record<string, any>;
+  after: Record<string, any>;
+  changed_columns: string[];
+};
+
+export type QueryDiff = {
+  key_columns: string[];
+  added: Record<string, any>[];
+  removed: Record<string, any>[];
+  changed: RowChange[];
+  unchanged_count: number;
+};
+
+// Clipboard formats for serialize_query_result
+export type ResultTextFormat = 'tsv' | 'csv' | 'markdown' | 'json';
+
+// How pivot_query_result combines rows sharing an index/pivot pair; 'error' rejects duplicates
+export type PivotAggregate = 'error' | 'first' | 'last' | 'count' | 'sum' | 'avg' | 'min' | 'max';
+
+export type ColumnProfile = {
+  table_name: string;
+  column_name: string;
+  data_type: string;
+  row_count: number;
+  null_count: number;
+  distinct_count: number;
+  min: string | null;
+  max: string | null;
+  avg: number | null;
+  stddev: number | null;
+};
+
+// Returned by clear_data_only / clear_database; nothing runs until confirmation_token is passed back
+export type ClearPlan = {
+  kind: 'data_only' | 'database';
+  database: string;
+  tables: string[];
+  confirmation_token: string; // the database name
+  executed: boolean;
+};
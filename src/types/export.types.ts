@@ -1,8 +1,33 @@
+export type ExportFormat = 'csv' | 'json' | 'ndjson' | 'xlsx' | 'parquet' | 'sql';
+
+export type MaskStrategy = 'null' | 'hash' | 'partial_email' | { fixed_string: string };
+
+export type CsvLineTerminator = 'lf' | 'crlf';
+
 export type ExportOptions = {
   connection_id: string;
   tables: string[];
   output_dir: string;
   create_zip: boolean;
+  format?: ExportFormat; // defaults to csv
+  mask_columns?: Record<string, MaskStrategy>; // "table.column" -> strategy
+  where_clause?: string; // condition without WHERE, applied to every table
+  max_rows?: number;
+  table_filters?: Record<string, TableFilter>; // per-table override of where_clause/max_rows
+  column_selection?: Record<string, string[]>; // table -> columns to export, in order
+  combine_into_single_file?: boolean; // csv/ndjson only: one file with a __table column
+  delimiter?: string | number; // single character or byte value, default ","
+  quote?: string | number; // single character or byte value, default '"'
+  has_headers?: boolean; // write a header row, default true
+  line_terminator?: CsvLineTerminator; // defaults to lf
+  resume?: boolean; // skip tables finished by an interrupted export to the same path
+  schema_only?: boolean; // write only schema.sql
+  data_only?: boolean; // write only table data, no schema.sql
+};
+
+export type TableFilter = {
+  where_clause?: string;
+  max_rows?: number;
 };
 
 export type ExportProgress = {
@@ -13,11 +38,29 @@ export type ExportProgress = {
   cancelled: boolean;
 };
 
+export type ImportMode = 'append' | 'upsert' | 'replace';
+
+export type TransactionScope = 'per_file' | 'all_or_nothing';
+
+export type CsvColumnMatching = 'positional' | 'by_name';
+
+export type CsvEncoding = 'utf8' | 'latin1' | 'windows1252';
+
 export type ImportOptions = {
   connection_id: string;
   source_path: string;
   is_zip: boolean;
-  table_mappings: Record<string, string>; // CSV filename -> table name
+  table_mappings: Record<string, string>; // Data file name -> table name
+  auto_create?: boolean; // Create missing tables with inferred column types
+  mode?: ImportMode; // defaults to append
+  transaction_scope?: TransactionScope; // defaults to per_file
+  null_token?: string; // unquoted CSV fields equal to this import as NULL (default "")
+  csv_column_matching?: CsvColumnMatching; // defaults to positional
+  ignore_extra_columns?: boolean; // by_name: skip CSV columns the table lacks instead of failing
+  delimiter?: string | number; // single character or byte value, default ","
+  quote?: string | number; // single character or byte value, default '"'
+  has_headers?: boolean; // first record holds column names, default true
+  encoding?: CsvEncoding; // defaults to utf8; a UTF-8 BOM is always stripped
 };
 
 export type ImportProgress = {
@@ -26,6 +69,8 @@ export type ImportProgress = {
   total: number;
   status: string;
   cancelled: boolean;
+  rolled_back: boolean;
+  rows_inserted?: number; // rows inserted so far from file_name, on per-batch events
 };
 
 export type ExportResult = {
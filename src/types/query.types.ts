@@ -16,6 +16,39 @@ export type QueryResult = {
   rows: Record<string, any>[];
   row_count: number;
   execution_time_ms: number;
+  summary?: Record<string, ColumnSummary>; // only when requested with compute_summary
+};
+
+// Footer aggregates; sum/avg/min/max are null for non-numeric columns
+export type ColumnSummary = {
+  count: number;
+  sum: number | null;
+  avg: number | null;
+  min: number | null;
+  max: number | null;
+};
+
+// Events from run_query_with_events, tagged with the caller's query_id
+export type QueryStartedPayload = {
+  query_id: string;
+  columns: string[]; // empty when the query couldn't be described up front
+  column_metadata: ColumnMetadata[];
+};
+
+export type QueryRowsPayload = {
+  query_id: string;
+  result: QueryResult;
+};
+
+export type QueryFinishedPayload = {
+  query_id: string;
+  row_count: number;
+  execution_time_ms: number;
+};
+
+export type QueryErrorPayload = {
+  query_id: string;
+  error: string;
 };
 
 export type PaginationState = {
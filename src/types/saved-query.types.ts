@@ -0,0 +1,15 @@
+export interface SavedQuery {
+  id: string;
+  name: string;
+  sql: string;
+  connection_id: string | null; // null = available on every connection
+  created_at: string;
+  updated_at: string;
+}
+
+export interface SaveQueryRequest {
+  id?: string; // omit to create a new saved query
+  name: string;
+  sql: string;
+  connection_id: string | null;
+}
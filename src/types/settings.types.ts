@@ -3,6 +3,66 @@ export type AppSettings = {
   text_to_sql_model: string;
   visualization_model: string;
   conversation_history_limit: number;
+  summarize_conversation_history?: boolean; // summarize messages beyond the limit instead of dropping them
+  conversation_token_budget?: number; // estimated tokens of history sent with each message, 0 disables
+  query_timeout_ms?: number; // 0 disables the timeout
+  max_retries?: number; // retries for rate-limited / 5xx OpenRouter calls, default 3
+  max_concurrent_ai_requests?: number; // OpenRouter requests in flight across all chats, default 4
+  include_routines?: boolean; // add stored functions/procedures to the AI schema context
+  llm_cache_ttl_secs?: number; // reuse classification/selection responses, 0 disables, default 600
+  query_result_cache_ttl_secs?: number; // reuse results of identical AI read queries, 0 disables, default 60
+  keepalive_interval_secs?: number; // ping open connection pools, 0 disables, default 60
+  ai_max_rows?: number; // row cap for AI-generated queries, default 100
+  fallback_models?: string[]; // tried in order when the configured model is unavailable
+  max_tokens?: number; // output cap for classification/selection/SQL generation, default 2000
+  final_answer_max_tokens?: number; // output cap for final answers and general replies, default 2000
+  classification_temperature?: number; // default 0
+  selector_temperature?: number; // default 0.1
+  decomposer_temperature?: number; // default 0.2
+  refiner_temperature?: number; // default 0.1
+  final_answer_temperature?: number; // default 0.3
+  top_p?: number | null; // omitted from requests when unset
+  frequency_penalty?: number | null;
+  presence_penalty?: number | null;
+  provider?: ProviderPreferences | null; // OpenRouter provider routing
+};
+
+export type ProviderPreferences = {
+  order?: string[]; // providers tried in order, e.g. ["Anthropic"]
+  allow_fallbacks?: boolean; // use other providers when those in order fail
+  require_parameters?: boolean;
+  ignore?: string[];
+  data_collection?: "allow" | "deny";
 };
 
 export type Theme = "light" | "dark" | "system";
+
+export type SettingsValidationStatus =
+  | "valid"
+  | "invalid_key"
+  | "insufficient_credits"
+  | "model_not_found"
+  | "error";
+
+export type SettingsValidation = {
+  status: SettingsValidationStatus;
+  key_valid: boolean | null;
+  model_available: boolean | null;
+  context_length: number | null;
+  message: string | null;
+};
+
+export type ModelPricing = {
+  /** USD per prompt token, as a decimal string */
+  prompt: string;
+  /** USD per completion token, as a decimal string */
+  completion: string;
+};
+
+export type OpenRouterModel = {
+  id: string;
+  name: string;
+  context_length: number | null;
+  pricing: ModelPricing;
+  supports_tools: boolean;
+};